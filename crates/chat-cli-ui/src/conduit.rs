@@ -3,6 +3,7 @@ use std::marker::PhantomData;
 
 use crossterm::style::{
     self,
+    Attribute,
     Print,
     Stylize,
 };
@@ -149,12 +150,34 @@ impl ViewEnd {
                 Event::Custom(_custom) => {},
                 Event::ActivitySnapshotEvent(_activity_snapshot_event) => {},
                 Event::ActivityDeltaEvent(_activity_delta_event) => {},
-                Event::ReasoningStart(_reasoning_start) => {},
+                Event::ReasoningStart(_reasoning_start) => {
+                    queue!(
+                        stdout,
+                        theme_source.secondary_fg(),
+                        style::SetAttribute(Attribute::Dim),
+                        Print("Thinking\n"),
+                    )?;
+                },
                 Event::ReasoningMessageStart(_reasoning_message_start) => {},
-                Event::ReasoningMessageContent(_reasoning_message_content) => {},
+                Event::ReasoningMessageContent(reasoning_message_content) => {
+                    queue!(
+                        stdout,
+                        theme_source.secondary_fg(),
+                        style::SetAttribute(Attribute::Dim),
+                        Print(reasoning_message_content.delta),
+                    )?;
+                    stdout.flush()?;
+                },
                 Event::ReasoningMessageEnd(_reasoning_message_end) => {},
                 Event::ReasoningMessageChunk(_reasoning_message_chunk) => {},
-                Event::ReasoningEnd(_reasoning_end) => {},
+                Event::ReasoningEnd(_reasoning_end) => {
+                    execute!(
+                        stdout,
+                        theme_source.reset_attributes(),
+                        theme_source.reset(),
+                        Print("\n\n")
+                    )?;
+                },
                 Event::MetaEvent(MetaEvent { meta_type, payload }) => {
                     if meta_type.as_str() == "timing" {
                         if let serde_json::Value::String(s) = payload {
@@ -185,6 +185,7 @@ impl RtsModel {
                     ConverseStreamErrorKind::ModelNotAvailable => StreamErrorKind::Other(err.to_string()),
                     ConverseStreamErrorKind::MessageConversion => StreamErrorKind::Other(err.to_string()),
                     ConverseStreamErrorKind::ApiError => StreamErrorKind::Other(err.to_string()),
+                    ConverseStreamErrorKind::GuardrailIntervened { .. } => StreamErrorKind::Other(err.to_string()),
                     ConverseStreamErrorKind::Unknown { .. } => StreamErrorKind::Other(err.to_string()),
                 };
                 let request_id = err.request_id.clone();
@@ -232,6 +233,7 @@ impl RtsModel {
                     user_input_message_context,
                     user_intent: None,
                     images,
+                    documents: None,
                     model_id: self.model_id.clone(),
                 }
             },
@@ -260,6 +262,7 @@ impl RtsModel {
                         user_input_message_context: ctx,
                         user_intent: None,
                         images: None,
+                        documents: None,
                         model_id: None,
                     };
                     rts::ChatMessage::UserInputMessage(msg)
@@ -269,6 +272,7 @@ impl RtsModel {
                         message_id: m.id.clone(),
                         content: m.text(),
                         tool_uses: m.tool_uses().map(|v| v.into_iter().map(Into::into).collect()),
+                        reasoning: None,
                     };
                     rts::ChatMessage::AssistantResponseMessage(msg)
                 },
@@ -280,8 +284,11 @@ impl RtsModel {
             user_input_message,
             history: Some(history),
             service_tier: None,
-            model_system_prompt: None,
+            model_info: None,
             agent_prompt: None,
+            reasoning_budget_tokens: None,
+            max_tokens: None,
+            guardrail: None,
         })
     }
 }
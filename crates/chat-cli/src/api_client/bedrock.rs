@@ -1,90 +1,97 @@
 // Bedrock Converse API integration
+use std::collections::HashMap;
+
 use aws_sdk_bedrockruntime::types::{
+    CachePointBlock,
+    CachePointType,
     ContentBlock,
     ConversationRole,
+    DocumentBlock as BedrockDocumentBlock,
+    DocumentFormat as BedrockDocumentFormat,
+    DocumentSource as BedrockDocumentSource,
+    GuardrailAssessment,
+    GuardrailStreamConfiguration,
+    GuardrailStreamProcessingMode,
+    GuardrailTrace,
+    GuardrailTraceAssessment,
+    ImageBlock as BedrockImageBlock,
+    ImageFormat as BedrockImageFormat,
+    ImageSource as BedrockImageSource,
     Message,
+    ReasoningContentBlock,
+    ReasoningTextBlock,
     SystemContentBlock,
     Tool as BedrockTool,
     ToolConfiguration,
     ToolInputSchema,
     ToolSpecification,
 };
-use aws_smithy_types::Document;
+use aws_smithy_types::{
+    Blob,
+    Document,
+};
 use eyre::Result;
 
 use super::model::{
     ChatMessage,
+    DocumentBlock,
+    DocumentFormat,
+    DocumentSource,
+    ImageBlock,
+    ImageFormat,
+    ImageSource,
+    ReasoningContent,
     Tool,
     UserInputMessage,
     UserInputMessageContext,
 };
+use crate::cli::{
+    GuardrailConfig,
+    GuardrailTraceMode,
+};
+use crate::cli::chat::cli::model::ModelInfo;
+
+/// Smallest reasoning budget accepted by the Converse API.
+const MIN_REASONING_BUDGET_TOKENS: u32 = 1024;
+
+/// Output tokens allowed on top of the reasoning budget for the response itself.
+const REASONING_RESPONSE_TOKENS: u32 = 8192;
 
 /// Convert internal message format to Bedrock Message format
+///
+/// For models with prompt caching, a cache point is added after the last history message since
+/// the history is the part of the conversation that stays the same between requests.
+///
+/// Reasoning blocks from previous responses are only sent back when `include_reasoning` is set,
+/// since models reject them when reasoning is not enabled for the request.
 pub fn convert_to_bedrock_messages(
     user_input: &UserInputMessage,
     history: Option<&Vec<ChatMessage>>,
-    model_system_prompt: Option<&str>,
+    model_info: &ModelInfo,
+    include_reasoning: bool,
 ) -> Result<Vec<Message>> {
     let mut messages = Vec::new();
+    let mut document_names = DocumentNames::default();
 
     // Add history messages first, skipping empty ones
     if let Some(hist) = history {
         for msg in hist {
-            if let Ok(bedrock_msg) = convert_chat_message_to_bedrock(msg) {
+            if let Ok(bedrock_msg) =
+                convert_chat_message_to_bedrock(msg, model_info, include_reasoning, &mut document_names)
+            {
                 tracing::debug!("Adding history message with {} content blocks", bedrock_msg.content().len());
                 messages.push(bedrock_msg);
             }
         }
     }
 
-    // Add current user message
-    let mut content_blocks = Vec::new();
-    
-    // Only add text if not empty
-    if !user_input.content.trim().is_empty() {
-        content_blocks.push(ContentBlock::Text(user_input.content.clone()));
-    }
-    
-    // Add tool results if present
-    if let Some(context) = &user_input.user_input_message_context {
-        if let Some(tool_results) = &context.tool_results {
-            for result in tool_results {
-                let status = match result.status {
-                    crate::api_client::model::ToolResultStatus::Success => 
-                        aws_sdk_bedrockruntime::types::ToolResultStatus::Success,
-                    crate::api_client::model::ToolResultStatus::Error => 
-                        aws_sdk_bedrockruntime::types::ToolResultStatus::Error,
-                };
-                
-                let mut result_content = Vec::new();
-                for item in &result.content {
-                    match item {
-                        crate::api_client::model::ToolResultContentBlock::Text(text) => {
-                            result_content.push(
-                                aws_sdk_bedrockruntime::types::ToolResultContentBlock::Text(text.clone())
-                            );
-                        }
-                        crate::api_client::model::ToolResultContentBlock::Json(doc) => {
-                            result_content.push(
-                                aws_sdk_bedrockruntime::types::ToolResultContentBlock::Json(doc.clone())
-                            );
-                        }
-                    }
-                }
-                
-                content_blocks.push(
-                    ContentBlock::ToolResult(
-                        aws_sdk_bedrockruntime::types::ToolResultBlock::builder()
-                            .tool_use_id(&result.tool_use_id)
-                            .set_content(Some(result_content))
-                            .status(status)
-                            .build()?
-                    )
-                );
-            }
-        }
+    if let Some(last) = messages.last_mut().filter(|_| model_info.supports_prompt_caching) {
+        last.content.push(ContentBlock::CachePoint(cache_point()?));
     }
-    
+
+    // Add current user message
+    let content_blocks = convert_user_content(user_input, model_info, &mut document_names)?;
+
     // Only add user message if we have content
     if !content_blocks.is_empty() {
         let mut builder = Message::builder().role(ConversationRole::User);
@@ -113,6 +120,18 @@ pub fn convert_to_bedrock_messages(
                 ContentBlock::ToolResult(tool_result) => {
                     tracing::debug!("  Block {}: ToolResult (id={}, status={:?})", j, tool_result.tool_use_id, tool_result.status);
                 }
+                ContentBlock::Image(image) => {
+                    tracing::debug!("  Block {}: Image (format={})", j, image.format.as_str());
+                }
+                ContentBlock::Document(document) => {
+                    tracing::debug!("  Block {}: Document (name={}, format={})", j, document.name, document.format.as_str());
+                }
+                ContentBlock::CachePoint(_) => {
+                    tracing::debug!("  Block {}: CachePoint", j);
+                }
+                ContentBlock::ReasoningContent(_) => {
+                    tracing::debug!("  Block {}: ReasoningContent", j);
+                }
                 _ => {
                     tracing::debug!("  Block {}: Other", j);
                 }
@@ -125,56 +144,16 @@ pub fn convert_to_bedrock_messages(
 
 /// Convert ChatMessage to Bedrock Message
 /// Returns Err if the message content is empty/whitespace (should be skipped)
-fn convert_chat_message_to_bedrock(msg: &ChatMessage) -> Result<Message> {
+fn convert_chat_message_to_bedrock(
+    msg: &ChatMessage,
+    model_info: &ModelInfo,
+    include_reasoning: bool,
+    document_names: &mut DocumentNames,
+) -> Result<Message> {
     match msg {
         ChatMessage::UserInputMessage(user_msg) => {
-            let mut content_blocks = Vec::new();
-            
-            // Add text content if not empty
-            if !user_msg.content.trim().is_empty() {
-                content_blocks.push(ContentBlock::Text(user_msg.content.clone()));
-            }
-            
-            // Add tool results if present
-            if let Some(context) = &user_msg.user_input_message_context {
-                if let Some(tool_results) = &context.tool_results {
-                    for result in tool_results {
-                        let status = match result.status {
-                            crate::api_client::model::ToolResultStatus::Success => 
-                                aws_sdk_bedrockruntime::types::ToolResultStatus::Success,
-                            crate::api_client::model::ToolResultStatus::Error => 
-                                aws_sdk_bedrockruntime::types::ToolResultStatus::Error,
-                        };
-                        
-                        let mut result_content = Vec::new();
-                        for item in &result.content {
-                            match item {
-                                crate::api_client::model::ToolResultContentBlock::Text(text) => {
-                                    result_content.push(
-                                        aws_sdk_bedrockruntime::types::ToolResultContentBlock::Text(text.clone())
-                                    );
-                                }
-                                crate::api_client::model::ToolResultContentBlock::Json(doc) => {
-                                    result_content.push(
-                                        aws_sdk_bedrockruntime::types::ToolResultContentBlock::Json(doc.clone())
-                                    );
-                                }
-                            }
-                        }
-                        
-                        content_blocks.push(
-                            ContentBlock::ToolResult(
-                                aws_sdk_bedrockruntime::types::ToolResultBlock::builder()
-                                    .tool_use_id(&result.tool_use_id)
-                                    .set_content(Some(result_content))
-                                    .status(status)
-                                    .build()?
-                            )
-                        );
-                    }
-                }
-            }
-            
+            let content_blocks = convert_user_content(user_msg, model_info, document_names)?;
+
             // Skip if no content blocks
             if content_blocks.is_empty() {
                 eyre::bail!("Empty user message");
@@ -189,6 +168,15 @@ fn convert_chat_message_to_bedrock(msg: &ChatMessage) -> Result<Message> {
         }
         ChatMessage::AssistantResponseMessage(assistant_msg) => {
             let mut content_blocks = Vec::new();
+
+            // Reasoning has to come before the text and tool uses it led to
+            if let Some(reasoning) = assistant_msg.reasoning.as_ref().filter(|_| include_reasoning) {
+                for block in reasoning {
+                    if let Some(block) = convert_reasoning(block)? {
+                        content_blocks.push(ContentBlock::ReasoningContent(block));
+                    }
+                }
+            }
             
             // Add text content if not empty
             if !assistant_msg.content.trim().is_empty() {
@@ -225,10 +213,173 @@ fn convert_chat_message_to_bedrock(msg: &ChatMessage) -> Result<Message> {
     }
 }
 
-/// Convert internal tools to Bedrock tool configuration
-pub fn convert_tools_to_bedrock(tools: Option<&Vec<Tool>>) -> Option<ToolConfiguration> {
+/// Convert the text, tool results and attachments of a user message to Bedrock content blocks
+fn convert_user_content(
+    user_msg: &UserInputMessage,
+    model_info: &ModelInfo,
+    document_names: &mut DocumentNames,
+) -> Result<Vec<ContentBlock>> {
+    let mut content_blocks = Vec::new();
+
+    // Add text content if not empty
+    let has_text = !user_msg.content.trim().is_empty();
+    if has_text {
+        content_blocks.push(ContentBlock::Text(user_msg.content.clone()));
+    }
+
+    // Add tool results if present
+    if let Some(context) = &user_msg.user_input_message_context {
+        content_blocks.extend(convert_tool_results(context)?);
+    }
+
+    let images = user_msg.images.as_deref().unwrap_or_default();
+    let documents = user_msg.documents.as_deref().unwrap_or_default();
+    if images.is_empty() && documents.is_empty() {
+        return Ok(content_blocks);
+    }
+
+    if !model_info.supports_attachments {
+        tracing::debug!(
+            "Model {} does not accept attachments, dropping {} images and {} documents",
+            model_info.model_id,
+            images.len(),
+            documents.len()
+        );
+        content_blocks.push(ContentBlock::Text(format!(
+            "[{} image(s) and {} document(s) were not attached because the selected model does not accept them]",
+            images.len(),
+            documents.len()
+        )));
+        return Ok(content_blocks);
+    }
+
+    for image in images {
+        if let Some(image) = convert_image(image)? {
+            content_blocks.push(ContentBlock::Image(image));
+        }
+    }
+
+    let mut names = Vec::new();
+    for document in documents {
+        if let Some(document) = convert_document(document, document_names)? {
+            names.push(document.name.clone());
+            content_blocks.push(ContentBlock::Document(document));
+        }
+    }
+
+    // Documents must be accompanied by a text block
+    if !has_text && !names.is_empty() {
+        content_blocks.push(ContentBlock::Text(format!("Attached documents: {}", names.join(", "))));
+    }
+
+    Ok(content_blocks)
+}
+
+fn convert_tool_results(context: &UserInputMessageContext) -> Result<Vec<ContentBlock>> {
+    let mut content_blocks = Vec::new();
+    if let Some(tool_results) = &context.tool_results {
+        for result in tool_results {
+            let status = match result.status {
+                crate::api_client::model::ToolResultStatus::Success => 
+                    aws_sdk_bedrockruntime::types::ToolResultStatus::Success,
+                crate::api_client::model::ToolResultStatus::Error => 
+                    aws_sdk_bedrockruntime::types::ToolResultStatus::Error,
+            };
+            
+            let mut result_content = Vec::new();
+            for item in &result.content {
+                match item {
+                    crate::api_client::model::ToolResultContentBlock::Text(text) => {
+                        result_content.push(
+                            aws_sdk_bedrockruntime::types::ToolResultContentBlock::Text(text.clone())
+                        );
+                    }
+                    crate::api_client::model::ToolResultContentBlock::Json(doc) => {
+                        result_content.push(
+                            aws_sdk_bedrockruntime::types::ToolResultContentBlock::Json(doc.clone())
+                        );
+                    }
+                }
+            }
+            
+            content_blocks.push(
+                ContentBlock::ToolResult(
+                    aws_sdk_bedrockruntime::types::ToolResultBlock::builder()
+                        .tool_use_id(&result.tool_use_id)
+                        .set_content(Some(result_content))
+                        .status(status)
+                        .build()?
+                )
+            );
+        }
+    }
+    Ok(content_blocks)
+}
+
+fn convert_image(image: &ImageBlock) -> Result<Option<BedrockImageBlock>> {
+    let ImageSource::Bytes(bytes) = &image.source else {
+        return Ok(None);
+    };
+    let format = match image.format {
+        ImageFormat::Gif => BedrockImageFormat::Gif,
+        ImageFormat::Jpeg => BedrockImageFormat::Jpeg,
+        ImageFormat::Png => BedrockImageFormat::Png,
+        ImageFormat::Webp => BedrockImageFormat::Webp,
+    };
+    Ok(Some(
+        BedrockImageBlock::builder()
+            .format(format)
+            .source(BedrockImageSource::Bytes(Blob::new(bytes.clone())))
+            .build()?,
+    ))
+}
+
+fn convert_document(document: &DocumentBlock, document_names: &mut DocumentNames) -> Result<Option<BedrockDocumentBlock>> {
+    let DocumentSource::Bytes(bytes) = &document.source else {
+        return Ok(None);
+    };
+    let format = match document.format {
+        DocumentFormat::Csv => BedrockDocumentFormat::Csv,
+        DocumentFormat::Doc => BedrockDocumentFormat::Doc,
+        DocumentFormat::Docx => BedrockDocumentFormat::Docx,
+        DocumentFormat::Html => BedrockDocumentFormat::Html,
+        DocumentFormat::Md => BedrockDocumentFormat::Md,
+        DocumentFormat::Pdf => BedrockDocumentFormat::Pdf,
+        DocumentFormat::Txt => BedrockDocumentFormat::Txt,
+        DocumentFormat::Xls => BedrockDocumentFormat::Xls,
+        DocumentFormat::Xlsx => BedrockDocumentFormat::Xlsx,
+    };
+    Ok(Some(
+        BedrockDocumentBlock::builder()
+            .format(format)
+            .name(document_names.unique(&document.name))
+            .source(BedrockDocumentSource::Bytes(Blob::new(bytes.clone())))
+            .build()?,
+    ))
+}
+
+/// Bedrock rejects requests where two documents share a name, so repeated names are suffixed
+/// with a counter.
+#[derive(Debug, Default)]
+struct DocumentNames(HashMap<String, usize>);
+
+impl DocumentNames {
+    fn unique(&mut self, name: &str) -> String {
+        let count = self.0.entry(name.to_string()).or_default();
+        *count += 1;
+        if *count == 1 {
+            name.to_string()
+        } else {
+            format!("{} ({})", name, count)
+        }
+    }
+}
+
+/// Convert internal tools to Bedrock tool configuration, ending with a cache point when
+/// `prompt_caching` is set
+pub fn convert_tools_to_bedrock(tools: Option<&Vec<Tool>>, prompt_caching: bool) -> Option<ToolConfiguration> {
     tools.map(|tool_list| {
-        let bedrock_tools: Vec<BedrockTool> = tool_list
+        let mut bedrock_tools: Vec<BedrockTool> = tool_list
             .iter()
             .filter_map(|tool| {
                 // Extract ToolSpecification from Tool enum
@@ -252,6 +403,14 @@ pub fn convert_tools_to_bedrock(tools: Option<&Vec<Tool>>) -> Option<ToolConfigu
             })
             .collect();
 
+        if prompt_caching && !bedrock_tools.is_empty() {
+            // Tools are still sent if the cache point can't be built, just without caching them
+            match cache_point() {
+                Ok(point) => bedrock_tools.push(BedrockTool::CachePoint(point)),
+                Err(err) => tracing::warn!("Failed to build cache point for tools: {}", err),
+            }
+        }
+
         ToolConfiguration::builder()
             .set_tools(Some(bedrock_tools))
             .build()
@@ -260,10 +419,12 @@ pub fn convert_tools_to_bedrock(tools: Option<&Vec<Tool>>) -> Option<ToolConfigu
     .flatten()
 }
 
-/// Extract system prompt from model and agent configuration
+/// Extract system prompt from model and agent configuration, ending with a cache point when
+/// `prompt_caching` is set
 pub fn extract_system_prompt(
     model_system_prompt: Option<&str>,
     agent_prompt: Option<&str>,
+    prompt_caching: bool,
 ) -> Option<Vec<SystemContentBlock>> {
     let mut blocks = Vec::new();
     
@@ -288,6 +449,419 @@ pub fn extract_system_prompt(
     if blocks.is_empty() {
         None
     } else {
+        if prompt_caching {
+            match cache_point() {
+                Ok(point) => blocks.push(SystemContentBlock::CachePoint(point)),
+                Err(err) => tracing::warn!("Failed to build cache point for system prompt: {}", err),
+            }
+        }
         Some(blocks)
     }
 }
+
+/// Convert a reasoning block from a previous response. Text without a signature is skipped since
+/// it can't be verified by the model.
+fn convert_reasoning(reasoning: &ReasoningContent) -> Result<Option<ReasoningContentBlock>> {
+    match reasoning {
+        ReasoningContent::Text {
+            text,
+            signature: Some(signature),
+        } => Ok(Some(ReasoningContentBlock::ReasoningText(
+            ReasoningTextBlock::builder()
+                .text(text)
+                .signature(signature)
+                .build()?,
+        ))),
+        ReasoningContent::Text { signature: None, .. } => Ok(None),
+        ReasoningContent::Redacted(data) => Ok(Some(ReasoningContentBlock::RedactedContent(Blob::new(data.clone())))),
+    }
+}
+
+/// Builds the `additionalModelRequestFields` that enable reasoning, along with the max tokens
+/// to request since the budget counts towards the output tokens.
+pub fn reasoning_request_fields(budget_tokens: u32) -> (Document, i32) {
+    let budget_tokens = budget_tokens.max(MIN_REASONING_BUDGET_TOKENS);
+    let thinking = Document::Object(HashMap::from([
+        ("type".to_string(), Document::String("enabled".to_string())),
+        (
+            "budget_tokens".to_string(),
+            Document::Number(aws_smithy_types::Number::PosInt(budget_tokens.into())),
+        ),
+    ]));
+    let max_tokens = budget_tokens.saturating_add(REASONING_RESPONSE_TOKENS);
+    (
+        Document::Object(HashMap::from([("thinking".to_string(), thinking)])),
+        i32::try_from(max_tokens).unwrap_or(i32::MAX),
+    )
+}
+
+/// Convert an agent's guardrail to the Bedrock guardrail configuration.
+///
+/// Guardrails are evaluated synchronously so that blocked content is not streamed to the user.
+pub fn convert_guardrail_config(config: &GuardrailConfig) -> GuardrailStreamConfiguration {
+    let trace = match config.trace {
+        GuardrailTraceMode::Enabled => GuardrailTrace::Enabled,
+        GuardrailTraceMode::EnabledFull => GuardrailTrace::EnabledFull,
+        GuardrailTraceMode::Disabled => GuardrailTrace::Disabled,
+    };
+    GuardrailStreamConfiguration::builder()
+        .guardrail_identifier(&config.identifier)
+        .guardrail_version(&config.version)
+        .trace(trace)
+        .stream_processing_mode(GuardrailStreamProcessingMode::Sync)
+        .build()
+}
+
+/// Summarizes the policies a guardrail acted on, one line per policy match.
+pub fn summarize_guardrail_trace(trace: &GuardrailTraceAssessment) -> Vec<String> {
+    let mut lines = Vec::new();
+    let inputs = trace.input_assessment.iter().flat_map(|a| a.values());
+    let outputs = trace.output_assessments.iter().flat_map(|a| a.values().flatten());
+    for assessment in inputs.chain(outputs) {
+        for line in summarize_guardrail_assessment(assessment) {
+            if !lines.contains(&line) {
+                lines.push(line);
+            }
+        }
+    }
+    if let Some(reason) = &trace.action_reason {
+        lines.push(reason.clone());
+    }
+    lines
+}
+
+fn summarize_guardrail_assessment(assessment: &GuardrailAssessment) -> Vec<String> {
+    // Full traces include the policies that were evaluated but did not act.
+    let acted = |action: &str| action != "NONE";
+    let mut lines = Vec::new();
+
+    if let Some(policy) = &assessment.topic_policy {
+        for topic in policy.topics.iter().filter(|t| acted(t.action.as_str())) {
+            lines.push(format!("Denied topic \"{}\" ({})", topic.name, topic.action.as_str()));
+        }
+    }
+    if let Some(policy) = &assessment.content_policy {
+        for filter in policy.filters.iter().filter(|f| acted(f.action.as_str())) {
+            lines.push(format!(
+                "Content filter {} with {} confidence ({})",
+                filter.r#type.as_str(),
+                filter.confidence.as_str(),
+                filter.action.as_str()
+            ));
+        }
+    }
+    if let Some(policy) = &assessment.word_policy {
+        for word in policy.custom_words.iter().filter(|w| acted(w.action.as_str())) {
+            lines.push(format!("Word \"{}\" ({})", word.r#match, word.action.as_str()));
+        }
+        for word in policy.managed_word_lists.iter().filter(|w| acted(w.action.as_str())) {
+            lines.push(format!(
+                "Word \"{}\" from the {} list ({})",
+                word.r#match,
+                word.r#type.as_str(),
+                word.action.as_str()
+            ));
+        }
+    }
+    if let Some(policy) = &assessment.sensitive_information_policy {
+        for entity in policy.pii_entities.iter().filter(|e| acted(e.action.as_str())) {
+            lines.push(format!(
+                "Sensitive information {} ({})",
+                entity.r#type.as_str(),
+                entity.action.as_str()
+            ));
+        }
+        for regex in policy.regexes.iter().filter(|r| acted(r.action.as_str())) {
+            lines.push(format!(
+                "Sensitive information pattern {} ({})",
+                regex.name.as_deref().unwrap_or("regex"),
+                regex.action.as_str()
+            ));
+        }
+    }
+    if let Some(filters) = assessment
+        .contextual_grounding_policy
+        .as_ref()
+        .and_then(|p| p.filters.as_ref())
+    {
+        for filter in filters.iter().filter(|f| acted(f.action.as_str())) {
+            lines.push(format!(
+                "Contextual grounding {} scored {:.2}, below the threshold of {:.2} ({})",
+                filter.r#type.as_str(),
+                filter.score,
+                filter.threshold,
+                filter.action.as_str()
+            ));
+        }
+    }
+    lines
+}
+
+fn cache_point() -> Result<CachePointBlock> {
+    Ok(CachePointBlock::builder().r#type(CachePointType::Default).build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_message_with_attachments() -> UserInputMessage {
+        UserInputMessage {
+            content: "What is wrong with this page?".to_string(),
+            user_input_message_context: None,
+            user_intent: None,
+            images: Some(vec![ImageBlock {
+                format: ImageFormat::Png,
+                source: ImageSource::Bytes(vec![1, 2, 3]),
+            }]),
+            documents: Some(vec![
+                DocumentBlock {
+                    format: DocumentFormat::Pdf,
+                    name: "spec".to_string(),
+                    source: DocumentSource::Bytes(vec![4, 5, 6]),
+                },
+                DocumentBlock {
+                    format: DocumentFormat::Docx,
+                    name: "spec".to_string(),
+                    source: DocumentSource::Bytes(vec![7, 8, 9]),
+                },
+            ]),
+            model_id: None,
+        }
+    }
+
+    #[test]
+    fn test_convert_attachments() {
+        let model_info = ModelInfo {
+            supports_attachments: true,
+            ..ModelInfo::from_id("us.anthropic.claude-haiku-4-5-20251001-v1:0".to_string())
+        };
+        let messages = convert_to_bedrock_messages(&user_message_with_attachments(), None, &model_info, false).unwrap();
+        assert_eq!(messages.len(), 1);
+
+        let content = messages[0].content();
+        assert_eq!(content.len(), 4);
+        assert!(matches!(&content[0], ContentBlock::Text(_)));
+        let ContentBlock::Image(image) = &content[1] else {
+            panic!("expected an image block");
+        };
+        assert_eq!(image.format, BedrockImageFormat::Png);
+        let ContentBlock::Document(first) = &content[2] else {
+            panic!("expected a document block");
+        };
+        let ContentBlock::Document(second) = &content[3] else {
+            panic!("expected a document block");
+        };
+        assert_eq!(first.name, "spec");
+        assert_eq!(second.name, "spec (2)");
+        assert_eq!(second.format, BedrockDocumentFormat::Docx);
+    }
+
+    #[test]
+    fn test_convert_attachments_unsupported() {
+        let model_info = ModelInfo::from_id("deepseek.v3-v1:0".to_string());
+        let messages = convert_to_bedrock_messages(&user_message_with_attachments(), None, &model_info, false).unwrap();
+
+        let content = messages[0].content();
+        assert_eq!(content.len(), 2);
+        assert!(content.iter().all(|block| matches!(block, ContentBlock::Text(_))));
+    }
+
+    #[test]
+    fn test_documents_without_text() {
+        let model_info = ModelInfo {
+            supports_attachments: true,
+            ..ModelInfo::from_id("amazon.nova-pro-v1:0".to_string())
+        };
+        let user_input = UserInputMessage {
+            content: String::new(),
+            images: None,
+            ..user_message_with_attachments()
+        };
+        let messages = convert_to_bedrock_messages(&user_input, None, &model_info, false).unwrap();
+
+        let content = messages[0].content();
+        let ContentBlock::Text(text) = content.last().unwrap() else {
+            panic!("expected a text block");
+        };
+        assert_eq!(text, "Attached documents: spec, spec (2)");
+    }
+
+    #[test]
+    fn test_cache_points() {
+        let model_info = ModelInfo {
+            supports_prompt_caching: true,
+            ..ModelInfo::from_id("us.anthropic.claude-haiku-4-5-20251001-v1:0".to_string())
+        };
+        let history = vec![
+            ChatMessage::UserInputMessage(UserInputMessage {
+                images: None,
+                documents: None,
+                ..user_message_with_attachments()
+            }),
+            ChatMessage::AssistantResponseMessage(crate::api_client::model::AssistantResponseMessage {
+                message_id: None,
+                content: "The button is misaligned.".to_string(),
+                tool_uses: None,
+                reasoning: None,
+            }),
+        ];
+        let user_input = UserInputMessage {
+            content: "How do I fix it?".to_string(),
+            images: None,
+            documents: None,
+            ..user_message_with_attachments()
+        };
+
+        let messages = convert_to_bedrock_messages(&user_input, Some(&history), &model_info, false).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert!(matches!(messages[1].content().last(), Some(ContentBlock::CachePoint(_))));
+        assert!(!messages[2].content().iter().any(|b| matches!(b, ContentBlock::CachePoint(_))));
+
+        let system = extract_system_prompt(None, Some("Be brief."), true).unwrap();
+        assert!(matches!(system.last(), Some(SystemContentBlock::CachePoint(_))));
+        assert!(extract_system_prompt(None, None, true).is_none());
+
+        let without_caching = ModelInfo::from_id("deepseek.v3-v1:0".to_string());
+        let messages = convert_to_bedrock_messages(&user_input, Some(&history), &without_caching, false).unwrap();
+        assert!(
+            !messages
+                .iter()
+                .flat_map(|m| m.content())
+                .any(|b| matches!(b, ContentBlock::CachePoint(_)))
+        );
+    }
+
+    #[test]
+    fn test_reasoning_in_history() {
+        let model_info = ModelInfo::from_id("us.anthropic.claude-haiku-4-5-20251001-v1:0".to_string());
+        let history = vec![
+            ChatMessage::UserInputMessage(UserInputMessage {
+                images: None,
+                documents: None,
+                ..user_message_with_attachments()
+            }),
+            ChatMessage::AssistantResponseMessage(crate::api_client::model::AssistantResponseMessage {
+                message_id: None,
+                content: "The button is misaligned.".to_string(),
+                tool_uses: None,
+                reasoning: Some(vec![
+                    ReasoningContent::Text {
+                        text: "Look at the layout.".to_string(),
+                        signature: Some("sig".to_string()),
+                    },
+                    ReasoningContent::Text {
+                        text: "Unsigned".to_string(),
+                        signature: None,
+                    },
+                    ReasoningContent::Redacted(vec![1, 2]),
+                ]),
+            }),
+        ];
+        let user_input = UserInputMessage {
+            content: "How do I fix it?".to_string(),
+            images: None,
+            documents: None,
+            ..user_message_with_attachments()
+        };
+
+        let messages = convert_to_bedrock_messages(&user_input, Some(&history), &model_info, true).unwrap();
+        let content = messages[1].content();
+        assert_eq!(content.len(), 3);
+        let ContentBlock::ReasoningContent(ReasoningContentBlock::ReasoningText(text)) = &content[0] else {
+            panic!("expected a reasoning text block");
+        };
+        assert_eq!(text.text, "Look at the layout.");
+        assert_eq!(text.signature.as_deref(), Some("sig"));
+        assert!(matches!(
+            &content[1],
+            ContentBlock::ReasoningContent(ReasoningContentBlock::RedactedContent(_))
+        ));
+        assert!(matches!(&content[2], ContentBlock::Text(_)));
+
+        let messages = convert_to_bedrock_messages(&user_input, Some(&history), &model_info, false).unwrap();
+        assert_eq!(messages[1].content().len(), 1);
+    }
+
+    #[test]
+    fn test_reasoning_request_fields() {
+        let (fields, max_tokens) = reasoning_request_fields(100);
+        assert_eq!(max_tokens, 1024 + 8192);
+        let Document::Object(fields) = fields else {
+            panic!("expected an object");
+        };
+        let Some(Document::Object(thinking)) = fields.get("thinking") else {
+            panic!("expected a thinking object");
+        };
+        assert_eq!(thinking.get("type"), Some(&Document::String("enabled".to_string())));
+        assert_eq!(
+            thinking.get("budget_tokens"),
+            Some(&Document::Number(aws_smithy_types::Number::PosInt(1024)))
+        );
+    }
+
+    #[test]
+    fn test_summarize_guardrail_trace() {
+        use aws_sdk_bedrockruntime::types::{
+            GuardrailContentFilter,
+            GuardrailContentFilterConfidence,
+            GuardrailContentFilterType,
+            GuardrailContentPolicyAction,
+            GuardrailContentPolicyAssessment,
+            GuardrailTopic,
+            GuardrailTopicPolicyAction,
+            GuardrailTopicPolicyAssessment,
+            GuardrailTopicType,
+        };
+
+        let assessment = GuardrailAssessment::builder()
+            .topic_policy(
+                GuardrailTopicPolicyAssessment::builder()
+                    .topics(
+                        GuardrailTopic::builder()
+                            .name("Investment advice")
+                            .r#type(GuardrailTopicType::Deny)
+                            .action(GuardrailTopicPolicyAction::Blocked)
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+                    .unwrap(),
+            )
+            .content_policy(
+                GuardrailContentPolicyAssessment::builder()
+                    .filters(
+                        GuardrailContentFilter::builder()
+                            .r#type(GuardrailContentFilterType::Violence)
+                            .confidence(GuardrailContentFilterConfidence::Low)
+                            .action(GuardrailContentPolicyAction::None)
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+                    .unwrap(),
+            )
+            .build();
+        let trace = GuardrailTraceAssessment::builder()
+            .input_assessment("gr-abc123", assessment.clone())
+            .output_assessments("gr-abc123", vec![assessment])
+            .build();
+
+        assert_eq!(summarize_guardrail_trace(&trace), vec![
+            "Denied topic \"Investment advice\" (BLOCKED)".to_string()
+        ]);
+    }
+
+    #[test]
+    fn test_convert_guardrail_config() {
+        let config = convert_guardrail_config(&GuardrailConfig {
+            identifier: "gr-abc123".to_string(),
+            version: "DRAFT".to_string(),
+            trace: GuardrailTraceMode::EnabledFull,
+        });
+        assert_eq!(config.guardrail_identifier, "gr-abc123");
+        assert_eq!(config.guardrail_version, "DRAFT");
+        assert_eq!(config.trace, GuardrailTrace::EnabledFull);
+        assert_eq!(config.stream_processing_mode, GuardrailStreamProcessingMode::Sync);
+    }
+}
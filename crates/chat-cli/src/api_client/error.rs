@@ -19,6 +19,8 @@ use aws_smithy_runtime_api::http::Response;
 use aws_smithy_types::event_stream::RawMessage;
 use thiserror::Error;
 
+use crate::api_client::model_catalog::ModelCatalogError;
+use crate::api_client::provider::OpenAiError;
 use crate::aws_common::SdkErrorDisplay;
 use crate::telemetry::ReasonCode;
 
@@ -69,6 +71,12 @@ pub enum ApiClientError {
     #[error("No default model found in the ListAvailableModels API response")]
     DefaultModelNotFound,
 
+    #[error("failed to discover Bedrock models: {}", .0)]
+    ModelCatalog(#[from] ModelCatalogError),
+
+    #[error("OpenAI compatible endpoint error: {}", .0)]
+    OpenAi(#[from] OpenAiError),
+
     #[error(transparent)]
     GetProfileError(#[from] SdkError<GetProfileError, HttpResponse>),
 }
@@ -90,6 +98,8 @@ impl ApiClientError {
             Self::Credentials(_e) => None,
             Self::ListAvailableModelsError(e) => sdk_status_code(e),
             Self::DefaultModelNotFound => None,
+            Self::ModelCatalog(e) => e.status_code(),
+            Self::OpenAi(e) => e.status_code(),
             Self::GetProfileError(e) => sdk_status_code(e),
         }
     }
@@ -112,6 +122,8 @@ impl ReasonCode for ApiClientError {
             Self::Credentials(_) => "CredentialsError".to_string(),
             Self::ListAvailableModelsError(e) => sdk_error_code(e),
             Self::DefaultModelNotFound => "DefaultModelNotFound".to_string(),
+            Self::ModelCatalog(_) => "ModelCatalogError".to_string(),
+            Self::OpenAi(_) => "OpenAiError".to_string(),
             Self::GetProfileError(e) => sdk_error_code(e),
         }
     }
@@ -160,6 +172,7 @@ impl ReasonCode for ConverseStreamError {
             ConverseStreamErrorKind::ModelNotAvailable => "ModelNotAvailable".to_string(),
             ConverseStreamErrorKind::MessageConversion => "MessageConversion".to_string(),
             ConverseStreamErrorKind::ApiError => "ApiError".to_string(),
+            ConverseStreamErrorKind::GuardrailIntervened { .. } => "GuardrailIntervened".to_string(),
             ConverseStreamErrorKind::Unknown { reason_code } => reason_code.clone(),
         }
     }
@@ -196,7 +209,7 @@ pub enum ConverseStreamErrorKind {
         "The model you've selected is temporarily unavailable. Please use '/model' to select a different model and try again."
     )]
     ModelOverloadedError,
-    #[error("This model is not supported in this deployment.")]
+    #[error("The selected model is not a valid Bedrock model identifier. Please use '/model' to select a different model.")]
     InvalidModel,
     #[error("Model may not be enabled in your AWS region. Check Bedrock model availability.")]
     ModelNotAvailable,
@@ -204,6 +217,10 @@ pub enum ConverseStreamErrorKind {
     MessageConversion,
     #[error("API error occurred")]
     ApiError,
+    /// A Bedrock guardrail blocked the request or the response. Contains a summary of the
+    /// guardrail trace, if one was returned.
+    #[error("The request was blocked by a guardrail")]
+    GuardrailIntervened { trace: Vec<String> },
     #[error("An unknown error occurred: {}", .reason_code)]
     Unknown { reason_code: String },
 }
@@ -221,6 +238,8 @@ pub enum ConverseStreamSdkError {
     BedrockError(#[from] aws_sdk_bedrockruntime::Error),
     #[error(transparent)]
     SmithyBuild(#[from] aws_smithy_types::error::operation::BuildError),
+    #[error(transparent)]
+    OpenAi(#[from] OpenAiError),
 }
 
 pub fn sdk_error_code<T: ProvideErrorMetadata, R>(e: &SdkError<T, R>) -> String {
@@ -308,6 +327,8 @@ mod tests {
                 raw_message(),
             )),
             ApiClientError::SmithyBuild(aws_smithy_types::error::operation::BuildError::other("<other>")),
+            ApiClientError::ModelCatalog(ModelCatalogError::MissingRegion),
+            ApiClientError::OpenAi(OpenAiError::Stream("<message>".to_string())),
         ]
     }
 
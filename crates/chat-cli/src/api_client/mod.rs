@@ -5,18 +5,18 @@ mod delay_interceptor;
 mod endpoints;
 pub mod error;
 pub mod model;
+pub mod model_catalog;
 mod opt_out;
 pub mod profile;
+pub mod provider;
 mod retry_classifier;
 pub mod send_message_output;
-use std::sync::Arc;
 use std::time::Duration;
 
 use amzn_codewhisperer_client::Client as CodewhispererClient;
 use amzn_codewhisperer_client::operation::create_subscription_token::CreateSubscriptionTokenOutput;
 use amzn_codewhisperer_client::types::Origin::Cli;
 use amzn_codewhisperer_client::types::{
-    Model,
     OptInFeatureToggle,
     OptOutPreference,
     SubscriptionStatus,
@@ -31,7 +31,6 @@ use aws_config::timeout::TimeoutConfig;
 use aws_credential_types::Credentials;
 use aws_credential_types::provider::ProvideCredentials;
 use aws_sdk_ssooidc::error::ProvideErrorMetadata;
-use aws_types::request_id::RequestId;
 use aws_types::sdk_config::StalledStreamProtectionConfig;
 pub use endpoints::Endpoint;
 pub use error::ApiClientError;
@@ -39,24 +38,32 @@ use error::{
     ConverseStreamError,
     ConverseStreamErrorKind,
 };
-use parking_lot::Mutex;
 pub use profile::list_available_profiles;
-use serde_json::Map;
-use tokio::sync::RwLock;
 use tracing::{
     debug,
     error,
+    warn,
 };
 
 use crate::api_client::credentials::CredentialsChain;
 use crate::api_client::delay_interceptor::DelayTrackingInterceptor;
-use crate::api_client::model::{
-    ChatResponseStream,
-    ConversationState,
-};
+use crate::api_client::model::ConversationState;
+use crate::api_client::model_catalog::ModelCatalogCache;
 use crate::api_client::opt_out::OptOutInterceptor;
+use crate::api_client::provider::{
+    BedrockProvider,
+    DEFAULT_OPENAI_ENDPOINT,
+    MockProvider,
+    ModelProvider,
+    OpenAiProvider,
+    ProviderKind,
+};
 use crate::api_client::send_message_output::SendMessageOutput;
 use crate::auth::builder_id::BearerResolver;
+use crate::cli::chat::cli::model::{
+    ModelInfo,
+    get_default_model,
+};
 use crate::aws_common::{
     UserAgentOverrideInterceptor,
     app_name,
@@ -71,7 +78,10 @@ use crate::os::{
     Env,
     Fs,
 };
-use crate::util::env_var::is_integ_test;
+use crate::util::env_var::{
+    get_openai_api_key,
+    is_integ_test,
+};
 
 // Opt out constants
 pub const X_AMZN_CODEWHISPERER_OPT_OUT_HEADER: &str = "x-amzn-codewhisperer-optout";
@@ -81,24 +91,18 @@ const DEFAULT_TIMEOUT_DURATION: Duration = Duration::from_secs(60 * 5);
 
 pub const MAX_RETRY_DELAY_DURATION: Duration = Duration::from_secs(10);
 
-#[derive(Clone, Debug)]
-pub struct ModelListResult {
-    pub models: Vec<Model>,
-    pub default_model: Model,
-}
-
-impl From<ModelListResult> for (Vec<Model>, Model) {
-    fn from(v: ModelListResult) -> Self {
-        (v.models, v.default_model)
-    }
-}
+/// How long a discovered model catalog is reused before Bedrock is queried again.
+const MODEL_CATALOG_TTL: Duration = Duration::from_secs(60 * 60 * 24);
 
 #[derive(Clone, Debug)]
 pub struct ApiClient {
-    bedrock_client: aws_sdk_bedrockruntime::Client,
+    bedrock: BedrockProvider,
+    openai: OpenAiProvider,
+    /// Provider for models that don't specify one
+    default_provider: ProviderKind,
     // Keep legacy client for telemetry and other non-chat operations
     client: CodewhispererClient,
-    mock_client: Option<Arc<Mutex<std::vec::IntoIter<Vec<ChatResponseStream>>>>>,
+    mock: Option<MockProvider>,
     profile: Option<AuthProfile>,
 }
 
@@ -111,7 +115,15 @@ impl ApiClient {
     ) -> Result<Self, ApiClientError> {
         // Load AWS config for Bedrock
         let aws_config = aws_config::load_from_env().await;
-        let bedrock_client = aws_sdk_bedrockruntime::Client::new(&aws_config);
+        let bedrock = BedrockProvider::new(&aws_config);
+        let openai = OpenAiProvider::new(
+            database
+                .settings
+                .get_string(Setting::ApiOpenAiEndpoint)
+                .unwrap_or_else(|| DEFAULT_OPENAI_ENDPOINT.to_string()),
+            get_openai_api_key(env),
+        );
+        let default_provider = ProviderKind::configured(database);
 
         // Keep legacy client for telemetry (uses dummy credentials)
         let endpoint = endpoint.unwrap_or(Endpoint::configured_value(database));
@@ -137,9 +149,11 @@ impl ApiClient {
         // Handle test mocking
         if cfg!(test) && !is_integ_test() {
             let mut this = Self {
-                bedrock_client,
+                bedrock,
+                openai,
+                default_provider,
                 client,
-                mock_client: None,
+                mock: None,
                 profile: None,
             };
 
@@ -151,9 +165,11 @@ impl ApiClient {
         }
 
         Ok(Self {
-            bedrock_client,
+            bedrock,
+            openai,
+            default_provider,
             client,
-            mock_client: None,
+            mock: None,
             profile: None,
         })
     }
@@ -208,36 +224,51 @@ impl ApiClient {
         Ok(profiles)
     }
 
-    // Legacy function - no longer used with Bedrock
-    pub async fn list_available_models(&self) -> Result<ModelListResult, ApiClientError> {
+    /// Discovers the models served by the configured provider, for Bedrock these are the text
+    /// models available in the configured region.
+    // Same error type as the other requests of the client
+    #[allow(clippy::result_large_err)]
+    pub async fn list_available_models(&self) -> Result<Vec<ModelInfo>, ApiClientError> {
         if cfg!(test) {
-            let m = Model::builder()
-                .model_id("model-1")
-                .description("Test Model 1")
-                .build()
-                .unwrap();
-
-            return Ok(ModelListResult {
-                models: vec![m.clone()],
-                default_model: m,
-            });
+            return Ok(vec![]);
         }
-        Err(ApiClientError::DefaultModelNotFound)
-    }
 
-    // Legacy function - no longer used with Bedrock
-    pub async fn list_available_models_cached(&self) -> Result<ModelListResult, ApiClientError> {
-        self.list_available_models().await
+        self.provider(self.default_provider).list_models().await
     }
 
-    // Legacy function - no longer used with Bedrock
-    pub async fn invalidate_model_cache(&self) {
-        // No-op
+    /// Same as [ApiClient::list_available_models], but reuses the Bedrock catalog cached in the
+    /// database while it is fresh and was discovered in the same region.
+    #[allow(clippy::result_large_err)]
+    pub async fn list_available_models_cached(&self, database: &Database) -> Result<Vec<ModelInfo>, ApiClientError> {
+        if self.default_provider != ProviderKind::Bedrock {
+            return self.list_available_models().await;
+        }
+
+        let region = self.bedrock.region().unwrap_or_default().to_string();
+        match database.get_model_catalog() {
+            Ok(Some(cache)) if cache.region == region && !cache.is_expired(MODEL_CATALOG_TTL) => {
+                return Ok(cache.models);
+            },
+            Ok(_) => (),
+            Err(err) => warn!(?err, "Failed to read the cached model catalog"),
+        }
+
+        let models = self.list_available_models().await?;
+        if models.is_empty() {
+            return Ok(models);
+        }
+
+        if let Err(err) = database.set_model_catalog(&ModelCatalogCache::new(region, models.clone())) {
+            warn!(?err, "Failed to cache the model catalog");
+        }
+
+        Ok(models)
     }
 
-    // Legacy function - no longer used with Bedrock
-    pub async fn get_available_models(&self, _region: &str) -> Result<ModelListResult, ApiClientError> {
-        self.list_available_models().await
+    pub async fn invalidate_model_cache(&self, database: &Database) {
+        if let Err(err) = database.unset_model_catalog() {
+            warn!(?err, "Failed to invalidate the cached model catalog");
+        }
     }
 
     pub async fn is_mcp_enabled(&self) -> Result<bool, ApiClientError> {
@@ -267,148 +298,26 @@ impl ApiClient {
     ) -> Result<SendMessageOutput, ConverseStreamError> {
         debug!("Sending conversation: {:#?}", conversation);
 
-        let ConversationState {
-            conversation_id,
-            user_input_message,
-            history,
-            service_tier,
-            model_system_prompt,
-            agent_prompt,
-        } = conversation;
-
-        let model_id = user_input_message.model_id.clone()
-            .unwrap_or_else(|| crate::cli::chat::cli::model::get_default_model().model_id);
-
-        debug!("Sending message to Bedrock with model: {}", model_id);
-
-        // Validate model ID
-        if let Err(e) = crate::cli::chat::cli::model::validate_model_id(&model_id) {
-            return Err(ConverseStreamError::new(
-                ConverseStreamErrorKind::InvalidModel,
-                None::<aws_sdk_bedrockruntime::Error>,
-            ));
-        }
-
-        // Handle mock client for testing
-        if let Some(client) = &self.mock_client {
-            let mut new_events = client.lock().next().unwrap_or_default().clone();
-            new_events.reverse();
-            return Ok(SendMessageOutput::Mock(new_events));
-        }
-
-        // Convert to Bedrock format
-        let messages = bedrock::convert_to_bedrock_messages(
-            &user_input_message,
-            history.as_ref(),
-            model_system_prompt.as_deref(),
-        )
-            .map_err(|e| {
-                debug!("Failed to convert messages: {}", e);
-                ConverseStreamError::new(
-                    ConverseStreamErrorKind::MessageConversion,
-                    None::<aws_sdk_bedrockruntime::Error>,
-                )
-            })?;
-
-        debug!("Converted {} messages for Bedrock", messages.len());
-
-        // Check if model supports tools by looking it up in the builtin list
-        let supports_tools = crate::cli::chat::cli::model::model_supports_tools(&model_id);
-
-        let tools = bedrock::convert_tools_to_bedrock(
-            user_input_message.user_input_message_context.as_ref()
-                .and_then(|ctx| ctx.tools.as_ref())
-        );
-
-        let system_prompt = bedrock::extract_system_prompt(
-            model_system_prompt.as_deref(),
-            agent_prompt.as_deref(),
-        );
-
-        // Call Bedrock Converse Stream API
-        debug!("Calling Bedrock converse_stream API");
-        let mut request = self.bedrock_client
-            .converse_stream()
-            .model_id(model_id.clone())
-            .set_messages(Some(messages));
-
-        // Only pass tools if model supports them
-        if supports_tools {
-            if let Some(tool_config) = tools {
-                debug!("Sending {} tools to Bedrock (model supports tools)", tool_config.tools().len());
-                request = request.tool_config(tool_config);
-            }
-        } else {
-            debug!("Model does not support tools, skipping tool config");
-        }
-
-        // Set service tier
-        if let Some(tier) = service_tier {
-            let tier_type = match tier.as_str() {
-                "flex" => aws_sdk_bedrockruntime::types::ServiceTierType::Flex,
-                _ => aws_sdk_bedrockruntime::types::ServiceTierType::Default,
-            };
-            let service_tier = aws_sdk_bedrockruntime::types::ServiceTier::builder()
-                .r#type(tier_type)
-                .build()?;
-            request = request.service_tier(service_tier);
-            debug!("Using service tier: {}", tier);
+        let model_info = conversation.model_info.clone().unwrap_or_else(get_default_model);
+        match &self.mock {
+            Some(mock) => mock.send_message(conversation, model_info).await,
+            None => {
+                let provider = model_info.provider.unwrap_or(self.default_provider);
+                self.provider(provider).send_message(conversation, model_info).await
+            },
         }
+    }
 
-        match request.send().await {
-            Ok(output) => {
-                debug!("Bedrock request successful, returning stream");
-                Ok(SendMessageOutput::Bedrock(
-                    crate::api_client::send_message_output::SendMessageOutputBedrock::new(output)
-                ))
-            }
-            Err(err) => {
-                debug!("Bedrock request failed: {:?}", err);
-                let request_id = err.meta().request_id().map(|s| s.to_string());
-                let status_code = err.raw_response().map(|res| res.status().as_u16());
-
-                // Check for region-specific errors
-                let error_kind = if let Some(code) = status_code {
-                    if code == 404 {
-                        let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "unknown".to_string());
-                        tracing::error!("Model {} may not be available in region {}", model_id, region);
-                        ConverseStreamErrorKind::ModelNotAvailable
-                    } else {
-                        ConverseStreamErrorKind::ApiError
-                    }
-                } else {
-                    ConverseStreamErrorKind::ApiError
-                };
-
-                Err(ConverseStreamError::new(error_kind, Some(err))
-                    .set_request_id(request_id)
-                    .set_status_code(status_code))
-            }
+    fn provider(&self, kind: ProviderKind) -> &dyn ModelProvider {
+        match kind {
+            ProviderKind::Bedrock => &self.bedrock,
+            ProviderKind::OpenAi => &self.openai,
         }
     }
 
     /// Only meant for testing. Do not use outside of testing responses.
     pub fn set_mock_output(&mut self, json: serde_json::Value) {
-        let mut mock = Vec::new();
-        for response in json.as_array().unwrap() {
-            let mut stream = Vec::new();
-            for event in response.as_array().unwrap() {
-                match event {
-                    serde_json::Value::String(assistant_text) => {
-                        stream.push(ChatResponseStream::AssistantResponseEvent {
-                            content: assistant_text.clone(),
-                        });
-                    },
-                    serde_json::Value::Object(tool_use) => {
-                        stream.append(&mut split_tool_use_event(tool_use));
-                    },
-                    other => panic!("Unexpected value: {:?}", other),
-                }
-            }
-            mock.push(stream);
-        }
-
-        self.mock_client = Some(Arc::new(Mutex::new(mock.into_iter())));
+        self.mock = Some(MockProvider::from_json(json));
     }
 
     // Add a helper method to check if using non-default endpoint
@@ -489,39 +398,6 @@ pub fn stalled_stream_protection_config() -> StalledStreamProtectionConfig {
         .build()
 }
 
-fn split_tool_use_event(value: &Map<String, serde_json::Value>) -> Vec<ChatResponseStream> {
-    let tool_use_id = value.get("tool_use_id").unwrap().as_str().unwrap().to_string();
-    let name = value.get("name").unwrap().as_str().unwrap().to_string();
-    let args_str = value.get("args").unwrap().to_string();
-    let split_point = args_str.len() / 2;
-    vec![
-        ChatResponseStream::ToolUseEvent {
-            tool_use_id: tool_use_id.clone(),
-            name: name.clone(),
-            input: None,
-            stop: None,
-        },
-        ChatResponseStream::ToolUseEvent {
-            tool_use_id: tool_use_id.clone(),
-            name: name.clone(),
-            input: Some(args_str.split_at(split_point).0.to_string()),
-            stop: None,
-        },
-        ChatResponseStream::ToolUseEvent {
-            tool_use_id: tool_use_id.clone(),
-            name: name.clone(),
-            input: Some(args_str.split_at(split_point).1.to_string()),
-            stop: None,
-        },
-        ChatResponseStream::ToolUseEvent {
-            tool_use_id: tool_use_id.clone(),
-            name: name.clone(),
-            input: None,
-            stop: Some(true),
-        },
-    ]
-}
-
 #[cfg(test)]
 mod tests {
     use amzn_codewhisperer_client::types::{
@@ -532,7 +408,10 @@ mod tests {
     use bstr::ByteSlice;
 
     use super::*;
-    use crate::api_client::model::UserInputMessage;
+    use crate::api_client::model::{
+        ChatResponseStream,
+        UserInputMessage,
+    };
 
     #[tokio::test]
     async fn create_clients() {
@@ -569,32 +448,36 @@ mod tests {
             .await
             .unwrap();
 
-        client.mock_client = Some(Arc::new(Mutex::new(
-            vec![vec![
-                ChatResponseStream::AssistantResponseEvent {
-                    content: "Hello!".to_owned(),
-                },
-                ChatResponseStream::AssistantResponseEvent {
-                    content: " How can I".to_owned(),
-                },
-                ChatResponseStream::AssistantResponseEvent {
-                    content: " assist you today?".to_owned(),
-                },
-            ]]
-            .into_iter(),
-        )));
+        client.mock = Some(MockProvider::new(vec![vec![
+            ChatResponseStream::AssistantResponseEvent {
+                content: "Hello!".to_owned(),
+            },
+            ChatResponseStream::AssistantResponseEvent {
+                content: " How can I".to_owned(),
+            },
+            ChatResponseStream::AssistantResponseEvent {
+                content: " assist you today?".to_owned(),
+            },
+        ]]));
 
         let mut output = client
             .send_message(ConversationState {
                 conversation_id: None,
                 user_input_message: UserInputMessage {
                     images: None,
+                    documents: None,
                     content: "Hello".into(),
                     user_input_message_context: None,
                     user_intent: None,
                     model_id: Some("model".to_owned()),
                 },
                 history: None,
+                service_tier: None,
+                model_info: None,
+                agent_prompt: None,
+                reasoning_budget_tokens: None,
+                max_tokens: None,
+                guardrail: None,
             })
             .await
             .unwrap();
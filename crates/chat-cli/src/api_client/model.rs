@@ -17,6 +17,9 @@ use serde::{
     Serializer,
 };
 
+use crate::cli::GuardrailConfig;
+use crate::cli::chat::cli::model::ModelInfo;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FileContext {
@@ -98,8 +101,15 @@ pub struct ConversationState {
     pub user_input_message: UserInputMessage,
     pub history: Option<Vec<ChatMessage>>,
     pub service_tier: Option<String>,
-    pub model_system_prompt: Option<String>,
+    pub model_info: Option<ModelInfo>,
     pub agent_prompt: Option<String>,
+    /// Number of tokens the model may spend on reasoning before answering. [None] disables
+    /// reasoning for the request.
+    pub reasoning_budget_tokens: Option<u32>,
+    /// Maximum number of tokens the model may generate. [None] uses the default of the provider.
+    pub max_tokens: Option<u32>,
+    /// Guardrail of the active agent, applied to the request.
+    pub guardrail: Option<GuardrailConfig>,
 }
 
 #[derive(Debug, Clone)]
@@ -524,6 +534,20 @@ pub struct AssistantResponseMessage {
     pub content: String,
     /// ToolUse Request
     pub tool_uses: Option<Vec<ToolUse>>,
+    /// Reasoning the model produced before the response.
+    pub reasoning: Option<Vec<ReasoningContent>>,
+}
+
+/// A block of reasoning produced by the model.
+///
+/// Reasoning has to be sent back unchanged, including its signature, when continuing a turn
+/// that contains tool uses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReasoningContent {
+    Text { text: String, signature: Option<String> },
+    /// Reasoning that was encrypted by the model provider.
+    Redacted(Vec<u8>),
 }
 
 impl TryFrom<AssistantResponseMessage> for amzn_codewhisperer_streaming_client::types::AssistantResponseMessage {
@@ -579,6 +603,17 @@ pub enum ChatResponseStream {
         input: Option<String>,
         stop: Option<bool>,
     },
+    /// Streaming response event for the model's reasoning text.
+    ReasoningContentEvent {
+        text: String,
+    },
+    /// Signature that closes the reasoning block currently being received.
+    ReasoningSignatureEvent {
+        signature: String,
+    },
+    RedactedReasoningEvent {
+        data: Vec<u8>,
+    },
 
     #[non_exhaustive]
     Unknown,
@@ -601,6 +636,9 @@ impl ChatResponseStream {
             ChatResponseStream::MessageMetadataEvent { .. } => 0,
             ChatResponseStream::SupplementaryWebLinksEvent(_) => 0,
             ChatResponseStream::ToolUseEvent { input, .. } => input.as_ref().map(|s| s.len()).unwrap_or_default(),
+            ChatResponseStream::ReasoningContentEvent { text } => text.len(),
+            ChatResponseStream::ReasoningSignatureEvent { .. } => 0,
+            ChatResponseStream::RedactedReasoningEvent { data } => data.len(),
             ChatResponseStream::Unknown => 0,
         }
     }
@@ -889,12 +927,88 @@ impl From<ImageBlock> for amzn_qdeveloper_streaming_client::types::ImageBlock {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentBlock {
+    pub format: DocumentFormat,
+    /// Name of the document, see [DocumentBlock::sanitize_name] for the characters Bedrock
+    /// accepts.
+    pub name: String,
+    pub source: DocumentSource,
+}
+
+impl DocumentBlock {
+    /// Bedrock only accepts alphanumeric characters, whitespace, hyphens, parentheses and square
+    /// brackets in document names, without consecutive whitespace.
+    pub fn sanitize_name(name: &str) -> String {
+        let mut sanitized = String::with_capacity(name.len());
+        for c in name.chars() {
+            let c = if c.is_ascii_alphanumeric() || matches!(c, '-' | '(' | ')' | '[' | ']') {
+                c
+            } else {
+                ' '
+            };
+            if c == ' ' && (sanitized.is_empty() || sanitized.ends_with(' ')) {
+                continue;
+            }
+            sanitized.push(c);
+        }
+        let sanitized = sanitized.trim_end();
+        if sanitized.is_empty() {
+            "document".to_string()
+        } else {
+            sanitized.to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Csv,
+    Doc,
+    Docx,
+    Html,
+    Md,
+    Pdf,
+    Txt,
+    Xls,
+    Xlsx,
+}
+
+impl std::str::FromStr for DocumentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(DocumentFormat::Csv),
+            "doc" => Ok(DocumentFormat::Doc),
+            "docx" => Ok(DocumentFormat::Docx),
+            "html" => Ok(DocumentFormat::Html),
+            "htm" => Ok(DocumentFormat::Html),
+            "md" => Ok(DocumentFormat::Md),
+            "pdf" => Ok(DocumentFormat::Pdf),
+            "txt" => Ok(DocumentFormat::Txt),
+            "xls" => Ok(DocumentFormat::Xls),
+            "xlsx" => Ok(DocumentFormat::Xlsx),
+            _ => Err(format!("Failed to parse '{}' as DocumentFormat", s)),
+        }
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DocumentSource {
+    Bytes(Vec<u8>),
+    #[non_exhaustive]
+    Unknown,
+}
+
 #[derive(Debug, Clone)]
 pub struct UserInputMessage {
     pub content: String,
     pub user_input_message_context: Option<UserInputMessageContext>,
     pub user_intent: Option<UserIntent>,
     pub images: Option<Vec<ImageBlock>>,
+    pub documents: Option<Vec<DocumentBlock>>,
     pub model_id: Option<String>,
 }
 
@@ -988,6 +1102,7 @@ mod tests {
                 format: ImageFormat::Png,
                 source: ImageSource::Bytes(vec![1, 2, 3]),
             }]),
+            documents: None,
             content: "test content".to_string(),
             user_input_message_context: Some(UserInputMessageContext {
                 env_state: Some(EnvState {
@@ -1026,6 +1141,7 @@ mod tests {
 
         let minimal_message = UserInputMessage {
             images: None,
+            documents: None,
             content: "test content".to_string(),
             user_input_message_context: None,
             user_intent: None,
@@ -1050,6 +1166,7 @@ mod tests {
                     [("key1".to_string(), AwsDocument::Null)].into_iter().collect(),
                 )),
             }]),
+            reasoning: None,
         };
         let codewhisper_input =
             amzn_codewhisperer_streaming_client::types::AssistantResponseMessage::try_from(message.clone()).unwrap();
@@ -1260,4 +1377,12 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_document_block_sanitize_name() {
+        assert_eq!(DocumentBlock::sanitize_name("Q3 report (final)"), "Q3 report (final)");
+        assert_eq!(DocumentBlock::sanitize_name("design_doc.v2"), "design doc v2");
+        assert_eq!(DocumentBlock::sanitize_name("  a  -  b  "), "a - b");
+        assert_eq!(DocumentBlock::sanitize_name("___"), "document");
+    }
 }
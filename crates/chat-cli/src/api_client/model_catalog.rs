@@ -0,0 +1,508 @@
+//! Discovery of the models exposed by the Bedrock control plane.
+//!
+//! `ListFoundationModels` and `ListInferenceProfiles` are plain SigV4 signed REST operations, so
+//! they are called directly instead of pulling in the full control plane SDK for two read-only
+//! requests.
+
+use std::time::{
+    Duration,
+    SystemTime,
+    UNIX_EPOCH,
+};
+
+use aws_credential_types::provider::error::CredentialsError;
+use aws_credential_types::provider::{
+    ProvideCredentials,
+    SharedCredentialsProvider,
+};
+use aws_sigv4::http_request::{
+    SignableBody,
+    SignableRequest,
+    SigningSettings,
+    sign,
+};
+use aws_sigv4::sign::v4;
+use aws_types::SdkConfig;
+use aws_types::region::Region;
+use serde::de::DeserializeOwned;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use thiserror::Error;
+use tracing::{
+    debug,
+    warn,
+};
+
+use crate::cli::chat::cli::model::{
+    ModelInfo,
+    default_context_window,
+};
+
+const SIGNING_NAME: &str = "bedrock";
+
+/// Model id prefixes of providers whose models accept a tool configuration through the Converse
+/// API. Models from other providers can still be opted in through the user's models file.
+const TOOL_USE_PREFIXES: &[&str] = &[
+    "anthropic.",
+    "amazon.nova",
+    "cohere.command-r",
+    "mistral.mistral-large",
+    "openai.",
+];
+
+/// Foundation models that accept cache points in the system prompt, tool config and messages.
+const PROMPT_CACHING_PREFIXES: &[&str] = &[
+    "anthropic.claude-3-5-haiku",
+    "anthropic.claude-3-7-sonnet",
+    "anthropic.claude-sonnet-4",
+    "anthropic.claude-opus-4",
+    "anthropic.claude-haiku-4",
+];
+
+/// Foundation models that support extended thinking through `additionalModelRequestFields`.
+const REASONING_PREFIXES: &[&str] = &[
+    "anthropic.claude-3-7-sonnet",
+    "anthropic.claude-sonnet-4",
+    "anthropic.claude-opus-4",
+    "anthropic.claude-haiku-4",
+];
+
+#[derive(Debug, Error)]
+pub enum ModelCatalogError {
+    #[error("no AWS region is configured")]
+    MissingRegion,
+    #[error("no AWS credentials are configured")]
+    MissingCredentials,
+    #[error("failed to load credentials: {}", .0)]
+    Credentials(#[from] CredentialsError),
+    #[error("failed to sign request: {}", .0)]
+    Signing(String),
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error("Bedrock returned status {status}: {message}")]
+    Service { status: u16, message: String },
+}
+
+impl ModelCatalogError {
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            Self::Service { status, .. } => Some(*status),
+            Self::Request(err) => err.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+}
+
+/// The model catalog as stored in the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCatalogCache {
+    /// Region the catalog was discovered in.
+    pub region: String,
+    /// Seconds since the unix epoch at which the catalog was discovered.
+    pub cached_at: u64,
+    pub models: Vec<ModelInfo>,
+}
+
+impl ModelCatalogCache {
+    pub fn new(region: String, models: Vec<ModelInfo>) -> Self {
+        Self {
+            region,
+            cached_at: unix_now(),
+            models,
+        }
+    }
+
+    pub fn is_expired(&self, ttl: Duration) -> bool {
+        unix_now().saturating_sub(self.cached_at) >= ttl.as_secs()
+    }
+}
+
+/// Client for the read-only model listing operations of the Bedrock control plane.
+#[derive(Clone, Debug)]
+pub struct ModelCatalogClient {
+    http: reqwest::Client,
+    region: Option<Region>,
+    credentials: Option<SharedCredentialsProvider>,
+}
+
+impl ModelCatalogClient {
+    pub fn new(sdk_config: &SdkConfig) -> Self {
+        Self {
+            http: crate::request::new_client().unwrap_or_default(),
+            region: sdk_config.region().cloned(),
+            credentials: sdk_config.credentials_provider(),
+        }
+    }
+
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_ref().map(|r| r.as_ref())
+    }
+
+    /// Lists the text models that can be streamed through the Converse API in the configured
+    /// region, including cross-region inference profiles.
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>, ModelCatalogError> {
+        let foundation_models = self.list_foundation_models().await?;
+        // Accounts without access to inference profiles can still use on-demand models.
+        let inference_profiles = match self.list_inference_profiles().await {
+            Ok(profiles) => profiles,
+            Err(err) => {
+                warn!(?err, "Failed to list inference profiles");
+                Vec::new()
+            },
+        };
+
+        Ok(catalog_models(&foundation_models, &inference_profiles))
+    }
+
+    async fn list_foundation_models(&self) -> Result<Vec<FoundationModelSummary>, ModelCatalogError> {
+        let output: ListFoundationModelsOutput =
+            self.get("/foundation-models", &[("byOutputModality", "TEXT")]).await?;
+        debug!("Discovered {} foundation models", output.model_summaries.len());
+        Ok(output.model_summaries)
+    }
+
+    async fn list_inference_profiles(&self) -> Result<Vec<InferenceProfileSummary>, ModelCatalogError> {
+        let mut profiles = Vec::new();
+        let mut next_token: Option<String> = None;
+        loop {
+            let mut query = vec![("maxResults", "1000"), ("type", "SYSTEM_DEFINED")];
+            if let Some(token) = next_token.as_deref() {
+                query.push(("nextToken", token));
+            }
+            let output: ListInferenceProfilesOutput = self.get("/inference-profiles", &query).await?;
+            profiles.extend(output.inference_profile_summaries);
+            match output.next_token {
+                Some(token) if !token.is_empty() => next_token = Some(token),
+                _ => break,
+            }
+        }
+        debug!("Discovered {} inference profiles", profiles.len());
+        Ok(profiles)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<T, ModelCatalogError> {
+        let region = self.region.as_ref().ok_or(ModelCatalogError::MissingRegion)?;
+        let credentials = self
+            .credentials
+            .as_ref()
+            .ok_or(ModelCatalogError::MissingCredentials)?
+            .provide_credentials()
+            .await?;
+
+        let mut url = url::Url::parse(&format!("https://bedrock.{region}.amazonaws.com{path}"))?;
+        url.query_pairs_mut().extend_pairs(query);
+
+        let identity = credentials.into();
+        let signing_params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region(region.as_ref())
+            .name(SIGNING_NAME)
+            .time(SystemTime::now())
+            .settings(SigningSettings::default())
+            .build()
+            .map_err(|err| ModelCatalogError::Signing(err.to_string()))?
+            .into();
+        let signable_request = SignableRequest::new("GET", url.as_str(), std::iter::empty(), SignableBody::Bytes(&[]))
+            .map_err(|err| ModelCatalogError::Signing(err.to_string()))?;
+        let (instructions, _signature) = sign(signable_request, &signing_params)
+            .map_err(|err| ModelCatalogError::Signing(err.to_string()))?
+            .into_parts();
+
+        let mut request = self.http.get(url.as_str());
+        for (name, value) in instructions.headers() {
+            request = request.header(name, value);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ModelCatalogError::Service {
+                status: status.as_u16(),
+                message: response.text().await.unwrap_or_default(),
+            });
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListFoundationModelsOutput {
+    #[serde(default)]
+    model_summaries: Vec<FoundationModelSummary>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FoundationModelSummary {
+    model_id: String,
+    model_name: Option<String>,
+    provider_name: Option<String>,
+    #[serde(default)]
+    input_modalities: Vec<String>,
+    #[serde(default)]
+    output_modalities: Vec<String>,
+    response_streaming_supported: Option<bool>,
+    #[serde(default)]
+    inference_types_supported: Vec<String>,
+    model_lifecycle: Option<ModelLifecycle>,
+}
+
+impl FoundationModelSummary {
+    /// Whether the model can back a chat session, either directly or through an inference
+    /// profile.
+    fn is_streaming_text_model(&self) -> bool {
+        self.output_modalities.iter().any(|m| m == "TEXT")
+            && self.response_streaming_supported.unwrap_or(false)
+            && self.model_lifecycle.as_ref().is_none_or(|l| l.status == "ACTIVE")
+    }
+
+    fn supports_on_demand(&self) -> bool {
+        self.inference_types_supported.iter().any(|t| t == "ON_DEMAND")
+    }
+
+    /// Models that take image input also accept documents through the Converse API.
+    fn supports_attachments(&self) -> bool {
+        self.input_modalities.iter().any(|m| m == "IMAGE")
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelLifecycle {
+    status: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListInferenceProfilesOutput {
+    #[serde(default)]
+    inference_profile_summaries: Vec<InferenceProfileSummary>,
+    next_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InferenceProfileSummary {
+    inference_profile_id: String,
+    inference_profile_name: Option<String>,
+    description: Option<String>,
+    status: Option<String>,
+    #[serde(default)]
+    models: Vec<InferenceProfileModel>,
+}
+
+impl InferenceProfileSummary {
+    /// Id of the foundation model the profile routes to, taken from the first model ARN, eg.
+    /// `arn:aws:bedrock:us-east-1::foundation-model/anthropic.claude-3-haiku-20240307-v1:0`.
+    fn foundation_model_id(&self) -> Option<&str> {
+        self.models
+            .first()
+            .and_then(|m| m.model_arn.as_deref())
+            .and_then(|arn| arn.rsplit_once("foundation-model/"))
+            .map(|(_, id)| id)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InferenceProfileModel {
+    model_arn: Option<String>,
+}
+
+/// Builds the list of selectable models from the control plane responses.
+///
+/// Foundation models are included when they can be invoked on demand, and inference profiles
+/// when they route to a streaming text model. Context window sizes are not reported by the
+/// control plane, so discovered models use the default until overridden.
+fn catalog_models(
+    foundation_models: &[FoundationModelSummary],
+    inference_profiles: &[InferenceProfileSummary],
+) -> Vec<ModelInfo> {
+    let mut models = Vec::new();
+
+    for model in foundation_models {
+        if model.is_streaming_text_model() && model.supports_on_demand() {
+            models.push(ModelInfo {
+                model_name: model.model_name.clone(),
+                description: model.provider_name.as_ref().map(|p| format!("{} model", p)),
+                context_window_tokens: default_context_window(),
+                supports_tools: supports_tools_by_default(&model.model_id),
+                supports_attachments: model.supports_attachments(),
+                supports_prompt_caching: supports_prompt_caching_by_default(&model.model_id),
+                supports_reasoning: supports_reasoning_by_default(&model.model_id),
+                ..ModelInfo::from_id(model.model_id.clone())
+            });
+        }
+    }
+
+    for profile in inference_profiles {
+        if profile.status.as_deref().is_some_and(|s| s != "ACTIVE") {
+            continue;
+        }
+        let Some(foundation_model_id) = profile.foundation_model_id() else {
+            continue;
+        };
+        let Some(foundation_model) = foundation_models
+            .iter()
+            .find(|m| m.model_id == foundation_model_id && m.is_streaming_text_model())
+        else {
+            continue;
+        };
+        models.push(ModelInfo {
+            model_name: profile.inference_profile_name.clone(),
+            description: profile.description.clone(),
+            context_window_tokens: default_context_window(),
+            supports_tools: supports_tools_by_default(foundation_model_id),
+            supports_attachments: foundation_model.supports_attachments(),
+            supports_prompt_caching: supports_prompt_caching_by_default(foundation_model_id),
+            supports_reasoning: supports_reasoning_by_default(foundation_model_id),
+            ..ModelInfo::from_id(profile.inference_profile_id.clone())
+        });
+    }
+
+    models.sort_by_key(|m| m.display_name().to_lowercase());
+    models
+}
+
+fn supports_tools_by_default(foundation_model_id: &str) -> bool {
+    TOOL_USE_PREFIXES.iter().any(|p| foundation_model_id.starts_with(p))
+}
+
+fn supports_prompt_caching_by_default(foundation_model_id: &str) -> bool {
+    PROMPT_CACHING_PREFIXES
+        .iter()
+        .any(|p| foundation_model_id.starts_with(p))
+}
+
+fn supports_reasoning_by_default(foundation_model_id: &str) -> bool {
+    REASONING_PREFIXES.iter().any(|p| foundation_model_id.starts_with(p))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn foundation_model(model_id: &str, inference_types: &[&str]) -> FoundationModelSummary {
+        FoundationModelSummary {
+            model_id: model_id.to_string(),
+            model_name: Some(model_id.to_string()),
+            provider_name: Some("Provider".to_string()),
+            input_modalities: vec!["TEXT".to_string()],
+            output_modalities: vec!["TEXT".to_string()],
+            response_streaming_supported: Some(true),
+            inference_types_supported: inference_types.iter().map(|s| s.to_string()).collect(),
+            model_lifecycle: Some(ModelLifecycle {
+                status: "ACTIVE".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_parse_list_foundation_models() {
+        let output: ListFoundationModelsOutput = serde_json::from_str(
+            r#"{
+                "modelSummaries": [{
+                    "modelArn": "arn:aws:bedrock:us-east-1::foundation-model/amazon.nova-pro-v1:0",
+                    "modelId": "amazon.nova-pro-v1:0",
+                    "modelName": "Nova Pro",
+                    "providerName": "Amazon",
+                    "inputModalities": ["TEXT", "IMAGE"],
+                    "outputModalities": ["TEXT"],
+                    "responseStreamingSupported": true,
+                    "inferenceTypesSupported": ["ON_DEMAND", "INFERENCE_PROFILE"],
+                    "modelLifecycle": { "status": "ACTIVE" }
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let models = catalog_models(&output.model_summaries, &[]);
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].model_id, "amazon.nova-pro-v1:0");
+        assert_eq!(models[0].model_name.as_deref(), Some("Nova Pro"));
+        assert!(models[0].supports_tools);
+        assert!(models[0].supports_attachments);
+    }
+
+    #[test]
+    fn test_catalog_models() {
+        let foundation_models = vec![
+            FoundationModelSummary {
+                input_modalities: vec!["TEXT".to_string(), "IMAGE".to_string()],
+                ..foundation_model("anthropic.claude-haiku-4-5-20251001-v1:0", &["INFERENCE_PROFILE"])
+            },
+            foundation_model("deepseek.v3-v1:0", &["ON_DEMAND"]),
+            FoundationModelSummary {
+                response_streaming_supported: Some(false),
+                ..foundation_model("example.no-streaming-v1:0", &["ON_DEMAND"])
+            },
+            FoundationModelSummary {
+                model_lifecycle: Some(ModelLifecycle {
+                    status: "LEGACY".to_string(),
+                }),
+                ..foundation_model("example.legacy-v1:0", &["ON_DEMAND"])
+            },
+        ];
+        let inference_profiles = vec![
+            InferenceProfileSummary {
+                inference_profile_id: "us.anthropic.claude-haiku-4-5-20251001-v1:0".to_string(),
+                inference_profile_name: Some("US Claude Haiku 4.5".to_string()),
+                description: None,
+                status: Some("ACTIVE".to_string()),
+                models: vec![InferenceProfileModel {
+                    model_arn: Some(
+                        "arn:aws:bedrock:us-east-1::foundation-model/anthropic.claude-haiku-4-5-20251001-v1:0"
+                            .to_string(),
+                    ),
+                }],
+            },
+            InferenceProfileSummary {
+                inference_profile_id: "us.example.unknown-v1:0".to_string(),
+                models: vec![InferenceProfileModel {
+                    model_arn: Some("arn:aws:bedrock:us-east-1::foundation-model/example.unknown-v1:0".to_string()),
+                }],
+                ..Default::default()
+            },
+        ];
+
+        let models = catalog_models(&foundation_models, &inference_profiles);
+        let ids = models.iter().map(|m| m.model_id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec![
+            "deepseek.v3-v1:0",
+            "us.anthropic.claude-haiku-4-5-20251001-v1:0"
+        ]);
+
+        let haiku = &models[1];
+        assert!(haiku.supports_tools);
+        assert!(haiku.supports_attachments);
+        assert!(haiku.supports_prompt_caching);
+        assert!(haiku.supports_reasoning);
+        assert_eq!(haiku.context_window_tokens, default_context_window());
+        assert!(!models[0].supports_tools);
+        assert!(!models[0].supports_attachments);
+        assert!(!models[0].supports_prompt_caching);
+        assert!(!models[0].supports_reasoning);
+    }
+
+    #[test]
+    fn test_cache_expiry() {
+        let mut cache = ModelCatalogCache::new("us-east-1".to_string(), vec![]);
+        assert!(!cache.is_expired(Duration::from_secs(60)));
+        cache.cached_at -= 120;
+        assert!(cache.is_expired(Duration::from_secs(60)));
+    }
+}
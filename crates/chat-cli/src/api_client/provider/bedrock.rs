@@ -0,0 +1,198 @@
+use aws_sdk_ssooidc::error::ProvideErrorMetadata;
+use aws_types::SdkConfig;
+use aws_types::request_id::RequestId;
+use tracing::debug;
+
+use super::ModelProvider;
+use crate::api_client::error::{
+    ConverseStreamError,
+    ConverseStreamErrorKind,
+};
+use crate::api_client::model::ConversationState;
+use crate::api_client::model_catalog::ModelCatalogClient;
+use crate::api_client::send_message_output::{
+    SendMessageOutput,
+    SendMessageOutputBedrock,
+};
+use crate::api_client::{
+    ApiClientError,
+    bedrock,
+};
+use crate::cli::chat::cli::model::ModelInfo;
+
+/// Provider for the Bedrock Converse API.
+#[derive(Clone, Debug)]
+pub struct BedrockProvider {
+    client: aws_sdk_bedrockruntime::Client,
+    model_catalog: ModelCatalogClient,
+}
+
+impl BedrockProvider {
+    pub fn new(sdk_config: &SdkConfig) -> Self {
+        Self {
+            client: aws_sdk_bedrockruntime::Client::new(sdk_config),
+            model_catalog: ModelCatalogClient::new(sdk_config),
+        }
+    }
+
+    /// The region requests are sent to.
+    pub fn region(&self) -> Option<&str> {
+        self.model_catalog.region()
+    }
+}
+
+#[async_trait::async_trait]
+impl ModelProvider for BedrockProvider {
+    async fn send_message(
+        &self,
+        conversation: ConversationState,
+        model_info: ModelInfo,
+    ) -> Result<SendMessageOutput, ConverseStreamError> {
+        let ConversationState {
+            user_input_message,
+            history,
+            service_tier,
+            agent_prompt,
+            reasoning_budget_tokens,
+            max_tokens,
+            guardrail,
+            ..
+        } = conversation;
+
+        let model_id = user_input_message
+            .model_id
+            .clone()
+            .unwrap_or_else(|| model_info.model_id.clone());
+
+        debug!("Sending message to Bedrock with model: {}", model_id);
+
+        // Convert to Bedrock format
+        let messages = bedrock::convert_to_bedrock_messages(
+            &user_input_message,
+            history.as_ref(),
+            &model_info,
+            reasoning_budget_tokens.is_some(),
+        )
+        .map_err(|e| {
+            debug!("Failed to convert messages: {}", e);
+            ConverseStreamError::new(
+                ConverseStreamErrorKind::MessageConversion,
+                None::<aws_sdk_bedrockruntime::Error>,
+            )
+        })?;
+
+        debug!("Converted {} messages for Bedrock", messages.len());
+
+        let supports_tools = model_info.supports_tools;
+
+        let tools = bedrock::convert_tools_to_bedrock(
+            user_input_message
+                .user_input_message_context
+                .as_ref()
+                .and_then(|ctx| ctx.tools.as_ref()),
+            model_info.supports_prompt_caching,
+        );
+
+        let system_prompt = bedrock::extract_system_prompt(
+            model_info.system_prompt.as_deref(),
+            agent_prompt.as_deref(),
+            model_info.supports_prompt_caching,
+        );
+
+        // Call Bedrock Converse Stream API
+        debug!("Calling Bedrock converse_stream API");
+        let mut request = self
+            .client
+            .converse_stream()
+            .model_id(model_id.clone())
+            .set_messages(Some(messages))
+            .set_system(system_prompt);
+
+        // Only pass tools if model supports them
+        if supports_tools {
+            if let Some(tool_config) = tools {
+                debug!(
+                    "Sending {} tools to Bedrock (model supports tools)",
+                    tool_config.tools().len()
+                );
+                request = request.tool_config(tool_config);
+            }
+        } else {
+            debug!("Model does not support tools, skipping tool config");
+        }
+
+        if let Some(guardrail) = guardrail.as_ref() {
+            debug!(
+                "Applying guardrail {} version {}",
+                guardrail.identifier, guardrail.version
+            );
+            request = request.guardrail_config(bedrock::convert_guardrail_config(guardrail));
+        }
+
+        if let Some(budget_tokens) = reasoning_budget_tokens {
+            let (fields, max_tokens) = bedrock::reasoning_request_fields(budget_tokens);
+            debug!("Enabling reasoning with a budget of {} tokens", budget_tokens);
+            request = request.additional_model_request_fields(fields).inference_config(
+                aws_sdk_bedrockruntime::types::InferenceConfiguration::builder()
+                    .max_tokens(max_tokens)
+                    .build(),
+            );
+        } else if let Some(max_tokens) = max_tokens {
+            request = request.inference_config(
+                aws_sdk_bedrockruntime::types::InferenceConfiguration::builder()
+                    .max_tokens(i32::try_from(max_tokens).unwrap_or(i32::MAX))
+                    .build(),
+            );
+        }
+
+        // Set service tier
+        if let Some(tier) = service_tier {
+            let tier_type = match tier.as_str() {
+                "flex" => aws_sdk_bedrockruntime::types::ServiceTierType::Flex,
+                _ => aws_sdk_bedrockruntime::types::ServiceTierType::Default,
+            };
+            let service_tier = aws_sdk_bedrockruntime::types::ServiceTier::builder()
+                .r#type(tier_type)
+                .build()?;
+            request = request.service_tier(service_tier);
+            debug!("Using service tier: {}", tier);
+        }
+
+        match request.send().await {
+            Ok(output) => {
+                debug!("Bedrock request successful, returning stream");
+                Ok(SendMessageOutput::Bedrock(SendMessageOutputBedrock::new(output)))
+            },
+            Err(err) => {
+                debug!("Bedrock request failed: {:?}", err);
+                let request_id = err.meta().request_id().map(|s| s.to_string());
+                let status_code = err.raw_response().map(|res| res.status().as_u16());
+
+                // Check for model and region specific errors
+                let is_invalid_model = err.as_service_error().is_some_and(|e| {
+                    e.is_validation_exception()
+                        && e.meta()
+                            .message()
+                            .is_some_and(|m| m.contains("model identifier is invalid"))
+                });
+                let error_kind = if is_invalid_model {
+                    ConverseStreamErrorKind::InvalidModel
+                } else if status_code == Some(404) {
+                    let region = self.region().unwrap_or("unknown");
+                    tracing::error!("Model {} may not be available in region {}", model_id, region);
+                    ConverseStreamErrorKind::ModelNotAvailable
+                } else {
+                    ConverseStreamErrorKind::ApiError
+                };
+
+                Err(ConverseStreamError::new(error_kind, Some(err))
+                    .set_request_id(request_id)
+                    .set_status_code(status_code))
+            },
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, ApiClientError> {
+        Ok(self.model_catalog.list_models().await?)
+    }
+}
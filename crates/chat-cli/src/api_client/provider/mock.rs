@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde_json::Map;
+
+use super::ModelProvider;
+use crate::api_client::ApiClientError;
+use crate::api_client::error::ConverseStreamError;
+use crate::api_client::model::{
+    ChatResponseStream,
+    ConversationState,
+};
+use crate::api_client::send_message_output::SendMessageOutput;
+use crate::cli::chat::cli::model::ModelInfo;
+
+/// Replays canned responses, one per request. Only meant for testing.
+#[derive(Clone, Debug)]
+pub struct MockProvider {
+    responses: Arc<Mutex<std::vec::IntoIter<Vec<ChatResponseStream>>>>,
+}
+
+impl MockProvider {
+    pub fn new(responses: Vec<Vec<ChatResponseStream>>) -> Self {
+        Self {
+            responses: Arc::new(Mutex::new(responses.into_iter())),
+        }
+    }
+
+    /// Creates the provider from a JSON array of responses, where each response is an array of
+    /// assistant text strings and tool use objects with `tool_use_id`, `name` and `args` keys.
+    pub fn from_json(json: serde_json::Value) -> Self {
+        let mut mock = Vec::new();
+        for response in json.as_array().unwrap() {
+            let mut stream = Vec::new();
+            for event in response.as_array().unwrap() {
+                match event {
+                    serde_json::Value::String(assistant_text) => {
+                        stream.push(ChatResponseStream::AssistantResponseEvent {
+                            content: assistant_text.clone(),
+                        });
+                    },
+                    serde_json::Value::Object(tool_use) => {
+                        stream.append(&mut split_tool_use_event(tool_use));
+                    },
+                    other => panic!("Unexpected value: {:?}", other),
+                }
+            }
+            mock.push(stream);
+        }
+
+        Self::new(mock)
+    }
+}
+
+#[async_trait::async_trait]
+impl ModelProvider for MockProvider {
+    async fn send_message(
+        &self,
+        _conversation: ConversationState,
+        _model_info: ModelInfo,
+    ) -> Result<SendMessageOutput, ConverseStreamError> {
+        let mut new_events = self.responses.lock().next().unwrap_or_default().clone();
+        new_events.reverse();
+        Ok(SendMessageOutput::Mock(new_events))
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, ApiClientError> {
+        Ok(vec![])
+    }
+}
+
+fn split_tool_use_event(value: &Map<String, serde_json::Value>) -> Vec<ChatResponseStream> {
+    let tool_use_id = value.get("tool_use_id").unwrap().as_str().unwrap().to_string();
+    let name = value.get("name").unwrap().as_str().unwrap().to_string();
+    let args_str = value.get("args").unwrap().to_string();
+    let split_point = args_str.len() / 2;
+    vec![
+        ChatResponseStream::ToolUseEvent {
+            tool_use_id: tool_use_id.clone(),
+            name: name.clone(),
+            input: None,
+            stop: None,
+        },
+        ChatResponseStream::ToolUseEvent {
+            tool_use_id: tool_use_id.clone(),
+            name: name.clone(),
+            input: Some(args_str.split_at(split_point).0.to_string()),
+            stop: None,
+        },
+        ChatResponseStream::ToolUseEvent {
+            tool_use_id: tool_use_id.clone(),
+            name: name.clone(),
+            input: Some(args_str.split_at(split_point).1.to_string()),
+            stop: None,
+        },
+        ChatResponseStream::ToolUseEvent {
+            tool_use_id: tool_use_id.clone(),
+            name: name.clone(),
+            input: None,
+            stop: Some(true),
+        },
+    ]
+}
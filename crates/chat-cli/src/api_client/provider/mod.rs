@@ -0,0 +1,93 @@
+//! Backends that serve chat responses.
+//!
+//! Every provider translates a [ConversationState] into its own request format and streams the
+//! response back as [ChatResponseStream](crate::api_client::model::ChatResponseStream) events
+//! through a [SendMessageOutput].
+
+mod bedrock;
+mod mock;
+mod openai;
+
+pub use bedrock::BedrockProvider;
+pub use mock::MockProvider;
+pub use openai::{
+    DEFAULT_OPENAI_ENDPOINT,
+    OpenAiError,
+    OpenAiProvider,
+    SendMessageOutputOpenAi,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use tracing::warn;
+
+use crate::api_client::ApiClientError;
+use crate::api_client::error::ConverseStreamError;
+use crate::api_client::model::ConversationState;
+use crate::api_client::send_message_output::SendMessageOutput;
+use crate::cli::chat::cli::model::ModelInfo;
+use crate::database::Database;
+use crate::database::settings::Setting;
+
+/// A backend that chat requests can be sent to.
+// async_trait marks the boxed futures it returns as must_use
+#[allow(clippy::double_must_use)]
+#[async_trait::async_trait]
+pub trait ModelProvider: std::fmt::Debug + Send + Sync {
+    /// Sends the conversation to the model described by `model_info` and returns the response
+    /// stream.
+    async fn send_message(
+        &self,
+        conversation: ConversationState,
+        model_info: ModelInfo,
+    ) -> Result<SendMessageOutput, ConverseStreamError>;
+
+    /// Lists the models served by this provider.
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, ApiClientError>;
+}
+
+/// The providers a model can be served by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// The Bedrock Converse API
+    #[default]
+    Bedrock,
+    /// A server implementing the OpenAI chat completions API, eg. Ollama or vLLM
+    OpenAi,
+}
+
+impl ProviderKind {
+    /// Returns the provider selected with the `chat.provider` setting, defaulting to Bedrock.
+    pub fn configured(database: &Database) -> Self {
+        match database.settings.get_string(Setting::ChatProvider) {
+            None => Self::default(),
+            Some(value) => serde_json::from_value(serde_json::Value::String(value.clone())).unwrap_or_else(|_| {
+                warn!(
+                    "Unknown provider '{}' in {}, using Bedrock",
+                    value,
+                    Setting::ChatProvider
+                );
+                Self::default()
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_configured_provider() {
+        let mut database = Database::new().await.unwrap();
+        assert_eq!(ProviderKind::configured(&database), ProviderKind::Bedrock);
+
+        database.settings.set(Setting::ChatProvider, "openai").await.unwrap();
+        assert_eq!(ProviderKind::configured(&database), ProviderKind::OpenAi);
+
+        database.settings.set(Setting::ChatProvider, "unknown").await.unwrap();
+        assert_eq!(ProviderKind::configured(&database), ProviderKind::Bedrock);
+    }
+}
@@ -0,0 +1,798 @@
+//! Provider for servers implementing the OpenAI chat completions API, such as Ollama, vLLM and
+//! llama.cpp, so that the chat can run against a local model.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use bytes::Bytes;
+use futures::{
+    Stream,
+    StreamExt,
+};
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::{
+    Value,
+    json,
+};
+use thiserror::Error;
+use tracing::{
+    debug,
+    warn,
+};
+
+use super::{
+    ModelProvider,
+    ProviderKind,
+};
+use crate::api_client::ApiClientError;
+use crate::api_client::error::{
+    ConverseStreamError,
+    ConverseStreamErrorKind,
+};
+use crate::api_client::model::{
+    AssistantResponseMessage,
+    ChatMessage,
+    ChatResponseStream,
+    ConversationState,
+    FigDocument,
+    ImageFormat,
+    ImageSource,
+    Tool,
+    ToolResult,
+    ToolResultContentBlock,
+    ToolResultStatus,
+    UserInputMessage,
+};
+use crate::api_client::send_message_output::{
+    SendMessageOutput,
+    TokenUsage,
+};
+use crate::cli::chat::cli::model::{
+    ModelInfo,
+    default_context_window,
+};
+
+/// Endpoint of a local Ollama server, used when `api.openai.endpoint` is not set.
+pub const DEFAULT_OPENAI_ENDPOINT: &str = "http://localhost:11434/v1";
+
+#[derive(Debug, Error)]
+pub enum OpenAiError {
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error("the server returned status {status}: {message}")]
+    Service { status: u16, message: String },
+    #[error("failed to parse the response: {}", .0)]
+    InvalidResponse(#[from] serde_json::Error),
+    #[error("the server reported an error: {}", .0)]
+    Stream(String),
+}
+
+impl OpenAiError {
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            Self::Service { status, .. } => Some(*status),
+            Self::Request(err) => err.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+}
+
+/// Provider for an OpenAI compatible chat completions endpoint.
+#[derive(Clone, Debug)]
+pub struct OpenAiProvider {
+    http: reqwest::Client,
+    endpoint: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiProvider {
+    pub fn new(endpoint: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            http: crate::request::new_client().unwrap_or_default(),
+            endpoint: endpoint.into(),
+            api_key,
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        let url = format!("{}/{}", self.endpoint.trim_end_matches('/'), path);
+        let request = self.http.request(method, url);
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ModelProvider for OpenAiProvider {
+    async fn send_message(
+        &self,
+        conversation: ConversationState,
+        model_info: ModelInfo,
+    ) -> Result<SendMessageOutput, ConverseStreamError> {
+        let request = chat_completion_request(conversation, &model_info);
+        debug!("Sending message to {} with model: {}", self.endpoint, request["model"]);
+
+        let response = self
+            .request(Method::POST, "chat/completions")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|err| ConverseStreamError::new(ConverseStreamErrorKind::ApiError, Some(OpenAiError::from(err))))?;
+
+        match error_for_status(response).await {
+            Ok(response) => Ok(SendMessageOutput::OpenAi(SendMessageOutputOpenAi::new(response))),
+            Err(err) => {
+                debug!("Chat completion request failed: {:?}", err);
+                let status_code = err.status_code();
+                let kind = match &err {
+                    OpenAiError::Service { status: 429, .. } => ConverseStreamErrorKind::Throttling,
+                    OpenAiError::Service { message, .. } if message.contains("context length") => {
+                        ConverseStreamErrorKind::ContextWindowOverflow
+                    },
+                    _ => ConverseStreamErrorKind::ApiError,
+                };
+                Err(ConverseStreamError::new(kind, Some(err)).set_status_code(status_code))
+            },
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, ApiClientError> {
+        let response = self
+            .request(Method::GET, "models")
+            .send()
+            .await
+            .map_err(OpenAiError::from)?;
+        let output: ListModelsOutput = error_for_status(response)
+            .await?
+            .json()
+            .await
+            .map_err(OpenAiError::from)?;
+        debug!("Discovered {} models at {}", output.data.len(), self.endpoint);
+
+        Ok(output
+            .data
+            .into_iter()
+            .map(|model| ModelInfo {
+                provider: Some(ProviderKind::OpenAi),
+                context_window_tokens: default_context_window(),
+                supports_tools: true,
+                ..ModelInfo::from_id(model.id)
+            })
+            .collect())
+    }
+}
+
+async fn error_for_status(response: reqwest::Response) -> Result<reqwest::Response, OpenAiError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    Err(OpenAiError::Service {
+        status: status.as_u16(),
+        message: response.text().await.unwrap_or_default(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct ListModelsOutput {
+    data: Vec<ListedModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListedModel {
+    id: String,
+}
+
+/// Builds the body of a streaming chat completion request.
+///
+/// Reasoning budgets, guardrails and service tiers are Bedrock features and are not sent.
+fn chat_completion_request(conversation: ConversationState, model_info: &ModelInfo) -> Value {
+    let ConversationState {
+        user_input_message,
+        history,
+        agent_prompt,
+        reasoning_budget_tokens,
+        max_tokens,
+        guardrail,
+        ..
+    } = conversation;
+
+    if reasoning_budget_tokens.is_some() {
+        debug!("Reasoning budgets are not supported by the OpenAI provider, ignoring");
+    }
+    if guardrail.is_some() {
+        warn!("Guardrails are only applied by the Bedrock provider, ignoring");
+    }
+
+    let model_id = user_input_message
+        .model_id
+        .clone()
+        .unwrap_or_else(|| model_info.model_id.clone());
+
+    let mut messages = Vec::new();
+    let system_prompt = [model_info.system_prompt.as_deref(), agent_prompt.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if !system_prompt.is_empty() {
+        messages.push(json!({ "role": "system", "content": system_prompt }));
+    }
+
+    for message in history.iter().flatten() {
+        match message {
+            ChatMessage::UserInputMessage(message) => convert_user_message(message, model_info, &mut messages),
+            ChatMessage::AssistantResponseMessage(message) => messages.extend(convert_assistant_message(message)),
+        }
+    }
+    convert_user_message(&user_input_message, model_info, &mut messages);
+
+    let mut request = json!({
+        "model": model_id,
+        "messages": messages,
+        "stream": true,
+        "stream_options": { "include_usage": true },
+    });
+
+    let tools = user_input_message
+        .user_input_message_context
+        .as_ref()
+        .and_then(|ctx| ctx.tools.as_deref())
+        .filter(|_| model_info.supports_tools)
+        .map(convert_tools)
+        .unwrap_or_default();
+    if !tools.is_empty() {
+        request["tools"] = Value::Array(tools);
+    }
+    if let Some(max_tokens) = max_tokens {
+        request["max_tokens"] = json!(max_tokens);
+    }
+
+    request
+}
+
+/// Converts a user message to a `user` message, preceded by one `tool` message per tool result.
+fn convert_user_message(message: &UserInputMessage, model_info: &ModelInfo, messages: &mut Vec<Value>) {
+    let tool_results = message
+        .user_input_message_context
+        .as_ref()
+        .and_then(|ctx| ctx.tool_results.as_deref())
+        .unwrap_or_default();
+    for result in tool_results {
+        messages.push(json!({
+            "role": "tool",
+            "tool_call_id": result.tool_use_id,
+            "content": tool_result_text(result),
+        }));
+    }
+
+    let mut parts = Vec::new();
+    if !message.content.trim().is_empty() {
+        parts.push(json!({ "type": "text", "text": message.content }));
+    }
+
+    let images = message.images.as_deref().unwrap_or_default();
+    if model_info.supports_attachments {
+        for image in images {
+            if let ImageSource::Bytes(bytes) = &image.source {
+                let url = format!(
+                    "data:{};base64,{}",
+                    image_mime_type(&image.format),
+                    BASE64.encode(bytes)
+                );
+                parts.push(json!({ "type": "image_url", "image_url": { "url": url } }));
+            }
+        }
+    } else if !images.is_empty() {
+        parts.push(json!({
+            "type": "text",
+            "text": format!("[{} image(s) were not attached because the selected model does not accept them]", images.len()),
+        }));
+    }
+
+    let documents = message.documents.as_deref().unwrap_or_default();
+    if !documents.is_empty() {
+        parts.push(json!({
+            "type": "text",
+            "text": format!("[{} document(s) were not attached because the OpenAI provider does not accept documents]", documents.len()),
+        }));
+    }
+
+    // Plain string content is understood by every server, content parts only by the ones with
+    // vision support.
+    let content = match parts.as_slice() {
+        [] => return,
+        [part] if part["type"] == "text" => part["text"].clone(),
+        _ => Value::Array(parts),
+    };
+    messages.push(json!({ "role": "user", "content": content }));
+}
+
+fn convert_assistant_message(message: &AssistantResponseMessage) -> Option<Value> {
+    let tool_calls = message
+        .tool_uses
+        .iter()
+        .flatten()
+        .map(|tool_use| {
+            json!({
+                "id": tool_use.tool_use_id,
+                "type": "function",
+                "function": {
+                    "name": tool_use.name,
+                    "arguments": serde_json::to_string(&tool_use.input).unwrap_or_default(),
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if message.content.trim().is_empty() && tool_calls.is_empty() {
+        return None;
+    }
+
+    let mut value = json!({ "role": "assistant", "content": message.content });
+    if !tool_calls.is_empty() {
+        value["tool_calls"] = Value::Array(tool_calls);
+    }
+    Some(value)
+}
+
+fn tool_result_text(result: &ToolResult) -> String {
+    let text = result
+        .content
+        .iter()
+        .map(|block| match block {
+            ToolResultContentBlock::Text(text) => text.clone(),
+            ToolResultContentBlock::Json(document) => {
+                serde_json::to_string(&FigDocument::from(document.clone())).unwrap_or_default()
+            },
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match result.status {
+        ToolResultStatus::Success => text,
+        ToolResultStatus::Error => format!("Error: {text}"),
+    }
+}
+
+fn convert_tools(tools: &[Tool]) -> Vec<Value> {
+    tools
+        .iter()
+        .filter_map(|tool| match tool {
+            Tool::ToolSpecification(spec) => {
+                let parameters = serde_json::to_value(spec.input_schema.json.as_ref()?).ok()?;
+                Some(json!({
+                    "type": "function",
+                    "function": {
+                        "name": spec.name,
+                        "description": spec.description,
+                        "parameters": parameters,
+                    },
+                }))
+            },
+        })
+        .collect()
+}
+
+fn image_mime_type(format: &ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Gif => "image/gif",
+        ImageFormat::Jpeg => "image/jpeg",
+        ImageFormat::Png => "image/png",
+        ImageFormat::Webp => "image/webp",
+    }
+}
+
+/// The streamed response of a chat completion request.
+pub struct SendMessageOutputOpenAi {
+    request_id: Option<String>,
+    body: Pin<Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Send>>,
+    decoder: ChunkDecoder,
+}
+
+impl std::fmt::Debug for SendMessageOutputOpenAi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendMessageOutputOpenAi")
+            .field("request_id", &self.request_id)
+            .field("decoder", &self.decoder)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SendMessageOutputOpenAi {
+    fn new(response: reqwest::Response) -> Self {
+        let request_id = response
+            .headers()
+            .get("x-request-id")
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        Self {
+            request_id,
+            body: Box::pin(response.bytes_stream()),
+            decoder: ChunkDecoder::default(),
+        }
+    }
+
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub fn token_usage(&self) -> Option<TokenUsage> {
+        self.decoder.usage
+    }
+
+    // Same error type as the other backends, see SendMessageOutput::recv
+    #[allow(clippy::result_large_err)]
+    pub async fn recv(&mut self) -> Result<Option<ChatResponseStream>, ApiClientError> {
+        loop {
+            if let Some(event) = self.decoder.events.pop_front() {
+                return Ok(Some(event));
+            }
+            if self.decoder.done {
+                return Ok(None);
+            }
+
+            let result = match self.body.next().await {
+                Some(Ok(bytes)) => self.decoder.push(&bytes),
+                Some(Err(err)) => Err(err.into()),
+                None => self.decoder.finish(),
+            };
+            if let Err(err) = result {
+                tracing::error!("Stream error: {:?}", err);
+                return Err(ConverseStreamError::new(ConverseStreamErrorKind::ApiError, Some(err)).into());
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ToolCallState {
+    index: usize,
+    tool_use_id: String,
+    name: String,
+}
+
+/// Decodes the server sent events of a chat completion stream into [ChatResponseStream] events.
+#[derive(Debug, Default)]
+struct ChunkDecoder {
+    buffer: Vec<u8>,
+    events: VecDeque<ChatResponseStream>,
+    current_tool: Option<ToolCallState>,
+    usage: Option<TokenUsage>,
+    done: bool,
+}
+
+impl ChunkDecoder {
+    fn push(&mut self, bytes: &[u8]) -> Result<(), OpenAiError> {
+        self.buffer.extend_from_slice(bytes);
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line = self.buffer.drain(..=pos).collect::<Vec<_>>();
+            self.decode_line(&line)?;
+        }
+        Ok(())
+    }
+
+    /// Handles the end of the response body, which some servers send without a `[DONE]` event.
+    fn finish(&mut self) -> Result<(), OpenAiError> {
+        let line = std::mem::take(&mut self.buffer);
+        self.decode_line(&line)?;
+        self.end();
+        Ok(())
+    }
+
+    fn end(&mut self) {
+        self.close_tool_call();
+        self.done = true;
+    }
+
+    fn decode_line(&mut self, line: &[u8]) -> Result<(), OpenAiError> {
+        if self.done {
+            return Ok(());
+        }
+        let line = String::from_utf8_lossy(line);
+        let Some(data) = line.trim().strip_prefix("data:").map(str::trim) else {
+            return Ok(());
+        };
+        if data == "[DONE]" {
+            self.end();
+            return Ok(());
+        }
+
+        let chunk: ChatCompletionChunk = serde_json::from_str(data)?;
+        if let Some(error) = chunk.error {
+            return Err(OpenAiError::Stream(error.message));
+        }
+        if let Some(usage) = chunk.usage {
+            self.usage = Some(usage.into());
+        }
+
+        for choice in chunk.choices {
+            let delta = choice.delta;
+            if let Some(text) = delta.reasoning_content.or(delta.reasoning).filter(|t| !t.is_empty()) {
+                self.events
+                    .push_back(ChatResponseStream::ReasoningContentEvent { text });
+            }
+            if let Some(content) = delta.content.filter(|c| !c.is_empty()) {
+                self.events
+                    .push_back(ChatResponseStream::AssistantResponseEvent { content });
+            }
+            for (position, tool_call) in delta.tool_calls.unwrap_or_default().into_iter().enumerate() {
+                self.decode_tool_call(tool_call.index.unwrap_or(position), tool_call);
+            }
+            if choice.finish_reason.is_some() {
+                self.close_tool_call();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tool calls are streamed one after the other, with the id and name in the first delta of
+    /// each call.
+    fn decode_tool_call(&mut self, index: usize, tool_call: ToolCallDelta) {
+        let function = tool_call.function.unwrap_or_default();
+        if self.current_tool.as_ref().is_none_or(|state| state.index != index) {
+            self.close_tool_call();
+            let state = ToolCallState {
+                index,
+                tool_use_id: tool_call
+                    .id
+                    .unwrap_or_else(|| format!("tooluse_{}", uuid::Uuid::new_v4().simple())),
+                name: function.name.unwrap_or_default(),
+            };
+            self.events.push_back(ChatResponseStream::ToolUseEvent {
+                tool_use_id: state.tool_use_id.clone(),
+                name: state.name.clone(),
+                input: None,
+                stop: None,
+            });
+            self.current_tool = Some(state);
+        }
+
+        if let (Some(state), Some(arguments)) = (&self.current_tool, function.arguments.filter(|a| !a.is_empty())) {
+            self.events.push_back(ChatResponseStream::ToolUseEvent {
+                tool_use_id: state.tool_use_id.clone(),
+                name: state.name.clone(),
+                input: Some(arguments),
+                stop: None,
+            });
+        }
+    }
+
+    fn close_tool_call(&mut self) {
+        if let Some(state) = self.current_tool.take() {
+            self.events.push_back(ChatResponseStream::ToolUseEvent {
+                tool_use_id: state.tool_use_id,
+                name: state.name,
+                input: None,
+                stop: Some(true),
+            });
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    usage: Option<ChunkUsage>,
+    error: Option<ChunkError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    delta: ChunkDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChunkDelta {
+    content: Option<String>,
+    /// Reasoning text, as sent by vLLM
+    reasoning_content: Option<String>,
+    /// Reasoning text, as sent by Ollama
+    reasoning: Option<String>,
+    tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolCallDelta {
+    index: Option<usize>,
+    id: Option<String>,
+    function: Option<FunctionDelta>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FunctionDelta {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    cached_tokens: Option<u32>,
+}
+
+impl From<ChunkUsage> for TokenUsage {
+    fn from(usage: ChunkUsage) -> Self {
+        let to_i32 = |tokens: u32| i32::try_from(tokens).unwrap_or(i32::MAX);
+        Self {
+            input_tokens: to_i32(usage.prompt_tokens),
+            output_tokens: to_i32(usage.completion_tokens),
+            cache_read_tokens: usage
+                .prompt_tokens_details
+                .and_then(|details| details.cached_tokens)
+                .map(to_i32),
+            cache_write_tokens: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkError {
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_client::model::{
+        ToolInputSchema,
+        ToolSpecification,
+        ToolUse,
+        UserInputMessageContext,
+    };
+
+    fn user_message(content: &str) -> UserInputMessage {
+        UserInputMessage {
+            content: content.to_string(),
+            user_input_message_context: None,
+            user_intent: None,
+            images: None,
+            documents: None,
+            model_id: None,
+        }
+    }
+
+    #[test]
+    fn test_chat_completion_request() {
+        let model_info = ModelInfo {
+            supports_tools: true,
+            system_prompt: Some("Be brief.".to_string()),
+            ..ModelInfo::from_id("llama3.1".to_string())
+        };
+        let tool_use_id = "call_1".to_string();
+        let conversation = ConversationState {
+            conversation_id: None,
+            user_input_message: UserInputMessage {
+                user_input_message_context: Some(UserInputMessageContext {
+                    tool_results: Some(vec![ToolResult {
+                        tool_use_id: tool_use_id.clone(),
+                        content: vec![ToolResultContentBlock::Text("Cargo.toml".to_string())],
+                        status: ToolResultStatus::Success,
+                    }]),
+                    tools: Some(vec![Tool::ToolSpecification(ToolSpecification {
+                        name: "fs_read".to_string(),
+                        description: "Read files".to_string(),
+                        input_schema: ToolInputSchema {
+                            json: Some(aws_smithy_types::Document::Object(Default::default()).into()),
+                        },
+                    })]),
+                    ..Default::default()
+                }),
+                ..user_message("")
+            },
+            history: Some(vec![
+                ChatMessage::UserInputMessage(user_message("List the files")),
+                ChatMessage::AssistantResponseMessage(AssistantResponseMessage {
+                    message_id: None,
+                    content: String::new(),
+                    tool_uses: Some(vec![ToolUse {
+                        tool_use_id,
+                        name: "fs_read".to_string(),
+                        input: aws_smithy_types::Document::Object(Default::default()).into(),
+                    }]),
+                    reasoning: None,
+                }),
+            ]),
+            service_tier: None,
+            model_info: None,
+            agent_prompt: Some("You are a helpful agent.".to_string()),
+            reasoning_budget_tokens: None,
+            max_tokens: None,
+            guardrail: None,
+        };
+
+        let request = chat_completion_request(conversation, &model_info);
+        assert_eq!(request["model"], "llama3.1");
+        assert_eq!(request["stream"], true);
+        assert_eq!(
+            request["messages"],
+            json!([
+                { "role": "system", "content": "Be brief.\n\nYou are a helpful agent." },
+                { "role": "user", "content": "List the files" },
+                {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": { "name": "fs_read", "arguments": "{}" },
+                    }],
+                },
+                { "role": "tool", "tool_call_id": "call_1", "content": "Cargo.toml" },
+            ])
+        );
+        assert_eq!(request["tools"][0]["function"]["name"], "fs_read");
+    }
+
+    #[test]
+    fn test_decode_stream() {
+        let mut decoder = ChunkDecoder::default();
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"reasoning_content\":\"Let me look.\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Checking\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"function\":{\"name\":\"fs_read\",\"arguments\":\"{\\\"pa\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"th\\\":\\\".\\\"}\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":12,\"completion_tokens\":5}}\n\n",
+            "data: [DONE]\n\n",
+        );
+
+        // Split the body mid-line to check that partial lines are buffered
+        let (first, second) = body.split_at(70);
+        decoder.push(first.as_bytes()).unwrap();
+        decoder.push(second.as_bytes()).unwrap();
+
+        assert!(decoder.done);
+        assert_eq!(
+            decoder.usage,
+            Some(TokenUsage {
+                input_tokens: 12,
+                output_tokens: 5,
+                cache_read_tokens: None,
+                cache_write_tokens: None,
+            })
+        );
+
+        let events = decoder.events.into_iter().collect::<Vec<_>>();
+        let tool_use = |input: Option<&str>, stop: Option<bool>| ChatResponseStream::ToolUseEvent {
+            tool_use_id: "call_1".to_string(),
+            name: "fs_read".to_string(),
+            input: input.map(String::from),
+            stop,
+        };
+        assert_eq!(events, vec![
+            ChatResponseStream::ReasoningContentEvent {
+                text: "Let me look.".to_string()
+            },
+            ChatResponseStream::AssistantResponseEvent {
+                content: "Checking".to_string()
+            },
+            tool_use(None, None),
+            tool_use(Some("{\"pa"), None),
+            tool_use(Some("th\":\".\"}"), None),
+            tool_use(None, Some(true)),
+        ]);
+    }
+
+    #[test]
+    fn test_decode_stream_error() {
+        let mut decoder = ChunkDecoder::default();
+        let result = decoder.push(b"data: {\"error\":{\"message\":\"model not found\"}}\n");
+        assert!(matches!(result, Err(OpenAiError::Stream(message)) if message == "model not found"));
+    }
+}
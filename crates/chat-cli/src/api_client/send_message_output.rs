@@ -2,7 +2,22 @@ use aws_sdk_bedrockruntime::operation::converse_stream::ConverseStreamOutput;
 use aws_types::request_id::RequestId;
 
 use crate::api_client::ApiClientError;
+use crate::api_client::bedrock::summarize_guardrail_trace;
+use crate::api_client::error::{
+    ConverseStreamError,
+    ConverseStreamErrorKind,
+};
 use crate::api_client::model::ChatResponseStream;
+use crate::api_client::provider::SendMessageOutputOpenAi;
+
+/// Token usage reported at the end of a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: i32,
+    pub output_tokens: i32,
+    pub cache_read_tokens: Option<i32>,
+    pub cache_write_tokens: Option<i32>,
+}
 
 #[derive(Debug)]
 struct ToolUseState {
@@ -15,6 +30,7 @@ pub struct SendMessageOutputBedrock {
     output: ConverseStreamOutput,
     current_tool: Option<ToolUseState>,
     metadata: Option<aws_sdk_bedrockruntime::types::ConverseStreamMetadataEvent>,
+    stop_reason: Option<aws_sdk_bedrockruntime::types::StopReason>,
 }
 
 impl SendMessageOutputBedrock {
@@ -23,6 +39,7 @@ impl SendMessageOutputBedrock {
             output,
             current_tool: None,
             metadata: None,
+            stop_reason: None,
         }
     }
 
@@ -81,6 +98,24 @@ impl SendMessageOutputBedrock {
                                         }
                                         continue;
                                     }
+                                    aws_sdk_bedrockruntime::types::ContentBlockDelta::ReasoningContent(reasoning) => {
+                                        use aws_sdk_bedrockruntime::types::ReasoningContentBlockDelta;
+
+                                        match reasoning {
+                                            ReasoningContentBlockDelta::Text(text) if !text.is_empty() => {
+                                                return Ok(Some(ChatResponseStream::ReasoningContentEvent { text }));
+                                            }
+                                            ReasoningContentBlockDelta::Signature(signature) => {
+                                                return Ok(Some(ChatResponseStream::ReasoningSignatureEvent { signature }));
+                                            }
+                                            ReasoningContentBlockDelta::RedactedContent(data) => {
+                                                return Ok(Some(ChatResponseStream::RedactedReasoningEvent {
+                                                    data: data.into_inner(),
+                                                }));
+                                            }
+                                            _ => tracing::debug!("Other reasoning delta type (ignoring)"),
+                                        }
+                                    }
                                     _ => {
                                         tracing::debug!("Other content block delta type (ignoring)");
                                         continue;
@@ -104,9 +139,11 @@ impl SendMessageOutputBedrock {
                             }
                             continue;
                         }
-                        BedrockStream::MessageStop(_) => {
-                            tracing::debug!("MessageStop - stream complete");
-                            return Ok(None);
+                        BedrockStream::MessageStop(stop) => {
+                            // The metadata event with token usage is sent after the message stop,
+                            // so keep reading until the stream itself ends.
+                            tracing::debug!("MessageStop - waiting for metadata");
+                            self.stop_reason = Some(stop.stop_reason);
                         }
                         BedrockStream::Metadata(metadata) => {
                             tracing::debug!("Metadata event: {:?}", metadata);
@@ -121,6 +158,9 @@ impl SendMessageOutputBedrock {
                 }
                 Ok(None) => {
                     tracing::debug!("Stream ended (None)");
+                    if let Some(aws_sdk_bedrockruntime::types::StopReason::GuardrailIntervened) = self.stop_reason {
+                        return Err(self.guardrail_intervened());
+                    }
                     return Ok(None);
                 }
                 Err(e) => {
@@ -133,14 +173,37 @@ impl SendMessageOutputBedrock {
         }
     }
 
-    pub fn get_metadata(&self) -> Option<&aws_sdk_bedrockruntime::types::ConverseStreamMetadataEvent> {
-        self.metadata.as_ref()
+    fn guardrail_intervened(&self) -> ApiClientError {
+        let trace = self
+            .metadata
+            .as_ref()
+            .and_then(|m| m.trace.as_ref())
+            .and_then(|t| t.guardrail.as_ref())
+            .map(summarize_guardrail_trace)
+            .unwrap_or_default();
+        tracing::warn!(?trace, "Guardrail intervened");
+        ConverseStreamError::new(
+            ConverseStreamErrorKind::GuardrailIntervened { trace },
+            None::<aws_sdk_bedrockruntime::Error>,
+        )
+        .into()
+    }
+
+    pub fn token_usage(&self) -> Option<TokenUsage> {
+        let usage = self.metadata.as_ref()?.usage.as_ref()?;
+        Some(TokenUsage {
+            input_tokens: usage.input_tokens(),
+            output_tokens: usage.output_tokens(),
+            cache_read_tokens: usage.cache_read_input_tokens(),
+            cache_write_tokens: usage.cache_write_input_tokens(),
+        })
     }
 }
 
 #[derive(Debug)]
 pub enum SendMessageOutput {
     Bedrock(SendMessageOutputBedrock),
+    OpenAi(SendMessageOutputOpenAi),
     Codewhisperer(
         amzn_codewhisperer_streaming_client::operation::generate_assistant_response::GenerateAssistantResponseOutput,
     ),
@@ -152,6 +215,7 @@ impl SendMessageOutput {
     pub fn request_id(&self) -> Option<&str> {
         match self {
             SendMessageOutput::Bedrock(bedrock) => bedrock.output.request_id(),
+            SendMessageOutput::OpenAi(openai) => openai.request_id(),
             SendMessageOutput::Codewhisperer(output) => output.request_id(),
             SendMessageOutput::QDeveloper(output) => output.request_id(),
             SendMessageOutput::Mock(_) => None,
@@ -161,6 +225,7 @@ impl SendMessageOutput {
     pub async fn recv(&mut self) -> Result<Option<ChatResponseStream>, ApiClientError> {
         match self {
             SendMessageOutput::Bedrock(bedrock) => bedrock.recv().await,
+            SendMessageOutput::OpenAi(openai) => openai.recv().await,
             SendMessageOutput::Codewhisperer(output) => Ok(output
                 .generate_assistant_response_response
                 .recv()
@@ -171,9 +236,11 @@ impl SendMessageOutput {
         }
     }
 
-    pub fn get_bedrock_metadata(&self) -> Option<&aws_sdk_bedrockruntime::types::ConverseStreamMetadataEvent> {
+    /// Token usage of the response, available once the stream has ended.
+    pub fn token_usage(&self) -> Option<TokenUsage> {
         match self {
-            SendMessageOutput::Bedrock(bedrock) => bedrock.get_metadata(),
+            SendMessageOutput::Bedrock(bedrock) => bedrock.token_usage(),
+            SendMessageOutput::OpenAi(openai) => openai.token_usage(),
             _ => None,
         }
     }
@@ -183,6 +250,7 @@ impl RequestId for SendMessageOutput {
     fn request_id(&self) -> Option<&str> {
         match self {
             SendMessageOutput::Bedrock(bedrock) => bedrock.output.request_id(),
+            SendMessageOutput::OpenAi(openai) => openai.request_id(),
             SendMessageOutput::Codewhisperer(output) => output.request_id(),
             SendMessageOutput::QDeveloper(output) => output.request_id(),
             SendMessageOutput::Mock(_) => Some("<mock-request-id>"),
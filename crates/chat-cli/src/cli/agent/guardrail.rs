@@ -0,0 +1,62 @@
+use schemars::JsonSchema;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// A Bedrock guardrail that is applied to every model request made for an agent.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GuardrailConfig {
+    /// The identifier or ARN of the guardrail
+    pub identifier: String,
+    /// The version of the guardrail to use, e.g. "1" or "DRAFT"
+    pub version: String,
+    /// Whether Bedrock returns the guardrail trace, which is shown when the guardrail intervenes
+    #[serde(default)]
+    pub trace: GuardrailTraceMode,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum GuardrailTraceMode {
+    /// Return the trace of the policies that matched
+    #[default]
+    Enabled,
+    /// Return the trace of all policies, including the ones that did not match
+    EnabledFull,
+    /// Do not return a trace
+    Disabled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_guardrail() {
+        let config: GuardrailConfig = serde_json::from_value(serde_json::json!({
+            "identifier": "gr-abc123",
+            "version": "DRAFT"
+        }))
+        .unwrap();
+        assert_eq!(config.trace, GuardrailTraceMode::Enabled);
+
+        let config: GuardrailConfig = serde_json::from_value(serde_json::json!({
+            "identifier": "gr-abc123",
+            "version": "2",
+            "trace": "enabledFull"
+        }))
+        .unwrap();
+        assert_eq!(config.trace, GuardrailTraceMode::EnabledFull);
+
+        assert!(
+            serde_json::from_value::<GuardrailConfig>(serde_json::json!({
+                "identifier": "gr-abc123",
+                "version": "2",
+                "mode": "sync"
+            }))
+            .is_err()
+        );
+    }
+}
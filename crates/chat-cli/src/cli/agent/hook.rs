@@ -23,6 +23,14 @@ pub enum HookTrigger {
     PostToolUse,
     /// Triggered when the assistant finishes responding
     Stop,
+    /// Triggered before the conversation history is compacted
+    PreCompact,
+    /// Triggered when the chat session ends
+    SessionEnd,
+    /// Triggered when the chat session waits for the user to approve a tool use
+    Notification,
+    /// Triggered when a delegated agent finishes its task
+    SubagentStop,
 }
 
 impl Display for HookTrigger {
@@ -33,6 +41,10 @@ impl Display for HookTrigger {
             HookTrigger::PreToolUse => write!(f, "preToolUse"),
             HookTrigger::PostToolUse => write!(f, "postToolUse"),
             HookTrigger::Stop => write!(f, "stop"),
+            HookTrigger::PreCompact => write!(f, "preCompact"),
+            HookTrigger::SessionEnd => write!(f, "sessionEnd"),
+            HookTrigger::Notification => write!(f, "notification"),
+            HookTrigger::SubagentStop => write!(f, "subagentStop"),
         }
     }
 }
@@ -1,3 +1,4 @@
+pub mod guardrail;
 pub mod hook;
 mod legacy;
 mod mcp_config;
@@ -56,6 +57,7 @@ use super::chat::tools::{
     NATIVE_TOOLS,
     ToolOrigin,
 };
+use crate::cli::agent::guardrail::GuardrailConfig;
 use crate::cli::agent::hook::{
     Hook,
     HookTrigger,
@@ -155,7 +157,7 @@ pub struct Agent {
     /// List of tools the agent is explicitly allowed to use
     #[serde(default)]
     pub allowed_tools: HashSet<String>,
-    /// Files to include in the agent's context
+    /// Files and MCP server resources to include in the agent's context
     #[serde(default)]
     pub resources: Vec<ResourcePath>,
     /// Commands to run when a chat session is created
@@ -174,6 +176,9 @@ pub struct Agent {
     /// The model ID to use for this agent. If not specified, uses the default model.
     #[serde(default)]
     pub model: Option<String>,
+    /// Bedrock guardrail to apply to every model request made by this agent
+    #[serde(default)]
+    pub guardrail: Option<GuardrailConfig>,
     #[serde(skip)]
     pub path: Option<PathBuf>,
 }
@@ -204,6 +209,7 @@ impl Default for Agent {
             tools_settings: Default::default(),
             use_legacy_mcp_json: true,
             model: None,
+            guardrail: None,
             path: None,
         }
     }
@@ -1292,6 +1298,7 @@ mod tests {
             hooks: Default::default(),
             use_legacy_mcp_json: false,
             model: None,
+            guardrail: None,
             path: None,
         };
 
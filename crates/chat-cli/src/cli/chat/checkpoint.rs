@@ -207,6 +207,17 @@ impl CheckpointManager {
     pub fn restore(&self, conversation: &mut ConversationState, tag: &str, hard: bool) -> Result<()> {
         let checkpoint = self.get_checkpoint(tag)?;
 
+        // Restore workspace files
+        self.restore_workspace(tag, hard)?;
+
+        // Restore conversation history
+        conversation.restore_to_checkpoint(checkpoint)?;
+
+        Ok(())
+    }
+
+    /// Restore only the workspace files to a specific checkpoint, leaving the conversation as is
+    pub fn restore_workspace(&self, tag: &str, hard: bool) -> Result<()> {
         if hard {
             // Hard: reset the whole work-tree to the tag
             let output = run_git(&self.shadow_repo_path, Some(&self.work_tree_path), &[
@@ -222,7 +233,6 @@ impl CheckpointManager {
             // Soft: only restore tracked files. If the tag is an empty tree, this is a no-op.
             if !self.tag_has_any_paths(tag)? {
                 // Nothing tracked in this checkpoint -> nothing to restore; treat as success.
-                conversation.restore_to_checkpoint(checkpoint)?;
                 return Ok(());
             }
             // Use checkout against work-tree
@@ -237,12 +247,18 @@ impl CheckpointManager {
             }
         }
 
-        // Restore conversation history
-        conversation.restore_to_checkpoint(checkpoint)?;
-
         Ok(())
     }
 
+    /// The latest turn checkpoint taken when the history had at most `history_len` entries, which
+    /// holds the workspace as it was at that point of the conversation
+    pub fn turn_checkpoint_at(&self, history_len: usize) -> Option<&Checkpoint> {
+        self.checkpoints
+            .iter()
+            .rev()
+            .find(|c| c.is_turn && c.history_snapshot.len() <= history_len)
+    }
+
     /// Return true iff the given tag/tree has any tracked paths.
     fn tag_has_any_paths(&self, tag: &str) -> eyre::Result<bool> {
         // Use `git ls-tree -r --name-only <tag>` to check if the tree is empty
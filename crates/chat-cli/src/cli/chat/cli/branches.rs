@@ -0,0 +1,417 @@
+//! `/fork` and `/branches`, which let a conversation branch off from an earlier turn and switch
+//! between its branches. Each branch is a session of its own, linked to the session it was forked
+//! from.
+
+use std::collections::HashMap;
+
+use clap::Args;
+use crossterm::execute;
+use crossterm::style::{
+    self,
+    Stylize,
+};
+use dialoguer::Select;
+
+use crate::cli::chat::checkpoint::CheckpointManager;
+use crate::cli::chat::util::truncate_safe;
+use crate::cli::chat::{
+    ChatError,
+    ChatSession,
+    ChatState,
+};
+use crate::cli::experiment::experiment_manager::{
+    ExperimentManager,
+    ExperimentName,
+};
+use crate::database::sessions::SessionSummary;
+use crate::os::Os;
+use crate::theme::StyledText;
+use crate::util::paths::PathResolver;
+
+/// Number of characters of the session ids shown, as in `q chat sessions list`.
+const SHORT_ID_LEN: usize = 8;
+const MAX_PROMPT_LEN: usize = 60;
+
+/// Arguments of `/fork`
+#[derive(Debug, PartialEq, Args)]
+pub struct ForkArgs {
+    /// Turn to fork from, counting the prompts of the conversation from 1. Leave empty to pick one
+    /// interactively.
+    turn: Option<usize>,
+    /// When restoring the checkpoint of that turn, also remove the files created after it
+    #[arg(long)]
+    hard: bool,
+}
+
+impl ForkArgs {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        if session.conversation.is_in_tangent_mode() {
+            return warn(
+                session,
+                "⚠️ Forking is disabled while in tangent mode. Please exit tangent mode first.\n\n",
+            );
+        }
+
+        let turns = session
+            .conversation
+            .user_turns()
+            .into_iter()
+            .map(|(history_len, prompt)| (history_len, prompt.to_string()))
+            .collect::<Vec<_>>();
+        if turns.is_empty() {
+            return warn(session, "There is nothing to fork yet.\n\n");
+        }
+
+        let turn = match self.turn {
+            Some(turn) if (1..=turns.len()).contains(&turn) => turn,
+            Some(turn) => {
+                return warn(
+                    session,
+                    &format!(
+                        "Turn {turn} does not exist, the conversation has {} turns.\n\n",
+                        turns.len()
+                    ),
+                );
+            },
+            None => {
+                let items = turns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, prompt))| format!("{}. {}", i + 1, prompt_line(prompt)))
+                    .collect::<Vec<_>>();
+                let selection = Select::with_theme(&crate::util::dialoguer_theme())
+                    .with_prompt("Fork the conversation after which turn?")
+                    .items(&items)
+                    .default(items.len() - 1)
+                    .report(false)
+                    .interact_opt()
+                    .unwrap_or(None);
+                match selection {
+                    Some(i) => i + 1,
+                    None => {
+                        return Ok(ChatState::PromptUser {
+                            skip_printing_tools: true,
+                        });
+                    },
+                }
+            },
+        };
+        let history_len = turns[turn - 1].0;
+
+        // The parent keeps its whole history, so that its branch can be switched back to
+        save_session(os, session)?;
+
+        let restored_tag = match restore_workspace(os, session, history_len, self.hard) {
+            Ok(tag) => tag,
+            Err(e) => {
+                return warn(
+                    session,
+                    &format!("⚠️ The conversation was not forked, its workspace could not be restored: {e}\n\n"),
+                );
+            },
+        };
+
+        let had_checkpoints = session.conversation.checkpoint_manager.is_some();
+        let fork_id = uuid::Uuid::new_v4().to_string();
+        session.conversation.fork(fork_id.clone(), turn, history_len);
+
+        // The fork gets checkpoints of its own, starting from the restored workspace
+        if had_checkpoints && ExperimentManager::is_enabled(os, ExperimentName::Checkpoint) {
+            let path = PathResolver::new(os)
+                .global()
+                .shadow_repo_dir()
+                .map_err(|e| ChatError::Custom(e.to_string().into()))?
+                .join(&fork_id);
+            match CheckpointManager::manual_init(os, path, session.conversation.history()).await {
+                Ok(manager) => session.conversation.checkpoint_manager = Some(manager),
+                Err(e) => execute!(
+                    session.stderr,
+                    StyledText::warning_fg(),
+                    style::Print(format!(
+                        "⚠️ Checkpoints could not be initialized for the new branch: {e}\n"
+                    )),
+                    StyledText::reset(),
+                )?,
+            }
+        }
+        save_session(os, session)?;
+
+        execute!(
+            session.stderr,
+            StyledText::success_fg(),
+            style::Print(format!(
+                "\n✔ Forked the conversation after turn {turn} into branch {}\n",
+                short_id(&fork_id)
+            )),
+            StyledText::reset(),
+        )?;
+        if let Some(tag) = restored_tag {
+            execute!(
+                session.stderr,
+                StyledText::secondary_fg(),
+                style::Print(format!("Restored the workspace to checkpoint {tag}\n")),
+                StyledText::reset(),
+            )?;
+        }
+        execute!(
+            session.stderr,
+            StyledText::secondary_fg(),
+            style::Print("Use /branches to switch back to the original conversation.\n\n"),
+            StyledText::reset(),
+        )?;
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
+
+/// Arguments of `/branches`
+#[derive(Debug, PartialEq, Args)]
+pub struct BranchesArgs {
+    /// When restoring the checkpoint of the branch switched to, also remove the files created
+    /// after it
+    #[arg(long)]
+    hard: bool,
+}
+
+impl BranchesArgs {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        if session.conversation.is_in_tangent_mode() {
+            return warn(
+                session,
+                "⚠️ Switching branches is disabled while in tangent mode. Please exit tangent mode first.\n\n",
+            );
+        }
+
+        let current_id = session.conversation.conversation_id().to_string();
+        let family = os
+            .database
+            .list_session_family(&current_id)
+            .map_err(|e| ChatError::Custom(e.to_string().into()))?;
+        if family.len() < 2 {
+            return warn(
+                session,
+                "This conversation has no branches. Use /fork to create one.\n\n",
+            );
+        }
+
+        let branches = branch_lines(&family, &current_id);
+        let items = branches.iter().map(|(_, line)| line.as_str()).collect::<Vec<_>>();
+        let selection = Select::with_theme(&crate::util::dialoguer_theme())
+            .with_prompt("Switch to which branch?")
+            .items(&items)
+            .default(branches.iter().position(|(id, _)| *id == current_id).unwrap_or(0))
+            .report(false)
+            .interact_opt()
+            .unwrap_or(None);
+        let Some(id) = selection.map(|i| branches[i].0.clone()) else {
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: true,
+            });
+        };
+        if id == current_id {
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: true,
+            });
+        }
+
+        let Some(mut branch) = os
+            .database
+            .get_session(&id)
+            .map_err(|e| ChatError::Custom(e.to_string().into()))?
+        else {
+            return warn(session, &format!("Branch {} no longer exists.\n\n", short_id(&id)));
+        };
+        save_session(os, session)?;
+
+        // The same as /load: the branch keeps the tools, agent and model of the session
+        std::mem::swap(&mut branch.tool_manager, &mut session.conversation.tool_manager);
+        std::mem::swap(&mut branch.mcp_enabled, &mut session.conversation.mcp_enabled);
+        std::mem::swap(&mut branch.model_info, &mut session.conversation.model_info);
+        std::mem::swap(&mut branch.context_manager, &mut session.conversation.context_manager);
+        std::mem::swap(&mut branch.agents, &mut session.conversation.agents);
+        session.conversation = branch;
+
+        let history_len = session.conversation.history().len();
+        let restored_tag = match restore_workspace(os, session, history_len, self.hard) {
+            Ok(tag) => tag,
+            Err(e) => {
+                execute!(
+                    session.stderr,
+                    StyledText::warning_fg(),
+                    style::Print(format!("⚠️ Could not restore the workspace of the branch: {e}\n")),
+                    StyledText::reset(),
+                )?;
+                None
+            },
+        };
+
+        execute!(
+            session.stderr,
+            StyledText::success_fg(),
+            style::Print(format!("\n✔ Switched to branch {}\n", short_id(&id))),
+            StyledText::reset(),
+        )?;
+        if let Some(tag) = restored_tag {
+            execute!(
+                session.stderr,
+                StyledText::secondary_fg(),
+                style::Print(format!("Restored the workspace to checkpoint {tag}\n")),
+                StyledText::reset(),
+            )?;
+        }
+        execute!(session.stderr, style::Print("\n"))?;
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
+
+fn warn(session: &mut ChatSession, message: &str) -> Result<ChatState, ChatError> {
+    execute!(
+        session.stderr,
+        StyledText::warning_fg(),
+        style::Print(message),
+        StyledText::reset(),
+    )?;
+    Ok(ChatState::PromptUser {
+        skip_printing_tools: true,
+    })
+}
+
+/// Restores the workspace to the checkpoint taken after the first `history_len` entries of the
+/// history, when checkpoints are enabled. Returns the tag of the checkpoint restored.
+fn restore_workspace(os: &Os, session: &ChatSession, history_len: usize, hard: bool) -> eyre::Result<Option<String>> {
+    if !ExperimentManager::is_enabled(os, ExperimentName::Checkpoint) {
+        return Ok(None);
+    }
+    let Some(manager) = &session.conversation.checkpoint_manager else {
+        return Ok(None);
+    };
+    let Some(tag) = manager.turn_checkpoint_at(history_len).map(|c| c.tag.clone()) else {
+        return Ok(None);
+    };
+    manager.restore_workspace(&tag, hard)?;
+    Ok(Some(tag))
+}
+
+fn save_session(os: &Os, session: &ChatSession) -> Result<(), ChatError> {
+    if session.conversation.history().is_empty() {
+        return Ok(());
+    }
+    let cwd = std::env::current_dir()?;
+    os.database
+        .set_session(cwd, &session.conversation)
+        .map_err(|e| ChatError::Custom(format!("Failed to save the session: {e}").into()))?;
+    Ok(())
+}
+
+fn short_id(id: &str) -> &str {
+    id.get(..SHORT_ID_LEN).unwrap_or(id)
+}
+
+/// The first line of a prompt, shortened to fit in a list.
+fn prompt_line(prompt: &str) -> String {
+    let line = prompt
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    match truncate_safe(line, MAX_PROMPT_LEN) {
+        truncated if truncated.len() < line.len() => format!("{truncated}..."),
+        truncated => truncated.to_string(),
+    }
+}
+
+/// Lines describing the branches of a conversation as a tree, each branch under the one it was
+/// forked from, along with their ids.
+fn branch_lines(family: &[SessionSummary], current_id: &str) -> Vec<(String, String)> {
+    let ids = family.iter().map(|s| s.id.as_str()).collect::<Vec<_>>();
+    let mut children: HashMap<&str, Vec<&SessionSummary>> = HashMap::new();
+    let mut roots = Vec::new();
+    for session in family {
+        match session.parent_id.as_deref() {
+            Some(parent_id) if ids.contains(&parent_id) => children.entry(parent_id).or_default().push(session),
+            _ => roots.push(session),
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut stack = roots.into_iter().rev().map(|s| (s, 0)).collect::<Vec<_>>();
+    while let Some((session, depth)) = stack.pop() {
+        let marker = if session.id == current_id { "*" } else { " " };
+        let mut line = format!("{marker} {}{}", "  ".repeat(depth), short_id(&session.id).yellow());
+        let title = match session.title.as_str() {
+            "" => "(untitled)",
+            title => title,
+        };
+        line.push_str(&format!("  {title}"));
+        if let Some(turn) = session.fork_turn.filter(|_| depth > 0) {
+            line.push_str(&format!(" {}", format!("(after turn {turn})").dark_grey()));
+        }
+        if session.id == current_id {
+            line.push_str(&format!(" {}", "(current)".green()));
+        }
+        lines.push((session.id.clone(), line));
+
+        if let Some(children) = children.get(session.id.as_str()) {
+            stack.extend(children.iter().rev().map(|child| (*child, depth + 1)));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn session(id: &str, parent: Option<(&str, usize)>) -> SessionSummary {
+        SessionSummary {
+            id: id.to_string(),
+            title: format!("Session {id}"),
+            cwd: "/work".to_string(),
+            agent: None,
+            model: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            parent_id: parent.map(|(id, _)| id.to_string()),
+            fork_turn: parent.map(|(_, turn)| turn),
+        }
+    }
+
+    #[test]
+    fn test_branch_lines() {
+        let family = vec![
+            session("root", None),
+            session("a", Some(("root", 2))),
+            session("b", Some(("root", 3))),
+            session("a1", Some(("a", 1))),
+        ];
+        let lines = branch_lines(&family, "a1")
+            .into_iter()
+            .map(|(id, line)| (id, String::from_utf8(strip_ansi_escapes::strip(line)).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![
+            ("root".to_string(), "  root  Session root".to_string()),
+            ("a".to_string(), "    a  Session a (after turn 2)".to_string()),
+            (
+                "a1".to_string(),
+                "*     a1  Session a1 (after turn 1) (current)".to_string()
+            ),
+            ("b".to_string(), "    b  Session b (after turn 3)".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_prompt_line() {
+        assert_eq!(prompt_line("\n  Fix the build\nIt fails"), "Fix the build");
+        assert_eq!(
+            prompt_line(&"a".repeat(100)),
+            format!("{}...", "a".repeat(MAX_PROMPT_LEN))
+        );
+    }
+}
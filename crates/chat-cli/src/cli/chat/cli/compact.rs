@@ -66,6 +66,7 @@ impl CompactArgs {
         // Compact interrupts the current conversation so this will always result in a new user
         // turn.
         session.reset_user_turn();
+        let prompt = session.run_pre_compact_hooks(os, prompt, false).await;
 
         session
             .compact_history(os, prompt, self.show_summary, CompactStrategy {
@@ -26,6 +26,7 @@ use futures::stream::{
     FuturesUnordered,
     StreamExt,
 };
+use serde::Deserialize;
 use spinners::{
     Spinner,
     Spinners,
@@ -52,6 +53,98 @@ use crate::util::pattern_matching::matches_any_pattern;
 /// Output is stdout if exit_code is 0, stderr otherwise.
 pub type HookOutput = (i32, String);
 
+/// What a `preToolUse` hook decided about a tool use. When hooks disagree, the greatest decision
+/// wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookDecision {
+    /// Run the tool without asking the user, unless it is denied by the tool settings.
+    Allow,
+    /// Ask the user for approval, even if the tool is trusted.
+    Ask,
+    /// Block the tool use, returning the reason to the model.
+    Deny,
+}
+
+/// A JSON object a hook can print to its stdout instead of plain text.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct HookResponse {
+    pub decision: Option<HookDecision>,
+    pub reason: Option<String>,
+    /// Replaces the input of the tool (`preToolUse` only).
+    pub updated_input: Option<serde_json::Value>,
+    /// Text returned to the model along with the result of the tool (`postToolUse` only).
+    pub additional_context: Option<String>,
+}
+
+impl HookResponse {
+    /// Parses the output of a hook, if it is a JSON object.
+    pub fn parse(output: &str) -> Option<Self> {
+        let output = output.trim();
+        if !output.starts_with('{') {
+            return None;
+        }
+        serde_json::from_str(output)
+            .map_err(|err| tracing::warn!(?err, "Hook printed a JSON object that is not a valid hook response"))
+            .ok()
+    }
+}
+
+/// The combined decision of the `preToolUse` hooks of a tool use.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PreToolUseOutcome {
+    pub decision: Option<HookDecision>,
+    /// The reasons given by the hooks that made the decision.
+    pub reasons: Vec<String>,
+    pub updated_input: Option<serde_json::Value>,
+}
+
+impl PreToolUseOutcome {
+    /// Combines the results of the `preToolUse` hooks of a tool use. If several hooks update the
+    /// input, the first of `hooks` wins, since hooks finish in no particular order.
+    pub fn from_results(results: &[((HookTrigger, Hook), HookOutput)], hooks: &[Hook]) -> Self {
+        let mut results = results.iter().collect::<Vec<_>>();
+        results.sort_by_key(|((_, hook), _)| hooks.iter().position(|h| h == hook));
+
+        let mut outcome = Self::default();
+        for (_, (exit_code, output)) in results {
+            let (decision, reason, updated_input) = match exit_code {
+                // Blocking with exit code 2 returns stderr as the reason
+                2 => (Some(HookDecision::Deny), Some(output.clone()), None),
+                0 => match HookResponse::parse(output) {
+                    Some(response) => (response.decision, response.reason, response.updated_input),
+                    None => continue,
+                },
+                _ => continue,
+            };
+
+            if outcome.updated_input.is_none() && decision != Some(HookDecision::Deny) {
+                outcome.updated_input = updated_input;
+            }
+            if decision.is_none() {
+                continue;
+            }
+            if decision > outcome.decision {
+                outcome.decision = decision;
+                outcome.reasons.clear();
+            }
+            if decision == outcome.decision {
+                outcome.reasons.extend(reason);
+            }
+        }
+        outcome
+    }
+}
+
+/// The additional context the `postToolUse` hooks of a tool use return to the model.
+pub fn additional_context(results: &[((HookTrigger, Hook), HookOutput)]) -> Vec<String> {
+    results
+        .iter()
+        .filter(|(_, (exit_code, _))| *exit_code == 0)
+        .filter_map(|(_, (_, output))| HookResponse::parse(output)?.additional_context)
+        .collect()
+}
+
 /// Check if a hook matches a tool name based on its matcher pattern
 fn hook_matches_tool(hook: &Hook, tool_name: &str) -> bool {
     match &hook.matcher {
@@ -84,6 +177,10 @@ fn hook_matches_tool(hook: &Hook, tool_name: &str) -> bool {
     }
 }
 
+/// Fields added to the JSON input of a hook for its trigger, such as the `conversation_id` of
+/// `sessionEnd` hooks.
+pub type EventInput = serde_json::Map<String, serde_json::Value>;
+
 #[derive(Debug, Clone)]
 pub struct ToolContext {
     pub tool_name: String,
@@ -123,6 +220,7 @@ impl HookExecutor {
         cwd: &str,
         prompt: Option<&str>,
         tool_context: Option<ToolContext>,
+        event_input: Option<&EventInput>,
     ) -> Result<Vec<((HookTrigger, Hook), HookOutput)>, ChatError> {
         let mut cached = vec![];
         let mut futures = FuturesUnordered::new();
@@ -142,7 +240,7 @@ impl HookExecutor {
                 cached.push((hook.clone(), (0, cache)));
                 continue;
             }
-            futures.push(self.run_hook(hook, cwd, prompt, tool_context.clone()));
+            futures.push(self.run_hook(hook, cwd, prompt, tool_context.clone(), event_input));
         }
 
         let mut complete = 0; // number of hooks that are run successfully with exit code 0
@@ -255,6 +353,10 @@ impl HookExecutor {
                     HookTrigger::PreToolUse => Some(Instant::now() + Duration::from_secs(hook.cache_ttl_seconds)),
                     HookTrigger::PostToolUse => Some(Instant::now() + Duration::from_secs(hook.cache_ttl_seconds)),
                     HookTrigger::Stop => Some(Instant::now() + Duration::from_secs(hook.cache_ttl_seconds)),
+                    HookTrigger::PreCompact => Some(Instant::now() + Duration::from_secs(hook.cache_ttl_seconds)),
+                    HookTrigger::SessionEnd => Some(Instant::now() + Duration::from_secs(hook.cache_ttl_seconds)),
+                    HookTrigger::Notification => Some(Instant::now() + Duration::from_secs(hook.cache_ttl_seconds)),
+                    HookTrigger::SubagentStop => Some(Instant::now() + Duration::from_secs(hook.cache_ttl_seconds)),
                 },
             });
         }
@@ -270,6 +372,7 @@ impl HookExecutor {
         cwd: &str,
         prompt: Option<&str>,
         tool_context: Option<ToolContext>,
+        event_input: Option<&EventInput>,
     ) -> ((HookTrigger, Hook), Result<HookOutput>, Duration) {
         let start_time = Instant::now();
 
@@ -319,6 +422,11 @@ impl HookExecutor {
                 hook_input["tool_response"] = response;
             }
         }
+
+        // Trigger specific input
+        if let (Some(event_input), Some(fields)) = (event_input, hook_input.as_object_mut()) {
+            fields.extend(event_input.clone());
+        }
         let json_input = serde_json::to_string(&hook_input).unwrap_or_default();
 
         // Build a future for hook command w/ the JSON input passed in through STDIN
@@ -588,7 +696,7 @@ mod tests {
 
         // Run the hook
         let result = executor
-            .run_hooks(hooks, &mut output, ".", None, Some(tool_context))
+            .run_hooks(hooks, &mut output, ".", None, Some(tool_context), None)
             .await;
 
         assert!(result.is_ok());
@@ -636,6 +744,7 @@ mod tests {
                 ".",  // cwd - using current directory for now
                 None, // prompt - no user prompt for this test
                 Some(tool_context),
+                None,
             )
             .await;
 
@@ -687,6 +796,7 @@ mod tests {
                 ".",  // cwd
                 None, // prompt
                 Some(tool_context),
+                None,
             )
             .await
             .unwrap();
@@ -729,6 +839,7 @@ mod tests {
                 ".",  // cwd
                 None, // prompt
                 None, // tool_context - Stop doesn't have tool context
+                None,
             )
             .await
             .unwrap();
@@ -742,6 +853,45 @@ mod tests {
         assert!(hook_output.contains("Turn completed successfully"));
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_event_hook_input() {
+        let mut executor = HookExecutor::new();
+        let mut output = Vec::new();
+
+        let hook = Hook {
+            command: "cat".to_string(),
+            timeout_ms: 5000,
+            cache_ttl_seconds: 0,
+            max_output_size: 1000,
+            matcher: None,
+            source: crate::cli::agent::hook::Source::Session,
+        };
+        let hooks = HashMap::from([(HookTrigger::SessionEnd, vec![hook])]);
+        let event_input = EventInput::from_iter([
+            ("conversation_id".to_string(), serde_json::json!("abc")),
+            ("transcript_path".to_string(), serde_json::json!("/tmp/abc.json")),
+        ]);
+
+        let results = executor
+            .run_hooks(hooks, &mut output, "/work", None, None, Some(&event_input))
+            .await
+            .unwrap();
+
+        let (_, (exit_code, hook_output)) = &results[0];
+        assert_eq!(*exit_code, 0);
+        let input: serde_json::Value = serde_json::from_str(hook_output).unwrap();
+        assert_eq!(
+            input,
+            serde_json::json!({
+                "hook_event_name": "sessionEnd",
+                "cwd": "/work",
+                "conversation_id": "abc",
+                "transcript_path": "/tmp/abc.json",
+            })
+        );
+    }
+
     #[test]
     fn test_sanitize_user_prompt_cjk_characters() {
         // Test with CJK characters that would cause panic with naive byte slicing
@@ -768,4 +918,62 @@ mod tests {
         let result = sanitize_user_prompt(text_with_controls);
         assert_eq!(result, "HelloWorld\nTest\r\tEnd");
     }
+
+    fn pre_tool_use_result(command: &str, exit_code: i32, output: &str) -> ((HookTrigger, Hook), HookOutput) {
+        let hook = Hook::new(command.to_string(), crate::cli::agent::hook::Source::Session);
+        ((HookTrigger::PreToolUse, hook), (exit_code, output.to_string()))
+    }
+
+    #[test]
+    fn test_hook_response_parse() {
+        assert_eq!(HookResponse::parse("all good"), None);
+        assert_eq!(HookResponse::parse("{not json"), None);
+        assert_eq!(
+            HookResponse::parse(r#" {"decision": "deny", "reason": "no", "extra": 1} "#),
+            Some(HookResponse {
+                decision: Some(HookDecision::Deny),
+                reason: Some("no".to_string()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(HookResponse::parse(r#"{"decision": "maybe"}"#), None);
+    }
+
+    #[test]
+    fn test_pre_tool_use_outcome() {
+        let results = vec![
+            pre_tool_use_result("b", 0, r#"{"decision": "allow", "updated_input": {"path": "b"}}"#),
+            pre_tool_use_result("a", 0, r#"{"decision": "ask", "reason": "writes outside the repo", "updated_input": {"path": "a"}}"#),
+            pre_tool_use_result("c", 0, "plain output"),
+            pre_tool_use_result("d", 1, "failed"),
+        ];
+        let hooks = ["a", "b", "c", "d"]
+            .map(|c| Hook::new(c.to_string(), crate::cli::agent::hook::Source::Session))
+            .to_vec();
+        let outcome = PreToolUseOutcome::from_results(&results, &hooks);
+        assert_eq!(outcome, PreToolUseOutcome {
+            decision: Some(HookDecision::Ask),
+            reasons: vec!["writes outside the repo".to_string()],
+            updated_input: Some(serde_json::json!({"path": "a"})),
+        });
+
+        // Blocking with exit code 2 wins over any other decision
+        let mut results = results;
+        results.push(pre_tool_use_result("e", 2, "blocked"));
+        let outcome = PreToolUseOutcome::from_results(&results, &hooks);
+        assert_eq!(outcome.decision, Some(HookDecision::Deny));
+        assert_eq!(outcome.reasons, vec!["blocked".to_string()]);
+
+        assert_eq!(PreToolUseOutcome::from_results(&[], &hooks), PreToolUseOutcome::default());
+    }
+
+    #[test]
+    fn test_additional_context() {
+        let results = vec![
+            pre_tool_use_result("a", 0, r#"{"additional_context": "the file is generated"}"#),
+            pre_tool_use_result("b", 0, "plain output"),
+            pre_tool_use_result("c", 1, r#"{"additional_context": "ignored"}"#),
+        ];
+        assert_eq!(additional_context(&results), vec!["the file is generated".to_string()]);
+    }
 }
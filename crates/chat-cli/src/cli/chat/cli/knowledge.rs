@@ -41,9 +41,12 @@ pub enum KnowledgeSubcommand {
         /// Exclude patterns (e.g., `node_modules/**`, `target/**`)
         #[arg(long, action = clap::ArgAction::Append)]
         exclude: Vec<String>,
-        /// Index type to use (Fast, Best)
+        /// Index type to use (Fast, Best, Hybrid)
         #[arg(long)]
         index_type: Option<String>,
+        /// Keep the knowledge base up to date as files under the path change
+        #[arg(long)]
+        watch: bool,
     },
     /// Remove specified knowledge base entry by path
     #[command(alias = "rm")]
@@ -121,7 +124,8 @@ impl KnowledgeSubcommand {
                 include,
                 exclude,
                 index_type,
-            } => Self::handle_add(os, session, name, path, include, exclude, index_type).await,
+                watch,
+            } => Self::handle_add(os, session, name, path, include, exclude, index_type, *watch).await,
             KnowledgeSubcommand::Remove { path } => Self::handle_remove(os, session, path).await,
             KnowledgeSubcommand::Update { path } => Self::handle_update(os, session, path).await,
             KnowledgeSubcommand::Clear => Self::handle_clear(os, session).await,
@@ -237,8 +241,18 @@ impl KnowledgeSubcommand {
                 StyledText::secondary_fg(),
                 style::Print(format!("{}", ctx.updated_at.format("%m/%d %H:%M"))),
                 StyledText::reset(),
-                style::Print("\n\n")
             )?;
+            if ctx.watch {
+                queue!(
+                    session.stderr,
+                    StyledText::secondary_fg(),
+                    style::Print(" • "),
+                    StyledText::info_fg(),
+                    style::Print("watching"),
+                    StyledText::reset(),
+                )?;
+            }
+            queue!(session.stderr, style::Print("\n\n"))?;
         }
         Ok(())
     }
@@ -253,6 +267,7 @@ impl KnowledgeSubcommand {
             .unwrap_or_default()
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn handle_add(
         os: &Os,
         session: &mut ChatSession,
@@ -261,6 +276,7 @@ impl KnowledgeSubcommand {
         include_patterns: &[String],
         exclude_patterns: &[String],
         index_type: &Option<String>,
+        watch: bool,
     ) -> OperationResult {
         match Self::validate_and_sanitize_path(os, path) {
             Ok(sanitized_path) => {
@@ -294,7 +310,8 @@ impl KnowledgeSubcommand {
                 let options = crate::util::knowledge_store::AddOptions::new()
                     .with_include_patterns(include)
                     .with_exclude_patterns(exclude)
-                    .with_embedding_type(embedding_type_resolved);
+                    .with_embedding_type(embedding_type_resolved)
+                    .with_watch(watch);
 
                 match store.add(name, &sanitized_path.clone(), options).await {
                     Ok(message) => OperationResult::Info(message),
@@ -432,10 +449,17 @@ impl KnowledgeSubcommand {
             // Description/path line (indented like knowledge entries)
             // Use actual path from operation type if available, otherwise use message
             let description = match &op.operation_type {
-                semantic_search_client::OperationType::Indexing { path, .. } => path.clone(),
+                semantic_search_client::OperationType::Indexing { path, .. }
+                | semantic_search_client::OperationType::Updating { path, .. } => path.clone(),
                 semantic_search_client::OperationType::Clearing => op.message.clone(),
             };
             output.push_str(&format!("       {}\n", description));
+            if matches!(
+                op.operation_type,
+                semantic_search_client::OperationType::Updating { .. }
+            ) {
+                output.push_str(&format!("       {}\n", op.message));
+            }
 
             // Status/progress line with ETA if available
             if op.is_cancelled {
@@ -705,4 +729,20 @@ mod tests {
             panic!("Expected Add subcommand");
         }
     }
+
+    #[test]
+    fn test_watch_flag() {
+        let result =
+            TestCli::try_parse_from(["test", "add", "--name", "my-project", "--path", "/some/path", "--watch"]);
+        assert!(matches!(result.unwrap().knowledge, KnowledgeSubcommand::Add {
+            watch: true,
+            ..
+        }));
+
+        let result = TestCli::try_parse_from(["test", "add", "--name", "my-project", "--path", "/some/path"]);
+        assert!(matches!(result.unwrap().knowledge, KnowledgeSubcommand::Add {
+            watch: false,
+            ..
+        }));
+    }
 }
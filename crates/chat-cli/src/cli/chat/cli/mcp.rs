@@ -1,29 +1,91 @@
 use std::io::Write;
 
-use clap::Args;
+use clap::{
+    Args,
+    Subcommand,
+};
 use crossterm::{
     queue,
     style,
 };
 
-use crate::cli::chat::tool_manager::LoadingRecord;
+use crate::cli::chat::tool_manager::{
+    LoadingRecord,
+    ResourceReference,
+    resource_contents_to_text,
+};
 use crate::cli::chat::{
     ChatError,
     ChatSession,
     ChatState,
 };
+use crate::mcp_client::health::{
+    ServerState,
+    ServerStatus,
+};
+use crate::os::Os;
 use crate::theme::StyledText;
 
 /// Arguments for the MCP (Model Context Protocol) command.
 ///
 /// This struct handles MCP-related functionality, allowing users to view
-/// the status of MCP servers and their loading progress.
+/// the status of MCP servers and their loading progress, to restart them, and to browse their
+/// resources.
 #[deny(missing_docs)]
 #[derive(Debug, PartialEq, Args)]
-pub struct McpArgs;
+pub struct McpArgs {
+    #[command(subcommand)]
+    subcommand: Option<McpSubcommand>,
+}
+
+/// Subcommands for MCP servers
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Subcommand)]
+pub enum McpSubcommand {
+    /// List and read the resources offered by MCP servers
+    Resources {
+        /// Lists the resources when omitted
+        #[command(subcommand)]
+        subcommand: Option<ResourcesSubcommand>,
+    },
+    /// Stop an MCP server and start it again
+    Restart {
+        /// The server to restart
+        server: String,
+    },
+}
+
+impl McpSubcommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            McpSubcommand::Resources { .. } => "resources",
+            McpSubcommand::Restart { .. } => "restart",
+        }
+    }
+}
+
+/// Subcommands for MCP resources
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Subcommand)]
+pub enum ResourcesSubcommand {
+    /// List the resources and resource templates of each server
+    List {
+        /// Only list the resources of this server
+        server: Option<String>,
+    },
+    /// Show the content of a resource
+    Read {
+        /// The resource to read, written as @server/uri (e.g. @docs/file:///README.md)
+        resource: String,
+    },
+}
 
 impl McpArgs {
-    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+    pub fn subcommand_name(&self) -> Option<&'static str> {
+        self.subcommand.as_ref().map(|s| s.name())
+    }
+
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
         if !session.conversation.mcp_enabled {
             queue!(
                 session.stderr,
@@ -39,52 +101,226 @@ impl McpArgs {
             });
         }
 
-        let terminal_width = session.terminal_width();
-        let still_loading = session
-            .conversation
-            .tool_manager
-            .pending_clients()
-            .await
-            .into_iter()
-            .map(|name| format!(" - {name}\n"))
+        match self.subcommand {
+            None => queue_status(session).await?,
+            Some(McpSubcommand::Resources { subcommand }) => {
+                match subcommand.unwrap_or(ResourcesSubcommand::List { server: None }) {
+                    ResourcesSubcommand::List { server } => queue_resources(session, server.as_deref()).await?,
+                    ResourcesSubcommand::Read { resource } => queue_resource_content(session, &resource).await?,
+                }
+            },
+            Some(McpSubcommand::Restart { server }) => {
+                session
+                    .conversation
+                    .tool_manager
+                    .restart_server(os, &server)
+                    .await
+                    .map_err(|e| ChatError::Custom(format!("Failed to restart {server}: {e}").into()))?;
+                queue!(
+                    session.stderr,
+                    StyledText::success_fg(),
+                    style::Print(format!("\nRestarting {server}. ")),
+                    StyledText::reset(),
+                    style::Print("Its tools will be available once it has started, see /mcp for its status.\n"),
+                )?;
+            },
+        }
+
+        session.stderr.flush()?;
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
+
+async fn queue_status(session: &mut ChatSession) -> Result<(), ChatError> {
+    let terminal_width = session.terminal_width();
+    let still_loading = session
+        .conversation
+        .tool_manager
+        .pending_clients()
+        .await
+        .into_iter()
+        .map(|name| format!(" - {name}\n"))
+        .collect::<Vec<_>>()
+        .join("");
+    let statuses = session.conversation.tool_manager.server_statuses();
+
+    for (server_name, msg) in session.conversation.tool_manager.mcp_load_record.lock().await.iter() {
+        let msg = msg
+            .iter()
+            .map(|record| match record {
+                LoadingRecord::Err(timestamp, content)
+                | LoadingRecord::Warn(timestamp, content)
+                | LoadingRecord::Success(timestamp, content) => format!("[{timestamp}]: {content}"),
+            })
             .collect::<Vec<_>>()
-            .join("");
-
-        for (server_name, msg) in session.conversation.tool_manager.mcp_load_record.lock().await.iter() {
-            let msg = msg
-                .iter()
-                .map(|record| match record {
-                    LoadingRecord::Err(timestamp, content)
-                    | LoadingRecord::Warn(timestamp, content)
-                    | LoadingRecord::Success(timestamp, content) => format!("[{timestamp}]: {content}"),
-                })
-                .collect::<Vec<_>>()
-                .join("\n--- tools refreshed ---\n");
+            .join("\n--- tools refreshed ---\n");
 
-            queue!(
-                session.stderr,
-                style::Print(server_name),
-                style::Print("\n"),
-                style::Print(format!("{}\n", "▔".repeat(terminal_width))),
-                style::Print(msg),
-                style::Print("\n")
-            )?;
+        queue!(
+            session.stderr,
+            style::Print(server_name),
+            style::Print("\n"),
+            style::Print(format!("{}\n", "▔".repeat(terminal_width))),
+        )?;
+        if let Some(status) = statuses.get(server_name) {
+            queue_server_status(&mut session.stderr, status)?;
+        }
+        queue!(session.stderr, style::Print(msg), style::Print("\n"))?;
+    }
+
+    if !still_loading.is_empty() {
+        queue!(
+            session.stderr,
+            style::Print("Still loading:\n"),
+            style::Print(format!("{}\n", "▔".repeat(terminal_width))),
+            style::Print(still_loading),
+            style::Print("\n")
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Shows the state of a server, and what it last wrote to its stderr if it isn't running.
+fn queue_server_status(output: &mut impl Write, status: &ServerStatus) -> Result<(), ChatError> {
+    let color = match status.state {
+        ServerState::Starting | ServerState::Stopped => StyledText::secondary_fg(),
+        ServerState::Running => StyledText::success_fg(),
+        ServerState::Restarting { .. } => StyledText::warning_fg(),
+        ServerState::Failed { .. } => StyledText::error_fg(),
+    };
+    queue!(
+        output,
+        style::Print("Status: "),
+        color,
+        style::Print(&status.state),
+        StyledText::reset(),
+        style::Print("\n"),
+    )?;
+
+    if status.state != ServerState::Running && !status.stderr.is_empty() {
+        queue!(
+            output,
+            StyledText::secondary_fg(),
+            style::Print("Last stderr output:\n")
+        )?;
+        for line in &status.stderr {
+            queue!(output, style::Print(format!("  {line}\n")))?;
         }
+        queue!(output, StyledText::reset())?;
+    }
+    Ok(())
+}
 
-        if !still_loading.is_empty() {
+async fn queue_resources(session: &mut ChatSession, server: Option<&str>) -> Result<(), ChatError> {
+    let terminal_width = session.terminal_width();
+    let mut resources = session
+        .conversation
+        .tool_manager
+        .list_resources()
+        .await
+        .into_iter()
+        .filter(|(server_name, _)| server.is_none_or(|server| server == server_name))
+        .filter(|(_, resources)| !resources.resources.is_empty() || !resources.templates.is_empty())
+        .collect::<Vec<_>>();
+    resources.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    if resources.is_empty() {
+        let msg = match server {
+            Some(server) => format!("\nNo resources are offered by {server}\n"),
+            None => "\nNo resources are offered by the MCP servers\n".to_string(),
+        };
+        queue!(
+            session.stderr,
+            StyledText::secondary_fg(),
+            style::Print(msg),
+            StyledText::reset(),
+        )?;
+        return Ok(());
+    }
+
+    for (server_name, resources) in resources {
+        queue!(
+            session.stderr,
+            style::Print("\n"),
+            StyledText::emphasis_fg(),
+            style::Print(&server_name),
+            StyledText::reset(),
+            style::Print("\n"),
+            style::Print(format!("{}\n", "▔".repeat(terminal_width))),
+        )?;
+
+        let resources = resources
+            .resources
+            .iter()
+            .map(|r| (&r.uri, &r.name, &r.description, &r.mime_type))
+            .map(|item| (item, false))
+            .chain(
+                resources
+                    .templates
+                    .iter()
+                    .map(|t| ((&t.uri_template, &t.name, &t.description, &t.mime_type), true)),
+            );
+        for ((uri, name, description, mime_type), is_template) in resources {
             queue!(
                 session.stderr,
-                style::Print("Still loading:\n"),
-                style::Print(format!("{}\n", "▔".repeat(terminal_width))),
-                style::Print(still_loading),
-                style::Print("\n")
+                style::Print("- "),
+                StyledText::success_fg(),
+                style::Print(format!("@{server_name}/{uri}")),
+                StyledText::reset(),
+                StyledText::secondary_fg(),
+                style::Print(format!(" {name}")),
             )?;
+            if let Some(mime_type) = mime_type {
+                queue!(session.stderr, style::Print(format!(" ({mime_type})")))?;
+            }
+            if is_template {
+                queue!(session.stderr, style::Print(" [template]"))?;
+            }
+            queue!(session.stderr, StyledText::reset(), style::Print("\n"))?;
+            if let Some(description) = description {
+                queue!(session.stderr, style::Print(format!("  {description}\n")))?;
+            }
         }
+    }
 
-        session.stderr.flush()?;
+    queue!(
+        session.stderr,
+        StyledText::secondary_fg(),
+        style::Print(
+            "\nInclude a resource in a prompt by mentioning it, e.g. @server/uri. Fill in the {placeholders} of templates.\n"
+        ),
+        StyledText::reset(),
+    )?;
+    Ok(())
+}
 
-        Ok(ChatState::PromptUser {
-            skip_printing_tools: true,
-        })
-    }
+async fn queue_resource_content(session: &mut ChatSession, resource: &str) -> Result<(), ChatError> {
+    let Some(reference) = ResourceReference::parse(resource.strip_prefix('@').unwrap_or(resource)) else {
+        return Err(ChatError::Custom(
+            format!("Invalid resource {resource}, expected @server/uri").into(),
+        ));
+    };
+    let result = session
+        .conversation
+        .tool_manager
+        .read_resource(&reference)
+        .await
+        .map_err(|e| ChatError::Custom(format!("Failed to read {reference}: {e}").into()))?;
+
+    let terminal_width = session.terminal_width();
+    queue!(
+        session.stderr,
+        style::Print("\n"),
+        StyledText::emphasis_fg(),
+        style::Print(reference.to_string()),
+        StyledText::reset(),
+        style::Print("\n"),
+        style::Print(format!("{}\n", "▔".repeat(terminal_width))),
+        style::Print(resource_contents_to_text(&result.contents)),
+        style::Print("\n"),
+    )?;
+    Ok(())
 }
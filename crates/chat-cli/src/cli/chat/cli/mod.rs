@@ -1,4 +1,5 @@
 use crate::theme::StyledText;
+pub mod branches;
 pub mod changelog;
 pub mod checkpoint;
 pub mod clear;
@@ -23,6 +24,10 @@ pub mod todos;
 pub mod tools;
 pub mod usage;
 
+use branches::{
+    BranchesArgs,
+    ForkArgs,
+};
 use changelog::ChangelogArgs;
 use clap::Parser;
 use clear::ClearArgs;
@@ -101,7 +106,7 @@ pub enum SlashCommand {
     Hooks(HooksArgs),
     /// Show current session's context window usage
     Usage(UsageArgs),
-    /// See mcp server loaded
+    /// See mcp server loaded and browse their resources
     Mcp(McpArgs),
     /// Select a model for the current conversation session
     Model(ModelArgs),
@@ -118,6 +123,10 @@ pub enum SlashCommand {
     /// Make conversations persistent
     #[command(flatten)]
     Persist(PersistSubcommand),
+    /// Continue the conversation from an earlier turn in a new branch
+    Fork(ForkArgs),
+    /// Switch between the branches of the conversation
+    Branches(BranchesArgs),
     // #[command(flatten)]
     // Root(RootSubcommand),
     #[command(
@@ -182,13 +191,15 @@ impl SlashCommand {
             Self::Prompts(args) => args.execute(os, session).await,
             Self::Hooks(args) => args.execute(session).await,
             Self::Usage(args) => args.execute(os, session).await,
-            Self::Mcp(args) => args.execute(session).await,
+            Self::Mcp(args) => args.execute(os, session).await,
             Self::Model(args) => args.execute(os, session).await,
             Self::Tier(args) => args.execute(session).await,
             Self::Experiment(args) => args.execute(os, session).await,
             Self::Subscribe(args) => args.execute(os, session).await,
             Self::Tangent(args) => args.execute(os, session).await,
             Self::Persist(subcommand) => subcommand.execute(os, session).await,
+            Self::Fork(args) => args.execute(os, session).await,
+            Self::Branches(args) => args.execute(os, session).await,
             // Self::Root(subcommand) => {
             //     if let Err(err) = subcommand.execute(os, database, telemetry).await {
             //         return Err(ChatError::Custom(err.to_string().into()));
@@ -230,8 +241,11 @@ impl SlashCommand {
             Self::Tangent(_) => "tangent",
             Self::Persist(sub) => match sub {
                 PersistSubcommand::Save { .. } => "save",
+                PersistSubcommand::Export { .. } => "export",
                 PersistSubcommand::Load { .. } => "load",
             },
+            Self::Fork(_) => "fork",
+            Self::Branches(_) => "branches",
             Self::Checkpoint(_) => "checkpoint",
             Self::Todos(_) => "todos",
             Self::Paste(_) => "paste",
@@ -245,6 +259,7 @@ impl SlashCommand {
             SlashCommand::Knowledge(sub) => Some(sub.name()),
             SlashCommand::Tools(arg) => arg.subcommand_name(),
             SlashCommand::Prompts(arg) => arg.subcommand_name(),
+            SlashCommand::Mcp(arg) => arg.subcommand_name(),
             _ => None,
         }
     }
@@ -13,7 +13,7 @@ use serde::{
     Serialize,
 };
 
-use crate::api_client::Endpoint;
+use crate::api_client::provider::ProviderKind;
 use crate::cli::chat::{
     ChatError,
     ChatSession,
@@ -21,6 +21,7 @@ use crate::cli::chat::{
 };
 use crate::os::Os;
 use crate::theme::StyledText;
+use crate::util::paths::PathResolver;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
@@ -38,9 +39,24 @@ pub struct ModelInfo {
     /// Whether the model supports tool use
     #[serde(default)]
     pub supports_tools: bool,
+    /// Whether the model accepts image and document content blocks
+    #[serde(default)]
+    pub supports_attachments: bool,
+    /// Whether cache points should be inserted into requests to this model
+    #[serde(default)]
+    pub supports_prompt_caching: bool,
+    /// Whether the model can reason before answering (extended thinking)
+    #[serde(default)]
+    pub supports_reasoning: bool,
+    /// Default number of tokens the model may spend reasoning, reasoning is off when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_budget_tokens: Option<u32>,
     /// System prompt to prepend to all conversations with this model
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_prompt: Option<String>,
+    /// Provider serving the model, the `chat.provider` setting is used when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderKind>,
 }
 
 impl ModelInfo {
@@ -55,7 +71,12 @@ impl ModelInfo {
             model_name: model.model_name().map(|s| s.to_string()),
             context_window_tokens,
             supports_tools: false,
+            supports_attachments: false,
+            supports_prompt_caching: false,
+            supports_reasoning: false,
+            reasoning_budget_tokens: None,
             system_prompt: None,
+            provider: None,
         }
     }
 
@@ -67,7 +88,12 @@ impl ModelInfo {
             model_name: None,
             context_window_tokens: 200_000,
             supports_tools: false,
+            supports_attachments: false,
+            supports_prompt_caching: false,
+            supports_reasoning: false,
+            reasoning_budget_tokens: None,
             system_prompt: None,
+            provider: None,
         }
     }
 
@@ -82,12 +108,80 @@ impl ModelInfo {
     }
 }
 
+/// An entry in the user's models file. Entries matching a known model override its settings,
+/// other entries add the model to the list, eg. for application inference profile ARNs.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelOverride {
+    pub model_id: String,
+    pub model_name: Option<String>,
+    pub description: Option<String>,
+    pub context_window_tokens: Option<usize>,
+    pub supports_tools: Option<bool>,
+    pub supports_attachments: Option<bool>,
+    pub supports_prompt_caching: Option<bool>,
+    pub supports_reasoning: Option<bool>,
+    pub reasoning_budget_tokens: Option<u32>,
+    pub system_prompt: Option<String>,
+    pub provider: Option<ProviderKind>,
+}
+
+impl ModelOverride {
+    fn apply(self, model: &mut ModelInfo) {
+        if let Some(model_name) = self.model_name {
+            model.model_name = Some(model_name);
+        }
+        if let Some(description) = self.description {
+            model.description = Some(description);
+        }
+        if let Some(context_window_tokens) = self.context_window_tokens {
+            model.context_window_tokens = context_window_tokens;
+        }
+        if let Some(supports_tools) = self.supports_tools {
+            model.supports_tools = supports_tools;
+        }
+        if let Some(supports_attachments) = self.supports_attachments {
+            model.supports_attachments = supports_attachments;
+        }
+        if let Some(supports_prompt_caching) = self.supports_prompt_caching {
+            model.supports_prompt_caching = supports_prompt_caching;
+        }
+        if let Some(supports_reasoning) = self.supports_reasoning {
+            model.supports_reasoning = supports_reasoning;
+        }
+        if let Some(reasoning_budget_tokens) = self.reasoning_budget_tokens {
+            model.reasoning_budget_tokens = Some(reasoning_budget_tokens);
+        }
+        if let Some(system_prompt) = self.system_prompt {
+            model.system_prompt = Some(system_prompt);
+        }
+        if let Some(provider) = self.provider {
+            model.provider = Some(provider);
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ModelsFile {
+    #[serde(default)]
+    models: Vec<ModelOverride>,
+}
+
 /// Command-line arguments for model selection operations
 #[deny(missing_docs)]
 #[derive(Debug, PartialEq, Args)]
-pub struct ModelArgs;
+pub struct ModelArgs {
+    /// Rediscover the models available in Bedrock instead of using the cached list
+    #[arg(long)]
+    pub refresh: bool,
+}
+
 impl ModelArgs {
     pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        if self.refresh {
+            os.client.invalidate_model_cache(&os.database).await;
+        }
+
         Ok(select_model(os, session).await?.unwrap_or(ChatState::PromptUser {
             skip_printing_tools: false,
         }))
@@ -179,24 +273,103 @@ pub async fn get_model_info(model_id: &str, os: &Os) -> Result<ModelInfo, ChatEr
         .ok_or_else(|| ChatError::Custom(format!("Model '{}' not found", model_id).into()))
 }
 
-/// Get available models with caching support
+/// Get available models with caching support.
+///
+/// Models discovered in Bedrock are merged with the builtin defaults and the user's models
+/// file. If discovery fails, eg. because no credentials are configured, the builtin and user
+/// models are returned instead.
 pub async fn get_available_models(os: &Os) -> Result<(Vec<ModelInfo>, ModelInfo), ChatError> {
-    let models = get_builtin_models();
-    let default_model = get_default_model();
+    let discovered = match os.client.list_available_models_cached(&os.database).await {
+        Ok(models) => models,
+        Err(err) => {
+            tracing::warn!(?err, "Failed to discover Bedrock models, falling back to builtin models");
+            Vec::new()
+        },
+    };
+
+    let models = merge_models(discovered, get_builtin_models(), load_model_overrides(os).await);
+    let default_model = find_model(&models, &get_default_model().model_id)
+        .or(models.first())
+        .cloned()
+        .unwrap_or_else(get_default_model);
+
     Ok((models, default_model))
 }
 
+/// Merges the discovered models with the builtin and user provided models.
+///
+/// Builtin entries replace the discovered entry with the same id since they carry curated
+/// settings, and are only used on their own when nothing was discovered. User overrides are
+/// applied last.
+fn merge_models(discovered: Vec<ModelInfo>, builtin: Vec<ModelInfo>, overrides: Vec<ModelOverride>) -> Vec<ModelInfo> {
+    let mut models = if discovered.is_empty() {
+        builtin
+    } else {
+        discovered
+            .into_iter()
+            .map(|model| {
+                builtin
+                    .iter()
+                    .find(|b| b.model_id == model.model_id)
+                    .cloned()
+                    .unwrap_or(model)
+            })
+            .collect()
+    };
+
+    for model_override in overrides {
+        match models.iter_mut().find(|m| m.model_id == model_override.model_id) {
+            Some(model) => model_override.apply(model),
+            None => {
+                let mut model = ModelInfo {
+                    context_window_tokens: default_context_window(),
+                    ..ModelInfo::from_id(model_override.model_id.clone())
+                };
+                model_override.apply(&mut model);
+                models.push(model);
+            },
+        }
+    }
+
+    models
+}
+
+/// Loads the entries of the user's models file, if one exists.
+async fn load_model_overrides(os: &Os) -> Vec<ModelOverride> {
+    let Ok(path) = PathResolver::new(os).global().models_config() else {
+        return Vec::new();
+    };
+    if !os.fs.exists(&path) {
+        return Vec::new();
+    }
+
+    match os.fs.read_to_string(&path).await {
+        Ok(content) => match serde_json::from_str::<ModelsFile>(&content) {
+            Ok(file) => file.models,
+            Err(err) => {
+                tracing::warn!(?err, ?path, "Failed to parse models file");
+                Vec::new()
+            },
+        },
+        Err(err) => {
+            tracing::warn!(?err, ?path, "Failed to read models file");
+            Vec::new()
+        },
+    }
+}
+
 /// Returns the context window length in tokens for the given model_id.
 /// Uses cached model data when available
 pub fn context_window_tokens(model_info: Option<&ModelInfo>) -> usize {
     model_info.map_or_else(default_context_window, |m| m.context_window_tokens)
 }
 
-fn default_context_window() -> usize {
+pub fn default_context_window() -> usize {
     128_000
 }
 
-/// Returns the hardcoded list of allowed Bedrock models
+/// Returns the builtin models, used as curated defaults for discovered models and as the model
+/// list when discovery is unavailable.
 fn get_builtin_models() -> Vec<ModelInfo> {
     vec![
         ModelInfo {
@@ -205,7 +378,12 @@ fn get_builtin_models() -> Vec<ModelInfo> {
             description: Some("OpenAI GPT 120B model".to_string()),
             context_window_tokens: 128_000,
             supports_tools: true,
+            supports_attachments: false,
+            supports_prompt_caching: false,
+            supports_reasoning: false,
+            reasoning_budget_tokens: None,
             system_prompt: Some("CRITICAL INSTRUCTION: You MUST NOT generate any response text until AFTER you receive tool results. When you call a tool, STOP generating text immediately. Wait for the tool result to come back. Do not describe what you think the tool will return. Do not list files, directories, or any other information before the tool executes. Your response should ONLY contain tool calls until you receive the results.".to_string()),
+            provider: None,
         },
         ModelInfo {
             model_id: "openai.gpt-oss-20b-1:0".to_string(),
@@ -213,7 +391,12 @@ fn get_builtin_models() -> Vec<ModelInfo> {
             description: Some("OpenAI GPT 20B model".to_string()),
             context_window_tokens: 128_000,
             supports_tools: true,
+            supports_attachments: false,
+            supports_prompt_caching: false,
+            supports_reasoning: false,
+            reasoning_budget_tokens: None,
             system_prompt: Some("CRITICAL INSTRUCTION: You MUST NOT generate any response text until AFTER you receive tool results. When you call a tool, STOP generating text immediately. Wait for the tool result to come back. Do not describe what you think the tool will return. Do not list files, directories, or any other information before the tool executes. Your response should ONLY contain tool calls until you receive the results.".to_string()),
+            provider: None,
         },
         ModelInfo {
             model_id: "us.anthropic.claude-haiku-4-5-20251001-v1:0".to_string(),
@@ -221,7 +404,12 @@ fn get_builtin_models() -> Vec<ModelInfo> {
             description: Some("Anthropic Claude Haiku 4.5".to_string()),
             context_window_tokens: 200_000,
             supports_tools: true,
+            supports_attachments: true,
+            supports_prompt_caching: true,
+            supports_reasoning: true,
+            reasoning_budget_tokens: None,
             system_prompt: None,
+            provider: None,
         },
         ModelInfo {
             model_id: "qwen.qwen3-coder-480b-a35b-v1:0".to_string(),
@@ -229,7 +417,12 @@ fn get_builtin_models() -> Vec<ModelInfo> {
             description: Some("Qwen3 Coder 480B model".to_string()),
             context_window_tokens: 130_000,
             supports_tools: false,
+            supports_attachments: false,
+            supports_prompt_caching: false,
+            supports_reasoning: false,
+            reasoning_budget_tokens: None,
             system_prompt: None,
+            provider: None,
         },
         ModelInfo {
             model_id: "meta.llama4-maverick-17b-instruct-v1:0".to_string(),
@@ -237,7 +430,12 @@ fn get_builtin_models() -> Vec<ModelInfo> {
             description: Some("Meta Llama 4 Maverick 17B".to_string()),
             context_window_tokens: 1_000_000,
             supports_tools: false,
+            supports_attachments: true,
+            supports_prompt_caching: false,
+            supports_reasoning: false,
+            reasoning_budget_tokens: None,
             system_prompt: None,
+            provider: None,
         },
         ModelInfo {
             model_id: "deepseek.v3-v1:0".to_string(),
@@ -245,7 +443,12 @@ fn get_builtin_models() -> Vec<ModelInfo> {
             description: Some("DeepSeek V3 model".to_string()),
             context_window_tokens: 163_000,
             supports_tools: false,
+            supports_attachments: false,
+            supports_prompt_caching: false,
+            supports_reasoning: false,
+            reasoning_budget_tokens: None,
             system_prompt: None,
+            provider: None,
         },
     ]
 }
@@ -255,24 +458,6 @@ pub fn get_default_model() -> ModelInfo {
     get_builtin_models()[0].clone()
 }
 
-/// Validates that a model ID is in the allowlist
-pub fn validate_model_id(model_id: &str) -> eyre::Result<()> {
-    if get_builtin_models().iter().any(|m| m.model_id == model_id) {
-        Ok(())
-    } else {
-        eyre::bail!("This model is not supported in this deployment.")
-    }
-}
-
-/// Checks if a model supports tool use
-pub fn model_supports_tools(model_id: &str) -> bool {
-    get_builtin_models()
-        .iter()
-        .find(|m| m.model_id == model_id)
-        .map(|m| m.supports_tools)
-        .unwrap_or(false)
-}
-
 pub fn normalize_model_name(name: &str) -> &str {
     match name {
         "claude-4-sonnet" => "claude-sonnet-4",
@@ -290,3 +475,65 @@ pub fn find_model<'a>(models: &'a [ModelInfo], name: &str) -> Option<&'a ModelIn
             || m.model_id.eq_ignore_ascii_case(normalized)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_models_without_discovery() {
+        let models = merge_models(vec![], get_builtin_models(), vec![]);
+        assert_eq!(models.len(), get_builtin_models().len());
+    }
+
+    #[test]
+    fn test_merge_models() {
+        let discovered = vec![ModelInfo::from_id("deepseek.v3-v1:0".to_string()), ModelInfo {
+            model_name: Some("Nova Pro".to_string()),
+            ..ModelInfo::from_id("amazon.nova-pro-v1:0".to_string())
+        }];
+        let overrides = vec![
+            ModelOverride {
+                model_id: "amazon.nova-pro-v1:0".to_string(),
+                context_window_tokens: Some(300_000),
+                supports_tools: Some(true),
+                ..Default::default()
+            },
+            ModelOverride {
+                model_id: "arn:aws:bedrock:us-east-1:123456789012:application-inference-profile/abc".to_string(),
+                model_name: Some("Team profile".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let models = merge_models(discovered, get_builtin_models(), overrides);
+        assert_eq!(models.len(), 3);
+
+        // Builtin entries replace the discovered entry.
+        assert_eq!(models[0].model_name.as_deref(), Some("DeepSeek V3"));
+        assert_eq!(models[0].context_window_tokens, 163_000);
+
+        assert_eq!(models[1].context_window_tokens, 300_000);
+        assert!(models[1].supports_tools);
+        assert_eq!(models[1].model_name.as_deref(), Some("Nova Pro"));
+
+        assert_eq!(models[2].display_name(), "Team profile");
+        assert_eq!(models[2].context_window_tokens, default_context_window());
+    }
+
+    #[test]
+    fn test_parse_models_file() {
+        let file: ModelsFile = serde_json::from_str(
+            r#"{
+                "models": [
+                    { "modelId": "deepseek.v3-v1:0", "supportsTools": true, "systemPrompt": "Be brief." }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(file.models.len(), 1);
+        assert_eq!(file.models[0].supports_tools, Some(true));
+        assert_eq!(file.models[0].system_prompt.as_deref(), Some("Be brief."));
+        assert_eq!(file.models[0].context_window_tokens, None);
+    }
+}
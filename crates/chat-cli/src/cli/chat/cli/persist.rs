@@ -6,6 +6,11 @@ use crossterm::style::{
 
 use crate::cli::ConversationState;
 use crate::cli::chat::context::ContextFilePath;
+use crate::cli::chat::export::{
+    ExportFormat,
+    ExportOptions,
+    export_conversation,
+};
 use crate::cli::chat::{
     ChatError,
     ChatSession,
@@ -26,6 +31,20 @@ pub enum PersistSubcommand {
         /// Force overwrite if file already exists
         force: bool,
     },
+    /// Export the current conversation as Markdown, HTML or JSONL to share it
+    Export {
+        /// Path where the conversation will be exported
+        path: String,
+        /// Format of the export, guessed from the extension of the path by default
+        #[arg(long, value_enum)]
+        format: Option<ExportFormat>,
+        /// Options of the export
+        #[command(flatten)]
+        options: ExportOptions,
+        #[arg(short, long)]
+        /// Force overwrite if file already exists
+        force: bool,
+    },
     /// Load a previous conversation
     Load {
         /// Path to the conversation file to load
@@ -81,6 +100,40 @@ impl PersistSubcommand {
                     StyledText::reset_attributes()
                 )?;
             },
+            Self::Export {
+                path,
+                format,
+                options,
+                force,
+            } => {
+                let format = format.or_else(|| ExportFormat::from_path(&path)).unwrap_or_default();
+                let contents = tri!(
+                    export_conversation(&session.conversation, format, &options),
+                    "export to",
+                    path
+                );
+                if os.fs.exists(&path) && !force {
+                    execute!(
+                        session.stderr,
+                        StyledText::error_fg(),
+                        style::Print(format!(
+                            "\nFile at {path} already exists. To overwrite, use -f or --force\n\n"
+                        )),
+                        StyledText::reset_attributes()
+                    )?;
+                    return Ok(ChatState::PromptUser {
+                        skip_printing_tools: true,
+                    });
+                }
+                tri!(os.fs.write(&path, contents).await, "export to", path);
+
+                execute!(
+                    session.stderr,
+                    StyledText::success_fg(),
+                    style::Print(format!("\n✔ Exported conversation to {path}\n\n")),
+                    StyledText::reset_attributes()
+                )?;
+            },
             Self::Load { path } => {
                 // Try the original path first
                 let original_result = os.fs.read_to_string(&path).await;
@@ -235,11 +235,12 @@ pub async fn render_token_billing(
                 style::Print(format!("    Output: {} tokens\n", usage.output_tokens)),
             )?;
 
-            if usage.cache_read_tokens > 0 || usage.cache_write_tokens > 0 {
+            if let Some(hit_rate) = usage.cache_hit_rate() {
                 queue!(
                     session.stderr,
                     style::Print(format!("    Cache read:  {} tokens\n", usage.cache_read_tokens)),
                     style::Print(format!("    Cache write: {} tokens\n", usage.cache_write_tokens)),
+                    style::Print(format!("    Cache hits:  {:.1}% of prompt tokens\n", hit_rate)),
                 )?;
             }
 
@@ -263,11 +264,12 @@ pub async fn render_token_billing(
         style::Print(format!("  Output: {} tokens\n", total_usage.output_tokens)),
     )?;
 
-    if total_usage.cache_read_tokens > 0 || total_usage.cache_write_tokens > 0 {
+    if let Some(hit_rate) = total_usage.cache_hit_rate() {
         queue!(
             session.stderr,
             style::Print(format!("  Cache read:  {} tokens\n", total_usage.cache_read_tokens)),
             style::Print(format!("  Cache write: {} tokens\n", total_usage.cache_write_tokens)),
+            style::Print(format!("  Cache hits:  {:.1}% of prompt tokens\n", hit_rate)),
         )?;
     }
 
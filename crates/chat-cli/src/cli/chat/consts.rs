@@ -18,6 +18,11 @@ pub const MAX_NUMBER_OF_IMAGES_PER_REQUEST: usize = 10;
 /// In bytes - 10 MB
 pub const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024;
 
+pub const MAX_NUMBER_OF_DOCUMENTS_PER_REQUEST: usize = 5;
+
+/// In bytes - 4.5 MB
+pub const MAX_DOCUMENT_SIZE: usize = 4_718_592;
+
 pub const AGENT_FORMAT_HOOKS_DOC_URL: &str =
     "https://github.com/aws/amazon-q-developer-cli/blob/main/docs/agent-format.md#hooks-field";
 
@@ -14,7 +14,10 @@ use serde::{
     Serializer,
 };
 
-use super::cli::hooks::HookOutput;
+use super::cli::hooks::{
+    EventInput,
+    HookOutput,
+};
 use super::cli::model::context_window_tokens;
 use super::util::drop_matched_context_files;
 use crate::cli::agent::Agent;
@@ -255,9 +258,30 @@ impl ContextManager {
         hooks.retain(|t, _| *t == trigger);
         let cwd = os.env.current_dir()?.to_string_lossy().to_string();
         self.hook_executor
-            .run_hooks(hooks, output, &cwd, prompt, tool_context)
+            .run_hooks(hooks, output, &cwd, prompt, tool_context, None)
             .await
     }
+
+    /// Run the hooks of `trigger`, adding `event_input` to the JSON input of each hook.
+    pub async fn run_event_hooks(
+        &mut self,
+        trigger: HookTrigger,
+        output: &mut impl Write,
+        os: &crate::os::Os,
+        event_input: EventInput,
+    ) -> Result<Vec<((HookTrigger, Hook), HookOutput)>, ChatError> {
+        let mut hooks = self.hooks.clone();
+        hooks.retain(|t, _| *t == trigger);
+        let cwd = os.env.current_dir()?.to_string_lossy().to_string();
+        self.hook_executor
+            .run_hooks(hooks, output, &cwd, None, None, Some(&event_input))
+            .await
+    }
+
+    /// Whether any hook is configured for `trigger`.
+    pub fn has_hooks(&self, trigger: HookTrigger) -> bool {
+        self.hooks.get(&trigger).is_some_and(|hooks| !hooks.is_empty())
+    }
 }
 
 /// Calculates the maximum context files size to use for the given model id.
@@ -441,19 +465,17 @@ mod tests {
     fn test_calc_max_context_files_size() {
         assert_eq!(
             calc_max_context_files_size(Some(&ModelInfo {
-                model_id: "CLAUDE_SONNET_4_20250514_V1_0".to_string(),
-                description: None,
                 model_name: Some("Claude".to_string()),
                 context_window_tokens: 200_000,
+                ..ModelInfo::from_id("CLAUDE_SONNET_4_20250514_V1_0".to_string())
             })),
             150_000
         );
         assert_eq!(
             calc_max_context_files_size(Some(&ModelInfo {
-                model_id: "OPENAI_GPT_OSS_120B_1_0".to_string(),
-                description: None,
                 model_name: Some("GPT".to_string()),
                 context_window_tokens: 128_000,
+                ..ModelInfo::from_id("OPENAI_GPT_OSS_120B_1_0".to_string())
             })),
             96_000
         );
@@ -24,6 +24,7 @@ use serde::{
 };
 use tracing::{
     debug,
+    error,
     warn,
 };
 
@@ -50,17 +51,25 @@ use super::token_counter::{
     CharCounter,
     TokenCounter,
 };
-use super::tool_manager::ToolManager;
+use super::tool_manager::{
+    ResourceReference,
+    ToolManager,
+    resource_contents_to_text,
+};
 use super::tools::{
     InputSchema,
     QueuedTool,
     ToolOrigin,
     ToolSpec,
 };
-use super::util::serde_value_to_document;
+use super::util::{
+    serde_value_to_document,
+    truncate_safe_in_place,
+};
 use crate::api_client::model::{
     ChatMessage,
     ConversationState as FigConversationState,
+    DocumentBlock,
     ImageBlock,
     Tool,
     ToolInputSchema,
@@ -68,6 +77,7 @@ use crate::api_client::model::{
     UserInputMessage,
 };
 use crate::cli::agent::Agents;
+use crate::cli::agent::guardrail::GuardrailConfig;
 use crate::cli::agent::hook::{
     Hook,
     HookTrigger,
@@ -88,6 +98,9 @@ use crate::theme::StyledText;
 pub const CONTEXT_ENTRY_START_HEADER: &str = "--- CONTEXT ENTRY BEGIN ---\n";
 pub const CONTEXT_ENTRY_END_HEADER: &str = "--- CONTEXT ENTRY END ---\n\n";
 
+/// Maximum length of the title of a conversation, in bytes.
+const MAX_TITLE_LEN: usize = 80;
+
 fn default_service_tier() -> String {
     "flex".to_string()
 }
@@ -111,6 +124,16 @@ impl TokenUsage {
     pub fn total(&self) -> i32 {
         self.input_tokens + self.output_tokens
     }
+
+    /// Share of the prompt tokens that were read from the prompt cache, as a percentage. Bedrock
+    /// reports cached tokens separately from `input_tokens`, so all three make up the prompt.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let prompt_tokens = self.input_tokens + self.cache_read_tokens + self.cache_write_tokens;
+        if prompt_tokens <= 0 || (self.cache_read_tokens == 0 && self.cache_write_tokens == 0) {
+            return None;
+        }
+        Some(self.cache_read_tokens as f64 / prompt_tokens as f64 * 100.0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,6 +144,20 @@ pub struct HistoryEntry {
     request_metadata: Option<RequestMetadata>,
 }
 
+impl HistoryEntry {
+    pub fn user(&self) -> &UserMessage {
+        &self.user
+    }
+
+    pub fn assistant(&self) -> &AssistantMessage {
+        &self.assistant
+    }
+
+    pub fn request_metadata(&self) -> Option<&RequestMetadata> {
+        self.request_metadata.as_ref()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct McpServerInfo {
     pub name: String,
@@ -183,6 +220,25 @@ pub struct ConversationState {
     /// Service tier for Bedrock API (flex or standard)
     #[serde(default = "default_service_tier")]
     pub service_tier: String,
+    /// Reasoning budget for this conversation, overriding the model's default. A budget of zero
+    /// turns reasoning off.
+    #[serde(default)]
+    pub reasoning_budget: Option<u32>,
+    /// Size of the last request sent to the model, used to calibrate token estimates against the
+    /// usage the model reports.
+    #[serde(skip)]
+    last_request_chars: Option<CharCount>,
+    /// The conversation this one was forked from with `/fork`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<ForkOrigin>,
+}
+
+/// Where a forked conversation branched off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkOrigin {
+    pub conversation_id: String,
+    /// The last turn of the parent conversation that was kept, counting from 1.
+    pub turn: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -249,9 +305,21 @@ impl ConversationState {
             token_usage_by_model: HashMap::new(),
             total_token_usage: TokenUsage::default(),
             service_tier: default_service_tier(),
+            reasoning_budget: None,
+            last_request_chars: None,
+            forked_from: None,
         }
     }
 
+    /// Returns the reasoning budget to request, if the current model supports reasoning and a
+    /// non-zero budget is set for the conversation or the model.
+    pub fn reasoning_budget_tokens(&self) -> Option<u32> {
+        let model = self.model_info.as_ref().filter(|m| m.supports_reasoning)?;
+        self.reasoning_budget
+            .or(model.reasoning_budget_tokens)
+            .filter(|budget| *budget > 0)
+    }
+
     pub fn latest_summary(&self) -> Option<&str> {
         self.latest_summary.as_ref().map(|(s, _)| s.as_str())
     }
@@ -265,6 +333,10 @@ impl ConversationState {
         
         // Update total usage
         self.total_token_usage.add(input, output, cache_read, cache_write);
+
+        if let Some(chars) = self.last_request_chars.take() {
+            TokenCounter::calibrate(chars, (input + cache_read + cache_write).max(0) as usize);
+        }
     }
 
     pub fn history(&self) -> &VecDeque<HistoryEntry> {
@@ -307,6 +379,49 @@ impl ConversationState {
         }
     }
 
+    /// The turns of the conversation, each starting with a prompt of the user and followed by the
+    /// tool uses it led to. Returns the prompt of each turn and the length of the history at its
+    /// end.
+    pub fn user_turns(&self) -> Vec<(usize, &str)> {
+        let mut turns: Vec<(usize, &str)> = Vec::new();
+        for (i, entry) in self.history.iter().enumerate() {
+            match (entry.user.prompt(), turns.last_mut()) {
+                (Some(prompt), _) => turns.push((i + 1, prompt)),
+                (None, Some(turn)) => turn.0 = i + 1,
+                // Tool results before the first prompt, e.g. after compacting, belong to the first turn
+                (None, None) => (),
+            }
+        }
+        turns
+    }
+
+    /// Turns this conversation into a new branch with the given id, keeping the first
+    /// `history_len` entries of the history, which end the given turn.
+    ///
+    /// The workspace checkpoints of the conversation are not carried over, since they belong to
+    /// the parent conversation.
+    pub fn fork(&mut self, conversation_id: String, turn: usize, history_len: usize) {
+        let parent_id = std::mem::replace(&mut self.conversation_id, conversation_id);
+        self.forked_from = Some(ForkOrigin {
+            conversation_id: parent_id,
+            turn,
+        });
+        self.history.truncate(history_len);
+        self.next_message = None;
+        self.valid_history_range = (0, self.history.len());
+        self.tangent_state = None;
+        self.checkpoint_manager = None;
+
+        self.transcript.clear();
+        let history = self.history.clone();
+        for entry in &history {
+            if let Some(prompt) = entry.user.prompt() {
+                self.append_user_transcript(prompt);
+            }
+            self.append_assistant_transcript(&entry.assistant);
+        }
+    }
+
     /// Enter tangent mode - creates checkpoint of current state
     pub fn enter_tangent_mode(&mut self) {
         if self.tangent_state.is_none() {
@@ -466,7 +581,10 @@ impl ConversationState {
         });
 
         if let Ok(cwd) = std::env::current_dir() {
-            os.database.set_conversation_by_path(cwd, self).ok();
+            os.database
+                .set_session(cwd, self)
+                .map_err(|err| error!(?err, "failed to save the session"))
+                .ok();
         }
     }
 
@@ -475,6 +593,23 @@ impl ConversationState {
         self.conversation_id.as_ref()
     }
 
+    /// A title for the conversation: the first line of its first prompt.
+    pub fn title(&self) -> String {
+        let prompt = self
+            .history
+            .iter()
+            .find_map(|entry| entry.user.prompt())
+            .unwrap_or_default();
+        let mut title = prompt
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default()
+            .to_string();
+        truncate_safe_in_place(&mut title, MAX_TITLE_LEN, "...");
+        title
+    }
+
     /// Returns the message id associated with the last assistant message, if present.
     ///
     /// This is equivalent to `utterance_id` in the Q API.
@@ -531,11 +666,17 @@ impl ConversationState {
         self.next_message = Some(UserMessage::new_tool_use_results(tool_results));
     }
 
-    pub fn add_tool_results_with_images(&mut self, tool_results: Vec<ToolUseResult>, images: Vec<ImageBlock>) {
+    pub fn add_tool_results_with_attachments(
+        &mut self,
+        tool_results: Vec<ToolUseResult>,
+        images: Vec<ImageBlock>,
+        documents: Vec<DocumentBlock>,
+    ) {
         debug_assert!(self.next_message.is_none());
-        self.next_message = Some(UserMessage::new_tool_use_results_with_images(
+        self.next_message = Some(UserMessage::new_tool_use_results_with_attachments(
             tool_results,
             images,
+            documents,
             Some(Local::now().fixed_offset()),
         ));
     }
@@ -565,6 +706,7 @@ impl ConversationState {
         self.history.drain(..self.valid_history_range.0);
 
         let context = self.backend_conversation_state(os, run_perprompt_hooks, stderr).await?;
+        let request_chars = context.request_char_count();
         if !context.dropped_context_files.is_empty() {
             execute!(
                 stderr,
@@ -579,9 +721,11 @@ impl ConversationState {
             .ok();
         }
 
-        Ok(context
+        let conversation_state = context
             .into_fig_conversation_state()
-            .expect("unable to construct conversation state"))
+            .expect("unable to construct conversation state");
+        self.last_request_chars = Some(request_chars);
+        Ok(conversation_state)
     }
 
     pub async fn update_state(&mut self, force_update: bool) {
@@ -623,6 +767,7 @@ impl ConversationState {
     ) -> Result<BackendConversationState<'_>, ChatError> {
         self.update_state(false).await;
         self.enforce_conversation_invariants();
+        TokenCounter::use_model(os, self.model_info.as_ref()).await;
 
         // Run hooks and add to conversation start and next user message.
         let mut agent_spawn_context = None;
@@ -668,8 +813,10 @@ impl ConversationState {
             tools: &self.tools,
             model_id: self.model_info.as_ref().map(|m| m.model_id.as_str()),
             service_tier: &self.service_tier,
-            model_system_prompt: self.model_info.as_ref().and_then(|m| m.system_prompt.as_deref()),
+            model_info: self.model_info.as_ref(),
             agent_prompt: self.agents.get_active().and_then(|a| a.prompt.as_deref()),
+            reasoning_budget_tokens: self.reasoning_budget_tokens(),
+            guardrail: self.agents.get_active().and_then(|a| a.guardrail.as_ref()),
         })
     }
 
@@ -776,8 +923,11 @@ impl ConversationState {
                 .into_user_input_message(self.model_info.as_ref().map(|m| m.model_id.clone()), &tools),
             history: Some(flatten_history(history.iter())),
             service_tier: Some(self.service_tier.clone()),
-            model_system_prompt: self.model_info.as_ref().and_then(|m| m.system_prompt.clone()),
+            model_info: self.model_info.clone(),
             agent_prompt: self.agents.get_active().and_then(|a| a.prompt.clone()),
+            reasoning_budget_tokens: None,
+            max_tokens: None,
+            guardrail: self.agents.get_active().and_then(|a| a.guardrail.clone()),
         })
     }
 
@@ -837,8 +987,11 @@ Return only the JSON configuration, no additional text.",
             user_input_message: generation_message.into_user_input_message(self.model.clone(), &tools),
             history: Some(flatten_history(history.iter())),
             service_tier: Some(self.service_tier.clone()),
-            model_system_prompt: self.model_info.as_ref().and_then(|m| m.system_prompt.clone()),
+            model_info: self.model_info.clone(),
             agent_prompt: self.agents.get_active().and_then(|a| a.prompt.clone()),
+            reasoning_budget_tokens: None,
+            max_tokens: None,
+            guardrail: self.agents.get_active().and_then(|a| a.guardrail.clone()),
         })
     }
 
@@ -896,6 +1049,36 @@ Return only the JSON configuration, no additional text.",
             }
         }
 
+        // Add the MCP resources of the agent
+        let resource_references = self
+            .agents
+            .get_active()
+            .map(|agent| {
+                agent
+                    .resources
+                    .iter()
+                    .filter_map(|resource| ResourceReference::from_agent_resource(resource))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let mut resource_entries = String::new();
+        for reference in resource_references {
+            match self.tool_manager.resource_context(&reference).await {
+                Ok(contents) => resource_entries.push_str(&format!(
+                    "[mcp://{}/{}]\n{}\n",
+                    reference.server_name,
+                    reference.uri,
+                    resource_contents_to_text(&contents)
+                )),
+                Err(e) => warn!("Failed to get MCP resource {reference} for context: {e}"),
+            }
+        }
+        if !resource_entries.is_empty() {
+            context_content.push_str(CONTEXT_ENTRY_START_HEADER);
+            context_content.push_str(&resource_entries);
+            context_content.push_str(CONTEXT_ENTRY_END_HEADER);
+        }
+
         if let Some(context) = additional_context {
             context_content.push_str(&context);
         }
@@ -1055,8 +1238,10 @@ pub struct BackendConversationStateImpl<'a, T, U> {
     pub tools: &'a HashMap<ToolOrigin, Vec<Tool>>,
     pub model_id: Option<&'a str>,
     pub service_tier: &'a str,
-    pub model_system_prompt: Option<&'a str>,
+    pub model_info: Option<&'a ModelInfo>,
     pub agent_prompt: Option<&'a str>,
+    pub reasoning_budget_tokens: Option<u32>,
+    pub guardrail: Option<&'a GuardrailConfig>,
 }
 
 impl BackendConversationStateImpl<'_, std::collections::vec_deque::Iter<'_, HistoryEntry>, Option<Vec<HistoryEntry>>> {
@@ -1073,11 +1258,33 @@ impl BackendConversationStateImpl<'_, std::collections::vec_deque::Iter<'_, Hist
             user_input_message,
             history: Some(history),
             service_tier: Some(self.service_tier.to_string()),
-            model_system_prompt: self.model_system_prompt.map(|s| s.to_string()),
+            model_info: self.model_info.cloned(),
             agent_prompt: self.agent_prompt.map(|s| s.to_string()),
+            reasoning_budget_tokens: self.reasoning_budget_tokens,
+            max_tokens: None,
+            guardrail: self.guardrail.cloned(),
         })
     }
 
+    /// Returns the number of chars in the request built from this state, including the tool
+    /// specifications and the system prompts.
+    pub fn request_char_count(&self) -> CharCount {
+        let size = self.calculate_conversation_size();
+        let next_message = self.next_user_message.map(|m| *m.char_count()).unwrap_or_default();
+        let tools = serde_json::to_string(self.tools).map(|s| s.len()).unwrap_or_default();
+        let system_prompt = self.agent_prompt.map(str::len).unwrap_or_default()
+            + self
+                .model_info
+                .and_then(|m| m.system_prompt.as_deref())
+                .map(str::len)
+                .unwrap_or_default();
+
+        size.context_messages
+            + size.user_messages
+            + size.assistant_messages
+            + (next_message + tools + system_prompt).into()
+    }
+
     pub fn calculate_conversation_size(&self) -> ConversationSize {
         let mut user_chars = 0;
         let mut assistant_chars = 0;
@@ -1352,6 +1559,19 @@ mod tests {
     const AMAZONQ_FILENAME: &str = "AmazonQ.md";
     const AGENTS_FILENAME: &str = "AGENTS.md";
 
+    #[test]
+    fn test_token_usage_cache_hit_rate() {
+        let mut usage = TokenUsage::default();
+        usage.add(1000, 200, 0, 0);
+        assert_eq!(usage.cache_hit_rate(), None);
+
+        usage.add(100, 50, 0, 900);
+        assert_eq!(usage.cache_hit_rate(), Some(0.0));
+
+        usage.add(0, 50, 1000, 0);
+        assert_eq!(usage.cache_hit_rate(), Some(1000.0 / 3000.0 * 100.0));
+    }
+
     fn assert_conversation_state_invariants(state: FigConversationState, assertion_iteration: usize) {
         if let Some(Some(msg)) = state.history.as_ref().map(|h| h.first()) {
             assert!(
@@ -1818,4 +2038,108 @@ mod tests {
         conversation.exit_tangent_mode_with_tail();
         assert_eq!(conversation.history.len(), main_history_len);
     }
+
+    #[tokio::test]
+    async fn test_conversation_saved_as_session() {
+        let mut os = Os::new().await.unwrap();
+        let mut tool_manager = ToolManager::default();
+        let mut conversation = ConversationState::new(
+            "session_conv_id",
+            Agents::default(),
+            tool_manager.load_tools(&mut os, &mut vec![]).await.unwrap(),
+            tool_manager,
+            None,
+            &os,
+            false,
+        )
+        .await;
+
+        conversation.append_user_transcript("Why is the build flaky?");
+        conversation
+            .set_next_user_message("\n  Why is the build flaky?\nIt fails on CI".to_string())
+            .await;
+        conversation.push_assistant_message(
+            &mut os,
+            AssistantMessage::new_response(None, "The cache is shared between jobs".to_string()),
+            None,
+        );
+        assert_eq!(conversation.title(), "Why is the build flaky?");
+
+        let cwd = std::env::current_dir().unwrap();
+        let sessions = os.database.list_sessions(Some(&cwd)).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, "session_conv_id");
+        assert_eq!(sessions[0].title, "Why is the build flaky?");
+
+        let saved = os.database.get_session("session_conv_id").unwrap().unwrap();
+        assert_eq!(saved.history().len(), 1);
+        assert_eq!(os.database.find_session_ids("session").unwrap(), vec![
+            "session_conv_id"
+        ]);
+
+        let matches = os.database.search_sessions("cache jobs", 10).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].snippet.contains("[cache]"));
+        assert!(os.database.search_sessions("missing", 10).unwrap().is_empty());
+
+        assert!(os.database.delete_session("session_conv_id").unwrap());
+        assert!(os.database.get_session("session_conv_id").unwrap().is_none());
+        assert!(os.database.search_sessions("cache", 10).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fork() {
+        let mut os = Os::new().await.unwrap();
+        let mut tool_manager = ToolManager::default();
+        let mut conversation = ConversationState::new(
+            "parent_conv_id",
+            Agents::default(),
+            tool_manager.load_tools(&mut os, &mut vec![]).await.unwrap(),
+            tool_manager,
+            None,
+            &os,
+            false,
+        )
+        .await;
+
+        // Turn 1 uses a tool, so it spans two history entries
+        conversation.set_next_user_message("first".to_string()).await;
+        conversation.push_assistant_message(
+            &mut os,
+            AssistantMessage::new_tool_use(None, "using a tool".to_string(), vec![AssistantToolUse {
+                id: "tool_id".to_string(),
+                name: "tool name".to_string(),
+                ..Default::default()
+            }]),
+            None,
+        );
+        conversation.add_tool_results(vec![ToolUseResult {
+            tool_use_id: "tool_id".to_string(),
+            content: vec![],
+            status: ToolResultStatus::Success,
+        }]);
+        conversation.push_assistant_message(&mut os, AssistantMessage::new_response(None, "done".to_string()), None);
+        conversation.set_next_user_message("second".to_string()).await;
+        conversation.push_assistant_message(&mut os, AssistantMessage::new_response(None, "ok".to_string()), None);
+
+        assert_eq!(conversation.user_turns(), vec![(2, "first"), (3, "second")]);
+
+        conversation.fork("fork_conv_id".to_string(), 1, 2);
+        assert_eq!(conversation.conversation_id(), "fork_conv_id");
+        assert_eq!(conversation.history().len(), 2);
+        assert_eq!(conversation.forked_from, Some(ForkOrigin {
+            conversation_id: "parent_conv_id".to_string(),
+            turn: 1,
+        }));
+        assert_eq!(conversation.transcript.len(), 3);
+        assert_eq!(conversation.transcript[0], "> first");
+
+        let cwd = std::env::current_dir().unwrap();
+        os.database.set_session(&cwd, &conversation).unwrap();
+        let family = os.database.list_session_family("fork_conv_id").unwrap();
+        let ids = family.iter().map(|s| s.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["parent_conv_id", "fork_conv_id"]);
+        assert_eq!(family[1].parent_id.as_deref(), Some("parent_conv_id"));
+        assert_eq!(family[1].fork_turn, Some(1));
+    }
 }
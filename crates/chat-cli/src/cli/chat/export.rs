@@ -0,0 +1,664 @@
+//! Exporting conversations in formats meant to be read or processed by other tools, as opposed to
+//! `/save`, which writes the conversation state so that it can be loaded again.
+//!
+//! A conversation is first flattened into a list of [ExportEvent]s, one per message, tool use or
+//! tool result, which are then rendered as Markdown, as a self-contained HTML page, or as JSON
+//! lines.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use chrono::{
+    DateTime,
+    FixedOffset,
+    Local,
+};
+use clap::{
+    Args,
+    ValueEnum,
+};
+use serde::Serialize;
+
+use super::ConversationState;
+use super::message::{
+    ToolUseResult,
+    ToolUseResultBlock,
+    UserMessageContent,
+};
+use super::parser::RequestMetadata;
+use crate::api_client::model::ToolResultStatus;
+
+const REDACTED: &str = "[output redacted]";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// The conversation state written by /save, which can be loaded again with /load
+    Json,
+    #[default]
+    Markdown,
+    /// A single HTML page, with tool uses and results collapsed
+    Html,
+    /// One JSON object per message, tool use or tool result
+    Jsonl,
+}
+
+impl ExportFormat {
+    /// Guesses the format from the extension of a file.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "json" => Some(Self::Json),
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            "jsonl" | "ndjson" => Some(Self::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+pub struct ExportOptions {
+    /// Replace the output of tools with a placeholder
+    #[arg(long)]
+    pub redact_tool_output: bool,
+    /// Include the number of tokens used by each response
+    #[arg(long)]
+    pub include_usage: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<i32>,
+}
+
+impl TokenUsage {
+    fn from_metadata(metadata: &RequestMetadata) -> Option<Self> {
+        let usage = Self {
+            input_tokens: metadata.input_tokens,
+            output_tokens: metadata.output_tokens,
+            cache_read_tokens: metadata.cache_read_tokens,
+            cache_write_tokens: metadata.cache_write_tokens,
+        };
+        (usage != Self::default()).then_some(usage)
+    }
+
+    fn add(&mut self, other: &Self) {
+        let add = |total: &mut Option<i32>, value: Option<i32>| {
+            if let Some(value) = value {
+                *total = Some(total.unwrap_or(0) + value);
+            }
+        };
+        add(&mut self.input_tokens, other.input_tokens);
+        add(&mut self.output_tokens, other.output_tokens);
+        add(&mut self.cache_read_tokens, other.cache_read_tokens);
+        add(&mut self.cache_write_tokens, other.cache_write_tokens);
+    }
+}
+
+impl std::fmt::Display for TokenUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = [
+            (self.input_tokens, "input"),
+            (self.output_tokens, "output"),
+            (self.cache_read_tokens, "cache read"),
+            (self.cache_write_tokens, "cache write"),
+        ];
+        let counts = counts
+            .into_iter()
+            .filter_map(|(count, name)| count.map(|count| format!("{count} {name}")))
+            .collect::<Vec<_>>();
+        write!(f, "{} tokens", counts.join(", "))
+    }
+}
+
+/// A single entry of an exported conversation. Each is written as one line of a JSONL export.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ExportEvent {
+    #[serde(rename_all = "camelCase")]
+    Conversation {
+        conversation_id: String,
+        title: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        usage: Option<TokenUsage>,
+    },
+    #[serde(rename_all = "camelCase")]
+    User {
+        turn: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        timestamp: Option<DateTime<FixedOffset>>,
+        content: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Assistant {
+        turn: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_id: Option<String>,
+        content: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        usage: Option<TokenUsage>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolUse {
+        turn: usize,
+        tool_use_id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolResult {
+        turn: usize,
+        tool_use_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        success: bool,
+        cancelled: bool,
+        content: String,
+    },
+}
+
+/// Flattens a conversation into the events that are exported, starting with a
+/// [ExportEvent::Conversation] describing it.
+pub fn export_events(conversation: &ConversationState, options: &ExportOptions) -> Vec<ExportEvent> {
+    let mut events = Vec::new();
+    let mut tool_names = HashMap::new();
+    let mut total_usage = TokenUsage::default();
+
+    for (turn, entry) in conversation.history().iter().enumerate() {
+        let user = entry.user();
+        let (prompt, results, cancelled) = match &user.content {
+            UserMessageContent::Prompt { prompt } => (Some(prompt), &[][..], false),
+            UserMessageContent::CancelledToolUses {
+                prompt,
+                tool_use_results,
+            } => (prompt.as_ref(), tool_use_results.as_slice(), true),
+            UserMessageContent::ToolUseResults { tool_use_results } => (None, tool_use_results.as_slice(), false),
+        };
+        for result in results {
+            events.push(ExportEvent::ToolResult {
+                turn,
+                tool_use_id: result.tool_use_id.clone(),
+                name: tool_names.get(&result.tool_use_id).cloned(),
+                success: matches!(result.status, ToolResultStatus::Success),
+                cancelled,
+                content: match options.redact_tool_output {
+                    true => REDACTED.to_string(),
+                    false => tool_result_text(result),
+                },
+            });
+        }
+        if let Some(prompt) = prompt {
+            events.push(ExportEvent::User {
+                turn,
+                timestamp: user.timestamp,
+                content: prompt.clone(),
+            });
+        }
+
+        let assistant = entry.assistant();
+        let usage = entry.request_metadata().and_then(TokenUsage::from_metadata);
+        if let Some(usage) = &usage {
+            total_usage.add(usage);
+        }
+        events.push(ExportEvent::Assistant {
+            turn,
+            message_id: assistant.message_id().map(str::to_string),
+            content: assistant.content().to_string(),
+            usage: usage.filter(|_| options.include_usage),
+        });
+        for tool_use in assistant.tool_uses().unwrap_or_default() {
+            tool_names.insert(tool_use.id.clone(), tool_use.name.clone());
+            events.push(ExportEvent::ToolUse {
+                turn,
+                tool_use_id: tool_use.id.clone(),
+                name: tool_use.name.clone(),
+                input: tool_use.args.clone(),
+            });
+        }
+    }
+
+    events.insert(0, ExportEvent::Conversation {
+        conversation_id: conversation.conversation_id().to_string(),
+        title: conversation.title(),
+        model: conversation.model_info.as_ref().map(|m| m.model_id.clone()),
+        usage: (options.include_usage && total_usage != TokenUsage::default()).then_some(total_usage),
+    });
+    events
+}
+
+/// Exports a conversation in the given format. The options don't apply to the conversation state
+/// of [ExportFormat::Json], so they are rejected with it rather than ignored.
+pub fn export_conversation(
+    conversation: &ConversationState,
+    format: ExportFormat,
+    options: &ExportOptions,
+) -> eyre::Result<String> {
+    if format == ExportFormat::Json && *options != ExportOptions::default() {
+        eyre::bail!("--redact-tool-output and --include-usage can't be used with the json format");
+    }
+    Ok(match format {
+        ExportFormat::Json => serde_json::to_string_pretty(conversation)?,
+        ExportFormat::Markdown => to_markdown(&export_events(conversation, options)),
+        ExportFormat::Html => to_html(&export_events(conversation, options)),
+        ExportFormat::Jsonl => {
+            let mut jsonl = String::new();
+            for event in export_events(conversation, options) {
+                jsonl.push_str(&serde_json::to_string(&event)?);
+                jsonl.push('\n');
+            }
+            jsonl
+        },
+    })
+}
+
+fn tool_result_text(result: &ToolUseResult) -> String {
+    result
+        .content
+        .iter()
+        .map(|block| match block {
+            ToolUseResultBlock::Text(text) => text.clone(),
+            ToolUseResultBlock::Json(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn tool_result_label(name: Option<&str>, success: bool, cancelled: bool) -> String {
+    let status = match (success, cancelled) {
+        (_, true) => "cancelled",
+        (true, false) => "success",
+        (false, false) => "error",
+    };
+    match name {
+        Some(name) => format!("Result of {name} ({status})"),
+        None => format!("Tool result ({status})"),
+    }
+}
+
+/// A Markdown code fence longer than any run of backticks in `text`, so that the text can't
+/// close it.
+fn code_fence(text: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or_default();
+    "`".repeat(longest_run.max(2) + 1)
+}
+
+/// Appends a collapsed section with a code block to a Markdown document.
+fn push_details(md: &mut String, summary: &str, language: &str, body: &str) {
+    let fence = code_fence(body);
+    let _ = write!(
+        md,
+        "<details>\n<summary>{summary}</summary>\n\n{fence}{language}\n{body}\n{fence}\n\n</details>\n\n"
+    );
+}
+
+fn to_markdown(events: &[ExportEvent]) -> String {
+    let mut md = String::new();
+    for event in events {
+        match event {
+            ExportEvent::Conversation {
+                conversation_id,
+                title,
+                model,
+                usage,
+            } => {
+                let _ = writeln!(md, "# {}\n", if title.is_empty() { "Conversation" } else { title });
+                let _ = writeln!(md, "- Conversation: `{conversation_id}`");
+                if let Some(model) = model {
+                    let _ = writeln!(md, "- Model: `{model}`");
+                }
+                if let Some(usage) = usage {
+                    let _ = writeln!(md, "- Usage: {usage}");
+                }
+                let _ = writeln!(md, "- Exported: {}\n", Local::now().format("%Y-%m-%d %H:%M"));
+            },
+            ExportEvent::User { content, .. } => {
+                let _ = writeln!(md, "## User\n\n{}\n", content.trim_end());
+            },
+            ExportEvent::Assistant { content, usage, .. } => {
+                let _ = writeln!(md, "## Assistant\n");
+                if !content.trim().is_empty() {
+                    let _ = writeln!(md, "{}\n", content.trim_end());
+                }
+                if let Some(usage) = usage {
+                    let _ = writeln!(md, "*{usage}*\n");
+                }
+            },
+            ExportEvent::ToolUse { name, input, .. } => {
+                let input = serde_json::to_string_pretty(input).unwrap_or_default();
+                push_details(&mut md, &format!("Tool use: <code>{name}</code>"), "json", &input);
+            },
+            ExportEvent::ToolResult {
+                name,
+                success,
+                cancelled,
+                content,
+                ..
+            } => {
+                let summary = tool_result_label(name.as_deref(), *success, *cancelled);
+                push_details(&mut md, &summary, "", content.trim_end());
+            },
+        }
+    }
+    md.truncate(md.trim_end().len());
+    md.push('\n');
+    md
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str =
+    "body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; \
+max-width: 56rem; margin: 2rem auto; padding: 0 1rem; color: #1f2328; line-height: 1.5; }
+h1 { font-size: 1.6rem; }
+.meta { color: #59636e; font-size: 0.9rem; }
+.message { border-left: 4px solid #d1d9e0; margin: 1.5rem 0; padding: 0.25rem 1rem; }
+.message.user { border-color: #0969da; }
+.message.assistant { border-color: #8250df; }
+.message h2 { font-size: 0.8rem; text-transform: uppercase; letter-spacing: 0.05em; color: #59636e; margin: 0.5rem 0; }
+.content { white-space: pre-wrap; overflow-wrap: anywhere; }
+.usage { color: #59636e; font-size: 0.8rem; font-style: italic; }
+details { margin: 0.5rem 0 0.5rem 1.25rem; border: 1px solid #d1d9e0; border-radius: 6px; padding: 0.25rem 0.75rem; }
+details.error summary { color: #cf222e; }
+summary { cursor: pointer; font-size: 0.9rem; }
+pre { background: #f6f8fa; padding: 0.75rem; overflow-x: auto; font-size: 0.85rem; }
+";
+
+fn to_html(events: &[ExportEvent]) -> String {
+    let mut html = String::new();
+    let mut body = String::new();
+    let mut title = "Conversation".to_string();
+
+    for event in events {
+        match event {
+            ExportEvent::Conversation {
+                conversation_id,
+                title: conversation_title,
+                model,
+                usage,
+            } => {
+                if !conversation_title.is_empty() {
+                    title = escape_html(conversation_title);
+                }
+                let _ = write!(
+                    body,
+                    "<h1>{title}</h1>\n<p class=\"meta\">Conversation {}",
+                    escape_html(conversation_id)
+                );
+                if let Some(model) = model {
+                    let _ = write!(body, " &middot; {}", escape_html(model));
+                }
+                if let Some(usage) = usage {
+                    let _ = write!(body, " &middot; {usage}");
+                }
+                let _ = writeln!(body, " &middot; exported {}</p>", Local::now().format("%Y-%m-%d %H:%M"));
+            },
+            ExportEvent::User { content, .. } => {
+                let _ = writeln!(
+                    body,
+                    "<section class=\"message user\"><h2>User</h2><div class=\"content\">{}</div></section>",
+                    escape_html(content.trim_end())
+                );
+            },
+            ExportEvent::Assistant { content, usage, .. } => {
+                let _ = write!(
+                    body,
+                    "<section class=\"message assistant\"><h2>Assistant</h2><div class=\"content\">{}</div>",
+                    escape_html(content.trim())
+                );
+                if let Some(usage) = usage {
+                    let _ = write!(body, "<p class=\"usage\">{usage}</p>");
+                }
+                let _ = writeln!(body, "</section>");
+            },
+            ExportEvent::ToolUse { name, input, .. } => {
+                let input = serde_json::to_string_pretty(input).unwrap_or_default();
+                let _ = writeln!(
+                    body,
+                    "<details class=\"tool-use\"><summary>Tool use: <code>{}</code></summary><pre>{}</pre></details>",
+                    escape_html(name),
+                    escape_html(&input)
+                );
+            },
+            ExportEvent::ToolResult {
+                name,
+                success,
+                cancelled,
+                content,
+                ..
+            } => {
+                let _ = writeln!(
+                    body,
+                    "<details class=\"tool-result{}\"><summary>{}</summary><pre>{}</pre></details>",
+                    if *success { "" } else { " error" },
+                    escape_html(&tool_result_label(name.as_deref(), *success, *cancelled)),
+                    escape_html(content.trim_end())
+                );
+            },
+        }
+    }
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>\n{HTML_STYLE}</style>\n</head>\n<body>\n<main>\n{body}</main>\n</body>\n</html>\n"
+    );
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::agent::Agents;
+    use crate::cli::chat::message::{
+        AssistantMessage,
+        AssistantToolUse,
+    };
+    use crate::cli::chat::tool_manager::ToolManager;
+    use crate::os::Os;
+
+    fn events() -> Vec<ExportEvent> {
+        vec![
+            ExportEvent::Conversation {
+                conversation_id: "abc".to_string(),
+                title: "Read <the> file".to_string(),
+                model: Some("claude".to_string()),
+                usage: None,
+            },
+            ExportEvent::User {
+                turn: 0,
+                timestamp: None,
+                content: "Read <the> file".to_string(),
+            },
+            ExportEvent::Assistant {
+                turn: 0,
+                message_id: None,
+                content: "Reading it".to_string(),
+                usage: Some(TokenUsage {
+                    input_tokens: Some(120),
+                    output_tokens: Some(30),
+                    ..Default::default()
+                }),
+            },
+            ExportEvent::ToolUse {
+                turn: 0,
+                tool_use_id: "t1".to_string(),
+                name: "fs_read".to_string(),
+                input: serde_json::json!({ "path": "README.md" }),
+            },
+            ExportEvent::ToolResult {
+                turn: 1,
+                tool_use_id: "t1".to_string(),
+                name: Some("fs_read".to_string()),
+                success: true,
+                cancelled: false,
+                content: "```rust\nfn main() {}\n```".to_string(),
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_export_events() {
+        let mut os = Os::new().await.unwrap();
+        let mut tool_manager = ToolManager::default();
+        let mut conversation = ConversationState::new(
+            "export_conv_id",
+            Agents::default(),
+            tool_manager.load_tools(&mut os, &mut vec![]).await.unwrap(),
+            tool_manager,
+            None,
+            &os,
+            false,
+        )
+        .await;
+
+        conversation.set_next_user_message("List the files".to_string()).await;
+        conversation.push_assistant_message(
+            &mut os,
+            AssistantMessage::new_tool_use(None, "Listing them".to_string(), vec![AssistantToolUse {
+                id: "t1".to_string(),
+                name: "execute_bash".to_string(),
+                args: serde_json::json!({ "command": "ls" }),
+                ..Default::default()
+            }]),
+            Some(RequestMetadata {
+                input_tokens: Some(100),
+                output_tokens: Some(20),
+                ..Default::default()
+            }),
+        );
+        conversation.add_tool_results(vec![ToolUseResult {
+            tool_use_id: "t1".to_string(),
+            content: vec![ToolUseResultBlock::Text("secret.txt".to_string())],
+            status: ToolResultStatus::Success,
+        }]);
+        conversation.push_assistant_message(
+            &mut os,
+            AssistantMessage::new_response(None, "There is one file".to_string()),
+            Some(RequestMetadata {
+                input_tokens: Some(150),
+                output_tokens: Some(10),
+                ..Default::default()
+            }),
+        );
+
+        let events = export_events(&conversation, &ExportOptions::default());
+        assert_eq!(events.len(), 6);
+        assert!(
+            matches!(&events[0], ExportEvent::Conversation { title, usage: None, .. } if title == "List the files")
+        );
+        assert!(matches!(&events[1], ExportEvent::User { turn: 0, content, .. } if content == "List the files"));
+        assert!(matches!(&events[2], ExportEvent::Assistant { usage: None, .. }));
+        assert!(matches!(&events[3], ExportEvent::ToolUse { name, .. } if name == "execute_bash"));
+        assert_eq!(events[4], ExportEvent::ToolResult {
+            turn: 1,
+            tool_use_id: "t1".to_string(),
+            name: Some("execute_bash".to_string()),
+            success: true,
+            cancelled: false,
+            content: "secret.txt".to_string(),
+        });
+
+        let options = ExportOptions {
+            redact_tool_output: true,
+            include_usage: true,
+        };
+        let events = export_events(&conversation, &options);
+        let ExportEvent::Conversation { usage: Some(usage), .. } = &events[0] else {
+            panic!("expected the total usage");
+        };
+        assert_eq!(usage.to_string(), "250 input, 30 output tokens");
+        assert!(matches!(&events[4], ExportEvent::ToolResult { content, .. } if content == REDACTED));
+
+        let jsonl = export_conversation(&conversation, ExportFormat::Jsonl, &options).unwrap();
+        assert_eq!(jsonl.lines().count(), 6);
+        assert!(!jsonl.contains("secret.txt"));
+
+        assert!(export_conversation(&conversation, ExportFormat::Json, &options).is_err());
+        assert!(export_conversation(&conversation, ExportFormat::Json, &ExportOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_export_format_from_path() {
+        assert_eq!(ExportFormat::from_path("chat.md"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::from_path("out/chat.HTML"), Some(ExportFormat::Html));
+        assert_eq!(ExportFormat::from_path("chat.jsonl"), Some(ExportFormat::Jsonl));
+        assert_eq!(ExportFormat::from_path("chat.json"), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::from_path("chat"), None);
+    }
+
+    #[test]
+    fn test_code_fence() {
+        assert_eq!(code_fence("plain"), "```");
+        assert_eq!(code_fence("```rust\n```"), "````");
+        assert_eq!(code_fence("`````"), "``````");
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let md = to_markdown(&events());
+        assert!(md.starts_with("# Read <the> file\n\n- Conversation: `abc`\n- Model: `claude`\n"));
+        assert!(
+            md.contains("## User\n\nRead <the> file\n\n## Assistant\n\nReading it\n\n*120 input, 30 output tokens*\n")
+        );
+        assert!(md.contains(
+            "<summary>Tool use: <code>fs_read</code></summary>\n\n```json\n{\n  \"path\": \"README.md\"\n}\n```"
+        ));
+        assert!(
+            md.contains("<summary>Result of fs_read (success)</summary>\n\n````\n```rust\nfn main() {}\n```\n````")
+        );
+    }
+
+    #[test]
+    fn test_to_html() {
+        let html = to_html(&events());
+        assert!(html.contains("<title>Read &lt;the&gt; file</title>"));
+        assert!(html.contains("<div class=\"content\">Read &lt;the&gt; file</div>"));
+        assert!(html.contains("<p class=\"usage\">120 input, 30 output tokens</p>"));
+        assert!(html.contains("<summary>Tool use: <code>fs_read</code></summary>"));
+        assert!(html.contains("&quot;path&quot;: &quot;README.md&quot;"));
+        assert!(html.contains("<details class=\"tool-result\"><summary>Result of fs_read (success)</summary>"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn test_event_json() {
+        let json = serde_json::to_value(&events()[4]).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "toolResult",
+                "turn": 1,
+                "toolUseId": "t1",
+                "name": "fs_read",
+                "success": true,
+                "cancelled": false,
+                "content": "```rust\nfn main() {}\n```",
+            })
+        );
+    }
+}
@@ -36,8 +36,10 @@ use super::util::{
 };
 use crate::api_client::model::{
     AssistantResponseMessage,
+    DocumentBlock,
     EnvState,
     ImageBlock,
+    ReasoningContent,
     Tool,
     ToolResult,
     ToolResultContentBlock,
@@ -57,6 +59,8 @@ pub struct UserMessage {
     pub content: UserMessageContent,
     pub timestamp: Option<DateTime<FixedOffset>>,
     pub images: Option<Vec<ImageBlock>>,
+    #[serde(default)]
+    pub documents: Option<Vec<DocumentBlock>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -111,6 +115,7 @@ impl UserMessage {
     pub fn new_prompt(prompt: String, timestamp: Option<DateTime<FixedOffset>>) -> Self {
         Self {
             images: None,
+            documents: None,
             timestamp,
             additional_context: String::new(),
             env_context: UserEnvContext::generate_new(),
@@ -125,6 +130,7 @@ impl UserMessage {
     ) -> Self {
         Self {
             images: None,
+            documents: None,
             timestamp,
             additional_context: String::new(),
             env_context: UserEnvContext::generate_new(),
@@ -152,12 +158,14 @@ impl UserMessage {
                 tool_use_results: results,
             },
             images: None,
+            documents: None,
         }
     }
 
-    pub fn new_tool_use_results_with_images(
+    pub fn new_tool_use_results_with_attachments(
         results: Vec<ToolUseResult>,
         images: Vec<ImageBlock>,
+        documents: Vec<DocumentBlock>,
         timestamp: Option<DateTime<FixedOffset>>,
     ) -> Self {
         Self {
@@ -167,7 +175,8 @@ impl UserMessage {
             content: UserMessageContent::ToolUseResults {
                 tool_use_results: results,
             },
-            images: Some(images),
+            images: (!images.is_empty()).then_some(images),
+            documents: (!documents.is_empty()).then_some(documents),
         }
     }
 
@@ -177,6 +186,7 @@ impl UserMessage {
         let content = self.content_with_context();
         UserInputMessage {
             images: self.images.clone(),
+            documents: self.documents.clone(),
             content,
             user_input_message_context: Some(UserInputMessageContext {
                 env_state: self.env_context.env_state,
@@ -205,6 +215,7 @@ impl UserMessage {
         let content = self.content_with_context();
         UserInputMessage {
             images: self.images,
+            documents: self.documents,
             content,
             user_input_message_context: Some(UserInputMessageContext {
                 env_state: self.env_context.env_state,
@@ -424,6 +435,7 @@ impl From<InvokeOutput> for ToolUseResultBlock {
             OutputKind::Text(text) => Self::Text(text),
             OutputKind::Json(value) => Self::Json(value),
             OutputKind::Images(_) => Self::Text("See images data supplied".to_string()),
+            OutputKind::Documents(_) => Self::Text("See documents data supplied".to_string()),
             OutputKind::Mixed { text, .. } => ToolUseResultBlock::Text(text),
         }
     }
@@ -448,18 +460,26 @@ pub enum AssistantMessage {
     Response {
         message_id: Option<String>,
         content: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        reasoning: Vec<ReasoningContent>,
     },
     /// An assistant message containing tool uses.
     ToolUse {
         message_id: Option<String>,
         content: String,
         tool_uses: Vec<AssistantToolUse>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        reasoning: Vec<ReasoningContent>,
     },
 }
 
 impl AssistantMessage {
     pub fn new_response(message_id: Option<String>, content: String) -> Self {
-        Self::Response { message_id, content }
+        Self::Response {
+            message_id,
+            content,
+            reasoning: Vec::new(),
+        }
     }
 
     pub fn new_tool_use(message_id: Option<String>, content: String, tool_uses: Vec<AssistantToolUse>) -> Self {
@@ -467,7 +487,18 @@ impl AssistantMessage {
             message_id,
             content,
             tool_uses,
+            reasoning: Vec::new(),
+        }
+    }
+
+    /// Attaches the reasoning blocks the model produced before this message.
+    pub fn with_reasoning(mut self, blocks: Vec<ReasoningContent>) -> Self {
+        match &mut self {
+            AssistantMessage::Response { reasoning, .. } | AssistantMessage::ToolUse { reasoning, .. } => {
+                *reasoning = blocks;
+            },
         }
+        self
     }
 
     pub fn message_id(&self) -> Option<&str> {
@@ -490,26 +521,41 @@ impl AssistantMessage {
             AssistantMessage::Response { .. } => None,
         }
     }
+
+    pub fn reasoning(&self) -> &[ReasoningContent] {
+        match self {
+            AssistantMessage::Response { reasoning, .. } | AssistantMessage::ToolUse { reasoning, .. } => {
+                reasoning.as_slice()
+            },
+        }
+    }
 }
 
 impl From<AssistantMessage> for AssistantResponseMessage {
     fn from(value: AssistantMessage) -> Self {
-        let (message_id, content, tool_uses) = match value {
-            AssistantMessage::Response { message_id, content } => (message_id, content, None),
+        let (message_id, content, tool_uses, reasoning) = match value {
+            AssistantMessage::Response {
+                message_id,
+                content,
+                reasoning,
+            } => (message_id, content, None, reasoning),
             AssistantMessage::ToolUse {
                 message_id,
                 content,
                 tool_uses,
+                reasoning,
             } => (
                 message_id,
                 content,
                 Some(tool_uses.into_iter().map(Into::into).collect()),
+                reasoning,
             ),
         };
         Self {
             message_id,
             content,
             tool_uses,
+            reasoning: (!reasoning.is_empty()).then_some(reasoning),
         }
     }
 }
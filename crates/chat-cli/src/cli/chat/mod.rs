@@ -12,6 +12,7 @@ pub mod cli;
 mod consts;
 pub mod context;
 mod conversation;
+pub mod export;
 mod input_source;
 mod message;
 mod parse;
@@ -22,6 +23,9 @@ mod parser;
 mod prompt;
 mod prompt_parser;
 pub mod server_messenger;
+mod sessions;
+pub use sessions::SessionsSubcommand;
+
 use crate::cli::chat::checkpoint::CHECKPOINT_MESSAGE_MAX_LENGTH;
 use crate::constants::ui_text;
 #[cfg(unix)]
@@ -58,6 +62,11 @@ use chat_cli_ui::conduit::{
 use chat_cli_ui::protocol::{
     Event,
     MessageRole,
+    ReasoningEnd,
+    ReasoningMessageContent,
+    ReasoningMessageEnd,
+    ReasoningMessageStart,
+    ReasoningStart,
     TextMessageContent,
     TextMessageEnd,
     TextMessageStart,
@@ -68,17 +77,28 @@ use clap::{
     Args,
     CommandFactory,
     Parser,
+    Subcommand,
     ValueEnum,
 };
 use cli::compact::CompactStrategy;
-use cli::hooks::ToolContext;
+use cli::hooks::{
+    EventInput,
+    HookDecision,
+    PreToolUseOutcome,
+    ToolContext,
+    additional_context,
+};
 use cli::model::{
     find_model,
     get_available_models,
     select_model,
 };
 pub use conversation::ConversationState;
-use conversation::TokenWarningLevel;
+use conversation::{
+    CONTEXT_ENTRY_END_HEADER,
+    CONTEXT_ENTRY_START_HEADER,
+    TokenWarningLevel,
+};
 use crossterm::style::{
     Attribute,
     Stylize,
@@ -125,8 +145,10 @@ use tokio::sync::{
 use tool_manager::{
     PromptQuery,
     PromptQueryResult,
+    ResourceReference,
     ToolManager,
     ToolManagerBuilder,
+    resource_contents_to_text,
 };
 use tools::delegate::{
     AgentExecution,
@@ -148,6 +170,7 @@ use tracing::{
     trace,
     warn,
 };
+use util::documents::RichDocumentBlock;
 use util::images::RichImageBlock;
 use util::ui::draw_box;
 use util::{
@@ -205,6 +228,7 @@ use crate::telemetry::{
     TelemetryResult,
     get_error_reason,
 };
+use crate::util::knowledge_store::KnowledgeStore;
 use crate::util::paths::PathResolver;
 use crate::util::{
     MCP_SERVER_TOOL_DELIMITER,
@@ -225,11 +249,25 @@ fn get_shadow_repo_dir(os: &Os, conversation_id: String) -> Result<PathBuf, crat
     Ok(PathResolver::new(os).global().shadow_repo_dir()?.join(conversation_id))
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum ChatSubcommand {
+    /// List, search and manage saved chat sessions
+    #[command(subcommand)]
+    Sessions(SessionsSubcommand),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default, Args)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct ChatArgs {
+    #[command(subcommand)]
+    pub subcommand: Option<ChatSubcommand>,
     /// Resumes the previous conversation from this directory.
     #[arg(short, long)]
     pub resume: bool,
+    /// Resumes the session with the given id (or the start of it), or one picked from a list with
+    /// "pick".
+    #[arg(long, value_name = "ID|pick", conflicts_with = "resume")]
+    pub resume_id: Option<String>,
     /// Context profile to use
     #[arg(long = "agent", alias = "profile")]
     pub agent: Option<String>,
@@ -239,6 +277,9 @@ pub struct ChatArgs {
     /// Service tier: flex or standard (default: flex)
     #[arg(long = "service-tier", value_name = "TIER")]
     pub service_tier: Option<String>,
+    /// Number of tokens the model may spend reasoning before it answers, 0 turns reasoning off
+    #[arg(long = "reasoning-budget", value_name = "TOKENS")]
+    pub reasoning_budget: Option<u32>,
     /// Allows the model to use any tool to run commands without asking for confirmation.
     #[arg(short = 'a', long)]
     pub trust_all_tools: bool,
@@ -258,6 +299,12 @@ pub struct ChatArgs {
 
 impl ChatArgs {
     pub async fn execute(mut self, os: &mut Os) -> Result<ExitCode> {
+        if let Some(ChatSubcommand::Sessions(subcommand)) = self.subcommand.take() {
+            return subcommand
+                .execute(os, &mut std::io::stdout(), &mut std::io::stderr())
+                .await;
+        }
+
         let mut input = self.input;
 
         if self.no_interactive && input.is_none() {
@@ -300,6 +347,12 @@ impl ChatArgs {
             )?;
         }
 
+        let previous_conversation = match (&self.resume_id, self.resume) {
+            (Some(id), _) => sessions::resume_session(os, id)?,
+            (None, true) => sessions::resume_last_session(os)?,
+            (None, false) => None,
+        };
+
         let conversation_id = uuid::Uuid::new_v4().to_string();
         info!(?conversation_id, "Generated new conversation id");
 
@@ -448,7 +501,7 @@ impl ChatArgs {
             agents,
             input,
             InputSource::new(os, prompt_request_sender, prompt_response_receiver)?,
-            self.resume,
+            previous_conversation,
             || terminal::window_size().map(|s| s.columns.into()).ok(),
             tool_manager,
             model_id,
@@ -464,6 +517,33 @@ impl ChatArgs {
             session.conversation.service_tier = tier;
         }
 
+        // Keep the watched knowledge contexts of the agent up to date during the session
+        if ExperimentManager::is_enabled(os, ExperimentName::Knowledge) {
+            let os = os.clone();
+            let agent = session.conversation.agents.get_active().cloned();
+            tokio::spawn(async move {
+                KnowledgeStore::resume_watching(&os, agent.as_ref()).await;
+            });
+        }
+
+        if let Some(budget) = self.reasoning_budget {
+            session.conversation.reasoning_budget = Some(budget);
+            let supports_reasoning = session
+                .conversation
+                .model_info
+                .as_ref()
+                .is_some_and(|model| model.supports_reasoning);
+            if budget > 0 && !supports_reasoning {
+                execute!(
+                    stderr,
+                    StyledText::warning_fg(),
+                    style::Print("WARNING: "),
+                    StyledText::reset(),
+                    style::Print("the selected model does not support reasoning, --reasoning-budget will be ignored\n"),
+                )?;
+            }
+        }
+
         session.spawn(os).await
         .map(|_| ExitCode::SUCCESS)
     }
@@ -543,7 +623,7 @@ fn format_rich_notification(executions: &[AgentExecution]) -> String {
 }
 
 const TOOL_BULLET: &str = " ● ";
-const CONTINUATION_LINE: &str = " ⋮ ";
+pub(crate) const CONTINUATION_LINE: &str = " ⋮ ";
 const PURPOSE_ARROW: &str = " ↳ ";
 const SUCCESS_TICK: &str = " ✓ ";
 const ERROR_EXCLAMATION: &str = " ❗ ";
@@ -608,6 +688,25 @@ impl ChatError {
     }
 }
 
+impl ChatError {
+    /// Returns the summary of the guardrail trace if a guardrail blocked the request or the
+    /// response.
+    fn guardrail_trace(&self) -> Option<&[String]> {
+        let kind = match self {
+            ChatError::SendMessage(e) => &e.source.kind,
+            ChatError::ResponseStream(e) => match &e.source {
+                RecvErrorKind::Client(ApiClientError::ConverseStream(e)) => &e.kind,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        match kind {
+            ConverseStreamErrorKind::GuardrailIntervened { trace } => Some(trace),
+            _ => None,
+        }
+    }
+}
+
 impl ReasonCode for ChatError {
     fn reason_code(&self) -> String {
         match self {
@@ -695,7 +794,7 @@ impl ChatSession {
         mut agents: Agents,
         mut input: Option<String>,
         input_source: InputSource,
-        resume_conversation: bool,
+        previous_conversation: Option<ConversationState>,
         terminal_width_provider: fn() -> Option<usize>,
         tool_manager: ToolManager,
         model_id: Option<String>,
@@ -720,55 +819,34 @@ impl ChatSession {
             }
         });
 
-        let conversation = match resume_conversation {
-            true => {
-                let previous_conversation = std::env::current_dir()
-                    .ok()
-                    .and_then(|cwd| os.database.get_conversation_by_path(cwd).ok())
-                    .flatten();
-
-                // Only restore conversations where there were actual messages
-                // Prevents edge case where user clears conversation then exits without chatting.
-                match previous_conversation.filter(|cs| !cs.history().is_empty()) {
-                    Some(mut cs) => {
-                        existing_conversation = true;
-                        input = Some(input.unwrap_or("In a few words, summarize our conversation so far.".to_owned()));
-                        cs.tool_manager = tool_manager;
-                        if let Some(profile) = cs.current_profile() {
-                            if agents.switch(profile).is_err() {
-                                execute!(
-                                    &mut control_end_stderr,
-                                    StyledText::error_fg(),
-                                    style::Print("Error"),
-                                    StyledText::reset(),
-                                    style::Print(format!(
-                                        ": cannot resume conversation with {profile} because it no longer exists. Using default.\n"
-                                    ))
-                                )?;
-                                let _ = agents.switch(DEFAULT_AGENT_NAME);
-                            }
-                        }
-                        cs.agents = agents;
-                        cs.mcp_enabled = mcp_enabled;
-                        cs.update_state(true).await;
-                        cs.enforce_tool_use_history_invariants();
-                        cs
-                    },
-                    None => {
-                        ConversationState::new(
-                            conversation_id,
-                            agents,
-                            tool_config,
-                            tool_manager,
-                            model_id,
-                            os,
-                            mcp_enabled,
-                        )
-                        .await
-                    },
+        // Only restore conversations where there were actual messages
+        // Prevents edge case where user clears conversation then exits without chatting.
+        let conversation = match previous_conversation.filter(|cs| !cs.history().is_empty()) {
+            Some(mut cs) => {
+                existing_conversation = true;
+                input = Some(input.unwrap_or("In a few words, summarize our conversation so far.".to_owned()));
+                cs.tool_manager = tool_manager;
+                if let Some(profile) = cs.current_profile()
+                    && agents.switch(profile).is_err()
+                {
+                    execute!(
+                        &mut control_end_stderr,
+                        StyledText::error_fg(),
+                        style::Print("Error"),
+                        StyledText::reset(),
+                        style::Print(format!(
+                            ": cannot resume conversation with {profile} because it no longer exists. Using default.\n"
+                        ))
+                    )?;
+                    let _ = agents.switch(DEFAULT_AGENT_NAME);
                 }
+                cs.agents = agents;
+                cs.mcp_enabled = mcp_enabled;
+                cs.update_state(true).await;
+                cs.enforce_tool_use_history_invariants();
+                cs
             },
-            false => {
+            None => {
                 ConversationState::new(
                     conversation_id,
                     agents,
@@ -861,10 +939,15 @@ impl ChatSession {
             },
             ChatState::ExecuteTools => {
                 let tool_uses_clone = self.tool_uses.clone();
-                tokio::select! {
+                let result = tokio::select! {
                     res = self.tool_use_execute(os) => res,
                     Ok(_) = ctrl_c_stream.recv() => Err(ChatError::Interrupted { tool_uses: Some(tool_uses_clone) })
+                };
+                if matches!(result, Err(ChatError::Interrupted { .. })) {
+                    // The interrupted MCP tool calls are cancelled as they are dropped
+                    self.conversation.tool_manager.finish_tool_call_cancellations().await;
                 }
+                result
             },
             ChatState::ValidateTools { tool_uses } => {
                 tokio::select! {
@@ -923,6 +1006,39 @@ impl ChatSession {
         }
 
         let (context, report, display_err_message) = match err {
+            _ if err.guardrail_trace().is_some() => {
+                let trace = err.guardrail_trace().unwrap_or_default();
+                execute!(
+                    self.stderr,
+                    style::SetAttribute(Attribute::Bold),
+                    StyledText::error_fg(),
+                    style::Print("\nThe request was blocked by the agent's guardrail.\n"),
+                    StyledText::reset_attributes(),
+                    StyledText::reset(),
+                )?;
+                if trace.is_empty() {
+                    execute!(
+                        self.stderr,
+                        StyledText::secondary_fg(),
+                        style::Print("Set \"trace\" in the agent's guardrail to see which policies matched.\n"),
+                        StyledText::reset(),
+                    )?;
+                }
+                for line in trace {
+                    execute!(
+                        self.stderr,
+                        StyledText::secondary_fg(),
+                        style::Print(format!("  • {line}\n")),
+                        StyledText::reset(),
+                    )?;
+                }
+                execute!(self.stderr, style::Print("\n"))?;
+                self.conversation.append_transcript(format!(
+                    "The request was blocked by the agent's guardrail.\n{}",
+                    trace.join("\n")
+                ));
+                ("The request was blocked by a guardrail", Report::from(err), false)
+            },
             ChatError::Interrupted { tool_uses: ref inter } => {
                 execute!(self.stderr, style::Print("\n\n"))?;
 
@@ -991,8 +1107,9 @@ impl ChatSession {
                         )?;
                         ("The conversation history has overflowed", eyre!(err), false)
                     } else {
+                        let prompt = self.run_pre_compact_hooks(os, None, true).await;
                         self.inner = Some(ChatState::CompactHistory {
-                            prompt: None,
+                            prompt,
                             show_summary: false,
                             strategy: CompactStrategy {
                                 truncate_large_messages: self.conversation.history().len() <= 2,
@@ -1464,11 +1581,107 @@ impl ChatSession {
             self.inner = Some(ChatState::HandleInput { input: user_input });
         }
 
+        // The session ends the same way whether the user exits or an error stops it
+        let mut result = Ok(());
         while !matches!(self.inner, Some(ChatState::Exit)) {
-            self.next(os).await?;
+            if let Err(err) = self.next(os).await {
+                result = Err(err.into());
+                break;
+            }
         }
 
-        Ok(())
+        self.run_session_end_hooks(os).await;
+        self.conversation.tool_manager.mark_servers_stopped();
+
+        result
+    }
+
+    /// Runs the `preCompact` hooks, returning the instructions for the summary: the custom prompt
+    /// followed by the output of each hook that succeeded, in the order the hooks are configured.
+    async fn run_pre_compact_hooks(
+        &mut self,
+        os: &Os,
+        custom_prompt: Option<String>,
+        automatic: bool,
+    ) -> Option<String> {
+        let Some(cm) = self.conversation.context_manager.as_mut() else {
+            return custom_prompt;
+        };
+
+        let trigger = crate::cli::agent::hook::HookTrigger::PreCompact;
+        let event_input = EventInput::from_iter([
+            (
+                "trigger".to_string(),
+                serde_json::json!(if automatic { "auto" } else { "manual" }),
+            ),
+            ("custom_instructions".to_string(), serde_json::json!(custom_prompt)),
+        ]);
+        let mut results = cm
+            .run_event_hooks(trigger, &mut std::io::stderr(), os, event_input)
+            .await
+            .unwrap_or_default();
+        let hooks = cm.hooks.get(&trigger).cloned().unwrap_or_default();
+        results.sort_by_key(|((_, hook), _)| hooks.iter().position(|h| h == hook));
+
+        let hook_instructions = results
+            .into_iter()
+            .filter(|(_, (exit_code, _))| *exit_code == 0)
+            .map(|(_, (_, output))| output.trim().to_string())
+            .filter(|output| !output.is_empty());
+        let instructions = custom_prompt.into_iter().chain(hook_instructions).collect::<Vec<_>>();
+        (!instructions.is_empty()).then(|| instructions.join("\n\n"))
+    }
+
+    /// Runs the `sessionEnd` hooks, saving the conversation to a transcript file for them to read.
+    /// The transcript file is removed once the hooks are done.
+    async fn run_session_end_hooks(&mut self, os: &Os) {
+        let trigger = crate::cli::agent::hook::HookTrigger::SessionEnd;
+        if !self
+            .conversation
+            .context_manager
+            .as_ref()
+            .is_some_and(|cm| cm.has_hooks(trigger))
+        {
+            return;
+        }
+
+        let conversation_id = self.conversation.conversation_id().to_string();
+        let transcript_path = match self.save_transcript(os, &conversation_id).await {
+            Ok(path) => Some(path),
+            Err(err) => {
+                error!(?err, "Failed to save the transcript of the conversation");
+                None
+            },
+        };
+        let event_input = EventInput::from_iter([
+            ("conversation_id".to_string(), serde_json::json!(conversation_id)),
+            (
+                "transcript_path".to_string(),
+                serde_json::json!(transcript_path.as_ref().map(|path| path.to_string_lossy())),
+            ),
+        ]);
+        if let Some(cm) = self.conversation.context_manager.as_mut() {
+            let _ = cm
+                .run_event_hooks(trigger, &mut std::io::stderr(), os, event_input)
+                .await;
+        }
+
+        if let Some(path) = transcript_path
+            && let Err(err) = os.fs.remove_file(&path).await
+        {
+            error!(?err, "Failed to remove the transcript of the conversation");
+        }
+    }
+
+    /// Saves the conversation the way `/save` does, to a file named after its id.
+    async fn save_transcript(&self, os: &Os, conversation_id: &str) -> Result<PathBuf> {
+        let dir = PathResolver::new(os).global().transcripts_dir()?;
+        os.fs.create_dir_all(&dir).await?;
+        let path = dir.join(format!("{conversation_id}.json"));
+        os.fs
+            .write(&path, serde_json::to_string_pretty(&self.conversation)?)
+            .await?;
+        Ok(path)
     }
 
     /// Compacts the conversation history using the strategy specified by [CompactStrategy],
@@ -2050,6 +2263,21 @@ impl ChatSession {
                 .put_skim_command_selector(os, Arc::new(context_manager.clone()), tool_names);
         }
 
+        // Let MCP servers know about changes to the workspace, whether they come from /context or
+        // from the current directory
+        let context_paths = self
+            .conversation
+            .context_manager
+            .as_ref()
+            .map(|cm| {
+                cm.paths
+                    .iter()
+                    .map(|p| p.get_path_as_str().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        self.conversation.tool_manager.update_roots(os, &context_paths).await;
+
         execute!(self.stderr, StyledText::reset(), StyledText::reset_attributes())?;
         let prompt = self.generate_tool_trust_prompt(os).await;
 
@@ -2202,7 +2430,12 @@ impl ChatSession {
             Ok(ChatState::PromptUser {
                 skip_printing_tools: false,
             })
-        } else if let Some(command) = input.strip_prefix("@") {
+        } else if let Some(command) = input.strip_prefix("@").filter(|command| {
+            !command
+                .split_whitespace()
+                .next()
+                .is_some_and(|word| ResourceReference::parse(word).is_some())
+        }) {
             let input_parts =
                 shlex::split(command).ok_or(ChatError::Custom("Error splitting prompt command".into()))?;
 
@@ -2322,7 +2555,8 @@ impl ChatSession {
                 self.conversation.abandon_tool_use(&self.tool_uses, user_input);
             } else {
                 // Add additional context if available (e.g., delegate summaries)
-                let context = self.pending_additional_context.take().unwrap_or_default();
+                let mut context = self.pending_additional_context.take().unwrap_or_default();
+                context.push_str(&self.referenced_resources_context(&user_input).await?);
                 self.conversation
                     .set_next_user_message_with_context(user_input, context)
                     .await;
@@ -2348,6 +2582,52 @@ impl ChatSession {
         }
     }
 
+    /// Reads the MCP resources referenced in the user input as `@server/uri`, returning their
+    /// content as context for the model.
+    ///
+    /// Only references to loaded servers are read, since other text can look like a reference.
+    /// Resources that can't be read are skipped with a warning rather than failing the prompt.
+    async fn referenced_resources_context(&mut self, user_input: &str) -> Result<String, ChatError> {
+        let references = ResourceReference::find_all(user_input)
+            .into_iter()
+            .filter(|reference| {
+                self.conversation
+                    .tool_manager
+                    .clients
+                    .contains_key(&reference.server_name)
+            })
+            .collect::<Vec<_>>();
+
+        let mut resources = String::new();
+        for reference in references {
+            match self.conversation.tool_manager.read_resource(&reference).await {
+                Ok(result) => {
+                    resources.push_str(&format!(
+                        "[{reference}]\n{}\n",
+                        resource_contents_to_text(&result.contents)
+                    ));
+                },
+                Err(err) => {
+                    warn!("Failed to read resource {reference}: {err}");
+                    execute!(
+                        self.stderr,
+                        StyledText::warning_fg(),
+                        style::Print("WARNING: "),
+                        StyledText::reset(),
+                        style::Print(format!("Failed to read {reference}, continuing without it: {err}\n")),
+                    )?;
+                },
+            }
+        }
+
+        if resources.is_empty() {
+            return Ok(resources);
+        }
+        Ok(format!(
+            "{CONTEXT_ENTRY_START_HEADER}{resources}{CONTEXT_ENTRY_END_HEADER}"
+        ))
+    }
+
     async fn tool_use_execute(&mut self, os: &mut Os) -> Result<ChatState, ChatError> {
         // Check if we should auto-enter tangent mode for introspect tool
         if ExperimentManager::is_enabled(os, ExperimentName::TangentMode)
@@ -2388,6 +2668,13 @@ impl ChatSession {
                         },
                     })
                     || self.conversation.agents.trust_all_tools;
+            // preToolUse hooks can approve a tool or insist on asking, but not allow what the tool
+            // settings deny
+            let allowed = match tool.hook_decision {
+                Some(HookDecision::Allow) => denied_match_set.is_none(),
+                Some(HookDecision::Ask) => false,
+                _ => allowed,
+            };
 
             if let Some(match_set) = denied_match_set {
                 let formatted_set = match_set.into_iter().fold(String::new(), |mut acc, rule| {
@@ -2450,6 +2737,25 @@ impl ChatSession {
                 continue;
             }
 
+            if let Some(cm) = self.conversation.context_manager.as_mut() {
+                let tool_name = self.tool_uses[i].name.clone();
+                let event_input = EventInput::from_iter([
+                    (
+                        "message".to_string(),
+                        serde_json::json!(format!("Waiting for approval to use {tool_name}")),
+                    ),
+                    ("tool_name".to_string(), serde_json::json!(tool_name)),
+                ]);
+                let _ = cm
+                    .run_event_hooks(
+                        crate::cli::agent::hook::HookTrigger::Notification,
+                        &mut std::io::stderr(),
+                        os,
+                        event_input,
+                    )
+                    .await;
+            }
+
             self.pending_tool_index = Some(i);
 
             return Ok(ChatState::PromptUser {
@@ -2461,6 +2767,7 @@ impl ChatSession {
         // Execute the requested tools.
         let mut tool_results = vec![];
         let mut image_blocks: Vec<RichImageBlock> = Vec::new();
+        let mut document_blocks: Vec<RichDocumentBlock> = Vec::new();
 
         for tool in &self.tool_uses {
             let tool_start = std::time::Instant::now();
@@ -2488,6 +2795,8 @@ impl ChatSession {
                     &mut self.stdout,
                     &mut self.conversation.file_line_tracker,
                     &self.conversation.agents,
+                    self.conversation.model_info.as_ref(),
+                    self.conversation.context_manager.as_ref(),
                 )
                 .await;
 
@@ -2609,9 +2918,22 @@ impl ChatSession {
                         OutputKind::Images(ref image) => {
                             image_blocks.extend(image.clone());
                         },
-                        OutputKind::Mixed { ref text, ref images } => {
-                            debug!("Output is Mixed: text = {:?}, images = {}", text, images.len());
+                        OutputKind::Documents(ref documents) => {
+                            document_blocks.extend(documents.clone());
+                        },
+                        OutputKind::Mixed {
+                            ref text,
+                            ref images,
+                            ref documents,
+                        } => {
+                            debug!(
+                                "Output is Mixed: text = {:?}, images = {}, documents = {}",
+                                text,
+                                images.len(),
+                                documents.len()
+                            );
                             image_blocks.extend(images.clone());
+                            document_blocks.extend(documents.clone());
                         },
                     }
 
@@ -2645,27 +2967,29 @@ impl ChatSession {
 
                     // Send telemetry for agent contribution
                     if let Tool::FsWrite(w) = &tool.tool {
-                        let sanitized_path_str = w.path(os).to_string_lossy().to_string();
-                        let conversation_id = self.conversation.conversation_id().to_string();
-                        let message_id = self.conversation.message_id().map(|s| s.to_string());
-                        if let Some(tracker) = self.conversation.file_line_tracker.get_mut(&sanitized_path_str) {
-                            let lines_by_agent = tracker.lines_by_agent();
-                            let lines_by_user = tracker.lines_by_user();
-
-                            os.telemetry
-                                .send_agent_contribution_metric(
-                                    &os.database,
-                                    conversation_id,
-                                    message_id,
-                                    Some(tool.id.clone()),   // Already a String
-                                    Some(tool.name.clone()), // Already a String
-                                    Some(lines_by_agent),
-                                    Some(lines_by_user),
-                                )
-                                .await
-                                .ok();
-
-                            tracker.prev_fswrite_lines = tracker.after_fswrite_lines;
+                        for path in w.paths(os) {
+                            let sanitized_path_str = path.to_string_lossy().to_string();
+                            let conversation_id = self.conversation.conversation_id().to_string();
+                            let message_id = self.conversation.message_id().map(|s| s.to_string());
+                            if let Some(tracker) = self.conversation.file_line_tracker.get_mut(&sanitized_path_str) {
+                                let lines_by_agent = tracker.lines_by_agent();
+                                let lines_by_user = tracker.lines_by_user();
+
+                                os.telemetry
+                                    .send_agent_contribution_metric(
+                                        &os.database,
+                                        conversation_id,
+                                        message_id,
+                                        Some(tool.id.clone()),   // Already a String
+                                        Some(tool.name.clone()), // Already a String
+                                        Some(lines_by_agent),
+                                        Some(lines_by_user),
+                                    )
+                                    .await
+                                    .ok();
+
+                                tracker.prev_fswrite_lines = tracker.after_fswrite_lines;
+                            }
                         }
                     }
 
@@ -2716,7 +3040,7 @@ impl ChatSession {
 
         // Run PostToolUse hooks for all executed tools after we have the tool_results
         if let Some(cm) = self.conversation.context_manager.as_mut() {
-            for result in &tool_results {
+            for result in &mut tool_results {
                 if let Some(tool) = self.tool_uses.iter().find(|t| t.id == result.tool_use_id) {
                     let content: Vec<serde_json::Value> = result
                         .content
@@ -2742,10 +3066,11 @@ impl ChatSession {
                     };
 
                     // Here is how we handle postToolUse output:
-                    // Exit code is 0: nothing. stdout is not shown to user. We don't support processing the PostToolUse
-                    // hook output yet. Exit code is non-zero: display an error to user (already
-                    // taken care of by the ContextManager.run_hooks)
-                    let _ = cm
+                    // Exit code is 0: stdout is not shown to user. If it is a JSON object, its
+                    // additional_context is returned to the model with the tool result.
+                    // Exit code is non-zero: display an error to user (already taken care of by
+                    // the ContextManager.run_hooks)
+                    let hook_results = cm
                         .run_hooks(
                             crate::cli::agent::hook::HookTrigger::PostToolUse,
                             &mut std::io::stderr(),
@@ -2753,14 +3078,37 @@ impl ChatSession {
                             None,
                             Some(tool_context),
                         )
-                        .await;
+                        .await
+                        .unwrap_or_default();
+                    for context in additional_context(&hook_results) {
+                        result.content.push(ToolUseResultBlock::Text(format!(
+                            "Additional context from a postToolUse hook: {context}"
+                        )));
+                    }
                 }
             }
         }
 
-        if !image_blocks.is_empty() {
+        if !image_blocks.is_empty() || !document_blocks.is_empty() {
+            if !self
+                .conversation
+                .model_info
+                .as_ref()
+                .is_some_and(|model| model.supports_attachments)
+            {
+                execute!(
+                    self.stderr,
+                    StyledText::warning_fg(),
+                    style::Print(
+                        "\nThe current model does not accept images or documents, they will not be sent. Use /model to switch to a model that does.\n"
+                    ),
+                    StyledText::reset(),
+                )?;
+            }
             let images = image_blocks.into_iter().map(|(block, _)| block).collect();
-            self.conversation.add_tool_results_with_images(tool_results, images);
+            let documents = document_blocks.into_iter().map(|(block, _)| block).collect();
+            self.conversation
+                .add_tool_results_with_attachments(tool_results, images, documents);
             execute!(
                 self.stderr,
                 StyledText::reset_attributes(),
@@ -2787,6 +3135,35 @@ impl ChatSession {
         ));
     }
 
+    /// Closes the reasoning output started by [parser::ResponseEvent::ReasoningText] events,
+    /// replacing it with the time spent reasoning when `collapse` is set.
+    fn end_reasoning(&mut self, message_id: String, started: Instant, collapse: bool) -> Result<(), ChatError> {
+        if self.stdout.should_send_structured_event {
+            self.stdout.send(Event::ReasoningMessageEnd(ReasoningMessageEnd {
+                message_id: message_id.clone(),
+            }))?;
+            self.stdout.send(Event::ReasoningEnd(ReasoningEnd { message_id }))?;
+        } else if collapse {
+            queue!(
+                self.stdout,
+                terminal::Clear(terminal::ClearType::CurrentLine),
+                cursor::MoveToColumn(0),
+                style::Print(format!("Thought for {:.1}s", started.elapsed().as_secs_f64())),
+                StyledText::reset_attributes(),
+                StyledText::reset(),
+                style::Print("\n\n"),
+            )?;
+        } else {
+            queue!(
+                self.stdout,
+                StyledText::reset_attributes(),
+                StyledText::reset(),
+                style::Print("\n\n"),
+            )?;
+        }
+        Ok(())
+    }
+
     /// Sends a [crate::api_client::ApiClient::send_message] request to the backend and consumes
     /// the response stream.
     ///
@@ -2824,6 +3201,13 @@ impl ChatSession {
             os.database.settings.get_bool(Setting::ChatDisableMarkdownRendering),
         );
         let mut response_prefix_printed = false;
+        let collapse_reasoning = os
+            .database
+            .settings
+            .get_bool(Setting::ChatCollapseReasoning)
+            .unwrap_or(false);
+        // Set to when the first reasoning text was received, while reasoning is being shown.
+        let mut reasoning_started: Option<Instant> = None;
 
         let mut tool_uses = Vec::new();
         let mut tool_name_being_recvd: Option<String> = None;
@@ -2842,7 +3226,51 @@ impl ChatSession {
                 Some(Ok(msg_event)) => {
                     trace!("Consumed: {:?}", msg_event);
 
+                    if let Some(started) =
+                        reasoning_started.take_if(|_| !matches!(msg_event, parser::ResponseEvent::ReasoningText(_)))
+                    {
+                        self.end_reasoning(request_id.clone().unwrap_or_default(), started, collapse_reasoning)?;
+                    }
+
                     match msg_event {
+                        parser::ResponseEvent::ReasoningText(text) => {
+                            let message_id = request_id.clone().unwrap_or_default();
+                            if reasoning_started.is_none() {
+                                reasoning_started = Some(Instant::now());
+                                if self.stdout.should_send_structured_event {
+                                    self.stdout.send(Event::ReasoningStart(ReasoningStart {
+                                        message_id: message_id.clone(),
+                                        encrypted_content: None,
+                                    }))?;
+                                    self.stdout.send(Event::ReasoningMessageStart(ReasoningMessageStart {
+                                        message_id: message_id.clone(),
+                                        role: MessageRole::Assistant,
+                                    }))?;
+                                } else {
+                                    queue!(
+                                        self.stdout,
+                                        StyledText::secondary_fg(),
+                                        style::SetAttribute(Attribute::Dim),
+                                        style::Print(if collapse_reasoning {
+                                            "Thinking..."
+                                        } else {
+                                            "Thinking\n"
+                                        }),
+                                    )?;
+                                }
+                            }
+
+                            if self.stdout.should_send_structured_event {
+                                self.stdout
+                                    .send(Event::ReasoningMessageContent(ReasoningMessageContent {
+                                        message_id,
+                                        delta: text,
+                                    }))?;
+                            } else if !collapse_reasoning {
+                                queue!(self.stdout, style::Print(text))?;
+                            }
+                            self.stdout.flush()?;
+                        },
                         parser::ResponseEvent::ToolUseStart { name } => {
                             // We need to flush the buffer here, otherwise text will not be
                             // printed while we are receiving tool use events.
@@ -3298,6 +3726,7 @@ impl ChatSession {
                                 tool,
                                 accepted: false,
                                 tool_input,
+                                hook_decision: None,
                             });
                         },
                         Err(err) => {
@@ -3372,7 +3801,13 @@ impl ChatSession {
         // Execute PreToolUse hooks for all validated tools
         // The mental model is preToolHook is like validate tools, but its behavior can be customized by
         // user Note that after preTookUse hook, user can still reject the took run
+        let mut outcomes = Vec::new();
         if let Some(cm) = self.conversation.context_manager.as_mut() {
+            let hooks = cm
+                .hooks
+                .get(&crate::cli::agent::hook::HookTrigger::PreToolUse)
+                .cloned()
+                .unwrap_or_default();
             for tool in &queued_tools {
                 let tool_context = ToolContext {
                     tool_name: match &tool.tool {
@@ -3395,23 +3830,49 @@ impl ChatSession {
                     .await?;
 
                 // Here is how we handle the preToolUse hook output:
-                // Exit code is 0: nothing. stdout is not shown to user.
+                // Exit code is 0: stdout is not shown to user. If it is a JSON object, it can
+                // decide whether the tool runs and replace its input.
                 // Exit code is 2: block the tool use. return stderr to LLM. show warning to user
                 // Other error: show warning to user.
+                outcomes.push(PreToolUseOutcome::from_results(&hook_results, &hooks));
+            }
+        }
 
-                // Check for exit code 2 and add to tool_results
-                for (_, (exit_code, output)) in &hook_results {
-                    if *exit_code == 2 {
-                        tool_results.push(ToolUseResult {
-                            tool_use_id: tool.id.clone(),
-                            content: vec![ToolUseResultBlock::Text(format!(
-                                "PreToolHook blocked the tool execution: {}",
-                                output
-                            ))],
-                            status: ToolResultStatus::Error,
-                        });
-                    }
-                }
+        for (tool, outcome) in queued_tools.iter_mut().zip(outcomes) {
+            let reasons = outcome.reasons.join("\n");
+            match outcome.decision {
+                Some(HookDecision::Deny) => {
+                    tool_results.push(ToolUseResult {
+                        tool_use_id: tool.id.clone(),
+                        content: vec![ToolUseResultBlock::Text(format!(
+                            "PreToolHook blocked the tool execution: {}",
+                            reasons
+                        ))],
+                        status: ToolResultStatus::Error,
+                    });
+                    continue;
+                },
+                Some(decision) if !reasons.is_empty() => {
+                    let verb = match decision {
+                        HookDecision::Allow => "approved",
+                        _ => "asks for approval of",
+                    };
+                    queue!(
+                        self.stderr,
+                        StyledText::secondary_fg(),
+                        style::Print(format!("A preToolUse hook {verb} {}: {reasons}\n", tool.name)),
+                        StyledText::reset(),
+                    )?;
+                },
+                _ => (),
+            }
+            tool.hook_decision = outcome.decision;
+
+            let Some(input) = outcome.updated_input else {
+                continue;
+            };
+            if let Err(result) = self.update_tool_input(os, tool, input).await {
+                tool_results.push(result);
             }
         }
 
@@ -3447,8 +3908,43 @@ impl ChatSession {
         Ok(ChatState::ExecuteTools)
     }
 
+    /// Replaces the input of a queued tool with the one returned by a `preToolUse` hook,
+    /// validating it like the input of the model.
+    async fn update_tool_input(
+        &mut self,
+        os: &Os,
+        tool: &mut QueuedTool,
+        input: serde_json::Value,
+    ) -> Result<(), ToolUseResult> {
+        let tool_use = AssistantToolUse {
+            id: tool.id.clone(),
+            name: tool.name.clone(),
+            orig_name: tool.name.clone(),
+            args: input.clone(),
+            orig_args: input.clone(),
+        };
+        let mut updated = self
+            .conversation
+            .tool_manager
+            .get_tool_from_tool_use(tool_use)
+            .await
+            .map_err(ToolUseResult::from)?;
+        self.contextualize_tool(&mut updated);
+        updated.validate(os).await.map_err(|err| ToolUseResult {
+            tool_use_id: tool.id.clone(),
+            content: vec![ToolUseResultBlock::Text(format!(
+                "Failed to validate the tool parameters updated by a preToolUse hook: {err}"
+            ))],
+            status: ToolResultStatus::Error,
+        })?;
+
+        tool.tool = updated;
+        tool.tool_input = input;
+        Ok(())
+    }
+
     async fn retry_model_overload(&mut self, os: &mut Os) -> Result<ChatState, ChatError> {
-        os.client.invalidate_model_cache().await;
+        os.client.invalidate_model_cache(&os.database).await;
         match select_model(os, self).await {
             Ok(Some(_)) => (),
             Ok(None) => {
@@ -4059,7 +4555,7 @@ mod tests {
                 "y".to_string(),
                 "exit".to_string(),
             ]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -4200,7 +4696,7 @@ mod tests {
                 "n".to_string(),             // cancel
                 "exit".to_string(),
             ]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -4296,7 +4792,7 @@ mod tests {
                 "y".to_string(),
                 "exit".to_string(),
             ]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -4370,7 +4866,7 @@ mod tests {
                 "create a new file".to_string(),
                 "exit".to_string(),
             ]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -4420,7 +4916,7 @@ mod tests {
             agents,
             None,
             InputSource::new_mock(vec!["/subscribe".to_string(), "y".to_string(), "/quit".to_string()]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -4527,7 +5023,7 @@ mod tests {
                 "y".to_string(), // Accept tool execution
                 "exit".to_string(),
             ]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -4657,7 +5153,7 @@ mod tests {
             agents,
             None,
             InputSource::new_mock(vec!["read /sensitive.txt".to_string(), "exit".to_string()]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
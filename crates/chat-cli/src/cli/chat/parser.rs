@@ -34,6 +34,7 @@ use crate::api_client::error::ConverseStreamError;
 use crate::api_client::model::{
     ChatResponseStream,
     ConversationState,
+    ReasoningContent,
 };
 use crate::api_client::send_message_output::SendMessageOutput;
 use crate::telemetry::ReasonCode;
@@ -298,6 +299,10 @@ struct ResponseParser {
     assistant_text: String,
     /// Tool uses requested by the model.
     tool_uses: Vec<AssistantToolUse>,
+    /// Completed reasoning blocks received so far.
+    reasoning: Vec<ReasoningContent>,
+    /// Buffer for the reasoning block currently being received, closed by its signature.
+    reasoning_text: String,
     /// Whether or not we are currently receiving tool use delta events. Tuple of
     /// `Some((tool_use_id, name))` if true, [None] otherwise.
     parsing_tool_use: Option<(String, String)>,
@@ -347,6 +352,8 @@ impl ResponseParser {
             peek: None,
             assistant_text: String::new(),
             tool_uses: Vec::new(),
+            reasoning: Vec::new(),
+            reasoning_text: String::new(),
             parsing_tool_use: None,
             request_start_time,
             request_start_time_sys,
@@ -429,11 +436,25 @@ impl ResponseParser {
                         self.parsing_tool_use = Some((tool_use_id.clone(), name.clone()));
                         return Ok(ResponseEvent::ToolUseStart { name });
                     },
+                    ChatResponseStream::ReasoningContentEvent { text } => {
+                        self.reasoning_text.push_str(&text);
+                        return Ok(ResponseEvent::ReasoningText(text));
+                    },
+                    ChatResponseStream::ReasoningSignatureEvent { signature } => {
+                        self.reasoning.push(ReasoningContent::Text {
+                            text: std::mem::take(&mut self.reasoning_text),
+                            signature: Some(signature),
+                        });
+                    },
+                    ChatResponseStream::RedactedReasoningEvent { data } => {
+                        self.reasoning.push(ReasoningContent::Redacted(data));
+                    },
                     _ => {},
                 },
                 Ok(None) => {
                     let message_id = Some(self.message_id.clone());
                     let content = std::mem::take(&mut self.assistant_text);
+                    let reasoning = self.take_reasoning();
                     let (message, conv_type) = if self.tool_uses.is_empty() {
                         (
                             AssistantMessage::new_response(message_id, content),
@@ -453,7 +474,7 @@ impl ResponseParser {
                     *self.request_metadata.lock().await = Some(request_metadata.clone());
                     self.ended = true;
                     return Ok(ResponseEvent::EndStream {
-                        message,
+                        message: message.with_reasoning(reasoning),
                         request_metadata,
                     });
                 },
@@ -503,11 +524,14 @@ impl ResponseParser {
                             args: warning_args.clone(),
                             orig_args: warning_args.clone(),
                         });
-                        let message = Box::new(AssistantMessage::new_tool_use(
-                            Some(self.message_id.clone()),
-                            std::mem::take(&mut self.assistant_text),
-                            self.tool_uses.clone().into_iter().collect(),
-                        ));
+                        let message = Box::new(
+                            AssistantMessage::new_tool_use(
+                                Some(self.message_id.clone()),
+                                std::mem::take(&mut self.assistant_text),
+                                self.tool_uses.clone().into_iter().collect(),
+                            )
+                            .with_reasoning(self.take_reasoning()),
+                        );
                         return Err(self.error(RecvErrorKind::ToolValidationError {
                             tool_use_id: id,
                             name,
@@ -544,11 +568,14 @@ impl ResponseParser {
                         args: args.clone(),
                         orig_args: args.clone(),
                     });
-                    let message = Box::new(AssistantMessage::new_tool_use(
-                        Some(self.message_id.clone()),
-                        std::mem::take(&mut self.assistant_text),
-                        self.tool_uses.clone().into_iter().collect(),
-                    ));
+                    let message = Box::new(
+                        AssistantMessage::new_tool_use(
+                            Some(self.message_id.clone()),
+                            std::mem::take(&mut self.assistant_text),
+                            self.tool_uses.clone().into_iter().collect(),
+                        )
+                        .with_reasoning(self.take_reasoning()),
+                    );
                     return Err(self.error(RecvErrorKind::UnexpectedToolUseEos {
                         tool_use_id: id,
                         name,
@@ -573,6 +600,18 @@ impl ResponseParser {
         })
     }
 
+    /// Takes the reasoning received so far, including a trailing block that never received a
+    /// signature.
+    fn take_reasoning(&mut self) -> Vec<ReasoningContent> {
+        if !self.reasoning_text.is_empty() {
+            self.reasoning.push(ReasoningContent::Text {
+                text: std::mem::take(&mut self.reasoning_text),
+                signature: None,
+            });
+        }
+        std::mem::take(&mut self.reasoning)
+    }
+
     /// Returns the next event in the [SendMessageOutput] without consuming it.
     async fn peek(&mut self) -> Result<Option<&ChatResponseStream>, RecvError> {
         if self.peek.is_some() {
@@ -612,6 +651,11 @@ impl ResponseParser {
                         ChatResponseStream::ToolUseEvent { input, .. } => {
                             self.received_response_size += input.as_ref().map(String::len).unwrap_or_default();
                         },
+                        ChatResponseStream::ReasoningContentEvent { .. }
+                        | ChatResponseStream::ReasoningSignatureEvent { .. }
+                        | ChatResponseStream::RedactedReasoningEvent { .. } => {
+                            self.received_response_size += r.len();
+                        },
                         _ => {
                             warn!(?r, "received unexpected event from the response stream");
                         },
@@ -640,22 +684,8 @@ impl ResponseParser {
     }
 
     fn make_metadata(&self, chat_conversation_type: Option<ChatConversationType>) -> RequestMetadata {
-        // Extract token usage from Bedrock metadata if available
-        let (input_tokens, output_tokens, cache_read_tokens, cache_write_tokens) = 
-            if let Some(metadata) = self.response.get_bedrock_metadata() {
-                if let Some(usage) = &metadata.usage {
-                    (
-                        Some(usage.input_tokens()),
-                        Some(usage.output_tokens()),
-                        usage.cache_read_input_tokens(),
-                        usage.cache_write_input_tokens(),
-                    )
-                } else {
-                    (None, None, None, None)
-                }
-            } else {
-                (None, None, None, None)
-            };
+        // Extract token usage reported by the provider if available
+        let usage = self.response.token_usage();
 
         RequestMetadata {
             request_id: self.response.request_id().map(String::from),
@@ -676,10 +706,10 @@ impl ResponseParser {
                 .map(|t| (t.id.clone(), t.name.clone()))
                 .collect::<_>(),
             model_id: self.model_id.clone(),
-            input_tokens,
-            output_tokens,
-            cache_read_tokens,
-            cache_write_tokens,
+            input_tokens: usage.map(|u| u.input_tokens),
+            output_tokens: usage.map(|u| u.output_tokens),
+            cache_read_tokens: usage.and_then(|u| u.cache_read_tokens),
+            cache_write_tokens: usage.and_then(|u| u.cache_write_tokens),
         }
     }
 }
@@ -688,6 +718,8 @@ impl ResponseParser {
 pub enum ResponseEvent {
     /// Text returned by the assistant. This should be displayed to the user as it is received.
     AssistantText(String),
+    /// Reasoning text produced by the model before its response.
+    ReasoningText(String),
     /// Notification that a tool use is being received.
     ToolUseStart { name: String },
     /// A tool use requested by the assistant. This should be displayed to the user as it is
@@ -731,7 +763,7 @@ pub struct RequestMetadata {
     pub model_id: Option<String>,
     /// Meta tags for the request.
     pub message_meta_tags: Vec<MessageMetaTag>,
-    /// Token usage from Bedrock (if available)
+    /// Token usage reported by the provider (if available)
     pub input_tokens: Option<i32>,
     pub output_tokens: Option<i32>,
     pub cache_read_tokens: Option<i32>,
@@ -894,4 +926,55 @@ mod tests {
             "Expected to find tool validation error for non-object JSON"
         );
     }
+
+    #[tokio::test]
+    async fn test_response_parser_keeps_reasoning() {
+        let mut events = vec![
+            ChatResponseStream::ReasoningContentEvent {
+                text: "The user".to_string(),
+            },
+            ChatResponseStream::ReasoningContentEvent {
+                text: " wants a list.".to_string(),
+            },
+            ChatResponseStream::ReasoningSignatureEvent {
+                signature: "sig".to_string(),
+            },
+            ChatResponseStream::RedactedReasoningEvent { data: vec![1, 2, 3] },
+            ChatResponseStream::AssistantResponseEvent {
+                content: "Here it is".to_string(),
+            },
+        ];
+        events.reverse();
+        let mut parser = ResponseParser::new(
+            SendMessageOutput::Mock(events),
+            "".to_string(),
+            None,
+            1,
+            vec![],
+            mpsc::channel(32).0,
+            Instant::now(),
+            SystemTime::now(),
+            CancellationToken::new(),
+            Arc::new(Mutex::new(None)),
+        );
+
+        let mut reasoning_text = String::new();
+        let message = loop {
+            match parser.recv().await.unwrap() {
+                ResponseEvent::ReasoningText(text) => reasoning_text.push_str(&text),
+                ResponseEvent::EndStream { message, .. } => break message,
+                _ => (),
+            }
+        };
+
+        assert_eq!(reasoning_text, "The user wants a list.");
+        assert_eq!(message.content(), "Here it is");
+        assert_eq!(message.reasoning(), &[
+            ReasoningContent::Text {
+                text: "The user wants a list.".to_string(),
+                signature: Some("sig".to_string()),
+            },
+            ReasoningContent::Redacted(vec![1, 2, 3]),
+        ]);
+    }
 }
@@ -142,7 +142,10 @@ pub const COMMANDS: &[&str] = &[
     "/usage",
     "/changelog",
     "/save",
+    "/export",
     "/load",
+    "/fork",
+    "/branches",
     "/paste",
     "/subscribe",
 ];
@@ -3,6 +3,7 @@ use rmcp::model::{
     ListResourceTemplatesResult,
     ListResourcesResult,
     ListToolsResult,
+    ReadResourceResult,
 };
 use rmcp::{
     Peer,
@@ -44,6 +45,11 @@ pub enum UpdateEventMessage {
         result: Result<ListResourceTemplatesResult>,
         peer: Option<Peer<RoleClient>>,
     },
+    ResourceUpdated {
+        server_name: String,
+        uri: String,
+        result: Result<ReadResourceResult>,
+    },
     OauthLink {
         server_name: String,
         link: String,
@@ -150,6 +156,18 @@ impl Messenger for ServerMessenger {
             .map_err(|e| MessengerError::Custom(e.to_string()))?)
     }
 
+    async fn send_resource_updated(&self, uri: String, result: Result<ReadResourceResult>) -> MessengerResult {
+        Ok(self
+            .update_event_sender
+            .send(UpdateEventMessage::ResourceUpdated {
+                server_name: self.server_name.clone(),
+                uri,
+                result,
+            })
+            .await
+            .map_err(|e| MessengerError::Custom(e.to_string()))?)
+    }
+
     async fn send_oauth_link(&self, link: String) -> MessengerResult {
         Ok(self
             .update_event_sender
@@ -0,0 +1,319 @@
+//! `q chat sessions`, and finding the conversation to continue with `q chat --resume` or
+//! `q chat --resume-id`.
+
+use std::io::{
+    IsTerminal,
+    Write,
+};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use chrono::{
+    DateTime,
+    Local,
+    Utc,
+};
+use clap::Subcommand;
+use crossterm::style::Stylize;
+use dialoguer::FuzzySelect;
+use eyre::{
+    Result,
+    bail,
+};
+
+use super::ConversationState;
+use super::export::{
+    ExportFormat,
+    ExportOptions,
+    export_conversation,
+};
+use crate::cli::OutputFormat;
+use crate::database::sessions::SessionSummary;
+use crate::os::Os;
+use crate::util::dialoguer_theme;
+
+/// The value of `--resume-id` that asks the user to pick a session.
+const PICK: &str = "pick";
+
+/// Number of characters of the ids shown in lists, which is enough to refer to a session.
+const SHORT_ID_LEN: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum SessionsSubcommand {
+    /// List the saved sessions, most recent first
+    List {
+        /// List the sessions of every directory, not only of the current one
+        #[arg(long, short)]
+        all: bool,
+        /// Format of the output
+        #[arg(long, short, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Show the details and transcript of a session
+    Show {
+        /// Id of the session, or the start of it
+        id: String,
+        /// Format of the output
+        #[arg(long, short, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Delete a session
+    Delete {
+        /// Id of the session, or the start of it
+        id: String,
+    },
+    /// Export the conversation of a session, as Markdown by default
+    Export {
+        /// Id of the session, or the start of it
+        id: String,
+        /// File to write the conversation to, instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Format of the export, guessed from the extension of the output file by default
+        #[arg(long, value_enum)]
+        format: Option<ExportFormat>,
+        #[command(flatten)]
+        options: ExportOptions,
+        /// Overwrite the file if it already exists
+        #[arg(long, short)]
+        force: bool,
+    },
+    /// Search the transcripts of the saved sessions
+    Search {
+        /// Words that must all appear in the session
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Maximum number of sessions to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Format of the output
+        #[arg(long, short, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+}
+
+impl SessionsSubcommand {
+    pub async fn execute(self, os: &mut Os, output: &mut impl Write, stderr: &mut impl Write) -> Result<ExitCode> {
+        match self {
+            Self::List { all, format } => {
+                let cwd = std::env::current_dir()?;
+                let sessions = os.database.list_sessions((!all).then_some(cwd.as_path()))?;
+                match format {
+                    OutputFormat::Plain if sessions.is_empty() => {
+                        writeln!(
+                            output,
+                            "No saved sessions. Use --all to list the sessions of every directory."
+                        )?;
+                    },
+                    OutputFormat::Plain => {
+                        for session in &sessions {
+                            writeln!(output, "{}", session_line(session, all))?;
+                        }
+                    },
+                    OutputFormat::Json => writeln!(output, "{}", serde_json::to_string(&sessions)?)?,
+                    OutputFormat::JsonPretty => writeln!(output, "{}", serde_json::to_string_pretty(&sessions)?)?,
+                }
+            },
+            Self::Show { id, format } => {
+                let id = resolve_session_id(os, &id)?;
+                let (Some(session), Some(conversation)) =
+                    (os.database.get_session_summary(&id)?, os.database.get_session(&id)?)
+                else {
+                    bail!("No session with id {id}");
+                };
+                match format {
+                    OutputFormat::Plain => {
+                        writeln!(output, "Id      : {}", session.id)?;
+                        writeln!(output, "Title   : {}", session.title)?;
+                        writeln!(output, "Dir     : {}", session.cwd)?;
+                        writeln!(output, "Agent   : {}", session.agent.as_deref().unwrap_or("-"))?;
+                        writeln!(output, "Model   : {}", session.model.as_deref().unwrap_or("-"))?;
+                        writeln!(output, "Created : {}", format_time(session.created_at))?;
+                        writeln!(output, "Updated : {}", format_time(session.updated_at))?;
+                        for entry in &conversation.transcript {
+                            writeln!(output, "\n{entry}")?;
+                        }
+                    },
+                    OutputFormat::Json | OutputFormat::JsonPretty => {
+                        let value = serde_json::json!({
+                            "session": session,
+                            "transcript": conversation.transcript,
+                        });
+                        match format {
+                            OutputFormat::Json => writeln!(output, "{}", serde_json::to_string(&value)?)?,
+                            _ => writeln!(output, "{}", serde_json::to_string_pretty(&value)?)?,
+                        }
+                    },
+                }
+            },
+            Self::Delete { id } => {
+                let id = resolve_session_id(os, &id)?;
+                os.database.delete_session(&id)?;
+                writeln!(output, "Deleted session {id}")?;
+            },
+            Self::Export {
+                id,
+                output: path,
+                format,
+                options,
+                force,
+            } => {
+                let id = resolve_session_id(os, &id)?;
+                let Some(conversation) = os.database.get_session(&id)? else {
+                    bail!("No session with id {id}");
+                };
+                let format = format
+                    .or_else(|| path.as_ref().and_then(ExportFormat::from_path))
+                    .unwrap_or_default();
+                let contents = export_conversation(&conversation, format, &options)?;
+                match path {
+                    Some(path) => {
+                        if os.fs.exists(&path) && !force {
+                            bail!(
+                                "File at {} already exists. To overwrite, use -f or --force",
+                                path.display()
+                            );
+                        }
+                        os.fs.write(&path, contents).await?;
+                        writeln!(stderr, "Exported session {id} to {}", path.display())?;
+                    },
+                    None => writeln!(output, "{contents}")?,
+                }
+            },
+            Self::Search { query, limit, format } => {
+                let matches = os.database.search_sessions(&query.join(" "), limit)?;
+                match format {
+                    OutputFormat::Plain if matches.is_empty() => writeln!(output, "No sessions found")?,
+                    OutputFormat::Plain => {
+                        for session_match in &matches {
+                            writeln!(output, "{}", session_line(&session_match.session, true))?;
+                            let snippet = session_match.snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+                            writeln!(output, "    {}", snippet.dark_grey())?;
+                        }
+                    },
+                    OutputFormat::Json => writeln!(output, "{}", serde_json::to_string(&matches)?)?,
+                    OutputFormat::JsonPretty => writeln!(output, "{}", serde_json::to_string_pretty(&matches)?)?,
+                }
+            },
+        }
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Finds the conversation to continue for `--resume`: the last session of the current directory.
+pub fn resume_last_session(os: &Os) -> Result<Option<ConversationState>> {
+    let cwd = std::env::current_dir()?;
+    match os.database.list_sessions(Some(&cwd))?.first() {
+        Some(session) => Ok(os.database.get_session(&session.id)?),
+        None => Ok(None),
+    }
+}
+
+/// Finds the conversation to continue for `--resume-id`: the session with the given id, or the
+/// one the user picks with `pick`.
+pub fn resume_session(os: &Os, resume_id: &str) -> Result<Option<ConversationState>> {
+    let id = match resume_id {
+        PICK => match pick_session(os)? {
+            Some(id) => id,
+            None => {
+                bail!("No session was picked");
+            },
+        },
+        id => resolve_session_id(os, id)?,
+    };
+    Ok(os.database.get_session(&id)?)
+}
+
+/// Resolves the start of a session id to the full id, as long as only one session starts with it.
+fn resolve_session_id(os: &Os, id: &str) -> Result<String> {
+    let ids = os.database.find_session_ids(id)?;
+    match ids.as_slice() {
+        [] => {
+            bail!("No session with id {id}. Run `q chat sessions list --all` to see the saved sessions.");
+        },
+        [full_id] => Ok(full_id.clone()),
+        _ if ids.iter().any(|full_id| full_id == id) => Ok(id.to_string()),
+        _ => {
+            bail!("{} sessions start with {id}, use more characters of the id", ids.len());
+        },
+    }
+}
+
+/// Lets the user pick a session of any directory, those of the current directory first.
+fn pick_session(os: &Os) -> Result<Option<String>> {
+    if !std::io::stdin().is_terminal() {
+        bail!("Picking a session requires an interactive terminal, pass the id of the session instead");
+    }
+
+    let cwd = std::env::current_dir()?.to_string_lossy().to_string();
+    let mut sessions = os.database.list_sessions(None)?;
+    if sessions.is_empty() {
+        bail!("No saved sessions");
+    }
+    // Sorting is stable, so the sessions stay ordered by time within each group
+    sessions.sort_by_key(|session| session.cwd != cwd);
+
+    let items = sessions
+        .iter()
+        .map(|session| session_line(session, session.cwd != cwd))
+        .collect::<Vec<_>>();
+    let selection = FuzzySelect::with_theme(&dialoguer_theme())
+        .with_prompt("Resume which session?")
+        .items(&items)
+        .default(0)
+        .interact_opt()?;
+    Ok(selection.map(|i| sessions[i].id.clone()))
+}
+
+/// A line describing a session in lists, with its directory if `show_cwd` is set.
+fn session_line(session: &SessionSummary, show_cwd: bool) -> String {
+    let short_id = session.id.get(..SHORT_ID_LEN).unwrap_or(&session.id);
+    let title = match session.title.as_str() {
+        "" => "(untitled)",
+        title => title,
+    };
+    let mut line = format!("{}  {}  {title}", short_id.yellow(), format_time(session.updated_at));
+    if let Some(agent) = &session.agent {
+        line.push_str(&format!(" {}", format!("[{agent}]").dark_grey()));
+    }
+    if show_cwd {
+        line.push_str(&format!(" {}", session.cwd.clone().dark_grey()));
+    }
+    line
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, agent: Option<&str>) -> SessionSummary {
+        SessionSummary {
+            id: id.to_string(),
+            title: "Fix the flaky test".to_string(),
+            cwd: "/work/repo".to_string(),
+            agent: agent.map(str::to_string),
+            model: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            parent_id: None,
+            fork_turn: None,
+        }
+    }
+
+    #[test]
+    fn test_session_line() {
+        let line = session_line(&session("3f1c9e2a-6b1d-4c7e-9a43-2f7f0c5d8e11", Some("rust")), false);
+        let line = String::from_utf8(strip_ansi_escapes::strip(line)).unwrap();
+        assert!(line.starts_with("3f1c9e2a  "));
+        assert!(line.ends_with("  Fix the flaky test [rust]"));
+
+        let line = session_line(&session("abc", None), true);
+        let line = String::from_utf8(strip_ansi_escapes::strip(line)).unwrap();
+        assert!(line.starts_with("abc  "));
+        assert!(line.ends_with("Fix the flaky test /work/repo"));
+    }
+}
@@ -1,4 +1,15 @@
 use std::ops::Deref;
+use std::sync::{
+    Arc,
+    LazyLock,
+};
+
+use parking_lot::RwLock;
+use tokenizers::Tokenizer;
+use tracing::{
+    debug,
+    warn,
+};
 
 use super::message::{
     AssistantMessage,
@@ -7,10 +18,20 @@ use super::message::{
     UserMessage,
     UserMessageContent,
 };
+use crate::cli::chat::cli::model::ModelInfo;
 use crate::cli::chat::conversation::{
     BackendConversationState,
     ConversationSize,
 };
+use crate::os::Os;
+use crate::util::paths::PathResolver;
+
+/// Prompts smaller than this are not used for calibration, since the parts of the prompt that are
+/// not counted locally (eg. the provider's own system prompt) would dominate the measurement.
+const MIN_CALIBRATION_TOKENS: usize = 1000;
+
+/// Tokenizer and bytes per token ratio of the active model family.
+static ACTIVE: LazyLock<RwLock<ActiveTokenizer>> = LazyLock::new(Default::default);
 
 #[derive(Debug, Clone, Copy)]
 pub struct CharCount(usize);
@@ -75,22 +96,150 @@ impl std::fmt::Display for TokenCount {
 pub struct TokenCounter;
 
 impl TokenCounter {
+    /// Bytes per token assumed until the ratio is calibrated from the usage reported by the model.
     pub const TOKEN_TO_CHAR_RATIO: usize = 4;
 
-    /// Estimates the number of tokens in the input content.
-    /// Currently uses a simple heuristic: content length / TOKEN_TO_CHAR_RATIO
+    /// Counts the number of tokens in the input content.
     ///
-    /// Rounds up to the nearest multiple of 10 to avoid giving users a false sense of precision.
+    /// Uses the tokenizer of the active model family if one is installed. Otherwise the count is
+    /// estimated from the content length and rounded up to the nearest multiple of 10 to avoid
+    /// giving users a false sense of precision.
     pub fn count_tokens(content: &str) -> usize {
-        Self::count_tokens_char_count(content.len())
+        ACTIVE.read().count_tokens(content)
     }
 
     fn count_tokens_char_count(count: usize) -> usize {
-        (count / Self::TOKEN_TO_CHAR_RATIO + 5) / 10 * 10
+        ACTIVE.read().estimate_tokens(count)
+    }
+
+    pub fn token_to_chars(token: usize) -> usize {
+        ACTIVE.read().token_to_chars(token)
+    }
+
+    /// Switches to the tokenizer of the model's family, loaded from `<family>.json` in the
+    /// tokenizers directory. The calibration is reset whenever the family changes.
+    pub async fn use_model(os: &Os, model_info: Option<&ModelInfo>) {
+        let family = model_info.and_then(|model| model_family(&model.model_id));
+        if ACTIVE.read().family == family {
+            return;
+        }
+
+        let tokenizer = match family {
+            Some(family) => load_tokenizer(os, family).await,
+            None => None,
+        };
+        *ACTIVE.write() = ActiveTokenizer {
+            family,
+            tokenizer,
+            chars_per_token: None,
+        };
+    }
+
+    /// Calibrates the bytes per token ratio from a request of `chars` bytes for which the model
+    /// reported `tokens` prompt tokens.
+    pub fn calibrate(chars: CharCount, tokens: usize) {
+        ACTIVE.write().calibrate(chars, tokens);
+    }
+}
+
+#[derive(Default)]
+struct ActiveTokenizer {
+    family: Option<&'static str>,
+    tokenizer: Option<Arc<Tokenizer>>,
+    /// Bytes per token measured from the usage reported by the model.
+    chars_per_token: Option<f64>,
+}
+
+impl ActiveTokenizer {
+    fn count_tokens(&self, content: &str) -> usize {
+        match self.tokenizer.as_ref().map(|t| t.encode_fast(content, false)) {
+            Some(Ok(encoding)) => encoding.len(),
+            Some(Err(err)) => {
+                debug!(?err, "Failed to tokenize content, estimating the token count");
+                self.estimate_tokens(content.len())
+            },
+            None => self.estimate_tokens(content.len()),
+        }
+    }
+
+    fn estimate_tokens(&self, chars: usize) -> usize {
+        let tokens = match self.chars_per_token {
+            Some(ratio) => (chars as f64 / ratio) as usize,
+            None => chars / TokenCounter::TOKEN_TO_CHAR_RATIO,
+        };
+        (tokens + 5) / 10 * 10
+    }
+
+    fn token_to_chars(&self, tokens: usize) -> usize {
+        match self.chars_per_token {
+            Some(ratio) => (tokens as f64 * ratio) as usize,
+            None => tokens * TokenCounter::TOKEN_TO_CHAR_RATIO,
+        }
+    }
+
+    /// Moves the ratio halfway towards the measured one, so that a single unusual request doesn't
+    /// swing the estimates.
+    fn calibrate(&mut self, chars: CharCount, tokens: usize) {
+        if tokens < MIN_CALIBRATION_TOKENS {
+            return;
+        }
+        let measured = (chars.value() as f64 / tokens as f64).clamp(1.0, 8.0);
+        self.chars_per_token = Some(match self.chars_per_token {
+            Some(ratio) => (ratio + measured) / 2.0,
+            None => measured,
+        });
+        debug!("Calibrated token estimates to {:.2} bytes per token", measured);
+    }
+}
+
+/// Returns the tokenizer family of a model, used as the name of its tokenizer file.
+fn model_family(model_id: &str) -> Option<&'static str> {
+    const FAMILIES: &[(&str, &str)] = &[
+        ("claude", "claude"),
+        ("nova", "nova"),
+        ("llama", "llama"),
+        ("mistral", "mistral"),
+        ("mixtral", "mistral"),
+        ("pixtral", "mistral"),
+        ("deepseek", "deepseek"),
+        ("qwen", "qwen"),
+        ("gpt", "gpt"),
+        ("gemma", "gemma"),
+        ("command", "cohere"),
+    ];
+    let model_id = model_id.to_lowercase();
+    FAMILIES
+        .iter()
+        .find(|(pattern, _)| model_id.contains(pattern))
+        .map(|(_, family)| *family)
+}
+
+async fn load_tokenizer(os: &Os, family: &str) -> Option<Arc<Tokenizer>> {
+    let path = PathResolver::new(os)
+        .global()
+        .tokenizers_dir()
+        .ok()?
+        .join(format!("{family}.json"));
+    if !os.fs.exists(&path) {
+        debug!(?path, "No tokenizer installed, estimating token counts");
+        return None;
     }
 
-    pub const fn token_to_chars(token: usize) -> usize {
-        token * Self::TOKEN_TO_CHAR_RATIO
+    let tokenizer = os
+        .fs
+        .read(&path)
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| Tokenizer::from_bytes(bytes).map_err(|err| err.to_string()));
+    match tokenizer {
+        Ok(tokenizer) => {
+            debug!(?path, "Loaded tokenizer");
+            Some(Arc::new(tokenizer))
+        },
+        Err(err) => {
+            warn!(?path, %err, "Failed to load tokenizer, estimating token counts");
+            None
+        },
     }
 }
 
@@ -190,6 +339,61 @@ mod tests {
         assert_eq!(count, (text.len() / 3 + 5) / 10 * 10);
     }
 
+    #[test]
+    fn test_tokenizer_count() {
+        use tokenizers::models::wordlevel::WordLevel;
+        use tokenizers::pre_tokenizers::whitespace::Whitespace;
+
+        let vocab = [("[UNK]", 0), ("fn", 1), ("main", 2)]
+            .into_iter()
+            .map(|(token, id)| (token.to_string(), id))
+            .collect();
+        let mut tokenizer = Tokenizer::new(
+            WordLevel::builder()
+                .vocab(vocab)
+                .unk_token("[UNK]".to_string())
+                .build()
+                .unwrap(),
+        );
+        tokenizer.with_pre_tokenizer(Some(Whitespace {}));
+
+        // Tokenizers are installed as their JSON serialization
+        let tokenizer = Tokenizer::from_bytes(tokenizer.to_string(false).unwrap()).unwrap();
+        let active = ActiveTokenizer {
+            family: Some("llama"),
+            tokenizer: Some(Arc::new(tokenizer)),
+            chars_per_token: None,
+        };
+        // Punctuation runs are a single (unknown) token
+        assert_eq!(active.count_tokens("fn main() {}"), 4);
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let mut active = ActiveTokenizer::default();
+        assert_eq!(active.estimate_tokens(40_000), 10_000);
+
+        // Small prompts are ignored
+        active.calibrate(2_000.into(), 500);
+        assert_eq!(active.chars_per_token, None);
+
+        active.calibrate(30_000.into(), 10_000);
+        assert_eq!(active.chars_per_token, Some(3.0));
+        assert_eq!(active.estimate_tokens(30_000), 10_000);
+        assert_eq!(active.token_to_chars(1_000), 3_000);
+
+        active.calibrate(50_000.into(), 10_000);
+        assert_eq!(active.chars_per_token, Some(4.0));
+    }
+
+    #[test]
+    fn test_model_family() {
+        assert_eq!(model_family("us.anthropic.claude-sonnet-4-20250514-v1:0"), Some("claude"));
+        assert_eq!(model_family("meta.llama3-1-70b-instruct-v1:0"), Some("llama"));
+        assert_eq!(model_family("qwen2.5-coder:14b"), Some("qwen"));
+        assert_eq!(model_family("ai21.jamba-1-5-large-v1:0"), None);
+    }
+
     #[test]
     fn test_calculate_value_char_count() {
         // Test simple types
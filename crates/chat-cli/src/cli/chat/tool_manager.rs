@@ -39,6 +39,13 @@ use rmcp::model::{
     GetPromptRequestParam,
     GetPromptResult,
     Prompt,
+    ReadResourceRequestParam,
+    ReadResourceResult,
+    Resource,
+    ResourceContents,
+    ResourceTemplate,
+    Root,
+    SubscribeRequestParam,
 };
 use tokio::signal::ctrl_c;
 use tokio::sync::{
@@ -88,6 +95,13 @@ use crate::cli::chat::tools::{
 };
 use crate::database::Database;
 use crate::database::settings::Setting;
+use crate::mcp_client::health::{
+    RestartedClients,
+    ServerHealth,
+    ServerState,
+    ServerStatus,
+    supervise,
+};
 use crate::mcp_client::messenger::Messenger;
 use crate::mcp_client::{
     InitializedMcpClient,
@@ -103,6 +117,8 @@ const NAMESPACE_DELIMITER: &str = "___";
 // This applies for both mcp server and tool name
 const VALID_TOOL_NAME: &str = "^[a-zA-Z][a-zA-Z0-9_]*$";
 const SPINNER_CHARS: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+/// How long an interrupt waits for the servers to be told to stop their tool calls.
+const TOOL_CALL_CANCELLATION_TIMEOUT: Duration = Duration::from_millis(500);
 
 use crate::util::paths::PathResolver;
 
@@ -184,6 +200,8 @@ pub struct ToolManagerBuilder {
     has_new_stuff: Arc<AtomicBool>,
     mcp_load_record: Arc<Mutex<HashMap<String, Vec<LoadingRecord>>>>,
     new_tool_specs: NewToolSpecs,
+    mcp_resources: McpResources,
+    mcp_roots: McpRoots,
     pending_clients: Option<Arc<RwLock<HashSet<String>>>>,
     is_first_launch: bool,
     agent: Option<Arc<Mutex<Agent>>>,
@@ -201,6 +219,8 @@ impl Default for ToolManagerBuilder {
             has_new_stuff: Default::default(),
             mcp_load_record: Default::default(),
             new_tool_specs: Default::default(),
+            mcp_resources: Default::default(),
+            mcp_roots: Default::default(),
             pending_clients: Default::default(),
             is_first_launch: true,
             agent: Default::default(),
@@ -222,6 +242,8 @@ impl From<&mut ToolManager> for ToolManagerBuilder {
             has_new_stuff: value.has_new_stuff.clone(),
             mcp_load_record: value.mcp_load_record.clone(),
             new_tool_specs: value.new_tool_specs.clone(),
+            mcp_resources: value.mcp_resources.clone(),
+            mcp_roots: value.mcp_roots.clone(),
             pending_clients: Some(value.pending_clients.clone()),
             // if we are getting a builder from an instantiated tool manager this field would be
             // false
@@ -323,6 +345,7 @@ impl ToolManagerBuilder {
 
         let mut clients = HashMap::<String, InitializedMcpClient>::new();
         let new_tool_specs = self.new_tool_specs;
+        let mcp_resources = self.mcp_resources;
         let has_new_stuff = self.has_new_stuff;
         let pending = self.pending_clients.unwrap_or(Arc::new(RwLock::new({
             let mut pending = HashSet::<String>::new();
@@ -333,6 +356,19 @@ impl ToolManagerBuilder {
         let load_record = self.mcp_load_record;
         let agent = self.agent.unwrap_or_default();
         let database = os.database.clone();
+
+        // Servers may ask for the roots as soon as they start, before the chat session reports the
+        // context paths, so they start out with those of the agent
+        let mcp_roots = self.mcp_roots;
+        let agent_paths = agent
+            .lock()
+            .await
+            .resources
+            .iter()
+            .filter_map(|resource| resource.strip_prefix("file://"))
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        *mcp_roots.lock().await = workspace_roots(os, &agent_paths);
         let mut messenger_builder = self.messenger_builder.take();
 
         let mut loading_servers = HashMap::<String, Instant>::new();
@@ -362,6 +398,7 @@ impl ToolManagerBuilder {
             let telemetry = os.telemetry.clone();
             let loading_status_sender = loading_status_sender.clone();
             let new_tool_specs = new_tool_specs.clone();
+            let mcp_resources = mcp_resources.clone();
             let conv_id = conversation_id.clone();
             let pending = pending.clone();
             let regex = Regex::new(VALID_TOOL_NAME)?;
@@ -381,6 +418,7 @@ impl ToolManagerBuilder {
                 telemetry,
                 loading_status_sender,
                 new_tool_specs,
+                mcp_resources,
                 total,
                 conv_id,
             );
@@ -396,14 +434,20 @@ impl ToolManagerBuilder {
                     McpClientService::new(
                         server_name.clone(),
                         server_config,
-                        messenger_builder.build_with_name(server_name),
+                        messenger_builder.build_with_name(server_name.clone()),
+                        mcp_roots.clone(),
+                        interactive,
+                        ServerHealth::new(server_name, os.database.clone()),
                     ),
                 )
             })
             .collect::<Vec<_>>();
 
+        let mut mcp_services = HashMap::<String, McpClientService>::new();
+        let mut supervisors = HashMap::<String, JoinHandle<()>>::new();
+        let restarted_clients = RestartedClients::default();
         for (mut name, mcp_client) in pre_initialized {
-            let init_res = mcp_client.init(os).await;
+            let init_res = mcp_client.clone().init(os).await;
             match init_res {
                 Ok(mut running_service) => {
                     while let Some(collided_service) = clients.insert(name.clone(), running_service) {
@@ -412,6 +456,14 @@ impl ToolManagerBuilder {
                         name.push('1');
                         running_service = collided_service;
                     }
+                    let supervisor = tokio::spawn(supervise(
+                        mcp_client.clone(),
+                        os.clone(),
+                        restarted_clients.clone(),
+                        has_new_stuff.clone(),
+                    ));
+                    supervisors.insert(name.clone(), supervisor);
+                    mcp_services.insert(name, mcp_client);
                 },
                 Err(e) => {
                     error!("Error initializing mcp client for server {}: {:?}", name, &e);
@@ -445,6 +497,11 @@ impl ToolManagerBuilder {
             loading_status_sender,
             loading_display_task,
             new_tool_specs,
+            mcp_resources,
+            mcp_roots,
+            mcp_services,
+            restarted_clients,
+            supervisors,
             has_new_stuff,
             is_interactive: interactive,
             mcp_load_record: load_record,
@@ -487,6 +544,128 @@ pub enum PromptQueryResult {
     Search(Vec<String>),
 }
 
+/// A reference to a resource of an MCP server. It is written as `@server/uri` in a prompt and as
+/// `mcp://server/uri` in the resources of an agent.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceReference {
+    pub server_name: String,
+    pub uri: String,
+}
+
+impl ResourceReference {
+    /// Parses `server/uri`. The uri must have a scheme (e.g. `file:///notes.md`), which is what
+    /// tells a resource apart from a prompt of the server.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (server_name, uri) = value.split_once('/')?;
+        let (scheme, rest) = uri.split_once("://")?;
+        let is_valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+        (!server_name.is_empty() && is_valid_scheme && !rest.is_empty()).then(|| Self {
+            server_name: server_name.to_string(),
+            uri: uri.to_string(),
+        })
+    }
+
+    /// Parses a resource of an agent of the form `mcp://server/uri`.
+    pub fn from_agent_resource(resource: &str) -> Option<Self> {
+        resource.strip_prefix("mcp://").and_then(Self::parse)
+    }
+
+    /// Finds the `@server/uri` references in a prompt. A reference ends at the next whitespace,
+    /// and punctuation that ends a sentence is not part of it.
+    pub fn find_all(input: &str) -> Vec<Self> {
+        let mut references = input
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix('@'))
+            .map(|word| word.trim_end_matches([',', '.', ';', ':', '!', '?', ')']))
+            .filter_map(Self::parse)
+            .collect::<Vec<_>>();
+        references.dedup();
+        references
+    }
+}
+
+impl std::fmt::Display for ResourceReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "@{}/{}", self.server_name, self.uri)
+    }
+}
+
+/// The resources and resource templates offered by an MCP server
+#[derive(Clone, Debug, Default)]
+pub struct ServerResources {
+    pub resources: Vec<Resource>,
+    pub templates: Vec<ResourceTemplate>,
+    /// Content of the resources used as context, keyed by uri. These are refreshed whenever the
+    /// server notifies that one of them is updated.
+    pub contents: HashMap<String, Vec<ResourceContents>>,
+}
+
+/// Formats the contents of a resource as text for the model. Binary contents are only described.
+pub fn resource_contents_to_text(contents: &[ResourceContents]) -> String {
+    contents
+        .iter()
+        .map(|content| match content {
+            ResourceContents::TextResourceContents { text, .. } => text.clone(),
+            ResourceContents::BlobResourceContents {
+                uri, mime_type, blob, ..
+            } => format!(
+                "<binary content of {uri} ({}, {} bytes base64 encoded) omitted>",
+                mime_type.as_deref().unwrap_or("unknown type"),
+                blob.len()
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the roots advertised to MCP servers: the current directory, followed by the
+/// directories of the context paths that are outside of it. Glob patterns contribute the
+/// directory before their first wildcard, and files the directory they are in.
+pub fn workspace_roots(os: &Os, context_paths: &[String]) -> Vec<Root> {
+    let Ok(cwd) = os.env.current_dir() else {
+        return Vec::new();
+    };
+
+    let mut dirs = vec![cwd.clone()];
+    for path in context_paths {
+        let path = match path.strip_prefix("~/") {
+            Some(rest) => match os.env.home() {
+                Some(home) => home.join(rest),
+                None => continue,
+            },
+            None => PathBuf::from(path),
+        };
+        // Joining an absolute path replaces the current directory
+        let path = cwd.join(path);
+        let glob_start = path
+            .components()
+            .position(|c| c.as_os_str().to_string_lossy().contains(['*', '?', '[']));
+        let dir = match glob_start {
+            Some(i) => path.components().take(i).collect::<PathBuf>(),
+            None if os.fs.chroot_path(&path).is_dir() => path,
+            None => match path.parent() {
+                Some(parent) => parent.to_path_buf(),
+                None => continue,
+            },
+        };
+
+        if !dirs.iter().any(|d| dir.starts_with(d)) {
+            dirs.retain(|d| !d.starts_with(&dir));
+            dirs.push(dir);
+        }
+    }
+
+    dirs.into_iter()
+        .filter_map(|dir| {
+            Some(Root {
+                uri: url::Url::from_file_path(&dir).ok()?.to_string(),
+                name: dir.file_name().map(|name| name.to_string_lossy().to_string()),
+            })
+        })
+        .collect()
+}
+
 /// Categorizes different types of tool name validation failures:
 /// - `TooLong`: The tool name exceeds the maximum allowed length
 /// - `IllegalChar`: The tool name contains characters that are not allowed
@@ -535,6 +714,13 @@ type ServerName = String;
 /// tool name).
 type NewToolSpecs = Arc<Mutex<HashMap<ServerName, (HashMap<ModelToolName, ToolInfo>, Vec<ToolSpec>)>>>;
 
+/// Resources of the MCP servers, kept up to date by the orchestrator task as servers report
+/// changes.
+type McpResources = Arc<Mutex<HashMap<ServerName, ServerResources>>>;
+
+/// Roots of the workspace, which every MCP client answers `roots/list` requests with.
+type McpRoots = Arc<Mutex<Vec<Root>>>;
+
 /// A pair of channels used for prompt list communication between the tool manager and chat helper.
 /// The sender broadcasts a list of available prompt names, while the receiver listens for
 /// search queries to filter the prompt list.
@@ -573,6 +759,22 @@ pub struct ToolManager {
     /// from server initialization processes.
     new_tool_specs: NewToolSpecs,
 
+    /// Resources offered by the MCP servers, along with the content of the resources used as
+    /// context.
+    mcp_resources: McpResources,
+
+    /// Roots of the workspace advertised to the MCP servers.
+    mcp_roots: McpRoots,
+
+    /// The services of the MCP servers, kept to start the servers again.
+    mcp_services: HashMap<String, McpClientService>,
+
+    /// Servers that stopped and were started again, waiting to replace their stopped clients.
+    restarted_clients: RestartedClients,
+
+    /// The tasks watching the health of each MCP server. See [supervise].
+    supervisors: HashMap<String, JoinHandle<()>>,
+
     /// A notifier to understand if the initial loading has completed.
     /// This is only used for initial loading and is discarded after.
     notify: Option<Arc<Notify>>,
@@ -626,6 +828,8 @@ impl Clone for ToolManager {
             conversation_id: self.conversation_id.clone(),
             has_new_stuff: self.has_new_stuff.clone(),
             new_tool_specs: self.new_tool_specs.clone(),
+            mcp_resources: self.mcp_resources.clone(),
+            mcp_roots: self.mcp_roots.clone(),
             tn_map: self.tn_map.clone(),
             schema: self.schema.clone(),
             is_interactive: self.is_interactive,
@@ -647,6 +851,10 @@ impl ToolManager {
     ///   function)
     /// - Calling load tools
     pub async fn swap_agent(&mut self, os: &mut Os, output: &mut impl Write, agent: &Agent) -> eyre::Result<()> {
+        // The servers are stopped on purpose, so they must not be started again
+        for (_, supervisor) in self.supervisors.drain() {
+            supervisor.abort();
+        }
         let to_evict = self.clients.drain().collect::<Vec<_>>();
         tokio::spawn(async move {
             for (server_name, initialized_client) in to_evict {
@@ -881,6 +1089,7 @@ impl ToolManager {
             // Note that this name is NO LONGER namespaced with server_name{DELIMITER}tool_name
             "delegate" => Tool::Delegate(serde_json::from_value::<Delegate>(value.args).map_err(map_err)?),
             name => {
+                self.refresh_clients().await;
                 // Note: tn_map also has tools that underwent no transformation. In otherwords, if
                 // it is a valid tool name, we should get a hit.
                 let ToolInfo {
@@ -899,6 +1108,16 @@ impl ToolManager {
                         })
                     },
                 }?;
+                let state = self.mcp_services.get(server_name).map(|s| s.health().state());
+                if let Some(state @ (ServerState::Restarting { .. } | ServerState::Failed { .. })) = state {
+                    return Err(ToolResult {
+                        tool_use_id: value.id,
+                        content: vec![ToolResultContentBlock::Text(format!(
+                            "The MCP server \"{server_name}\" stopped unexpectedly and is {state}"
+                        ))],
+                        status: ToolResultStatus::Error,
+                    });
+                }
                 let Some(client) = self.clients.get_mut(server_name) else {
                     return Err(ToolResult {
                         tool_use_id: value.id,
@@ -927,6 +1146,7 @@ impl ToolManager {
 
     /// Updates tool managers various states with new information
     pub async fn update(&mut self) {
+        self.refresh_clients().await;
         // A hashmap of <tool name, tool spec>
         let mut tool_specs = HashMap::<String, ToolSpec>::new();
         let new_tools = {
@@ -1139,6 +1359,202 @@ impl ToolManager {
     pub async fn pending_clients(&self) -> Vec<String> {
         self.pending_clients.read().await.iter().cloned().collect::<Vec<_>>()
     }
+
+    /// Returns the status of each MCP server.
+    pub fn server_statuses(&self) -> HashMap<String, ServerStatus> {
+        self.mcp_services
+            .iter()
+            .map(|(server_name, service)| (server_name.clone(), service.health().status()))
+            .collect()
+    }
+
+    /// Waits briefly for the servers to be told to stop working on the tool calls the user
+    /// interrupted.
+    pub async fn finish_tool_call_cancellations(&self) {
+        let deadline = tokio::time::Instant::now() + TOOL_CALL_CANCELLATION_TIMEOUT;
+        for client in self.clients.values() {
+            if let InitializedMcpClient::Ready(running_service) = client {
+                running_service.finish_cancellations(deadline).await;
+            }
+        }
+    }
+
+    /// Records that the servers stopped, for `q mcp status` not to show them running once the
+    /// chat session has exited.
+    pub fn mark_servers_stopped(&mut self) {
+        // Supervisors still running could record the servers as restarting afterwards
+        for (_, supervisor) in self.supervisors.drain() {
+            supervisor.abort();
+        }
+        for service in self.mcp_services.values() {
+            service.health().set_state(ServerState::Stopped);
+        }
+    }
+
+    /// Stops an MCP server and starts it again, which lists its tools and prompts anew.
+    pub async fn restart_server(&mut self, os: &Os, server_name: &str) -> eyre::Result<()> {
+        let Some(service) = self.mcp_services.get(server_name).cloned() else {
+            return Err(eyre::eyre!("No MCP server named {server_name} is loaded"));
+        };
+
+        if let Some(supervisor) = self.supervisors.remove(server_name) {
+            supervisor.abort();
+        }
+        self.restarted_clients.lock().await.remove(server_name);
+        if let Some(client) = self.clients.remove(server_name) {
+            let server_name = server_name.to_string();
+            tokio::spawn(async move {
+                let running_service = match client {
+                    InitializedMcpClient::Pending(handle) => match handle.await {
+                        Ok(Ok(running_service)) => running_service,
+                        Ok(Err(_)) | Err(_) => return,
+                    },
+                    InitializedMcpClient::Ready(running_service) => running_service,
+                };
+                let InnerService::Original(client) = running_service.inner_service else {
+                    unreachable!();
+                };
+                match client.cancel().await {
+                    Ok(_) => info!("Server {server_name} stopped to be restarted"),
+                    Err(e) => error!("Server {server_name} has failed to cancel: {e}"),
+                }
+            });
+        }
+
+        // The tools are listed again once the server is running
+        self.evict_server_tools(server_name);
+        self.has_new_stuff.store(true, Ordering::Release);
+        service.health().set_state(ServerState::Starting);
+        if let Some(messenger_builder) = &self.messenger_builder {
+            let _ = messenger_builder
+                .build_with_name(server_name.to_string())
+                .send_init_msg()
+                .await;
+        }
+
+        let client = service.clone().init(os).await?;
+        self.clients.insert(server_name.to_string(), client);
+        let supervisor = tokio::spawn(supervise(
+            service,
+            os.clone(),
+            self.restarted_clients.clone(),
+            self.has_new_stuff.clone(),
+        ));
+        self.supervisors.insert(server_name.to_string(), supervisor);
+        Ok(())
+    }
+
+    /// Replaces the clients of the servers that were started again, and removes the tools of the
+    /// servers that failed.
+    async fn refresh_clients(&mut self) {
+        for (server_name, running_service) in self.restarted_clients.lock().await.drain() {
+            info!("Server {server_name} is running again");
+            self.clients
+                .insert(server_name, InitializedMcpClient::Ready(running_service));
+        }
+
+        let failed = self
+            .mcp_services
+            .iter()
+            .filter(|(_, service)| matches!(service.health().state(), ServerState::Failed { .. }))
+            .map(|(server_name, _)| server_name.clone())
+            .collect::<Vec<_>>();
+        for server_name in failed {
+            if !self.evict_server_tools(&server_name) {
+                continue;
+            }
+            // Also drops the prompts and resources of the server
+            if let Some(messenger_builder) = &self.messenger_builder {
+                messenger_builder.build_with_name(server_name).send_deinit_msg();
+            }
+        }
+    }
+
+    /// Removes the tools of a server. Returns whether it had any.
+    fn evict_server_tools(&mut self, server_name: &str) -> bool {
+        let origin = ToolOrigin::McpServer(server_name.to_string());
+        let count = self.schema.len();
+        self.tn_map.retain(|_, tool_info| tool_info.server_name != server_name);
+        self.schema.retain(|_, spec| spec.tool_origin != origin);
+        self.schema.len() != count
+    }
+
+    /// Returns the resources and resource templates offered by each server.
+    pub async fn list_resources(&self) -> HashMap<String, ServerResources> {
+        self.mcp_resources.lock().await.clone()
+    }
+
+    /// Reads a resource from its server.
+    pub async fn read_resource(&mut self, reference: &ResourceReference) -> eyre::Result<ReadResourceResult> {
+        let client = self
+            .clients
+            .get_mut(&reference.server_name)
+            .ok_or(eyre::eyre!("MCP server {} is not loaded", reference.server_name))?;
+        let running_service = client.get_running_service().await?;
+        let params = ReadResourceRequestParam {
+            uri: reference.uri.clone(),
+        };
+        Ok(running_service.read_resource(params).await?)
+    }
+
+    /// Returns the content of a resource used as context. The first time a resource is used, it
+    /// is read from its server, which is also asked to notify us of updates to it if it can.
+    pub async fn resource_context(&mut self, reference: &ResourceReference) -> eyre::Result<Vec<ResourceContents>> {
+        if let Some(contents) = self
+            .mcp_resources
+            .lock()
+            .await
+            .get(&reference.server_name)
+            .and_then(|resources| resources.contents.get(&reference.uri))
+        {
+            return Ok(contents.clone());
+        }
+
+        let result = self.read_resource(reference).await?;
+        if let Some(client) = self.clients.get_mut(&reference.server_name) {
+            let running_service = client.get_running_service().await?;
+            if running_service.supports_resource_subscriptions() {
+                let params = SubscribeRequestParam {
+                    uri: reference.uri.clone(),
+                };
+                if let Err(e) = running_service.subscribe(params).await {
+                    warn!("Failed to subscribe to updates of {reference}: {e}");
+                }
+            }
+        }
+
+        self.mcp_resources
+            .lock()
+            .await
+            .entry(reference.server_name.clone())
+            .or_default()
+            .contents
+            .insert(reference.uri.clone(), result.contents.clone());
+        Ok(result.contents)
+    }
+
+    /// Updates the roots advertised to MCP servers from the current directory and the context
+    /// paths, and notifies the servers that are loaded if the roots changed.
+    pub async fn update_roots(&mut self, os: &Os, context_paths: &[String]) {
+        let roots = workspace_roots(os, context_paths);
+        {
+            let mut current = self.mcp_roots.lock().await;
+            if *current == roots {
+                return;
+            }
+            *current = roots;
+        }
+
+        for (server_name, client) in &mut self.clients {
+            // Servers that are still loading will ask for the roots once they are initialized
+            let Ok(running_service) = client.get_running_service().await else {
+                continue;
+            };
+            if let Err(e) = running_service.notify_roots_list_changed().await {
+                warn!("Failed to notify {server_name} of the roots change: {e}");
+            }
+        }
+    }
 }
 
 type DisplayTaskJoinHandle = JoinHandle<Result<(), eyre::Report>>;
@@ -1298,6 +1714,7 @@ fn spawn_orchestrator_task(
     telemetry: TelemetryThread,
     loading_status_sender: Option<LoadingStatusSender>,
     new_tool_specs: NewToolSpecs,
+    mcp_resources: McpResources,
     total: usize,
     conv_id: String,
 ) {
@@ -1384,6 +1801,7 @@ fn spawn_orchestrator_task(
             telemetry_clone: &TelemetryThread,
             mut loading_status_sender: Option<&MpscSender<LoadingMsg>>,
             new_tool_specs: &NewToolSpecs,
+            mcp_resources: &McpResources,
             has_new_stuff: &Arc<AtomicBool>,
             load_record: &Arc<Mutex<HashMap<String, Vec<LoadingRecord>>>>,
             notify_weak: &std::sync::Weak<Notify>,
@@ -1667,8 +2085,44 @@ fn spawn_orchestrator_task(
                             .or_insert(vec![record]);
                     },
                 },
-                UpdateEventMessage::ListResourcesResult { .. } => {},
-                UpdateEventMessage::ResourceTemplatesListResult { .. } => {},
+                UpdateEventMessage::ListResourcesResult {
+                    server_name, result, ..
+                } => match result {
+                    Ok(result) => {
+                        mcp_resources.lock().await.entry(server_name).or_default().resources = result.resources;
+                    },
+                    Err(e) => error!("Error fetching resources from server {server_name}: {:?}", e),
+                },
+                UpdateEventMessage::ResourceTemplatesListResult {
+                    server_name, result, ..
+                } => match result {
+                    Ok(result) => {
+                        mcp_resources.lock().await.entry(server_name).or_default().templates =
+                            result.resource_templates;
+                    },
+                    Err(e) => error!("Error fetching resource templates from server {server_name}: {:?}", e),
+                },
+                UpdateEventMessage::ResourceUpdated {
+                    server_name,
+                    uri,
+                    result,
+                } => {
+                    let mut mcp_resources = mcp_resources.lock().await;
+                    let contents = &mut mcp_resources.entry(server_name.clone()).or_default().contents;
+                    match result {
+                        Ok(result) => {
+                            contents.insert(uri, result.contents);
+                        },
+                        Err(e) => {
+                            // Dropping the stale content makes the next request read it again
+                            error!(
+                                "Error reading updated resource {uri} from server {server_name}: {:?}",
+                                e
+                            );
+                            contents.remove(&uri);
+                        },
+                    }
+                },
                 UpdateEventMessage::OauthLink { server_name, link } => {
                     let mut buf_writer = BufWriter::new(&mut *record_temp_buf);
                     let msg = eyre::eyre!(link);
@@ -1703,13 +2157,14 @@ fn spawn_orchestrator_task(
                     loading_servers.insert(server_name, std::time::Instant::now());
                 },
                 UpdateEventMessage::Deinit { server_name, .. } => {
-                    // Only prompts are stored here so we'll just be clearing that
+                    // Only prompts and resources are stored here so we'll just be clearing that
                     // In the future if we are also storing tools, we need to make sure that
                     // the tools are also pruned.
                     for (_prompt_name, bundles) in prompts.iter_mut() {
                         bundles.retain(|bundle| bundle.server_name != server_name);
                     }
                     prompts.retain(|_, bundles| !bundles.is_empty());
+                    mcp_resources.lock().await.remove(&server_name);
                     has_new_stuff.store(true, Ordering::Release);
                 },
             }
@@ -1733,6 +2188,7 @@ fn spawn_orchestrator_task(
                             &telemetry,
                             loading_status_sender.as_ref(),
                             &new_tool_specs,
+                            &mcp_resources,
                             &has_new_stuff,
                             &load_record,
                             &notify_weak,
@@ -2202,4 +2658,81 @@ mod tests {
         );
         assert_eq!(result, Some(expected_map));
     }
+
+    #[test]
+    fn test_resource_reference_parsing() {
+        let reference = ResourceReference::parse("docs/file:///guides/style.md").unwrap();
+        assert_eq!(reference.server_name, "docs");
+        assert_eq!(reference.uri, "file:///guides/style.md");
+        assert_eq!(reference.to_string(), "@docs/file:///guides/style.md");
+
+        // Prompts of a server have no scheme
+        assert_eq!(ResourceReference::parse("docs/summarize"), None);
+        assert_eq!(ResourceReference::parse("/file:///a"), None);
+        assert_eq!(ResourceReference::parse("docs/1x://a"), None);
+        assert_eq!(ResourceReference::parse("docs/db://"), None);
+
+        assert_eq!(
+            ResourceReference::from_agent_resource("mcp://db/postgres://localhost/users"),
+            ResourceReference::parse("db/postgres://localhost/users")
+        );
+        assert_eq!(ResourceReference::from_agent_resource("file://README.md"), None);
+    }
+
+    #[test]
+    fn test_find_resource_references() {
+        let references =
+            ResourceReference::find_all("compare @docs/file:///a.md with @db/table://users, not @docs/summarize");
+        assert_eq!(references, vec![
+            ResourceReference::parse("docs/file:///a.md").unwrap(),
+            ResourceReference::parse("db/table://users").unwrap(),
+        ]);
+        assert!(ResourceReference::find_all("email me@example.com").is_empty());
+    }
+
+    #[test]
+    fn test_resource_contents_to_text() {
+        let contents = vec![
+            ResourceContents::text("hello", "file:///a.txt"),
+            ResourceContents::BlobResourceContents {
+                uri: "file:///a.png".to_string(),
+                mime_type: Some("image/png".to_string()),
+                blob: "aGVsbG8=".to_string(),
+                meta: None,
+            },
+        ];
+        assert_eq!(
+            resource_contents_to_text(&contents),
+            "hello\n<binary content of file:///a.png (image/png, 8 bytes base64 encoded) omitted>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_workspace_roots() {
+        let os = Os::new().await.unwrap();
+        os.env.set_current_dir_for_test(PathBuf::from("/workspace"));
+        os.fs.create_dir_all("/docs").await.unwrap();
+        os.fs.write("/docs/guide.md", "guide").await.unwrap();
+
+        let context_paths = [
+            "README.md",
+            "src/**/*.rs",
+            "/docs/guide.md",
+            "/docs/more/*.md",
+            "~/.amazonq/rules/**/*.md",
+        ]
+        .map(String::from);
+        let uris = workspace_roots(&os, &context_paths)
+            .into_iter()
+            .map(|root| root.uri)
+            .collect::<Vec<_>>();
+        let home = os.env.home().unwrap();
+        assert_eq!(uris, vec![
+            "file:///workspace".to_string(),
+            "file:///docs".to_string(),
+            url::Url::from_file_path(home.join(".amazonq/rules"))
+                .unwrap()
+                .to_string(),
+        ]);
+    }
 }
@@ -7,13 +7,27 @@ use crossterm::{
     style,
 };
 use eyre::Result;
-use rmcp::model::CallToolRequestParam;
+use indicatif::{
+    ProgressBar,
+    ProgressDrawTarget,
+    ProgressStyle,
+};
+use rmcp::ServiceError;
+use rmcp::model::{
+    CallToolRequestParam,
+    CallToolResult,
+    ProgressNotificationParam,
+};
 use schemars::JsonSchema;
 use serde::{
     Deserialize,
     Serialize,
 };
-use tracing::warn;
+use tokio::sync::mpsc;
+use tracing::{
+    info,
+    warn,
+};
 
 use super::InvokeOutput;
 use crate::cli::agent::{
@@ -22,6 +36,16 @@ use crate::cli::agent::{
 };
 use crate::cli::chat::CONTINUATION_LINE;
 use crate::cli::chat::token_counter::TokenCounter;
+use crate::cli::chat::cli::model::ModelInfo;
+use crate::mcp_client::elicitation::{
+    ElicitationRequest,
+    handle_elicitation_request,
+};
+use crate::mcp_client::interaction::UserRequest;
+use crate::mcp_client::sampling::{
+    SamplingRequest,
+    handle_sampling_request,
+};
 use crate::mcp_client::{
     RunningService,
     oauth_util,
@@ -84,6 +108,17 @@ pub struct CustomToolConfig {
     /// Timeout for each mcp request in ms
     #[serde(default = "default_timeout")]
     pub timeout: u64,
+    /// Whether the timeout of a tool call restarts whenever the server reports progress, so that
+    /// long calls that are still making progress aren't ended
+    #[serde(default)]
+    pub reset_timeout_on_progress: bool,
+    /// Whether the server can request completions from the model of the chat session. Each
+    /// request still needs to be approved by the user.
+    #[serde(default)]
+    pub allow_sampling: bool,
+    /// The maximum number of tokens the server can request to generate in each sampling request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling_max_tokens: Option<u32>,
     /// A boolean flag to denote whether or not to load this mcp server
     #[serde(default)]
     pub disabled: bool,
@@ -126,13 +161,35 @@ impl CustomTool {
         format!("@{}{}{}", self.server_name, MCP_SERVER_TOOL_DELIMITER, self.name)
     }
 
-    pub async fn invoke(&self, _os: &Os, _updates: &mut impl Write) -> Result<InvokeOutput> {
+    pub async fn invoke(
+        &self,
+        os: &Os,
+        updates: &mut impl Write,
+        model_info: Option<&ModelInfo>,
+    ) -> Result<InvokeOutput> {
         let params = CallToolRequestParam {
             name: Cow::from(self.name.clone()),
             arguments: self.params.clone(),
         };
 
-        let resp = self.client.call_tool(params.clone()).await?;
+        // Holding the receivers tells the client that sampling and elicitation requests and
+        // progress notifications of the server can be handled, which is only the case while the
+        // tool is running
+        let mut user_requests = self.client.user_requests.lock().await;
+        let mut progress = self.client.progress.lock().await;
+        let resp = match self
+            .call_tool(os, updates, model_info, params.clone(), &mut user_requests, &mut progress)
+            .await
+        {
+            Err(e) if e.is::<ServiceError>() && self.client.refresh_token().await => {
+                info!("Token refreshed");
+                self.call_tool(os, updates, model_info, params, &mut user_requests, &mut progress)
+                    .await?
+            },
+            resp => resp?,
+        };
+        drop(progress);
+        drop(user_requests);
 
         if resp.is_error.is_none_or(|v| !v) {
             Ok(InvokeOutput {
@@ -146,6 +203,69 @@ impl CustomTool {
         }
     }
 
+    /// Calls the tool, handling the requests and showing the progress of the server until it
+    /// responds. The call times out if the server takes longer than its timeout to respond, or to
+    /// report progress if the timeout resets on progress.
+    async fn call_tool(
+        &self,
+        os: &Os,
+        updates: &mut impl Write,
+        model_info: Option<&ModelInfo>,
+        params: CallToolRequestParam,
+        user_requests: &mut mpsc::Receiver<UserRequest>,
+        progress: &mut mpsc::Receiver<ProgressNotificationParam>,
+    ) -> Result<CallToolResult> {
+        let timeout = self.client.timeout;
+        let deadline = tokio::time::sleep(timeout);
+        tokio::pin!(deadline);
+
+        let mut call = self.client.start_tool_call(params).await?;
+        let mut progress_display = ProgressDisplay::default();
+        loop {
+            tokio::select! {
+                resp = call.result() => {
+                    progress_display.clear();
+                    return Ok(resp?);
+                },
+                () = &mut deadline => {
+                    progress_display.clear();
+                    call.cancel("The tool call timed out").await;
+                    return Err(eyre::eyre!(
+                        "{} did not respond within {} seconds",
+                        self.namespaced_tool_name(),
+                        timeout.as_secs_f64()
+                    ));
+                },
+                Some(params) = progress.recv() => {
+                    if params.progress_token != call.progress_token {
+                        continue;
+                    }
+                    if self.client.reset_timeout_on_progress {
+                        deadline.as_mut().reset(tokio::time::Instant::now() + timeout);
+                    }
+                    progress_display.update(&params);
+                },
+                Some(request) = user_requests.recv() => {
+                    // The server waits for the user, which shouldn't count against its timeout
+                    progress_display.clear();
+                    let started = tokio::time::Instant::now();
+                    match request {
+                        UserRequest::Sampling(SamplingRequest { params, responder }) => {
+                            let result = handle_sampling_request(os, &self.server_name, params, model_info, updates).await;
+                            let _ = responder.send(result);
+                        },
+                        UserRequest::Elicitation(ElicitationRequest { params, responder }) => {
+                            let result = handle_elicitation_request(&self.server_name, params, updates).await;
+                            let _ = responder.send(result);
+                        },
+                    }
+                    let new_deadline = deadline.deadline() + started.elapsed();
+                    deadline.as_mut().reset(new_deadline);
+                },
+            }
+        }
+    }
+
     pub fn queue_description(&self, output: &mut impl Write) -> Result<()> {
         queue!(
             output,
@@ -196,3 +316,111 @@ impl CustomTool {
         }
     }
 }
+
+/// Shows the progress notifications of a tool call as a progress bar, or as a spinner if the
+/// server doesn't know the total.
+#[derive(Default)]
+struct ProgressDisplay {
+    bar: Option<ProgressBar>,
+}
+
+impl ProgressDisplay {
+    /// Resolution of the progress bar, since progress is reported as a fraction.
+    const LENGTH: u64 = 1000;
+
+    fn update(&mut self, params: &ProgressNotificationParam) {
+        let bar = self.bar.get_or_insert_with(|| {
+            let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr());
+            bar.enable_steady_tick(std::time::Duration::from_millis(100));
+            bar
+        });
+
+        match progress_position(params) {
+            Some(position) => {
+                if bar.length() != Some(Self::LENGTH) {
+                    bar.set_length(Self::LENGTH);
+                    bar.set_style(
+                        ProgressStyle::with_template("{spinner:.green} [{bar:30.green/dim}] {percent:>3}% {msg}")
+                            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+                    );
+                }
+                bar.set_position(position);
+            },
+            None => {
+                if bar.length().is_some() {
+                    bar.unset_length();
+                    bar.set_style(
+                        ProgressStyle::with_template("{spinner:.green} {msg}")
+                            .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+                    );
+                }
+            },
+        }
+        bar.set_message(progress_message(params));
+    }
+
+    /// Removes the progress bar, e.g. before asking the user something. It's shown again on the
+    /// next notification.
+    fn clear(&mut self) {
+        if let Some(bar) = self.bar.take() {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+impl Drop for ProgressDisplay {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// The position of the progress bar, out of [ProgressDisplay::LENGTH], if the total is known.
+fn progress_position(params: &ProgressNotificationParam) -> Option<u64> {
+    let total = params.total.filter(|total| *total > 0.0)?;
+    let fraction = (params.progress / total).clamp(0.0, 1.0);
+    Some((fraction * ProgressDisplay::LENGTH as f64).round() as u64)
+}
+
+fn progress_message(params: &ProgressNotificationParam) -> String {
+    let message = params.message.as_deref().unwrap_or("Working...");
+    match params.total {
+        Some(total) => format!("{message} ({}/{})", params.progress, total),
+        None => format!("{message} ({})", params.progress),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::model::{
+        NumberOrString,
+        ProgressToken,
+    };
+
+    use super::*;
+
+    fn progress(progress: f64, total: Option<f64>, message: Option<&str>) -> ProgressNotificationParam {
+        ProgressNotificationParam {
+            progress_token: ProgressToken(NumberOrString::Number(1)),
+            progress,
+            total,
+            message: message.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_progress_position() {
+        assert_eq!(progress_position(&progress(5.0, Some(20.0), None)), Some(250));
+        assert_eq!(progress_position(&progress(30.0, Some(20.0), None)), Some(1000));
+        assert_eq!(progress_position(&progress(5.0, Some(0.0), None)), None);
+        assert_eq!(progress_position(&progress(5.0, None, None)), None);
+    }
+
+    #[test]
+    fn test_progress_message() {
+        assert_eq!(
+            progress_message(&progress(3.0, Some(10.0), Some("Indexing files"))),
+            "Indexing files (3/10)"
+        );
+        assert_eq!(progress_message(&progress(2.5, None, None)), "Working... (2.5)");
+    }
+}
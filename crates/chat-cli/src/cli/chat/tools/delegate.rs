@@ -24,6 +24,9 @@ use strum::{
 };
 
 use crate::cli::agent::Agents;
+use crate::cli::agent::hook::HookTrigger;
+use crate::cli::chat::cli::hooks::EventInput;
+use crate::cli::chat::context::ContextManager;
 use crate::cli::chat::tools::{
     InvokeOutput,
     OutputKind,
@@ -83,7 +86,13 @@ impl Delegate {
         ExperimentManager::is_enabled(os, ExperimentName::Delegate)
     }
 
-    pub async fn invoke(&self, os: &Os, _output: &mut impl Write, agents: &Agents) -> Result<InvokeOutput> {
+    pub async fn invoke(
+        &self,
+        os: &Os,
+        _output: &mut impl Write,
+        agents: &Agents,
+        context_manager: Option<&ContextManager>,
+    ) -> Result<InvokeOutput> {
         if !Self::is_enabled(os) {
             return Ok(InvokeOutput {
                 output: OutputKind::Text(
@@ -101,7 +110,7 @@ impl Delegate {
 
                 let agent_name = self.agent.as_deref().unwrap_or(DEFAULT_AGENT_NAME);
 
-                launch_agent(os, agent_name, agents, task).await?
+                launch_agent(os, agent_name, agents, task, context_manager).await?
             },
             Operation::Status => match &self.agent {
                 Some(agent_name) => status_agent(os, agent_name).await?,
@@ -142,7 +151,15 @@ impl Delegate {
     }
 }
 
-pub async fn launch_agent(os: &Os, agent: &str, agents: &Agents, task: &str) -> Result<String> {
+/// Launches `agent` on `task` in the background. The `subagentStop` hooks of `context_manager`
+/// run once it completes.
+pub async fn launch_agent(
+    os: &Os,
+    agent: &str,
+    agents: &Agents,
+    task: &str,
+    context_manager: Option<&ContextManager>,
+) -> Result<String> {
     validate_agent_availability(os, agent).await?;
 
     // Check if agent is already running
@@ -163,7 +180,10 @@ pub async fn launch_agent(os: &Os, agent: &str, agents: &Agents, task: &str) ->
         request_user_approval(agent, agents, task).await?;
     }
 
-    spawn_agent_process(os, agent, task).await?;
+    let hooks = context_manager
+        .filter(|cm| cm.has_hooks(HookTrigger::SubagentStop))
+        .cloned();
+    spawn_agent_process(os, agent, task, hooks).await?;
 
     Ok(format_launch_success(agent, task))
 }
@@ -334,7 +354,12 @@ impl From<&Agent> for AgentConfig {
     }
 }
 
-pub async fn spawn_agent_process(os: &Os, agent: &str, task: &str) -> Result<AgentExecution> {
+pub async fn spawn_agent_process(
+    os: &Os,
+    agent: &str,
+    task: &str,
+    hooks: Option<ContextManager>,
+) -> Result<AgentExecution> {
     let now = Utc::now();
 
     // Run Q chat with specific agent in background, non-interactive
@@ -374,7 +399,7 @@ pub async fn spawn_agent_process(os: &Os, agent: &str, task: &str) -> Result<Age
     save_agent_execution(os, &execution).await?;
 
     // Start monitoring with the actual child process
-    tokio::spawn(monitor_child_process(child, execution.clone(), os.clone()));
+    tokio::spawn(monitor_child_process(child, execution.clone(), os.clone(), hooks));
 
     Ok(execution)
 }
@@ -415,7 +440,12 @@ async fn generate_summary(task: &str, output: &str) -> Result<String> {
     }
 }
 
-async fn monitor_child_process(child: tokio::process::Child, mut execution: AgentExecution, os: Os) {
+async fn monitor_child_process(
+    child: tokio::process::Child,
+    mut execution: AgentExecution,
+    os: Os,
+    hooks: Option<ContextManager>,
+) {
     match child.wait_with_output().await {
         Ok(output) => {
             execution.status = if output.status.success() {
@@ -466,6 +496,35 @@ async fn monitor_child_process(child: tokio::process::Child, mut execution: Agen
             }
         },
     }
+
+    if let Some(mut cm) = hooks {
+        run_subagent_stop_hooks(&mut cm, &os, &execution).await;
+    }
+}
+
+/// Runs the `subagentStop` hooks for a completed execution. They run in the background while the
+/// user may be typing, so failures are logged rather than shown.
+async fn run_subagent_stop_hooks(cm: &mut ContextManager, os: &Os, execution: &AgentExecution) {
+    let event_input = EventInput::from_iter([
+        ("agent_name".to_string(), serde_json::json!(execution.agent)),
+        ("task".to_string(), serde_json::json!(execution.task)),
+        ("status".to_string(), serde_json::json!(execution.status)),
+        ("exit_code".to_string(), serde_json::json!(execution.exit_code)),
+        ("summary".to_string(), serde_json::json!(execution.summary)),
+    ]);
+    match cm
+        .run_event_hooks(HookTrigger::SubagentStop, &mut std::io::sink(), os, event_input)
+        .await
+    {
+        Ok(results) => {
+            for ((_, hook), (exit_code, output)) in results {
+                if exit_code != 0 {
+                    tracing::warn!(command = hook.command, exit_code, output, "A subagentStop hook failed");
+                }
+            }
+        },
+        Err(err) => tracing::warn!(?err, "Failed to run the subagentStop hooks"),
+    }
 }
 
 pub async fn status_agent(os: &Os, agent: &str) -> Result<String> {
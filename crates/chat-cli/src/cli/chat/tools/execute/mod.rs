@@ -4,7 +4,10 @@ use crossterm::queue;
 use crossterm::style::{
     self,
 };
-use eyre::Result;
+use eyre::{
+    Result,
+    WrapErr,
+};
 use regex::Regex;
 use serde::Deserialize;
 use tracing::error;
@@ -15,6 +18,7 @@ use crate::cli::agent::{
     PermissionEvalResult,
 };
 use crate::cli::chat::sanitize_unicode_tags;
+use crate::cli::chat::tools::fs_write::FsWrite;
 use crate::cli::chat::tools::{
     InvokeOutput,
     MAX_TOOL_RESPONSE_SIZE,
@@ -36,6 +40,19 @@ mod unix;
 #[cfg(not(windows))]
 pub use unix::*;
 
+mod sandbox;
+use sandbox::{
+    Sandbox,
+    SandboxSettings,
+};
+
+mod shell;
+use shell::{
+    Command,
+    Script,
+    SimpleCommand,
+};
+
 // Common readonly commands that are safe to execute without user confirmation
 pub const READONLY_COMMANDS: &[&str] = &[
     "ls", "cat", "echo", "pwd", "which", "head", "tail", "find", "grep", "dir", "type",
@@ -49,6 +66,57 @@ pub struct ExecuteCommand {
 
 impl ExecuteCommand {
     pub fn requires_acceptance(&self, allowed_commands: Option<&Vec<String>>, allow_read_only: bool) -> bool {
+        self.requires_acceptance_with_writable(allowed_commands, allow_read_only, &[])
+    }
+
+    /// Like [Self::requires_acceptance], but redirections may also write to `writable_paths`
+    /// without approval.
+    fn requires_acceptance_with_writable(
+        &self,
+        allowed_commands: Option<&Vec<String>>,
+        allow_read_only: bool,
+        writable_paths: &[String],
+    ) -> bool {
+        // Commands run through cmd.exe on Windows, which doesn't follow the quoting rules of bash.
+        if cfg!(windows) {
+            return self.requires_acceptance_cmd(allowed_commands, allow_read_only);
+        }
+
+        // Carriage returns can hide part of the command in the approval prompt.
+        if self.command.contains('\r') {
+            return true;
+        }
+
+        // Commands that can't be parsed, e.g. `if` or `for` statements, always need approval.
+        let Ok(script) = shell::parse(&self.command) else {
+            return true;
+        };
+
+        let allowed_regexes = allowed_commands
+            .into_iter()
+            .flatten()
+            .filter_map(|cmd| Regex::new(&format!(r"\A{}\z", cmd)).ok())
+            .collect::<Vec<_>>();
+
+        // Every command that would run, including those in pipelines, lists and substitutions,
+        // must be allowed on its own.
+        script.commands().into_iter().any(|command| {
+            let writes_elsewhere = command.redirects().iter().any(|redirect| {
+                redirect.is_write() && !(redirect.target.is_static() && writable_paths.contains(&redirect.target.value))
+            });
+            writes_elsewhere
+                || match command {
+                    Command::Simple(simple) => {
+                        simple_command_requires_acceptance(simple, &allowed_regexes, allow_read_only)
+                    },
+                    Command::Subshell { .. } | Command::Group { .. } => false,
+                }
+        })
+    }
+
+    /// Like [Self::requires_acceptance] for commands run through cmd.exe, which are never parsed:
+    /// any command containing a shell metacharacter needs approval.
+    fn requires_acceptance_cmd(&self, allowed_commands: Option<&Vec<String>>, allow_read_only: bool) -> bool {
         // Always require acceptance for multi-line commands.
         if self.command.contains("\n") || self.command.contains("\r") {
             return true;
@@ -147,8 +215,65 @@ impl ExecuteCommand {
         false
     }
 
-    pub async fn invoke(&self, os: &Os, output: &mut impl Write) -> Result<InvokeOutput> {
-        let output = run_command(os, &self.command, MAX_TOOL_RESPONSE_SIZE / 3, Some(output)).await?;
+    /// Returns a reason for each deny rule matched by the command, naming the subcommand that
+    /// matched it.
+    fn denied_reasons(&self, script: Option<&Script>, denied_commands: &[String]) -> Vec<String> {
+        let subcommands = script
+            .map(|script| script.commands())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|command| match command {
+                Command::Simple(simple) => Some(simple),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        denied_commands
+            .iter()
+            .filter_map(|dc| match Regex::new(&format!(r"\A{dc}\z")) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    error!(
+                        "Invalid regex pattern '{}' in deniedCommands: {:?}. Treating as deny-all for security.",
+                        dc, e
+                    );
+                    // Invalid regex - treat as "deny all" for security
+                    Regex::new(r"\A.*\z").ok()
+                },
+            })
+            .flat_map(|regex| {
+                let mut reasons = subcommands
+                    .iter()
+                    .filter(|simple| command_matches(simple, &regex))
+                    .map(|simple| format!("{regex} matched by `{}`", simple.source))
+                    .collect::<Vec<_>>();
+                // Rules may also be written for the command as a whole, e.g. `.* && rm .*`
+                if reasons.is_empty() && regex.is_match(&self.command) {
+                    reasons.push(format!("{regex} matched by `{}`", self.command));
+                }
+                reasons
+            })
+            .collect()
+    }
+
+    pub async fn invoke(&self, os: &Os, output: &mut impl Write, agent: Option<&Agent>) -> Result<InvokeOutput> {
+        let sandbox = match agent {
+            Some(agent) => SandboxSettings::from_agent(agent).wrap_err("Invalid sandbox settings")?,
+            None => None,
+        };
+        let sandbox = sandbox
+            .map(|settings| Sandbox::new(os, &settings))
+            .transpose()
+            .wrap_err("The agent runs commands in a sandbox, but the sandbox is not available")?;
+
+        let output = run_command(
+            os,
+            &self.command,
+            MAX_TOOL_RESPONSE_SIZE / 3,
+            sandbox.as_ref(),
+            Some(output),
+        )
+        .await?;
         let clean_stdout = sanitize_unicode_tags(&output.stdout);
         let clean_stderr = sanitize_unicode_tags(&output.stderr);
 
@@ -194,7 +319,7 @@ impl ExecuteCommand {
         Ok(())
     }
 
-    pub fn eval_perm(&self, _os: &Os, agent: &Agent) -> PermissionEvalResult {
+    pub fn eval_perm(&self, os: &Os, agent: &Agent) -> PermissionEvalResult {
         #[derive(Debug, Deserialize)]
         #[serde(rename_all = "camelCase")]
         struct Settings {
@@ -215,6 +340,19 @@ impl ExecuteCommand {
         let Self { command, .. } = self;
         let tool_name = if cfg!(windows) { "execute_cmd" } else { "execute_bash" };
         let is_in_allowlist = is_tool_in_allowlist(&agent.allowed_tools, tool_name, None);
+        // Only commands run through bash are parsed, see [Self::requires_acceptance_with_writable]
+        let script = if cfg!(windows) {
+            None
+        } else {
+            shell::parse(command).ok()
+        };
+
+        // Redirections are held to the same rules as `fs_write`
+        let (denied_writes, writable_paths) = eval_redirects(os, agent, script.as_ref());
+        if !denied_writes.is_empty() {
+            return PermissionEvalResult::Deny(denied_writes);
+        }
+
         match agent.tools_settings.get(tool_name) {
             Some(settings) => {
                 let Settings {
@@ -230,27 +368,18 @@ impl ExecuteCommand {
                     },
                 };
 
-                let denied_match_set = denied_commands
-                    .iter()
-                    .filter_map(|dc| match Regex::new(&format!(r"\A{dc}\z")) {
-                        Ok(regex) => Some(regex),
-                        Err(e) => {
-                            error!("Invalid regex pattern '{}' in deniedCommands: {:?}. Treating as deny-all for security.", dc, e);
-                            // Invalid regex - treat as "deny all" for security
-                            Regex::new(r"\A.*\z").ok()
-                        }
-                    })
-                    .filter(|r| r.is_match(command))
-                    .map(|r| r.to_string())
-                    .collect::<Vec<_>>();
-
+                let denied_match_set = self.denied_reasons(script.as_ref(), &denied_commands);
                 if !denied_match_set.is_empty() {
                     return PermissionEvalResult::Deny(denied_match_set);
                 }
 
                 if is_in_allowlist {
                     PermissionEvalResult::Allow
-                } else if self.requires_acceptance(Some(&allowed_commands), auto_allow_readonly) {
+                } else if self.requires_acceptance_with_writable(
+                    Some(&allowed_commands),
+                    auto_allow_readonly,
+                    &writable_paths,
+                ) {
                     if deny_by_default {
                         PermissionEvalResult::Deny(vec!["not in allowed commands list".to_string()])
                     } else {
@@ -280,6 +409,100 @@ pub struct CommandResult {
     pub stderr: String,
 }
 
+/// Checks the files written to by redirections against the `fs_write` path settings.
+///
+/// Returns a reason for each denied file, and the files that may be written to without
+/// approval.
+fn eval_redirects(os: &Os, agent: &Agent, script: Option<&Script>) -> (Vec<String>, Vec<String>) {
+    let mut denied = Vec::new();
+    let mut writable = Vec::new();
+    for command in script.map(|script| script.commands()).unwrap_or_default() {
+        for redirect in command.redirects() {
+            if !redirect.is_write() || !redirect.target.is_static() {
+                continue;
+            }
+            let path = &redirect.target.value;
+            let write = FsWrite::Append {
+                path: path.clone(),
+                new_str: String::new(),
+                summary: None,
+            };
+            match write.eval_perm(os, agent) {
+                PermissionEvalResult::Allow => writable.push(path.clone()),
+                PermissionEvalResult::Ask => {},
+                PermissionEvalResult::Deny(rules) => denied.extend(
+                    rules
+                        .into_iter()
+                        .map(|rule| format!("fs_write path {rule} matched by `{}`", command.source())),
+                ),
+            }
+        }
+    }
+    (denied, writable)
+}
+
+/// Whether a simple command needs approval, given the `allowedCommands` regexes.
+fn simple_command_requires_acceptance(
+    simple: &SimpleCommand,
+    allowed_regexes: &[Regex],
+    allow_read_only: bool,
+) -> bool {
+    // Assignments can change how the command runs, e.g. `IFS=` or `LD_PRELOAD=`.
+    if !simple.assignments.is_empty() {
+        return true;
+    }
+    // The value of expansions is only known when the command runs.
+    let Some(argv) = simple.argv() else {
+        return true;
+    };
+    let Some(cmd) = argv.first() else {
+        return false;
+    };
+
+    // Special casing for `find` so that we support most cases while safeguarding
+    // against unwanted mutations
+    if *cmd == "find"
+        && argv.iter().any(|arg| {
+            arg.contains("-exec") // includes -execdir
+                || arg.contains("-delete")
+                || arg.contains("-ok") // includes -okdir
+                || arg.contains("-fprint") // includes -fprint0 and -fprintf
+                || arg.contains("-fls")
+        })
+    {
+        return true;
+    }
+    // Special casing for `grep`. -P flag for perl regexp has RCE issues, apparently
+    // should not be supported within grep but is flagged as a possibility since this is perl
+    // regexp.
+    if *cmd == "grep"
+        && argv
+            .iter()
+            .any(|arg| arg.contains("-P") || arg.contains("--perl-regexp"))
+    {
+        return true;
+    }
+
+    if allowed_regexes.iter().any(|regex| command_matches(simple, regex)) {
+        return false;
+    }
+
+    !allow_read_only || !READONLY_COMMANDS.contains(cmd)
+}
+
+/// Whether a rule matches a simple command, either as written or as its arguments joined by
+/// spaces. Arguments with expansions are matched as written.
+fn command_matches(simple: &SimpleCommand, regex: &Regex) -> bool {
+    let argv = simple
+        .words
+        .iter()
+        .map(|word| if word.is_static() { &word.value } else { &word.raw })
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ");
+    regex.is_match(&simple.source) || regex.is_match(&argv)
+}
+
 // Helper function to format command output with truncation
 pub fn format_output(output: &str, max_size: usize) -> String {
     format!(
@@ -357,6 +580,19 @@ mod tests {
             // `grep` command arguments
             ("echo 'test data' | grep -P '(?{system(\"date\")})'", true),
             ("echo 'test data' | grep --perl-regexp '(?{system(\"date\")})'", true),
+            // Commands are checked after parsing, except on Windows where cmd.exe runs them
+            ("ls 2>/dev/null", cfg!(windows)),
+            ("ls -la 2>&1 | head", cfg!(windows)),
+            ("cat <<EOF\nhello world\nEOF", cfg!(windows)),
+            ("cat <<EOF\n$(rm myimportantfile)\nEOF", true),
+            ("cat <<'EOF'\n$(rm myimportantfile)\nEOF", cfg!(windows)),
+            ("ls; pwd\necho done", cfg!(windows)),
+            ("(ls && pwd) | grep src", cfg!(windows)),
+            ("(ls; rm myimportantfile)", true),
+            ("(ls) > myimportantfile", true),
+            ("echo \"$(cat file.txt)\"", true),
+            ("if true; then ls; fi", true),
+            ("ls 'unterminated", true),
         ];
         for (cmd, expected) in cmds {
             let tool = serde_json::from_value::<ExecuteCommand>(serde_json::json!({
@@ -429,19 +665,26 @@ mod tests {
             ("command subcommand a=0123456789 b=0123456789", false),
             ("command subcommand a=0123456789 b=012345678", true),
             ("command subcommand alternate a=0123456789 b=0123456789", true),
+            // Quoted text is a plain argument, except for cmd.exe which ignores single quotes
+            ("echo 'test<(data'", cfg!(windows)),
+            ("echo 'test$(data)'", cfg!(windows)),
+            ("echo 'test`data`'", cfg!(windows)),
+            ("echo 'test\nrm file'", cfg!(windows)),
+            ("git 'status'", false),
+            ("echo 'a & del x'", cfg!(windows)),
             // dangerous patterns
-            ("echo 'test<(data'", true),
-            ("echo 'test$(data)'", true),
-            ("echo 'test`data`'", true),
             ("echo 'test' > output.txt", true),
             ("echo 'test data' && touch main.py", true),
             ("echo 'test' || rm file", true),
             ("echo 'test' & background", true),
             ("echo 'test data'; touch main.py", true),
             ("echo $HOME", true),
-            ("echo 'test\nrm file'", true),
             ("echo 'test\rrm file'", true),
             ("IFS=/ malicious", true),
+            ("IFS=/ git status", true),
+            ("git status && echo done", cfg!(windows)),
+            ("git status && git push", true),
+            ("root | command subcommand a=0123456789 b=0123456789", cfg!(windows)),
         ];
         for (cmd, expected) in cmds {
             let tool = serde_json::from_value::<ExecuteCommand>(serde_json::json!({
@@ -484,7 +727,9 @@ mod tests {
         .unwrap();
 
         let res = tool_one.eval_perm(&os, &agent);
-        assert!(matches!(res, PermissionEvalResult::Deny(ref rules) if rules.contains(&"\\Agit .*\\z".to_string())));
+        assert!(
+            matches!(res, PermissionEvalResult::Deny(ref rules) if rules.contains(&"\\Agit .*\\z matched by `git status`".to_string()))
+        );
 
         let tool_two = serde_json::from_value::<ExecuteCommand>(serde_json::json!({
             "command": "this_is_not_a_read_only_command",
@@ -522,7 +767,116 @@ mod tests {
 
         // Denied list should remain denied
         let res = tool_one.eval_perm(&os, &agent);
-        assert!(matches!(res, PermissionEvalResult::Deny(ref rules) if rules.contains(&"\\Agit .*\\z".to_string())));
+        assert!(
+            matches!(res, PermissionEvalResult::Deny(ref rules) if rules.contains(&"\\Agit .*\\z matched by `git status`".to_string()))
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_eval_perm_denied_subcommands() {
+        let tool_name = if cfg!(windows) { "execute_cmd" } else { "execute_bash" };
+        let agent = Agent {
+            name: "test_agent".to_string(),
+            tools_settings: {
+                let mut map = HashMap::<ToolSettingTarget, serde_json::Value>::new();
+                map.insert(
+                    ToolSettingTarget(tool_name.to_string()),
+                    serde_json::json!({
+                        "allowedCommands": ["echo .*"],
+                        "deniedCommands": ["git push.*", "rm .*"]
+                    }),
+                );
+                map
+            },
+            ..Default::default()
+        };
+        let os = Os::new().await.unwrap();
+
+        for (command, expected) in [
+            ("echo hi && git push origin main", vec![
+                "\\Agit push.*\\z matched by `git push origin main`",
+            ]),
+            ("echo $(rm -rf build) | git push", vec![
+                "\\Agit push.*\\z matched by `git push`",
+                "\\Arm .*\\z matched by `rm -rf build`",
+            ]),
+            ("git 'push' --force 2>/dev/null", vec![
+                "\\Agit push.*\\z matched by `git 'push' --force 2>/dev/null`",
+            ]),
+        ] {
+            let tool = serde_json::from_value::<ExecuteCommand>(serde_json::json!({ "command": command })).unwrap();
+            let expected = expected.into_iter().map(String::from).collect::<Vec<_>>();
+            assert_eq!(tool.eval_perm(&os, &agent), PermissionEvalResult::Deny(expected));
+        }
+
+        let tool =
+            serde_json::from_value::<ExecuteCommand>(serde_json::json!({ "command": "echo 'git push'" })).unwrap();
+        assert_eq!(tool.eval_perm(&os, &agent), PermissionEvalResult::Allow);
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_eval_perm_redirects() {
+        let tool_name = if cfg!(windows) { "execute_cmd" } else { "execute_bash" };
+        let mut agent = Agent {
+            name: "test_agent".to_string(),
+            tools_settings: {
+                let mut map = HashMap::<ToolSettingTarget, serde_json::Value>::new();
+                map.insert(
+                    ToolSettingTarget(tool_name.to_string()),
+                    serde_json::json!({
+                        "autoAllowReadonly": true
+                    }),
+                );
+                map.insert(
+                    ToolSettingTarget("fs_write".to_string()),
+                    serde_json::json!({
+                        "allowedPaths": ["/allowed"],
+                        "deniedPaths": ["/denied"]
+                    }),
+                );
+                map
+            },
+            ..Default::default()
+        };
+        let os = Os::new().await.unwrap();
+        let eval = |command: &str, agent: &Agent| {
+            serde_json::from_value::<ExecuteCommand>(serde_json::json!({ "command": command }))
+                .unwrap()
+                .eval_perm(&os, agent)
+        };
+
+        assert_eq!(eval("echo hi > /allowed/out.txt", &agent), PermissionEvalResult::Allow);
+        assert_eq!(
+            eval("ls >> /allowed/a 2> /allowed/b", &agent),
+            PermissionEvalResult::Allow
+        );
+        assert_eq!(eval("echo hi > /other/out.txt", &agent), PermissionEvalResult::Ask);
+        assert_eq!(eval("echo hi > $OUT", &agent), PermissionEvalResult::Ask);
+        // `~root` and `~+` aren't relative paths, even where the working directory is writable
+        let mut cwd_agent = agent.clone();
+        cwd_agent.tools_settings.insert(
+            ToolSettingTarget("fs_write".to_string()),
+            serde_json::json!({ "allowedPaths": [os.env.current_dir().unwrap()] }),
+        );
+        assert_eq!(eval("echo hi > out.txt", &cwd_agent), PermissionEvalResult::Allow);
+        assert_eq!(eval("echo hi > ~root/x", &cwd_agent), PermissionEvalResult::Ask);
+        assert_eq!(eval("echo hi > ~+/x", &cwd_agent), PermissionEvalResult::Ask);
+        assert_eq!(
+            eval("ls && (echo hi > /denied/out.txt)", &agent),
+            PermissionEvalResult::Deny(vec![
+                "fs_write path /denied matched by `echo hi > /denied/out.txt`".to_string()
+            ])
+        );
+
+        // Writes are denied even if execute_bash is trusted
+        agent.allowed_tools.insert(tool_name.to_string());
+        assert!(matches!(
+            eval("rm -rf / > /denied/log", &agent),
+            PermissionEvalResult::Deny(_)
+        ));
+        assert_eq!(eval("rm -rf / > /other/log", &agent), PermissionEvalResult::Allow);
     }
 
     #[tokio::test]
@@ -624,7 +978,7 @@ mod tests {
         let res = denied_readonly_cmd.eval_perm(&os, &agent);
         // Should deny even read-only commands if they're in denied list
         assert!(
-            matches!(res, PermissionEvalResult::Deny(ref commands) if commands.contains(&"\\Als .*\\z".to_string()))
+            matches!(res, PermissionEvalResult::Deny(ref commands) if commands.contains(&"\\Als .*\\z matched by `ls -la`".to_string()))
         );
 
         // Test different read-only command not in denied list
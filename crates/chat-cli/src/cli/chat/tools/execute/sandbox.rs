@@ -0,0 +1,533 @@
+//! Sandbox for commands run by `execute_bash`, configured with
+//! `toolsSettings.execute_bash.sandbox` in the agent config.
+//!
+//! On Linux, sandboxed commands:
+//! - can only write to the configured writable paths (Landlock)
+//! - can't open network sockets unless network access is enabled (seccomp, and a separate network
+//!   namespace where user namespaces are available)
+//! - can't use syscalls that escape or inspect the sandbox, like `ptrace` and `mount` (seccomp)
+//! - only see the allowed environment variables
+//!
+//! Sandboxing is not available on other platforms, and commands fail to run rather than running
+//! unsandboxed.
+
+use std::collections::HashMap;
+
+use eyre::Result;
+use serde::Deserialize;
+
+use crate::cli::agent::Agent;
+
+/// Environment variables that sandboxed commands can always read.
+const DEFAULT_ENV_VARS: &[&str] = &[
+    "PATH", "HOME", "USER", "LOGNAME", "SHELL", "TERM", "LANG", "LC_ALL", "LC_CTYPE", "TZ", "TMPDIR",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxSettings {
+    /// Paths commands can write to. Relative paths are resolved against the current directory.
+    #[serde(default = "default_writable_paths")]
+    pub writable_paths: Vec<String>,
+    /// Whether commands can access the network.
+    #[serde(default)]
+    pub network: bool,
+    /// Environment variables passed to commands, in addition to [DEFAULT_ENV_VARS].
+    #[serde(default)]
+    pub allowed_env_vars: Vec<String>,
+}
+
+/// The current directory, and the temporary directory that many commands write to.
+fn default_writable_paths() -> Vec<String> {
+    vec![".".to_string(), std::env::temp_dir().to_string_lossy().to_string()]
+}
+
+impl SandboxSettings {
+    /// Returns the sandbox settings of the agent, if it runs commands in a sandbox.
+    pub fn from_agent(agent: &Agent) -> Result<Option<Self>> {
+        let tool_name = if cfg!(windows) { "execute_cmd" } else { "execute_bash" };
+        let Some(sandbox) = agent
+            .tools_settings
+            .get(tool_name)
+            .and_then(|settings| settings.get("sandbox"))
+        else {
+            return Ok(None);
+        };
+        Ok(Some(serde_json::from_value(sandbox.clone())?))
+    }
+
+    /// Returns the environment variables of `env_vars` that are passed to sandboxed commands.
+    fn filter_env(&self, env_vars: HashMap<String, String>, always_allowed: &[&str]) -> HashMap<String, String> {
+        env_vars
+            .into_iter()
+            .filter(|(name, _)| {
+                DEFAULT_ENV_VARS.contains(&name.as_str())
+                    || always_allowed.contains(&name.as_str())
+                    || self.allowed_env_vars.contains(name)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub use linux::Sandbox;
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod linux {
+    use std::collections::HashMap;
+    use std::ffi::CString;
+    use std::io;
+    use std::os::fd::{
+        AsRawFd,
+        FromRawFd,
+        OwnedFd,
+        RawFd,
+    };
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    use eyre::{
+        Result,
+        bail,
+    };
+    use tracing::{
+        debug,
+        warn,
+    };
+
+    use super::SandboxSettings;
+    use crate::os::Os;
+    use crate::util::paths;
+
+    const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1 << 0;
+    const LANDLOCK_RULE_PATH_BENEATH: u32 = 1;
+
+    const LANDLOCK_ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const LANDLOCK_ACCESS_FS_REMOVE_DIR: u64 = 1 << 4;
+    const LANDLOCK_ACCESS_FS_REMOVE_FILE: u64 = 1 << 5;
+    const LANDLOCK_ACCESS_FS_MAKE_CHAR: u64 = 1 << 6;
+    const LANDLOCK_ACCESS_FS_MAKE_DIR: u64 = 1 << 7;
+    const LANDLOCK_ACCESS_FS_MAKE_REG: u64 = 1 << 8;
+    const LANDLOCK_ACCESS_FS_MAKE_SOCK: u64 = 1 << 9;
+    const LANDLOCK_ACCESS_FS_MAKE_FIFO: u64 = 1 << 10;
+    const LANDLOCK_ACCESS_FS_MAKE_BLOCK: u64 = 1 << 11;
+    const LANDLOCK_ACCESS_FS_MAKE_SYM: u64 = 1 << 12;
+    /// Available from ABI version 2
+    const LANDLOCK_ACCESS_FS_REFER: u64 = 1 << 13;
+    /// Available from ABI version 3
+    const LANDLOCK_ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+
+    /// Access rights that modify the file system. Reading and executing is not restricted.
+    const WRITE_ACCESS: u64 = LANDLOCK_ACCESS_FS_WRITE_FILE
+        | LANDLOCK_ACCESS_FS_REMOVE_DIR
+        | LANDLOCK_ACCESS_FS_REMOVE_FILE
+        | LANDLOCK_ACCESS_FS_MAKE_CHAR
+        | LANDLOCK_ACCESS_FS_MAKE_DIR
+        | LANDLOCK_ACCESS_FS_MAKE_REG
+        | LANDLOCK_ACCESS_FS_MAKE_SOCK
+        | LANDLOCK_ACCESS_FS_MAKE_FIFO
+        | LANDLOCK_ACCESS_FS_MAKE_BLOCK
+        | LANDLOCK_ACCESS_FS_MAKE_SYM;
+    /// Access rights that can be granted on a file rather than a directory.
+    const FILE_ACCESS: u64 = LANDLOCK_ACCESS_FS_WRITE_FILE | LANDLOCK_ACCESS_FS_TRUNCATE;
+
+    /// Devices that are writable even without being in the writable paths, since shells redirect
+    /// to them all the time.
+    const WRITABLE_DEVICES: &[&str] = &["/dev/null", "/dev/tty", "/dev/zero"];
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xc000_003e;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xc000_00b7;
+
+    /// Syscalls that let a process escape or inspect the sandbox, or the rest of the system.
+    const BLOCKED_SYSCALLS: &[libc::c_long] = &[
+        libc::SYS_ptrace,
+        libc::SYS_process_vm_readv,
+        libc::SYS_process_vm_writev,
+        libc::SYS_mount,
+        libc::SYS_umount2,
+        libc::SYS_pivot_root,
+        libc::SYS_setns,
+        libc::SYS_keyctl,
+        libc::SYS_add_key,
+        libc::SYS_request_key,
+        libc::SYS_bpf,
+        libc::SYS_perf_event_open,
+        libc::SYS_kexec_load,
+        libc::SYS_init_module,
+        libc::SYS_finit_module,
+        libc::SYS_delete_module,
+        libc::SYS_userfaultfd,
+        libc::SYS_open_by_handle_at,
+        // io_uring operations aren't subject to seccomp filters
+        libc::SYS_io_uring_setup,
+    ];
+
+    #[repr(C)]
+    struct LandlockRulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    #[repr(C, packed)]
+    struct LandlockPathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: i32,
+    }
+
+    /// A prepared sandbox, applied to commands between `fork` and `exec`.
+    #[derive(Debug)]
+    pub struct Sandbox {
+        settings: SandboxSettings,
+        ruleset: OwnedFd,
+        filter: Vec<libc::sock_filter>,
+        /// Contents of `/proc/self/uid_map` and `/proc/self/gid_map` in the network namespace.
+        id_maps: (CString, CString),
+    }
+
+    impl Sandbox {
+        pub fn new(os: &Os, settings: &SandboxSettings) -> Result<Self> {
+            let abi = landlock_abi()?;
+            let mut handled_access = WRITE_ACCESS;
+            if abi >= 2 {
+                handled_access |= LANDLOCK_ACCESS_FS_REFER;
+            }
+            if abi >= 3 {
+                handled_access |= LANDLOCK_ACCESS_FS_TRUNCATE;
+            }
+
+            let attr = LandlockRulesetAttr {
+                handled_access_fs: handled_access,
+            };
+            // SAFETY: attr is a valid ruleset attribute of the given size
+            let fd = unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_create_ruleset,
+                    &attr as *const LandlockRulesetAttr,
+                    std::mem::size_of::<LandlockRulesetAttr>(),
+                    0u32,
+                )
+            };
+            if fd < 0 {
+                bail!("failed to create the Landlock ruleset: {}", io::Error::last_os_error());
+            }
+            // SAFETY: the syscall returned a new file descriptor that nothing else owns
+            let ruleset = unsafe { OwnedFd::from_raw_fd(fd as RawFd) };
+
+            let writable_paths = settings
+                .writable_paths
+                .iter()
+                .filter_map(|path| paths::canonicalizes_path(os, path).ok())
+                .chain(WRITABLE_DEVICES.iter().map(|path| path.to_string()));
+            for path in writable_paths {
+                if let Err(err) = add_writable_path(&ruleset, Path::new(&path), handled_access) {
+                    warn!(?path, %err, "Unable to make path writable in the sandbox");
+                }
+            }
+
+            // SAFETY: getuid and getgid always succeed
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            Ok(Self {
+                settings: settings.clone(),
+                ruleset,
+                filter: seccomp_filter(settings.network),
+                id_maps: (
+                    CString::new(format!("{uid} {uid} 1"))?,
+                    CString::new(format!("{gid} {gid} 1"))?,
+                ),
+            })
+        }
+
+        /// Configures `command` to run in the sandbox with the allowed variables of `env_vars`.
+        /// `always_allowed` names variables that are set by us rather than inherited.
+        pub fn configure(
+            &self,
+            command: &mut tokio::process::Command,
+            env_vars: HashMap<String, String>,
+            always_allowed: &[&str],
+        ) {
+            command
+                .env_clear()
+                .envs(self.settings.filter_env(env_vars, always_allowed));
+
+            let ruleset = self.ruleset.as_raw_fd();
+            let filter = self.filter.clone();
+            let network = self.settings.network;
+            let id_maps = self.id_maps.clone();
+            // SAFETY: the closure only makes async-signal-safe syscalls on memory allocated
+            // before the fork.
+            unsafe {
+                command.pre_exec(move || {
+                    if !network {
+                        enter_network_namespace(&id_maps);
+                    }
+                    if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    if libc::syscall(libc::SYS_landlock_restrict_self, ruleset, 0u32) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    let program = libc::sock_fprog {
+                        len: filter.len() as u16,
+                        filter: filter.as_ptr() as *mut libc::sock_filter,
+                    };
+                    if libc::prctl(
+                        libc::PR_SET_SECCOMP,
+                        libc::SECCOMP_MODE_FILTER,
+                        &program as *const libc::sock_fprog,
+                    ) != 0
+                    {
+                        return Err(io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    /// Returns the Landlock ABI version supported by the kernel.
+    fn landlock_abi() -> Result<i64> {
+        // SAFETY: querying the version doesn't read the attribute
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<LandlockRulesetAttr>(),
+                0usize,
+                LANDLOCK_CREATE_RULESET_VERSION,
+            )
+        };
+        if abi < 1 {
+            bail!(
+                "Landlock is not available ({}), it requires Linux 5.13 or later with Landlock enabled",
+                io::Error::last_os_error()
+            );
+        }
+        debug!("Landlock ABI version {abi}");
+        Ok(abi)
+    }
+
+    fn add_writable_path(ruleset: &OwnedFd, path: &Path, handled_access: u64) -> io::Result<()> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        // SAFETY: c_path is a valid C string
+        let fd = unsafe { libc::open(c_path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: open returned a new file descriptor that nothing else owns
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let allowed_access = if path.is_dir() {
+            handled_access
+        } else {
+            handled_access & FILE_ACCESS
+        };
+        let attr = LandlockPathBeneathAttr {
+            allowed_access,
+            parent_fd: fd.as_raw_fd(),
+        };
+        // SAFETY: attr is a valid path beneath attribute and both file descriptors are open
+        let res = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_add_rule,
+                ruleset.as_raw_fd(),
+                LANDLOCK_RULE_PATH_BENEATH,
+                &attr as *const LandlockPathBeneathAttr,
+                0u32,
+            )
+        };
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Moves the process to a new user and network namespace, which only has a loopback
+    /// interface. Failures are ignored since the seccomp filter blocks network sockets anyway, and
+    /// user namespaces are often disabled.
+    ///
+    /// Must only be called between `fork` and `exec`.
+    unsafe fn enter_network_namespace(id_maps: &(CString, CString)) {
+        // SAFETY: only makes async-signal-safe syscalls on memory allocated before the fork
+        unsafe {
+            if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                return;
+            }
+            for (file, content) in [
+                (c"/proc/self/setgroups", c"deny"),
+                (c"/proc/self/uid_map", id_maps.0.as_c_str()),
+                (c"/proc/self/gid_map", id_maps.1.as_c_str()),
+            ] {
+                let fd = libc::open(file.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+                if fd >= 0 {
+                    let bytes = content.to_bytes();
+                    libc::write(fd, bytes.as_ptr().cast(), bytes.len());
+                    libc::close(fd);
+                }
+            }
+        }
+    }
+
+    /// Returns a seccomp program that fails [BLOCKED_SYSCALLS] with `EPERM` and, if `network` is
+    /// false, fails creating sockets other than Unix sockets with `EACCES`.
+    fn seccomp_filter(network: bool) -> Vec<libc::sock_filter> {
+        const SECCOMP_DATA_NR: u32 = 0;
+        const SECCOMP_DATA_ARCH: u32 = 4;
+        const SECCOMP_DATA_ARG0: u32 = 16;
+
+        // SAFETY: BPF_STMT and BPF_JUMP only construct a sock_filter
+        let load = |offset| unsafe { libc::BPF_STMT((libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16, offset) };
+        let jump_eq =
+            |k, jt, jf| unsafe { libc::BPF_JUMP((libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16, k, jt, jf) };
+        let ret = |k| unsafe { libc::BPF_STMT((libc::BPF_RET | libc::BPF_K) as u16, k) };
+        let errno = |errno: i32| ret(libc::SECCOMP_RET_ERRNO | errno as u32);
+
+        // Syscalls of other architectures (eg. 32 bit syscalls on x86_64) are rejected
+        let mut filter = vec![
+            load(SECCOMP_DATA_ARCH),
+            jump_eq(AUDIT_ARCH, 1, 0),
+            errno(libc::EPERM),
+            load(SECCOMP_DATA_NR),
+        ];
+        for syscall in BLOCKED_SYSCALLS {
+            filter.push(jump_eq(*syscall as u32, 0, 1));
+            filter.push(errno(libc::EPERM));
+        }
+        if !network {
+            filter.extend([
+                jump_eq(libc::SYS_socket as u32, 0, 3),
+                load(SECCOMP_DATA_ARG0),
+                jump_eq(libc::AF_UNIX as u32, 1, 0),
+                errno(libc::EACCES),
+                load(SECCOMP_DATA_NR),
+            ]);
+        }
+        filter.push(ret(libc::SECCOMP_RET_ALLOW));
+        filter
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_seccomp_filter() {
+            let with_network = seccomp_filter(true);
+            let without_network = seccomp_filter(false);
+            assert_eq!(with_network.len(), 5 + BLOCKED_SYSCALLS.len() * 2);
+            assert_eq!(without_network.len(), with_network.len() + 5);
+            assert_eq!(without_network.last().unwrap().k, libc::SECCOMP_RET_ALLOW);
+        }
+
+        #[tokio::test]
+        async fn test_sandbox() {
+            let os = Os::new().await.unwrap();
+            let writable = tempfile::tempdir().unwrap();
+            let read_only = tempfile::tempdir().unwrap();
+            let settings = SandboxSettings {
+                writable_paths: vec![writable.path().to_string_lossy().to_string()],
+                network: false,
+                allowed_env_vars: vec![],
+            };
+            let sandbox = match Sandbox::new(&os, &settings) {
+                Ok(sandbox) => sandbox,
+                // Landlock is not available on every kernel that runs the tests
+                Err(_) => return,
+            };
+
+            let run = |script: String| {
+                let mut command = tokio::process::Command::new("bash");
+                command.arg("-c").arg(script);
+                sandbox.configure(
+                    &mut command,
+                    HashMap::from([("SECRET".to_string(), "1".to_string())]),
+                    &[],
+                );
+                command.output()
+            };
+            let output = run(format!(
+                "echo ok > {}/file && echo $SECRET >/dev/null",
+                writable.path().display()
+            ))
+            .await
+            .unwrap();
+            assert!(output.status.success(), "{output:?}");
+            assert!(writable.path().join("file").exists());
+
+            let output = run(format!("echo no > {}/file", read_only.path().display()))
+                .await
+                .unwrap();
+            assert!(!output.status.success());
+            assert!(!read_only.path().join("file").exists());
+
+            let output = run("echo $SECRET".to_string()).await.unwrap();
+            assert_eq!(String::from_utf8_lossy(&output.stdout), "\n");
+
+            let output = run("exec 3<>/dev/tcp/127.0.0.1/9".to_string()).await.unwrap();
+            assert!(!output.status.success());
+        }
+    }
+}
+
+/// Stand-in on platforms without sandbox support, which can't be created.
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+#[derive(Debug)]
+pub enum Sandbox {}
+
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+impl Sandbox {
+    pub fn new(_os: &crate::os::Os, _settings: &SandboxSettings) -> Result<Self> {
+        eyre::bail!("sandboxing commands is only supported on Linux (x86_64 and aarch64)")
+    }
+
+    pub fn configure(
+        &self,
+        _command: &mut tokio::process::Command,
+        _env_vars: HashMap<String, String>,
+        _always_allowed: &[&str],
+    ) {
+        match *self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::agent::ToolSettingTarget;
+
+    #[test]
+    fn test_sandbox_settings() {
+        let mut agent = Agent::default();
+        assert_eq!(SandboxSettings::from_agent(&agent).unwrap(), None);
+
+        let tool_name = if cfg!(windows) { "execute_cmd" } else { "execute_bash" };
+        agent.tools_settings.insert(
+            ToolSettingTarget(tool_name.to_string()),
+            serde_json::json!({ "sandbox": { "allowedEnvVars": ["AWS_PROFILE"] } }),
+        );
+        let settings = SandboxSettings::from_agent(&agent).unwrap().unwrap();
+        assert_eq!(settings.writable_paths, vec![
+            ".".to_string(),
+            std::env::temp_dir().to_string_lossy().to_string()
+        ]);
+        assert!(!settings.network);
+
+        let env = [
+            ("PATH", "/bin"),
+            ("AWS_PROFILE", "dev"),
+            ("SECRET_TOKEN", "abc"),
+            ("UA", "q"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let mut filtered = settings.filter_env(env, &["UA"]).into_keys().collect::<Vec<_>>();
+        filtered.sort();
+        assert_eq!(filtered, vec!["AWS_PROFILE", "PATH", "UA"]);
+
+        agent.tools_settings.insert(
+            ToolSettingTarget(tool_name.to_string()),
+            serde_json::json!({ "sandbox": { "network": "yes" } }),
+        );
+        assert!(SandboxSettings::from_agent(&agent).is_err());
+    }
+}
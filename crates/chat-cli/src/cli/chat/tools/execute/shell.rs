@@ -0,0 +1,1028 @@
+//! Parsing of shell commands for `execute_bash` permission checks.
+//!
+//! Commands are parsed into lists of pipelines, so that permission rules can be applied to every
+//! command that would run, including those in command substitutions and here-documents. Only the
+//! subset of bash needed for one-line commands is supported: compound commands such as `if` and
+//! `for` fail to parse, and should be treated as requiring approval.
+
+use eyre::{
+    Result,
+    bail,
+    eyre,
+};
+
+/// Reserved words that start compound commands, which aren't supported.
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac", "select", "function",
+    "coproc", "time", "[[", "]]",
+];
+
+/// Files that redirections may write to without changing anything on disk.
+const HARMLESS_WRITE_TARGETS: &[&str] = &["/dev/null", "/dev/stdout", "/dev/stderr"];
+
+/// A parsed command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    pub list: List,
+    /// Here-documents in the order they appear, referenced by [RedirectKind::HereDoc].
+    pub heredocs: Vec<HereDoc>,
+}
+
+impl Script {
+    /// Returns every command in the script, including commands nested in subshells, groups and
+    /// substitutions.
+    pub fn commands(&self) -> Vec<&Command> {
+        let mut commands = Vec::new();
+        collect_list(&self.list, &mut commands);
+        for heredoc in &self.heredocs {
+            for list in &heredoc.substitutions {
+                collect_list(list, &mut commands);
+            }
+        }
+        commands
+    }
+}
+
+/// Pipelines separated by `;`, `&`, `&&`, `||` or newlines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct List {
+    pub items: Vec<ListItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListItem {
+    pub pipeline: Pipeline,
+    /// The operator following the pipeline.
+    pub separator: Separator,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Separator {
+    /// `;`, a newline or the end of the list.
+    Sequential,
+    /// `&`
+    Background,
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+}
+
+/// Commands connected by `|` or `|&`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    /// Whether the pipeline is prefixed with `!`.
+    pub negated: bool,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Simple(SimpleCommand),
+    /// `( list )`
+    Subshell {
+        body: List,
+        redirects: Vec<Redirect>,
+        source: String,
+    },
+    /// `{ list; }`
+    Group {
+        body: List,
+        redirects: Vec<Redirect>,
+        source: String,
+    },
+}
+
+impl Command {
+    /// The command as written in the script.
+    pub fn source(&self) -> &str {
+        match self {
+            Command::Simple(simple) => &simple.source,
+            Command::Subshell { source, .. } | Command::Group { source, .. } => source,
+        }
+    }
+
+    pub fn redirects(&self) -> &[Redirect] {
+        match self {
+            Command::Simple(simple) => &simple.redirects,
+            Command::Subshell { redirects, .. } | Command::Group { redirects, .. } => redirects,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleCommand {
+    /// Variable assignments before the command name, e.g. `FOO=bar`.
+    pub assignments: Vec<Word>,
+    /// The command name and its arguments.
+    pub words: Vec<Word>,
+    pub redirects: Vec<Redirect>,
+    /// The command as written in the script.
+    pub source: String,
+}
+
+impl SimpleCommand {
+    /// Returns the arguments of the command after quote removal, or `None` if any of them
+    /// depend on an expansion.
+    pub fn argv(&self) -> Option<Vec<&str>> {
+        self.words
+            .iter()
+            .map(|word| word.is_static().then_some(word.value.as_str()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Word {
+    /// The word as written in the script.
+    pub raw: String,
+    /// The word after quote removal. Expansions are left out.
+    pub value: String,
+    /// Whether the word contains a parameter expansion, arithmetic expansion, command or process
+    /// substitution, or a tilde prefix other than `~/`, in which case [Word::value] isn't the
+    /// value the shell would use.
+    pub expanded: bool,
+    /// Commands run by command and process substitutions in the word.
+    pub substitutions: Vec<List>,
+}
+
+impl Word {
+    pub fn is_static(&self) -> bool {
+        !self.expanded
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    /// The file descriptor being redirected, if given.
+    pub fd: Option<u32>,
+    pub kind: RedirectKind,
+    /// The file name, file descriptor, here-string or here-document delimiter.
+    pub target: Word,
+}
+
+impl Redirect {
+    /// Whether the redirection opens its target for writing. Writes to `/dev/null` and the
+    /// standard streams aren't counted.
+    pub fn is_write(&self) -> bool {
+        matches!(
+            self.kind,
+            RedirectKind::Output | RedirectKind::Append | RedirectKind::ReadWrite
+        ) && !(self.target.is_static() && HARMLESS_WRITE_TARGETS.contains(&self.target.value.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectKind {
+    /// `<`
+    Input,
+    /// `>`, `>|`, `&>`, or `>&` followed by a file name
+    Output,
+    /// `>>` or `&>>`
+    Append,
+    /// `<>`
+    ReadWrite,
+    /// `>&` or `<&` followed by a file descriptor or `-`
+    Duplicate,
+    /// `<<` or `<<-`, with the index of the body in [Script::heredocs]
+    HereDoc(usize),
+    /// `<<<`
+    HereString,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HereDoc {
+    pub body: String,
+    /// Commands run by command substitutions in the body. Always empty if the delimiter is
+    /// quoted.
+    pub substitutions: Vec<List>,
+}
+
+/// Parses a command line.
+pub fn parse(command: &str) -> Result<Script> {
+    let mut parser = Parser::new(command);
+    let list = parser.parse_list(None)?;
+    if let Some(c) = parser.peek() {
+        bail!("unexpected `{c}`");
+    }
+    if let Some(pending) = parser.pending.first() {
+        bail!("here-document delimited by `{}` has no body", pending.delimiter);
+    }
+    Ok(Script {
+        list,
+        heredocs: parser.heredocs,
+    })
+}
+
+fn collect_list<'a>(list: &'a List, commands: &mut Vec<&'a Command>) {
+    for item in &list.items {
+        for command in &item.pipeline.commands {
+            collect_command(command, commands);
+        }
+    }
+}
+
+fn collect_command<'a>(command: &'a Command, commands: &mut Vec<&'a Command>) {
+    commands.push(command);
+    match command {
+        Command::Simple(simple) => {
+            for word in simple.assignments.iter().chain(&simple.words) {
+                collect_word(word, commands);
+            }
+        },
+        Command::Subshell { body, .. } | Command::Group { body, .. } => collect_list(body, commands),
+    }
+    for redirect in command.redirects() {
+        collect_word(&redirect.target, commands);
+    }
+}
+
+fn collect_word<'a>(word: &'a Word, commands: &mut Vec<&'a Command>) {
+    for list in &word.substitutions {
+        collect_list(list, commands);
+    }
+}
+
+/// What closes the list being parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Close {
+    Paren,
+    Brace,
+}
+
+/// A here-document whose body hasn't been read yet.
+#[derive(Debug)]
+struct PendingHereDoc {
+    index: usize,
+    delimiter: String,
+    strip_tabs: bool,
+    expand: bool,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    heredocs: Vec<HereDoc>,
+    /// Here-documents whose bodies start after the next newline.
+    pending: Vec<PendingHereDoc>,
+}
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            pos: 0,
+            heredocs: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.peek_at(i) == Some(c))
+    }
+
+    fn text(&self, start: usize, end: usize) -> String {
+        self.chars[start..end].iter().collect()
+    }
+
+    /// Whether the reserved word `word` is at the current position.
+    fn at_reserved(&self, word: &str) -> bool {
+        self.starts_with(word) && self.peek_at(word.chars().count()).is_none_or(is_word_end)
+    }
+
+    /// Returns the unquoted word at the current position without consuming it.
+    fn peek_plain_word(&self) -> String {
+        self.chars[self.pos..]
+            .iter()
+            .take_while(|c| !is_word_end(**c) && !matches!(c, '\'' | '"' | '\\' | '$' | '`'))
+            .collect()
+    }
+
+    /// Skips blanks, line continuations and comments, but not newlines.
+    fn skip_spaces(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' => self.pos += 1,
+                '\\' if self.peek_at(1) == Some('\n') => self.pos += 2,
+                '#' if self.pos == 0 || self.chars[self.pos - 1].is_whitespace() => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.pos += 1;
+                    }
+                },
+                _ => break,
+            }
+        }
+    }
+
+    /// Skips blanks and newlines, reading any here-documents that start after a newline.
+    fn skip_blank_lines(&mut self) -> Result<()> {
+        loop {
+            self.skip_spaces();
+            if self.peek() != Some('\n') {
+                return Ok(());
+            }
+            self.pos += 1;
+            self.read_heredocs()?;
+        }
+    }
+
+    fn parse_list(&mut self, close: Option<Close>) -> Result<List> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank_lines()?;
+            match self.peek() {
+                None => break,
+                Some(')') if close == Some(Close::Paren) => break,
+                _ if close == Some(Close::Brace) && self.at_reserved("}") => break,
+                _ => {},
+            }
+
+            let pipeline = self.parse_pipeline()?;
+            self.skip_spaces();
+            let separator = if self.starts_with("&&") {
+                self.pos += 2;
+                Separator::And
+            } else if self.starts_with("||") {
+                self.pos += 2;
+                Separator::Or
+            } else if self.starts_with(";;") {
+                bail!("unexpected `;;`");
+            } else {
+                match self.peek() {
+                    Some(';') => {
+                        self.pos += 1;
+                        Separator::Sequential
+                    },
+                    Some('&') => {
+                        self.pos += 1;
+                        Separator::Background
+                    },
+                    Some('\n') => {
+                        self.pos += 1;
+                        self.read_heredocs()?;
+                        Separator::Sequential
+                    },
+                    None => Separator::Sequential,
+                    Some(')') if close == Some(Close::Paren) => Separator::Sequential,
+                    Some(c) => return Err(eyre!("unexpected `{c}`")),
+                }
+            };
+            items.push(ListItem { pipeline, separator });
+
+            if matches!(separator, Separator::And | Separator::Or) {
+                self.skip_blank_lines()?;
+                if self.peek().is_none_or(|c| c == ')') {
+                    bail!("expected a command after `&&` or `||`");
+                }
+            }
+        }
+
+        if items.is_empty() && close.is_some() {
+            bail!("expected a command");
+        }
+        Ok(List { items })
+    }
+
+    fn parse_pipeline(&mut self) -> Result<Pipeline> {
+        self.skip_spaces();
+        let negated = self.at_reserved("!");
+        if negated {
+            self.pos += 1;
+        }
+
+        let mut commands = vec![self.parse_command()?];
+        loop {
+            self.skip_spaces();
+            if self.peek() != Some('|') || self.starts_with("||") {
+                break;
+            }
+            self.pos += if self.starts_with("|&") { 2 } else { 1 };
+            self.skip_blank_lines()?;
+            commands.push(self.parse_command()?);
+        }
+        Ok(Pipeline { negated, commands })
+    }
+
+    fn parse_command(&mut self) -> Result<Command> {
+        self.skip_spaces();
+        let start = self.pos;
+        if self.peek() == Some('(') {
+            if self.peek_at(1) == Some('(') {
+                bail!("arithmetic commands are not supported");
+            }
+            self.pos += 1;
+            let body = self.parse_list(Some(Close::Paren))?;
+            self.expect(')')?;
+            let redirects = self.parse_trailing_redirects()?;
+            return Ok(Command::Subshell {
+                body,
+                redirects,
+                source: self.text(start, self.pos).trim().to_string(),
+            });
+        }
+        if self.at_reserved("{") {
+            self.pos += 1;
+            let body = self.parse_list(Some(Close::Brace))?;
+            if !self.at_reserved("}") {
+                bail!("missing `}}`");
+            }
+            self.pos += 1;
+            let redirects = self.parse_trailing_redirects()?;
+            return Ok(Command::Group {
+                body,
+                redirects,
+                source: self.text(start, self.pos).trim().to_string(),
+            });
+        }
+        let word = self.peek_plain_word();
+        if UNSUPPORTED_KEYWORDS.contains(&word.as_str()) {
+            bail!("`{word}` is not supported");
+        }
+
+        self.parse_simple_command().map(Command::Simple)
+    }
+
+    fn parse_simple_command(&mut self) -> Result<SimpleCommand> {
+        let start = self.pos;
+        let mut assignments = Vec::new();
+        let mut words = Vec::new();
+        let mut redirects = Vec::new();
+        loop {
+            self.skip_spaces();
+            let Some(c) = self.peek() else {
+                break;
+            };
+            match c {
+                '\n' | ';' | '|' | ')' => break,
+                '&' if self.peek_at(1) != Some('>') => break,
+                '(' => return Err(eyre!("unexpected `(`")),
+                '<' | '>' if self.peek_at(1) == Some('(') => words.push(self.parse_process_substitution()?),
+                '<' | '>' | '&' => redirects.push(self.parse_redirect(None)?),
+                _ => {
+                    if let Some(fd) = self.take_fd() {
+                        redirects.push(self.parse_redirect(Some(fd))?);
+                        continue;
+                    }
+                    let word = self.parse_word()?;
+                    if words.is_empty() && is_assignment(&word.raw) {
+                        assignments.push(word);
+                    } else {
+                        words.push(word);
+                    }
+                },
+            }
+        }
+
+        if assignments.is_empty() && words.is_empty() && redirects.is_empty() {
+            match self.peek() {
+                Some(c) => return Err(eyre!("unexpected `{c}`")),
+                None => return Err(eyre!("expected a command")),
+            }
+        }
+        Ok(SimpleCommand {
+            assignments,
+            words,
+            redirects,
+            source: self.text(start, self.pos).trim().to_string(),
+        })
+    }
+
+    /// Parses redirections after a subshell or group.
+    fn parse_trailing_redirects(&mut self) -> Result<Vec<Redirect>> {
+        let mut redirects = Vec::new();
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some('<' | '>') => redirects.push(self.parse_redirect(None)?),
+                Some('&') if self.peek_at(1) == Some('>') => redirects.push(self.parse_redirect(None)?),
+                _ => match self.take_fd() {
+                    Some(fd) => redirects.push(self.parse_redirect(Some(fd))?),
+                    None => return Ok(redirects),
+                },
+            }
+        }
+    }
+
+    /// If the current position is a file descriptor number followed by a redirection operator,
+    /// consumes the number and returns it.
+    fn take_fd(&mut self) -> Option<u32> {
+        let digits = self.chars[self.pos..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 || !matches!(self.peek_at(digits), Some('<' | '>')) {
+            return None;
+        }
+        let fd = self.text(self.pos, self.pos + digits).parse().ok()?;
+        self.pos += digits;
+        Some(fd)
+    }
+
+    fn parse_redirect(&mut self, fd: Option<u32>) -> Result<Redirect> {
+        const OPERATORS: &[&str] = &["&>>", "&>", "<<<", "<<-", "<<", "<&", "<>", "<", ">>", ">|", ">&", ">"];
+        let Some(op) = OPERATORS.iter().copied().find(|op| self.starts_with(op)) else {
+            bail!("expected a redirection");
+        };
+        self.pos += op.len();
+        self.skip_spaces();
+        if self.peek().is_none_or(is_word_end) {
+            bail!("expected a word after `{op}`");
+        }
+        let target = self.parse_word()?;
+
+        let is_fd = |word: &Word| {
+            word.is_static() && (word.value == "-" || word.value.trim_end_matches('-').parse::<u32>().is_ok())
+        };
+        let kind = match op {
+            "&>" | ">|" | ">" => RedirectKind::Output,
+            "&>>" | ">>" => RedirectKind::Append,
+            "<>" => RedirectKind::ReadWrite,
+            "<" => RedirectKind::Input,
+            "<<<" => RedirectKind::HereString,
+            ">&" | "<&" if is_fd(&target) => RedirectKind::Duplicate,
+            ">&" => RedirectKind::Output,
+            "<&" => RedirectKind::Input,
+            _ => {
+                let index = self.heredocs.len();
+                self.heredocs.push(HereDoc::default());
+                self.pending.push(PendingHereDoc {
+                    index,
+                    delimiter: target.value.clone(),
+                    strip_tabs: op == "<<-",
+                    expand: !target.raw.contains(['\'', '"', '\\']),
+                });
+                RedirectKind::HereDoc(index)
+            },
+        };
+        Ok(Redirect { fd, kind, target })
+    }
+
+    /// Reads the bodies of pending here-documents, which start at the current position.
+    fn read_heredocs(&mut self) -> Result<()> {
+        for pending in std::mem::take(&mut self.pending) {
+            let mut body = String::new();
+            loop {
+                if self.pos >= self.chars.len() {
+                    bail!("here-document delimited by `{}` is not terminated", pending.delimiter);
+                }
+                let end = self.chars[self.pos..]
+                    .iter()
+                    .position(|c| *c == '\n')
+                    .map_or(self.chars.len(), |i| self.pos + i);
+                let line = self.text(self.pos, end);
+                self.pos = (end + 1).min(self.chars.len());
+
+                let line = if pending.strip_tabs {
+                    line.trim_start_matches('\t')
+                } else {
+                    &line
+                };
+                if line == pending.delimiter {
+                    break;
+                }
+                body.push_str(line);
+                body.push('\n');
+            }
+
+            let substitutions = if pending.expand {
+                parse_substitutions(&body)?
+            } else {
+                Vec::new()
+            };
+            self.heredocs[pending.index] = HereDoc { body, substitutions };
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        self.skip_spaces();
+        if self.peek() != Some(c) {
+            bail!("missing `{c}`");
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_word(&mut self) -> Result<Word> {
+        let start = self.pos;
+        let mut word = Word::default();
+        // `~user` and `~+` expand to other directories, while `~/` is left to the tools that
+        // expand it to the home directory.
+        if self.peek() == Some('~') && self.peek_at(1).is_some_and(|c| c != '/' && !is_word_end(c)) {
+            word.expanded = true;
+        }
+        while let Some(c) = self.peek() {
+            match c {
+                c if is_word_end(c) => break,
+                '\'' => {
+                    self.pos += 1;
+                    let Some(len) = self.chars[self.pos..].iter().position(|c| *c == '\'') else {
+                        bail!("unterminated single quote");
+                    };
+                    word.value.push_str(&self.text(self.pos, self.pos + len));
+                    self.pos += len + 1;
+                },
+                '"' => self.parse_double_quoted(&mut word)?,
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('\n') => self.pos += 1,
+                        Some(c) => {
+                            word.value.push(c);
+                            self.pos += 1;
+                        },
+                        None => word.value.push('\\'),
+                    }
+                },
+                '$' => self.parse_dollar(&mut word, false)?,
+                '`' => self.parse_backquotes(&mut word)?,
+                c => {
+                    word.value.push(c);
+                    self.pos += 1;
+                },
+            }
+        }
+        word.raw = self.text(start, self.pos);
+        Ok(word)
+    }
+
+    fn parse_double_quoted(&mut self, word: &mut Word) -> Result<()> {
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                None => return Err(eyre!("unterminated double quote")),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(());
+                },
+                Some('\\') => {
+                    match self.peek_at(1) {
+                        Some('\n') => {},
+                        Some(c @ ('$' | '`' | '"' | '\\')) => word.value.push(c),
+                        Some(c) => {
+                            word.value.push('\\');
+                            word.value.push(c);
+                        },
+                        None => return Err(eyre!("unterminated double quote")),
+                    }
+                    self.pos += 2;
+                },
+                Some('$') => self.parse_dollar(word, true)?,
+                Some('`') => self.parse_backquotes(word)?,
+                Some(c) => {
+                    word.value.push(c);
+                    self.pos += 1;
+                },
+            }
+        }
+    }
+
+    fn parse_dollar(&mut self, word: &mut Word, in_double_quotes: bool) -> Result<()> {
+        self.pos += 1;
+        match self.peek() {
+            Some('(') if self.peek_at(1) == Some('(') => {
+                let start = self.pos;
+                self.skip_parens()?;
+                if self.text(start, self.pos).contains(['$', '`']) {
+                    bail!("expansions in arithmetic expressions are not supported");
+                }
+                word.expanded = true;
+            },
+            Some('(') => {
+                self.pos += 1;
+                let list = self.parse_list(Some(Close::Paren))?;
+                self.expect(')')?;
+                word.substitutions.push(list);
+                word.expanded = true;
+            },
+            Some('{') => {
+                self.pos += 1;
+                // Parameter expansions may contain substitutions, e.g. `${name:-$(cmd)}`
+                let mut inner = Word::default();
+                let mut depth = 0;
+                loop {
+                    match self.peek() {
+                        None => return Err(eyre!("unterminated parameter expansion")),
+                        Some('}') if depth == 0 => {
+                            self.pos += 1;
+                            break;
+                        },
+                        Some('}') => {
+                            depth -= 1;
+                            self.pos += 1;
+                        },
+                        Some('{') => {
+                            depth += 1;
+                            self.pos += 1;
+                        },
+                        Some('\\') => self.pos += 2,
+                        Some('\'') if !in_double_quotes => {
+                            self.pos += 1;
+                            let Some(len) = self.chars[self.pos..].iter().position(|c| *c == '\'') else {
+                                bail!("unterminated single quote");
+                            };
+                            self.pos += len + 1;
+                        },
+                        Some('"') => self.parse_double_quoted(&mut inner)?,
+                        Some('$') => self.parse_dollar(&mut inner, in_double_quotes)?,
+                        Some('`') => self.parse_backquotes(&mut inner)?,
+                        Some(_) => self.pos += 1,
+                    }
+                }
+                word.substitutions.append(&mut inner.substitutions);
+                word.expanded = true;
+            },
+            Some('\'') if !in_double_quotes => {
+                // ANSI-C quoting. Escape sequences aren't decoded, so only quotes without them
+                // have a known value.
+                self.pos += 1;
+                let start = self.pos;
+                loop {
+                    match self.peek() {
+                        None => return Err(eyre!("unterminated single quote")),
+                        Some('\'') => break,
+                        Some('\\') => self.pos += 2,
+                        Some(_) => self.pos += 1,
+                    }
+                }
+                let text = self.text(start, self.pos);
+                self.pos += 1;
+                if text.contains('\\') {
+                    word.expanded = true;
+                } else {
+                    word.value.push_str(&text);
+                }
+            },
+            // Locale-specific translation, which is treated as a plain double-quoted string
+            Some('"') if !in_double_quotes => {},
+            Some(c) if c.is_ascii_digit() => {
+                self.pos += 1;
+                word.expanded = true;
+            },
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    self.pos += 1;
+                }
+                word.expanded = true;
+            },
+            Some('@' | '*' | '#' | '?' | '$' | '!' | '-') => {
+                self.pos += 1;
+                word.expanded = true;
+            },
+            _ => word.value.push('$'),
+        }
+        Ok(())
+    }
+
+    /// Skips balanced parentheses starting at the current position.
+    fn skip_parens(&mut self) -> Result<()> {
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                },
+                _ => {},
+            }
+        }
+        Err(eyre!("missing `)`"))
+    }
+
+    fn parse_backquotes(&mut self, word: &mut Word) -> Result<()> {
+        self.pos += 1;
+        let mut content = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(eyre!("unterminated backquote")),
+                Some('`') => {
+                    self.pos += 1;
+                    break;
+                },
+                Some('\\') if matches!(self.peek_at(1), Some('`' | '$' | '\\')) => {
+                    content.push(self.chars[self.pos + 1]);
+                    self.pos += 2;
+                },
+                Some(c) => {
+                    content.push(c);
+                    self.pos += 1;
+                },
+            }
+        }
+
+        let script = parse(&content)?;
+        if !script.heredocs.is_empty() {
+            bail!("here-documents in backquotes are not supported");
+        }
+        word.substitutions.push(script.list);
+        word.expanded = true;
+        Ok(())
+    }
+
+    fn parse_process_substitution(&mut self) -> Result<Word> {
+        let start = self.pos;
+        self.pos += 2;
+        let list = self.parse_list(Some(Close::Paren))?;
+        self.expect(')')?;
+        Ok(Word {
+            raw: self.text(start, self.pos),
+            value: String::new(),
+            expanded: true,
+            substitutions: vec![list],
+        })
+    }
+}
+
+/// Parses the command substitutions in the body of a here-document.
+fn parse_substitutions(body: &str) -> Result<Vec<List>> {
+    let mut parser = Parser::new(body);
+    let mut word = Word::default();
+    while let Some(c) = parser.peek() {
+        match c {
+            '\\' => parser.pos += 2,
+            '$' => parser.parse_dollar(&mut word, true)?,
+            '`' => parser.parse_backquotes(&mut word)?,
+            _ => parser.pos += 1,
+        }
+    }
+    if !parser.heredocs.is_empty() {
+        bail!("nested here-documents are not supported");
+    }
+    Ok(word.substitutions)
+}
+
+fn is_word_end(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | ';' | '&' | '|' | '(' | ')' | '<' | '>')
+}
+
+/// Whether `raw` is a variable assignment such as `FOO=bar` or `PATH+=:/bin`.
+fn is_assignment(raw: &str) -> bool {
+    let Some((name, _)) = raw.split_once('=') else {
+        return false;
+    };
+    let name = name.strip_suffix('+').unwrap_or(name);
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(command: &str) -> Vec<String> {
+        parse(command)
+            .unwrap()
+            .commands()
+            .iter()
+            .map(|command| command.source().to_string())
+            .collect()
+    }
+
+    fn simple(script: &Script, i: usize) -> &SimpleCommand {
+        match script.commands()[i] {
+            Command::Simple(simple) => simple,
+            command => panic!("expected a simple command, found {command:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_lists_and_pipelines() {
+        let script = parse("ls -la | grep foo && echo 'a b'; pwd &").unwrap();
+        let items = &script.list.items;
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].pipeline.commands.len(), 2);
+        assert_eq!(items[0].separator, Separator::And);
+        assert_eq!(items[1].separator, Separator::Sequential);
+        assert_eq!(items[2].separator, Separator::Background);
+        assert_eq!(simple(&script, 2).argv(), Some(vec!["echo", "a b"]));
+        assert_eq!(sources("a|b||c&&d\ne"), vec!["a", "b", "c", "d", "e"]);
+        assert!(parse("! ls").unwrap().list.items[0].pipeline.negated);
+    }
+
+    #[test]
+    fn test_parse_quoting() {
+        let script = parse(r#"echo 'a $b' "c \"d\" \$e" f\ g $'h' "$HOME" ${x}y"#).unwrap();
+        let words = &simple(&script, 0).words;
+        let values: Vec<_> = words.iter().map(|w| w.value.as_str()).collect();
+        assert_eq!(values[..5], ["echo", "a $b", r#"c "d" $e"#, "f g", "h"]);
+        assert!(words[..5].iter().all(Word::is_static));
+        assert!(words[5..].iter().all(|w| !w.is_static()));
+        assert!(simple(&script, 0).argv().is_none());
+    }
+
+    #[test]
+    fn test_parse_substitutions() {
+        assert_eq!(sources("echo $(rm -rf /)"), vec!["echo $(rm -rf /)", "rm -rf /"]);
+        assert_eq!(sources("echo `rm a` \"$(touch b)\""), vec![
+            "echo `rm a` \"$(touch b)\"",
+            "rm a",
+            "touch b"
+        ]);
+        assert_eq!(sources("diff <(ls a) >(cat)"), vec![
+            "diff <(ls a) >(cat)",
+            "ls a",
+            "cat"
+        ]);
+        assert_eq!(sources("echo ${x:-$(whoami)}"), vec!["echo ${x:-$(whoami)}", "whoami"]);
+        assert_eq!(sources("(cd a && make) > log"), vec![
+            "(cd a && make) > log",
+            "cd a",
+            "make"
+        ]);
+        assert_eq!(sources("{ ls; pwd; }"), vec!["{ ls; pwd; }", "ls", "pwd"]);
+        // Quoted substitutions are literal text
+        assert_eq!(sources("echo '$(rm a)'"), vec!["echo '$(rm a)'"]);
+    }
+
+    #[test]
+    fn test_parse_redirects() {
+        let script = parse("cmd <in >out 2>>err 2>&1 &>all >&file 3<>rw <<<str").unwrap();
+        let kinds: Vec<_> = simple(&script, 0)
+            .redirects
+            .iter()
+            .map(|r| (r.fd, r.kind, r.target.value.as_str()))
+            .collect();
+        assert_eq!(kinds, vec![
+            (None, RedirectKind::Input, "in"),
+            (None, RedirectKind::Output, "out"),
+            (Some(2), RedirectKind::Append, "err"),
+            (Some(2), RedirectKind::Duplicate, "1"),
+            (None, RedirectKind::Output, "all"),
+            (None, RedirectKind::Output, "file"),
+            (Some(3), RedirectKind::ReadWrite, "rw"),
+            (None, RedirectKind::HereString, "str"),
+        ]);
+        assert_eq!(simple(&script, 0).argv(), Some(vec!["cmd"]));
+
+        let script = parse("ls >/dev/null 2>&1").unwrap();
+        assert!(simple(&script, 0).redirects.iter().all(|r| !r.is_write()));
+
+        let script = parse("cmd >~/a >~root/b >~+/c >'~root'/d").unwrap();
+        let targets: Vec<_> = simple(&script, 0)
+            .redirects
+            .iter()
+            .map(|r| (r.target.value.as_str(), r.target.is_static()))
+            .collect();
+        assert_eq!(targets, vec![
+            ("~/a", true),
+            ("~root/b", false),
+            ("~+/c", false),
+            ("~root/d", true)
+        ]);
+    }
+
+    #[test]
+    fn test_parse_heredocs() {
+        let script = parse("cat <<EOF > out\nhello $(whoami)\nEOF\ncat <<-'END'\n\t$(rm a)\n\tEND").unwrap();
+        assert_eq!(script.heredocs.len(), 2);
+        assert_eq!(script.heredocs[0].body, "hello $(whoami)\n");
+        assert_eq!(script.heredocs[1].body, "$(rm a)\n");
+        assert!(script.heredocs[1].substitutions.is_empty());
+        let sources: Vec<_> = script.commands().iter().map(|c| c.source().to_string()).collect();
+        assert_eq!(sources, vec!["cat <<EOF > out", "cat <<-'END'", "whoami"]);
+        assert_eq!(simple(&script, 0).redirects[0].kind, RedirectKind::HereDoc(0));
+        assert!(simple(&script, 0).redirects[1].is_write());
+    }
+
+    #[test]
+    fn test_parse_assignments_and_comments() {
+        let script = parse("FOO=1 BAR+=2 env a=b # rm -rf /").unwrap();
+        let command = simple(&script, 0);
+        assert_eq!(command.assignments.len(), 2);
+        assert_eq!(command.argv(), Some(vec!["env", "a=b"]));
+        assert_eq!(script.commands().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for command in [
+            "echo 'unterminated",
+            "echo \"unterminated",
+            "echo $(ls",
+            "echo `ls",
+            "ls &&",
+            "| ls",
+            "ls )",
+            "(ls",
+            "{ ls }",
+            "if true; then ls; fi",
+            "for f in *; do rm $f; done",
+            "f() { ls; }",
+            "cat <<EOF\nno end",
+            "echo >",
+            "(( x++ ))",
+        ] {
+            assert!(parse(command).is_err(), "expected `{command}` to fail to parse");
+        }
+    }
+}
@@ -12,9 +12,11 @@ use tracing::error;
 
 use super::{
     CommandResult,
+    Sandbox,
     env_vars_with_user_agent,
     format_output,
 };
+use crate::cli::chat::tools::USER_AGENT_ENV_VAR;
 use crate::os::Os;
 use crate::util::env_var::get_chat_shell;
 
@@ -22,6 +24,7 @@ use crate::util::env_var::get_chat_shell;
 /// # Arguments
 /// * `command` - The command to run
 /// * `max_result_size` - max size of output streams, truncating if required
+/// * `sandbox` - sandbox to run the command in, if any
 /// * `updates` - output stream to push informational messages about the progress
 /// # Returns
 /// A [`CommandResult`]
@@ -29,6 +32,7 @@ pub async fn run_command<W: Write>(
     os: &Os,
     command: &str,
     max_result_size: usize,
+    sandbox: Option<&Sandbox>,
     mut updates: Option<W>,
 ) -> Result<CommandResult> {
     let shell = get_chat_shell();
@@ -37,13 +41,19 @@ pub async fn run_command<W: Write>(
     let env_vars = env_vars_with_user_agent(os);
 
     // We need to maintain a handle on stderr and stdout, but pipe it to the terminal as well
-    let mut child = tokio::process::Command::new(shell)
-        .arg("-c")
+    let mut cmd = tokio::process::Command::new(shell);
+    cmd.arg("-c")
         .arg(command)
-        .envs(env_vars)
         .stdin(Stdio::inherit())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
+        .stderr(Stdio::piped());
+    match sandbox {
+        Some(sandbox) => sandbox.configure(&mut cmd, env_vars, &[USER_AGENT_ENV_VAR]),
+        None => {
+            cmd.envs(env_vars);
+        },
+    }
+    let mut child = cmd
         .spawn()
         .wrap_err_with(|| format!("Unable to spawn command '{}'", command))?;
 
@@ -144,7 +154,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&os, &mut stdout)
+            .invoke(&os, &mut stdout, None)
             .await
             .unwrap();
 
@@ -162,7 +172,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&os, &mut stdout)
+            .invoke(&os, &mut stdout, None)
             .await
             .unwrap();
 
@@ -180,7 +190,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&os, &mut stdout)
+            .invoke(&os, &mut stdout, None)
             .await
             .unwrap();
         if let OutputKind::Json(json) = out.output {
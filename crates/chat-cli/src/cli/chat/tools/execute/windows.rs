@@ -12,6 +12,7 @@ use tracing::error;
 
 use super::{
     CommandResult,
+    Sandbox,
     env_vars_with_user_agent,
     format_output,
 };
@@ -21,6 +22,7 @@ use crate::os::Os;
 /// # Arguments
 /// * `command` - The command to run
 /// * `max_result_size` - max size of output streams, truncating if required
+/// * `sandbox` - sandbox to run the command in, if any
 /// * `updates` - output stream to push informational messages about the progress
 /// # Returns
 /// A [`CommandResult`]
@@ -28,8 +30,14 @@ pub async fn run_command<W: Write>(
     os: &Os,
     command: &str,
     max_result_size: usize,
+    sandbox: Option<&Sandbox>,
     mut updates: Option<W>,
 ) -> Result<CommandResult> {
+    if let Some(sandbox) = sandbox {
+        // Sandboxes can't be created on Windows
+        match *sandbox {}
+    }
+
     // Set up environment variables with user agent metadata for CloudTrail tracking
     let env_vars = env_vars_with_user_agent(os);
 
@@ -136,7 +144,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&os, &mut stdout)
+            .invoke(&os, &mut stdout, None)
             .await
             .unwrap();
 
@@ -154,7 +162,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&os, &mut stdout)
+            .invoke(&os, &mut stdout, None)
             .await
             .unwrap();
 
@@ -172,7 +180,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&os, &mut stdout)
+            .invoke(&os, &mut stdout, None)
             .await
             .unwrap();
         if let OutputKind::Json(json) = out.output {
@@ -34,6 +34,10 @@ use crate::cli::agent::{
     PermissionEvalResult,
 };
 use crate::cli::chat::tools::display_purpose;
+use crate::cli::chat::util::documents::{
+    handle_documents_from_paths,
+    is_supported_document_type,
+};
 use crate::cli::chat::util::images::{
     handle_images_from_paths,
     is_supported_image_type,
@@ -62,6 +66,7 @@ pub enum FsReadOperation {
     Directory(FsDirectory),
     Search(FsSearch),
     Image(FsImage),
+    Document(FsDocument),
 }
 
 impl FsRead {
@@ -203,8 +208,8 @@ impl FsRead {
                                     ask = true;
                                 }
                             },
-                            FsReadOperation::Image(fs_image) => {
-                                let paths = &fs_image.image_paths;
+                            FsReadOperation::Image(FsImage { image_paths: paths })
+                            | FsReadOperation::Document(FsDocument { document_paths: paths }) => {
                                 let denied_match_set = paths
                                     .iter()
                                     .flat_map(|path| {
@@ -274,6 +279,7 @@ impl FsRead {
             // Multiple operations - combine results
             let mut combined_results = Vec::new();
             let mut all_images = Vec::new();
+            let mut all_documents = Vec::new();
             let mut has_non_image_ops = false;
             let mut success_ops = 0usize;
             let mut failed_ops = 0usize;
@@ -304,9 +310,17 @@ impl FsRead {
                                     images.len()
                                 ));
                             },
+                            OutputKind::Documents(documents) => {
+                                all_documents.extend(documents.clone());
+                                combined_results.push(format!(
+                                    "=== Operation {} Result (Documents) ===\n[{} documents processed]",
+                                    i + 1,
+                                    documents.len()
+                                ));
+                            },
                             // This branch won't be reached because single operation execution never returns a Mixed
                             // result
-                            OutputKind::Mixed { text: _, images: _ } => {},
+                            OutputKind::Mixed { .. } => {},
                         }
                     },
 
@@ -337,17 +351,23 @@ impl FsRead {
 
             let combined_text = combined_results.join("\n\n");
 
-            if !all_images.is_empty() && has_non_image_ops {
+            let has_attachments = !all_images.is_empty() || !all_documents.is_empty();
+            if has_attachments && (has_non_image_ops || (!all_images.is_empty() && !all_documents.is_empty())) {
                 Ok(InvokeOutput {
                     output: OutputKind::Mixed {
                         text: combined_text,
                         images: all_images,
+                        documents: all_documents,
                     },
                 })
             } else if !all_images.is_empty() {
                 Ok(InvokeOutput {
                     output: OutputKind::Images(all_images),
                 })
+            } else if !all_documents.is_empty() {
+                Ok(InvokeOutput {
+                    output: OutputKind::Documents(all_documents),
+                })
             } else {
                 Ok(InvokeOutput {
                     output: OutputKind::Text(combined_text),
@@ -364,6 +384,7 @@ impl FsReadOperation {
             FsReadOperation::Directory(fs_directory) => fs_directory.validate(os).await,
             FsReadOperation::Search(fs_search) => fs_search.validate(os).await,
             FsReadOperation::Image(fs_image) => fs_image.validate(os).await,
+            FsReadOperation::Document(fs_document) => fs_document.validate(os).await,
         }
     }
 
@@ -373,6 +394,7 @@ impl FsReadOperation {
             FsReadOperation::Directory(fs_directory) => fs_directory.queue_description(updates),
             FsReadOperation::Search(fs_search) => fs_search.queue_description(updates),
             FsReadOperation::Image(fs_image) => fs_image.queue_description(updates),
+            FsReadOperation::Document(fs_document) => fs_document.queue_description(updates),
         }
     }
 
@@ -382,6 +404,7 @@ impl FsReadOperation {
             FsReadOperation::Directory(fs_directory) => fs_directory.invoke(os, updates).await,
             FsReadOperation::Search(fs_search) => fs_search.invoke(os, updates).await,
             FsReadOperation::Image(fs_image) => fs_image.invoke(updates).await,
+            FsReadOperation::Document(fs_document) => fs_document.invoke(os, updates).await,
         }
     }
 }
@@ -434,6 +457,53 @@ impl FsImage {
     }
 }
 
+/// Read documents, eg. PDFs and office files, from given paths.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FsDocument {
+    pub document_paths: Vec<String>,
+}
+
+impl FsDocument {
+    pub async fn validate(&mut self, os: &Os) -> Result<()> {
+        for path in &self.document_paths {
+            let path = sanitize_path_tool_arg(os, path);
+            if !is_supported_document_type(&path.to_string_lossy()) {
+                bail!("'{}' is not a supported document type", path.display());
+            }
+            let is_file = os.fs.symlink_metadata(&path).await?.is_file();
+            if !is_file {
+                bail!("'{}' is not a file", path.display());
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn invoke(&self, os: &Os, updates: &mut impl Write) -> Result<InvokeOutput> {
+        let paths: Vec<String> = self
+            .document_paths
+            .iter()
+            .map(|path| sanitize_path_tool_arg(os, path).to_string_lossy().to_string())
+            .collect();
+        let valid_documents = handle_documents_from_paths(updates, &paths);
+        super::queue_function_result("Successfully read document", updates, false, false)?;
+        Ok(InvokeOutput {
+            output: OutputKind::Documents(valid_documents),
+        })
+    }
+
+    pub fn queue_description(&self, updates: &mut impl Write) -> Result<()> {
+        queue!(
+            updates,
+            style::Print("Reading documents: "),
+            StyledText::success_fg(),
+            style::Print(&self.document_paths.join("\n")),
+            style::Print("\n"),
+            StyledText::reset(),
+        )?;
+        Ok(())
+    }
+}
+
 /// Read lines from a file.
 #[derive(Debug, Clone, Deserialize)]
 pub struct FsLine {
@@ -912,6 +982,10 @@ mod tests {
             "operations": [{ "image_paths": ["/img1.png", "/img2.jpg"], "mode": "Image" }]
         }))
         .unwrap();
+        serde_json::from_value::<FsRead>(serde_json::json!({
+            "operations": [{ "document_paths": ["/report.pdf", "/budget.xlsx"], "mode": "Document" }]
+        }))
+        .unwrap();
 
         // Test mixed batch operations
         serde_json::from_value::<FsRead>(serde_json::json!({
@@ -919,7 +993,8 @@ mod tests {
                 { "path": "/file.txt", "mode": "Line" },
                 { "path": "/dir", "mode": "Directory", "depth": 1 },
                 { "path": "/log.txt", "mode": "Search", "pattern": "warning" },
-                { "image_paths": ["/photo.jpg"], "mode": "Image" }
+                { "image_paths": ["/photo.jpg"], "mode": "Image" },
+                { "document_paths": ["/spec.docx"], "mode": "Document" }
             ],
             "purpose": "Comprehensive file analysis"
         }))
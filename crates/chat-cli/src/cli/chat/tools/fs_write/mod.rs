@@ -50,6 +50,15 @@ use crate::theme::{
 use crate::util::paths;
 use crate::util::tool_permission_checker::is_tool_in_allowlist;
 
+mod patch;
+
+use patch::{
+    AppliedHunk,
+    FilePatch,
+    apply_hunks,
+    parse_patch,
+};
+
 static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
 static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
 
@@ -85,6 +94,14 @@ pub enum FsWrite {
         new_str: String,
         summary: Option<String>,
     },
+    /// Applies a unified diff that may change several files. Relative paths in the diff are
+    /// resolved against `path`.
+    #[serde(rename = "patch")]
+    Patch {
+        path: String,
+        diff: String,
+        summary: Option<String>,
+    },
 }
 
 impl FsWrite {
@@ -94,15 +111,33 @@ impl FsWrite {
             FsWrite::StrReplace { path, .. } => path.as_str(),
             FsWrite::Insert { path, .. } => path.as_str(),
             FsWrite::Append { path, .. } => path.as_str(),
+            FsWrite::Patch { path, .. } => path.as_str(),
         })
     }
 
+    /// Returns the paths of the files written to. For [FsWrite::Patch], this is every file changed
+    /// by the diff.
+    pub fn paths(&self, os: &Os) -> Vec<PathBuf> {
+        match self {
+            FsWrite::Patch { path, diff, .. } => patch_paths(path, diff)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|path| sanitize_path_tool_arg(os, path))
+                .collect(),
+            _ => vec![self.path(os)],
+        }
+    }
+
     pub async fn invoke(
         &self,
         os: &Os,
         output: &mut impl Write,
         line_tracker: &mut HashMap<String, FileLineTracker>,
     ) -> Result<InvokeOutput> {
+        if let FsWrite::Patch { .. } = self {
+            return self.invoke_patch(os, output, line_tracker).await;
+        }
+
         let cwd = os.env.current_dir()?;
         let path = self.path(os);
 
@@ -192,6 +227,7 @@ impl FsWrite {
                 file.push_str(new_str);
                 write_to_file(os, &path, file).await?;
             },
+            FsWrite::Patch { .. } => unreachable!("patches are applied by invoke_patch"),
         };
 
         self.update_line_tracker_after_invoke(os, line_tracker).await?;
@@ -199,6 +235,171 @@ impl FsWrite {
         Ok(Default::default())
     }
 
+    /// Applies every file of a [FsWrite::Patch], or none of them if any file fails to apply or be
+    /// written.
+    async fn invoke_patch(
+        &self,
+        os: &Os,
+        output: &mut impl Write,
+        line_tracker: &mut HashMap<String, FileLineTracker>,
+    ) -> Result<InvokeOutput> {
+        let cwd = os.env.current_dir()?;
+        let files = self.patch_files(os)?;
+
+        for file in &files {
+            let curr_lines = file.original.as_ref().map_or(0, |content| content.lines().count());
+            track_lines_before(line_tracker, file.path(), curr_lines, file.old.is_none());
+        }
+
+        // Write the patched files next to their destination first, under names no other file has,
+        // so that no file is changed unless all of them could be written.
+        let mut staged = Vec::new();
+        for file in &files {
+            let Some(new) = &file.new else {
+                continue;
+            };
+            let staged_path = new.with_file_name(format!(
+                ".{}.{}.patch",
+                new.file_name().unwrap_or_default().to_string_lossy(),
+                uuid::Uuid::new_v4().simple()
+            ));
+            let res = match new.parent() {
+                Some(parent) => os.fs.create_dir_all(parent).await,
+                None => Ok(()),
+            };
+            let res = match res {
+                Ok(()) => os.fs.write(&staged_path, &file.content).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = res {
+                for (staged_path, _) in staged {
+                    os.fs.remove_file(staged_path).await.ok();
+                }
+                bail!("failed to write {}: {err}", format_path(&cwd, new));
+            }
+            staged.push((staged_path, file));
+        }
+
+        // Then move the staged files in place and remove the deleted and moved files, restoring
+        // the files changed so far if any of it fails.
+        let mut changed = Vec::new();
+        let mut error = None;
+        let mut staged = staged.into_iter();
+        for (staged_path, file) in staged.by_ref() {
+            let new = file.new.as_deref().unwrap_or(Path::new(""));
+            if let Err(err) = os.fs.rename(&staged_path, new).await {
+                os.fs.remove_file(staged_path).await.ok();
+                error = Some(eyre!("failed to write {}: {err}", format_path(&cwd, new)));
+                break;
+            }
+            changed.push(PatchStep::Written(file));
+        }
+        if error.is_none() {
+            for file in &files {
+                let Some(old) = file.old.as_ref().filter(|old| file.new.as_ref() != Some(old)) else {
+                    continue;
+                };
+                if let Err(err) = os.fs.remove_file(old).await {
+                    error = Some(eyre!("failed to remove {}: {err}", format_path(&cwd, old)));
+                    break;
+                }
+                changed.push(PatchStep::Removed(file));
+            }
+        }
+        if let Some(error) = error {
+            for (staged_path, _) in staged {
+                os.fs.remove_file(staged_path).await.ok();
+            }
+            for step in changed.into_iter().rev() {
+                step.undo(os).await;
+            }
+            return Err(error);
+        }
+
+        for file in &files {
+            let description = match (&file.old, &file.new) {
+                (Some(_), None) => "Deleting: ",
+                (Some(old), Some(new)) if old != new => "Moving to: ",
+                (None, _) => "Creating: ",
+                (Some(_), Some(_)) => "Updating: ",
+            };
+            queue!(
+                output,
+                style::Print(description),
+                StyledText::success_fg(),
+                style::Print(format_path(&cwd, file.path())),
+                StyledText::reset(),
+                style::Print("\n"),
+            )?;
+
+            let (lines_added, lines_removed) = file.patch.lines_changed();
+            let after_lines = file.new.as_ref().map_or(0, |_| file.content.lines().count());
+            track_lines_after(line_tracker, file.path(), after_lines, lines_added, lines_removed);
+        }
+
+        Ok(Default::default())
+    }
+
+    /// Parses the diff of a [FsWrite::Patch] and applies it to every file it changes, without
+    /// writing anything.
+    fn patch_files(&self, os: &Os) -> Result<Vec<PatchedFile>> {
+        let FsWrite::Patch { path: base, diff, .. } = self else {
+            return Ok(Vec::new());
+        };
+
+        let mut files: Vec<PatchedFile> = Vec::new();
+        for patch in parse_patch(diff)? {
+            let resolve = |path: &Option<String>| {
+                path.as_ref()
+                    .map(|p| sanitize_path_tool_arg(os, Path::new(base).join(p)))
+            };
+            let (old, new) = (resolve(&patch.old_path), resolve(&patch.new_path));
+            let path = new.as_ref().or(old.as_ref()).cloned().unwrap_or_default();
+            if files.iter().any(|file| file.path() == path) {
+                bail!("{} is changed more than once in the diff", path.display());
+            }
+
+            if let (Some(old), Some(new)) = (&old, &new)
+                && old != new
+                && os.fs.exists(new)
+            {
+                bail!(
+                    "{} already exists, but the diff moves {} to it",
+                    new.display(),
+                    old.display()
+                );
+            }
+
+            let original = match &old {
+                Some(old) => {
+                    if !os.fs.exists(old) {
+                        bail!("{} does not exist", old.display());
+                    }
+                    Some(os.fs.read_to_string_sync(old)?)
+                },
+                None => {
+                    if os.fs.exists(&path) {
+                        bail!("{} already exists, but the diff creates it", path.display());
+                    }
+                    None
+                },
+            };
+            let (content, hunks) = apply_hunks(original.as_deref().unwrap_or_default(), &patch.hunks)
+                .map_err(|err| eyre!("{}: {err}", path.display()))?;
+
+            files.push(PatchedFile {
+                old,
+                new,
+                original,
+                content,
+                hunks,
+                patch,
+            });
+        }
+
+        Ok(files)
+    }
+
     async fn update_line_tracker_before_invoke(
         &self,
         os: &Os,
@@ -213,23 +414,7 @@ impl FsWrite {
             0
         };
 
-        let tracker = line_tracker.entry(path.to_string_lossy().to_string()).or_default();
-        match self {
-            FsWrite::Create { .. } => {
-                // For Create, always set prev_lines to 0 since we're creating a new file
-                if tracker.is_first_write {
-                    tracker.prev_fswrite_lines = 0;
-                }
-            },
-            _ => {
-                // For StrReplace, Insert, Append - if it's the first time we're tracking this file,
-                // set prev_lines to curr_lines so we only track changes from this point forward
-                if tracker.is_first_write {
-                    tracker.prev_fswrite_lines = curr_lines;
-                }
-            },
-        }
-        tracker.before_fswrite_lines = curr_lines;
+        track_lines_before(line_tracker, &path, curr_lines, matches!(self, FsWrite::Create { .. }));
 
         Ok(())
     }
@@ -248,15 +433,9 @@ impl FsWrite {
             0
         };
 
-        let tracker = line_tracker.entry(path.to_string_lossy().to_string()).or_default();
-        tracker.after_fswrite_lines = after_lines;
-
         // Calculate actual lines added and removed by analyzing the diff
         let (lines_added, lines_removed) = self.calculate_diff_lines(os).await?;
-        tracker.lines_added_by_agent = lines_added;
-        tracker.lines_removed_by_agent = lines_removed;
-
-        tracker.is_first_write = false;
+        track_lines_after(line_tracker, &path, after_lines, lines_added, lines_removed);
 
         Ok(())
     }
@@ -296,6 +475,10 @@ impl FsWrite {
                 let lines_added = new_str.lines().count();
                 (lines_added, 0)
             },
+            FsWrite::Patch { diff, .. } => parse_patch(diff)?
+                .iter()
+                .map(FilePatch::lines_changed)
+                .fold((0, 0), |(added, removed), (a, r)| (added + a, removed + r)),
         };
 
         Ok(result)
@@ -381,6 +564,39 @@ impl FsWrite {
                 // Display summary as purpose if available after the diff
                 super::display_purpose(self.get_summary(), output)?;
 
+                Ok(())
+            },
+            FsWrite::Patch { .. } => {
+                for file in self.patch_files(os)? {
+                    let relative_path = format_path(&cwd, file.path());
+                    let note = match (&file.old, &file.new) {
+                        (None, _) => " (new file)".to_string(),
+                        (Some(_), None) => " (deleted)".to_string(),
+                        (Some(old), Some(new)) if old != new => format!(" (moved from {})", format_path(&cwd, old)),
+                        (Some(_), Some(_)) => String::new(),
+                    };
+                    queue!(
+                        output,
+                        style::Print("Path: "),
+                        StyledText::success_fg(),
+                        style::Print(&relative_path),
+                        StyledText::reset(),
+                        style::Print(note),
+                        style::Print("\n\n"),
+                    )?;
+
+                    if file.new.is_some() {
+                        for AppliedHunk { start_line, old, new } in &file.hunks {
+                            let old = stylize_output_if_able(&relative_path, old);
+                            let new = stylize_output_if_able(&relative_path, new);
+                            print_diff(output, &old, &new, *start_line)?;
+                        }
+                    }
+                }
+
+                // Display summary as purpose if available after the diff
+                super::display_purpose(self.get_summary(), output)?;
+
                 Ok(())
             },
         }
@@ -407,6 +623,9 @@ impl FsWrite {
                     bail!("Content to append must not be empty")
                 };
             },
+            FsWrite::Patch { .. } => {
+                self.patch_files(os)?;
+            },
         }
 
         Ok(())
@@ -419,6 +638,8 @@ impl FsWrite {
             FsWrite::StrReplace { path, .. } => path,
             FsWrite::Insert { path, .. } => path,
             FsWrite::Append { path, .. } => path,
+            // Patches print the path of every file they change instead
+            FsWrite::Patch { .. } => return Ok(()),
         };
         // Sanitize the path to handle tilde expansion
         let path = sanitize_path_tool_arg(os, path);
@@ -460,6 +681,7 @@ impl FsWrite {
             FsWrite::StrReplace { summary, .. } => summary.as_ref(),
             FsWrite::Insert { summary, .. } => summary.as_ref(),
             FsWrite::Append { summary, .. } => summary.as_ref(),
+            FsWrite::Patch { summary, .. } => summary.as_ref(),
         }
     }
 
@@ -521,27 +743,37 @@ impl FsWrite {
 
                 match (allow_set, deny_set) {
                     (Ok(allow_set), Ok(deny_set)) => {
-                        match self {
+                        let paths = match self {
                             Self::Create { path, .. }
                             | Self::Insert { path, .. }
                             | Self::Append { path, .. }
-                            | Self::StrReplace { path, .. } => {
-                                let Ok(path) = paths::canonicalizes_path(os, path) else {
-                                    return PermissionEvalResult::Ask;
-                                };
-                                let denied_match_set = deny_set.matches(path.as_ref() as &str);
-                                if !denied_match_set.is_empty() {
-                                    return PermissionEvalResult::Deny({
-                                        denied_match_set
-                                            .iter()
-                                            .filter_map(|i| sanitized_deny_list.get(*i).map(|s| (*s).clone()))
-                                            .collect::<Vec<_>>()
-                                    });
-                                }
-                                if is_in_allowlist || allow_set.is_match(path.as_ref() as &str) {
-                                    return PermissionEvalResult::Allow;
-                                }
+                            | Self::StrReplace { path, .. } => vec![path.clone()],
+                            Self::Patch { path, diff, .. } => match patch_paths(path, diff) {
+                                Ok(paths) => paths,
+                                Err(_) => return PermissionEvalResult::Ask,
                             },
+                        };
+
+                        // Every file written to must be allowed
+                        let mut denied = Vec::new();
+                        let mut is_allowed = true;
+                        for path in &paths {
+                            let Ok(path) = paths::canonicalizes_path(os, path) else {
+                                return PermissionEvalResult::Ask;
+                            };
+                            let denied_match_set = deny_set.matches(path.as_ref() as &str);
+                            for rule in denied_match_set.iter().filter_map(|i| sanitized_deny_list.get(*i)) {
+                                if !denied.contains(*rule) {
+                                    denied.push((*rule).clone());
+                                }
+                            }
+                            is_allowed &= is_in_allowlist || allow_set.is_match(path.as_ref() as &str);
+                        }
+                        if !denied.is_empty() {
+                            return PermissionEvalResult::Deny(denied);
+                        }
+                        if is_allowed {
+                            return PermissionEvalResult::Allow;
                         }
                         PermissionEvalResult::Ask
                     },
@@ -563,6 +795,107 @@ impl FsWrite {
     }
 }
 
+/// A file changed by a [FsWrite::Patch].
+#[derive(Debug)]
+struct PatchedFile {
+    /// Path the file is read from, `None` if the patch creates it.
+    old: Option<PathBuf>,
+    /// Path the patched file is written to, `None` if the patch deletes it.
+    new: Option<PathBuf>,
+    /// Content of the file before the patch.
+    original: Option<String>,
+    /// Content of the file after the patch.
+    content: String,
+    hunks: Vec<AppliedHunk>,
+    patch: FilePatch,
+}
+
+impl PatchedFile {
+    fn path(&self) -> &Path {
+        self.new.as_deref().or(self.old.as_deref()).unwrap_or(Path::new(""))
+    }
+}
+
+/// A change made to the file system while applying a patch, which can be undone.
+enum PatchStep<'a> {
+    /// The patched file was written to its new path.
+    Written(&'a PatchedFile),
+    /// The file was removed from its old path, after being deleted or moved.
+    Removed(&'a PatchedFile),
+}
+
+impl PatchStep<'_> {
+    /// Restores the files changed by the step from their original content.
+    async fn undo(self, os: &Os) {
+        let (PatchStep::Written(file) | PatchStep::Removed(file)) = self;
+        let res = match self {
+            PatchStep::Written(file) => match (&file.old, &file.original, &file.new) {
+                (Some(old), Some(original), Some(new)) if old == new => os.fs.write(new, original).await,
+                (_, _, Some(new)) => os.fs.remove_file(new).await,
+                _ => Ok(()),
+            },
+            PatchStep::Removed(file) => match (&file.old, &file.original) {
+                (Some(old), Some(original)) => os.fs.write(old, original).await,
+                _ => Ok(()),
+            },
+        };
+        if let Err(err) = res {
+            error!(
+                ?err,
+                "Failed to restore {} after the patch failed",
+                file.path().display()
+            );
+        }
+    }
+}
+
+/// Returns the unsanitized path of every file read or written by the diff, resolved against
+/// `base`.
+fn patch_paths(base: &str, diff: &str) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for patch in parse_patch(diff)? {
+        for path in [patch.new_path, patch.old_path].into_iter().flatten() {
+            let path = Path::new(base).join(path).to_string_lossy().to_string();
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+/// Records the line count of `path` before it's written to.
+fn track_lines_before(
+    line_tracker: &mut HashMap<String, FileLineTracker>,
+    path: &Path,
+    curr_lines: usize,
+    is_create: bool,
+) {
+    let tracker = line_tracker.entry(path.to_string_lossy().to_string()).or_default();
+    if tracker.is_first_write {
+        // For Create, always set prev_lines to 0 since we're creating a new file. Otherwise, if
+        // it's the first time we're tracking this file, set prev_lines to curr_lines so we only
+        // track changes from this point forward
+        tracker.prev_fswrite_lines = if is_create { 0 } else { curr_lines };
+    }
+    tracker.before_fswrite_lines = curr_lines;
+}
+
+/// Records the line count of `path` and the lines changed after it's written to.
+fn track_lines_after(
+    line_tracker: &mut HashMap<String, FileLineTracker>,
+    path: &Path,
+    after_lines: usize,
+    lines_added: usize,
+    lines_removed: usize,
+) {
+    let tracker = line_tracker.entry(path.to_string_lossy().to_string()).or_default();
+    tracker.after_fswrite_lines = after_lines;
+    tracker.lines_added_by_agent = lines_added;
+    tracker.lines_removed_by_agent = lines_removed;
+    tracker.is_first_write = false;
+}
+
 /// Writes `content` to `path`, adding a newline if necessary.
 async fn write_to_file(os: &Os, path: impl AsRef<Path>, mut content: String) -> Result<()> {
     let path_ref = path.as_ref();
@@ -867,6 +1200,7 @@ mod tests {
     use crate::cli::chat::util::test::{
         TEST_FILE_CONTENTS,
         TEST_FILE_PATH,
+        TEST_HIDDEN_FILE_PATH,
         setup_test_directory,
     };
 
@@ -1220,6 +1554,101 @@ mod tests {
         assert!(result.is_err(), "Appending to non-existent file should fail");
     }
 
+    #[tokio::test]
+    async fn test_fs_write_tool_patch() {
+        let os = setup_test_directory().await;
+        let mut stdout = std::io::stdout();
+        let mut line_tracker = HashMap::new();
+
+        let diff = "--- a/test_file.txt\n+++ b/test_file.txt\n@@ -2,2 +2,2 @@\n 2: This is line 2\n-3: asdf\n+3: qwerty\n 4: Hello world!\n--- /dev/null\n+++ b/aaaa1/new.txt\n@@ -0,0 +1,2 @@\n+new\n+file\n--- a/aaaa2/.hidden\n+++ /dev/null\n@@ -1 +0,0 @@\n-this is a hidden file\n";
+        let mut fs_write = serde_json::from_value::<FsWrite>(serde_json::json!({
+            "path": "/",
+            "command": "patch",
+            "diff": diff,
+        }))
+        .unwrap();
+        fs_write.validate(&os).await.unwrap();
+        assert_eq!(fs_write.paths(&os).len(), 3);
+        fs_write.invoke(&os, &mut stdout, &mut line_tracker).await.unwrap();
+
+        assert_eq!(
+            os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(),
+            TEST_FILE_CONTENTS.replace("asdf", "qwerty")
+        );
+        assert_eq!(os.fs.read_to_string("/aaaa1/new.txt").await.unwrap(), "new\nfile\n");
+        assert!(!os.fs.exists(TEST_HIDDEN_FILE_PATH));
+
+        let tracker = line_tracker
+            .get(
+                &sanitize_path_tool_arg(&os, TEST_FILE_PATH)
+                    .to_string_lossy()
+                    .to_string(),
+            )
+            .unwrap();
+        assert_eq!(tracker.before_fswrite_lines, 4);
+        assert_eq!(tracker.after_fswrite_lines, 4);
+        assert_eq!((tracker.lines_added_by_agent, tracker.lines_removed_by_agent), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_tool_patch_is_atomic() {
+        let os = setup_test_directory().await;
+        let mut stdout = std::io::stdout();
+        let mut line_tracker = HashMap::new();
+
+        // The second file doesn't apply, so the first must not be changed either
+        let diff = "--- a/test_file.txt\n+++ b/test_file.txt\n@@ -3 +3 @@\n-3: asdf\n+3: qwerty\n--- a/aaaa2/.hidden\n+++ b/aaaa2/.hidden\n@@ -1 +1 @@\n-not in the file\n+replacement\n";
+        let mut fs_write = serde_json::from_value::<FsWrite>(serde_json::json!({
+            "path": "/",
+            "command": "patch",
+            "diff": diff,
+        }))
+        .unwrap();
+        assert!(fs_write.validate(&os).await.is_err());
+        assert!(fs_write.invoke(&os, &mut stdout, &mut line_tracker).await.is_err());
+        assert_eq!(os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(), TEST_FILE_CONTENTS);
+        assert!(line_tracker.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_tool_patch_keeps_existing_files() {
+        let os = setup_test_directory().await;
+        let mut stdout = std::io::stdout();
+        let mut line_tracker = HashMap::new();
+        let patch = |diff: &str| {
+            serde_json::from_value::<FsWrite>(serde_json::json!({
+                "path": "/",
+                "command": "patch",
+                "diff": diff,
+            }))
+            .unwrap()
+        };
+
+        // Moving a file onto an existing one is rejected
+        let mut fs_write = patch("--- a/test_file.txt\n+++ b/aaaa2/.hidden\n@@ -3 +3 @@\n-3: asdf\n+3: qwerty\n");
+        assert!(fs_write.validate(&os).await.is_err());
+        assert!(fs_write.invoke(&os, &mut stdout, &mut line_tracker).await.is_err());
+        assert_eq!(os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(), TEST_FILE_CONTENTS);
+        assert_eq!(
+            os.fs.read_to_string(TEST_HIDDEN_FILE_PATH).await.unwrap(),
+            "this is a hidden file"
+        );
+
+        // Patched files are not staged over files of the user
+        os.fs.write("/.test_file.txt.patch", "user file").await.unwrap();
+        let mut fs_write = patch("--- a/test_file.txt\n+++ b/test_file.txt\n@@ -3 +3 @@\n-3: asdf\n+3: qwerty\n");
+        fs_write.validate(&os).await.unwrap();
+        fs_write.invoke(&os, &mut stdout, &mut line_tracker).await.unwrap();
+        assert_eq!(
+            os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(),
+            TEST_FILE_CONTENTS.replace("asdf", "qwerty")
+        );
+        assert_eq!(
+            os.fs.read_to_string("/.test_file.txt.patch").await.unwrap(),
+            "user file"
+        );
+    }
+
     #[test]
     fn test_lines_with_context() {
         let content = "Hello\nWorld!\nhow\nare\nyou\ntoday?";
@@ -1460,6 +1889,28 @@ mod tests {
 
         let res = tool_exact_allowed_dir.eval_perm(&os, &agent);
         assert!(matches!(res, PermissionEvalResult::Allow));
+
+        // Patches must be allowed for every file they change
+        let patch = |diff: &str| {
+            serde_json::from_value::<FsWrite>(serde_json::json!({
+                "path": "/",
+                "command": "patch",
+                "diff": diff,
+            }))
+            .unwrap()
+        };
+        agent.allowed_tools.clear();
+        let res = patch("--- a/some/allow/path/a.txt\n+++ b/some/allow/path/a.txt\n@@ -1 +1 @@\n-a\n+b\n")
+            .eval_perm(&os, &agent);
+        assert!(matches!(res, PermissionEvalResult::Allow));
+
+        let res = patch("--- a/some/allow/path/a.txt\n+++ b/some/allow/path/a.txt\n@@ -1 +1 @@\n-a\n+b\n--- a/elsewhere.txt\n+++ b/elsewhere.txt\n@@ -1 +1 @@\n-a\n+b\n").eval_perm(&os, &agent);
+        assert!(matches!(res, PermissionEvalResult::Ask));
+
+        let res = patch("--- a/some/allow/path/a.txt\n+++ b/some/allow/path/a.txt\n@@ -1 +1 @@\n-a\n+b\n--- a/some/denied/path/b.txt\n+++ b/some/denied/path/b.txt\n@@ -1 +1 @@\n-a\n+b\n").eval_perm(&os, &agent);
+        assert!(
+            matches!(res, PermissionEvalResult::Deny(ref deny_list) if deny_list == &vec![DENIED_PATH_ONE.to_string()])
+        );
     }
 
     #[tokio::test]
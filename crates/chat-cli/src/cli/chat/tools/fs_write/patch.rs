@@ -0,0 +1,396 @@
+//! Parsing and applying unified diffs for the `patch` command.
+//!
+//! Hunk headers are only used as a hint for where a hunk starts, since diffs written by a model
+//! often have wrong line numbers and counts. Every hunk is instead located by searching the file
+//! for its context and removed lines.
+
+use eyre::{
+    Result,
+    bail,
+    eyre,
+};
+
+/// Maximum number of context lines that may be ignored at either end of a hunk that doesn't
+/// otherwise apply, the same as the default fuzz factor of GNU patch.
+const MAX_FUZZ: usize = 2;
+
+/// The changes a diff makes to a single file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// Path of the file before the change, `None` if the patch creates the file.
+    pub old_path: Option<String>,
+    /// Path of the file after the change, `None` if the patch deletes the file.
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// Returns the `(added, removed)` line counts of the patch.
+    pub fn lines_changed(&self) -> (usize, usize) {
+        self.hunks
+            .iter()
+            .flat_map(|hunk| &hunk.lines)
+            .fold((0, 0), |(added, removed), line| match line {
+                HunkLine::Add(_) => (added + 1, removed),
+                HunkLine::Remove(_) => (added, removed + 1),
+                HunkLine::Context(_) => (added, removed),
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// 1-indexed line the hunk starts at according to its header, or 0 if unknown.
+    pub old_start: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+/// A hunk as it was applied to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedHunk {
+    /// 1-indexed line of the original file the hunk was applied at.
+    pub start_line: usize,
+    /// The lines of the original file replaced by the hunk.
+    pub old: String,
+    /// The lines the hunk replaced them with.
+    pub new: String,
+}
+
+/// Parses a unified diff, which may change several files. Lines outside of the file headers and
+/// hunks (eg. `diff --git` and `index` lines) are ignored.
+pub fn parse_patch(diff: &str) -> Result<Vec<FilePatch>> {
+    let lines = diff.trim_end_matches(['\n', '\r']).lines().collect::<Vec<_>>();
+    let mut patches = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(old) = lines[i].strip_prefix("--- ") else {
+            if lines[i].starts_with("@@") {
+                bail!("hunk '{}' is not preceded by '---' and '+++' file headers", lines[i]);
+            }
+            i += 1;
+            continue;
+        };
+        let Some(new) = lines.get(i + 1).and_then(|line| line.strip_prefix("+++ ")) else {
+            bail!("expected a '+++' line after '{}'", lines[i]);
+        };
+        i += 2;
+
+        let mut patch = FilePatch {
+            old_path: parse_header_path(old, "a/"),
+            new_path: parse_header_path(new, "b/"),
+            hunks: Vec::new(),
+        };
+        if patch.old_path.is_none() && patch.new_path.is_none() {
+            bail!("the file headers '--- {old}' and '+++ {new}' don't name a file");
+        }
+
+        while let Some(header) = lines.get(i).filter(|line| line.starts_with("@@")) {
+            i += 1;
+            let (old_start, mut old_left) = parse_hunk_range(header, '-').unwrap_or_default();
+            let (_, mut new_left) = parse_hunk_range(header, '+').unwrap_or_default();
+            let mut hunk = Hunk {
+                old_start,
+                lines: Vec::new(),
+            };
+            while let Some(line) = lines.get(i) {
+                // Lines removing `-- x` or adding `++ x` look like file headers, so those are only
+                // recognized once the line counts of the hunk header have been consumed
+                let in_body = old_left > 0 || new_left > 0;
+                if line.starts_with("@@") || line.starts_with("diff ") || (!in_body && is_file_header(&lines[i..])) {
+                    break;
+                }
+                i += 1;
+                let hunk_line = match line.split_at_checked(1) {
+                    // Some editors strip the space from empty context lines
+                    None => HunkLine::Context(String::new()),
+                    Some((" ", rest)) => HunkLine::Context(rest.to_string()),
+                    Some(("-", rest)) => HunkLine::Remove(rest.to_string()),
+                    Some(("+", rest)) => HunkLine::Add(rest.to_string()),
+                    // "\ No newline at end of file"
+                    Some(("\\", _)) => continue,
+                    _ => return Err(eyre!("unexpected line in hunk '{header}': '{line}'")),
+                };
+                if !matches!(hunk_line, HunkLine::Add(_)) {
+                    old_left = old_left.saturating_sub(1);
+                }
+                if !matches!(hunk_line, HunkLine::Remove(_)) {
+                    new_left = new_left.saturating_sub(1);
+                }
+                hunk.lines.push(hunk_line);
+            }
+            if !hunk.lines.iter().any(|line| !matches!(line, HunkLine::Context(_))) {
+                bail!("hunk '{header}' doesn't change any lines");
+            }
+            patch.hunks.push(hunk);
+        }
+
+        if patch.hunks.is_empty() && patch.old_path == patch.new_path {
+            bail!(
+                "the diff for '{}' has no hunks",
+                patch.new_path.as_deref().unwrap_or_default()
+            );
+        }
+        patches.push(patch);
+    }
+
+    if patches.is_empty() {
+        bail!("the diff doesn't contain any '---' and '+++' file headers");
+    }
+    Ok(patches)
+}
+
+/// Applies `hunks` in order to `content`, returning the patched content and where each hunk was
+/// applied.
+///
+/// A hunk is applied at the position nearest to its header that matches its context and removed
+/// lines, first exactly and then ignoring trailing whitespace. If there is no such position, up
+/// to [MAX_FUZZ] context lines are ignored at either end of the hunk.
+pub fn apply_hunks(content: &str, hunks: &[Hunk]) -> Result<(String, Vec<AppliedHunk>)> {
+    let line_ending = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let lines = content.lines().collect::<Vec<_>>();
+
+    let mut result: Vec<&str> = Vec::new();
+    let mut applied = Vec::new();
+    // Index of the first line of the original file that hasn't been copied to the result yet.
+    let mut copied = 0;
+    // How far the hunks applied so far were from the position in their header.
+    let mut offset = 0isize;
+    for (n, hunk) in hunks.iter().enumerate() {
+        let Some((pos, front, back)) = locate_hunk(&lines, hunk, copied, offset) else {
+            let old = hunk_old_lines(hunk, 0, 0);
+            bail!(
+                "hunk {} does not apply, the file doesn't contain the lines:\n{}",
+                n + 1,
+                old.join("\n")
+            );
+        };
+        let trimmed = &hunk.lines[front..hunk.lines.len() - back];
+        let old_len = trimmed.iter().filter(|line| !matches!(line, HunkLine::Add(_))).count();
+        offset = pos as isize - (hunk_hint(hunk) + front) as isize;
+
+        result.extend(&lines[copied..pos]);
+        let window = &lines[pos..pos + old_len];
+        let mut window_lines = window.iter();
+        let mut new = Vec::new();
+        for line in trimmed {
+            match line {
+                // Keep the file's own version of lines that only matched ignoring whitespace
+                HunkLine::Context(_) => new.extend(window_lines.next()),
+                HunkLine::Remove(_) => {
+                    window_lines.next();
+                },
+                HunkLine::Add(added) => new.push(added.as_str()),
+            }
+        }
+        applied.push(AppliedHunk {
+            start_line: pos + 1,
+            old: join_lines(window, "\n"),
+            new: join_lines(&new, "\n"),
+        });
+        result.extend(new);
+        copied = pos + old_len;
+    }
+    result.extend(&lines[copied..]);
+
+    let mut patched = result.join(line_ending);
+    if !result.is_empty() && (content.is_empty() || content.ends_with('\n')) {
+        patched.push_str(line_ending);
+    }
+    Ok((patched, applied))
+}
+
+/// Returns the position to apply `hunk` at, along with the number of context lines ignored at the
+/// front and back of the hunk.
+fn locate_hunk(lines: &[&str], hunk: &Hunk, min_pos: usize, offset: isize) -> Option<(usize, usize, usize)> {
+    let leading = hunk
+        .lines
+        .iter()
+        .take_while(|l| matches!(l, HunkLine::Context(_)))
+        .count();
+    let trailing = hunk
+        .lines
+        .iter()
+        .rev()
+        .take_while(|l| matches!(l, HunkLine::Context(_)))
+        .count();
+    let hint = (hunk_hint(hunk) as isize + offset).max(0) as usize;
+
+    let mut ignored = None;
+    for fuzz in 0..=MAX_FUZZ {
+        let (front, back) = (fuzz.min(leading), fuzz.min(trailing));
+        if ignored == Some(front + back) {
+            // The hunk has no more context lines to ignore
+            break;
+        }
+        ignored = Some(front + back);
+        let old = hunk_old_lines(hunk, front, back);
+        let hint = hint + front;
+        if old.is_empty() {
+            return Some((hint.clamp(min_pos, lines.len()), front, back));
+        }
+        if old.len() > lines.len().saturating_sub(min_pos) {
+            continue;
+        }
+
+        let mut candidates = (min_pos..=lines.len() - old.len()).collect::<Vec<_>>();
+        candidates.sort_by_key(|pos| pos.abs_diff(hint));
+        for matches in [
+            (|a: &str, b: &str| a == b) as fn(&str, &str) -> bool,
+            |a: &str, b: &str| a.trim_end() == b.trim_end(),
+        ] {
+            let found = candidates.iter().find(|pos| {
+                lines[**pos..**pos + old.len()]
+                    .iter()
+                    .zip(&old)
+                    .all(|(line, old)| matches(line, old))
+            });
+            if let Some(pos) = found {
+                return Some((*pos, front, back));
+            }
+        }
+    }
+    None
+}
+
+/// Returns the context and removed lines of `hunk`, skipping `front` lines from the start and
+/// `back` lines from the end.
+fn hunk_old_lines(hunk: &Hunk, front: usize, back: usize) -> Vec<&str> {
+    hunk.lines[front..hunk.lines.len() - back]
+        .iter()
+        .filter_map(|line| match line {
+            HunkLine::Context(line) | HunkLine::Remove(line) => Some(line.as_str()),
+            HunkLine::Add(_) => None,
+        })
+        .collect()
+}
+
+/// Returns the 0-indexed line the header of `hunk` says it starts at. Hunks that only add lines
+/// give the line they are added after.
+fn hunk_hint(hunk: &Hunk) -> usize {
+    let only_adds = hunk.lines.iter().all(|line| matches!(line, HunkLine::Add(_)));
+    if only_adds {
+        hunk.old_start
+    } else {
+        hunk.old_start.saturating_sub(1)
+    }
+}
+
+fn join_lines(lines: &[&str], line_ending: &str) -> String {
+    lines.iter().fold(String::new(), |mut acc, line| {
+        acc.push_str(line);
+        acc.push_str(line_ending);
+        acc
+    })
+}
+
+/// Returns whether `lines` starts with a `---` and `+++` file header pair.
+fn is_file_header(lines: &[&str]) -> bool {
+    matches!(lines, [old, new, ..] if old.starts_with("--- ") && new.starts_with("+++ "))
+}
+
+/// Returns the path in a `---` or `+++` header, without its git `a/` or `b/` prefix and any
+/// trailing timestamp. Returns `None` for `/dev/null`.
+fn parse_header_path(header: &str, git_prefix: &str) -> Option<String> {
+    let path = header.split('\t').next().unwrap_or_default().trim();
+    if path == "/dev/null" || path.is_empty() {
+        return None;
+    }
+    Some(path.strip_prefix(git_prefix).unwrap_or(path).to_string())
+}
+
+/// Returns the start line and line count of the old (`-`) or new (`+`) range of a
+/// `@@ -l,s +l,s @@` hunk header. The count defaults to 1 when it's omitted.
+fn parse_hunk_range(header: &str, side: char) -> Option<(usize, usize)> {
+    let range = header.split_whitespace().find_map(|part| part.strip_prefix(side))?;
+    let (start, count) = range.split_once(',').unwrap_or((range, "1"));
+    Some((start.parse().ok()?, count.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "fn main() {\n    let a = 1;\n    let b = 2;\n    println!(\"{}\", a + b);\n}\n\nfn other() {\n    todo!()\n}\n";
+
+    #[test]
+    fn test_parse_patch() {
+        let diff = "diff --git a/src/main.rs b/src/main.rs\nindex 123..456 100644\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    let a = 1;\n+    let a = 3;\n     let b = 2;\n--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1,2 @@\n+hello\n+world\n";
+        let patches = parse_patch(diff).unwrap();
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0].old_path.as_deref(), Some("src/main.rs"));
+        assert_eq!(patches[0].new_path.as_deref(), Some("src/main.rs"));
+        assert_eq!(patches[0].hunks[0].old_start, 1);
+        assert_eq!(patches[0].lines_changed(), (1, 1));
+        assert_eq!(patches[1].old_path, None);
+        assert_eq!(patches[1].new_path.as_deref(), Some("new.txt"));
+        assert_eq!(patches[1].hunks[0].lines, vec![
+            HunkLine::Add("hello".to_string()),
+            HunkLine::Add("world".to_string())
+        ]);
+
+        // Hunk lines removing `-- x` and adding `++ x` aren't file headers
+        let diff = "--- a/q.sql\n+++ b/q.sql\n@@ -1,2 +1,2 @@\n--- old comment\n+++ new comment\n select 1;\n--- a/r.sql\n+++ b/r.sql\n@@ -1 +1 @@\n-a\n+b\n";
+        let patches = parse_patch(diff).unwrap();
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0].hunks[0].lines, vec![
+            HunkLine::Remove("-- old comment".to_string()),
+            HunkLine::Add("++ new comment".to_string()),
+            HunkLine::Context("select 1;".to_string())
+        ]);
+        assert_eq!(patches[1].new_path.as_deref(), Some("r.sql"));
+
+        assert!(parse_patch("@@ -1 +1 @@\n-a\n+b\n").is_err());
+        assert!(parse_patch("just some text").is_err());
+    }
+
+    #[test]
+    fn test_apply_hunks() {
+        // Wrong line numbers in the header are corrected by searching for the context
+        let diff = "--- a/main.rs\n+++ b/main.rs\n@@ -5,3 +5,3 @@\n     let a = 1;\n-    let b = 2;\n+    let b = 3;\n     println!(\"{}\", a + b);\n@@ -8,1 +8,1 @@\n fn other() {\n-    todo!()\n+    unimplemented!()\n }\n";
+        let patches = parse_patch(diff).unwrap();
+        let (patched, applied) = apply_hunks(FILE, &patches[0].hunks).unwrap();
+        assert_eq!(
+            patched,
+            FILE.replace("let b = 2", "let b = 3")
+                .replace("todo!", "unimplemented!")
+        );
+        assert_eq!(applied[0].start_line, 2);
+        assert_eq!(applied[1].start_line, 7);
+        assert_eq!(applied[1].new, "fn other() {\n    unimplemented!()\n}\n");
+
+        // Context that doesn't match is ignored up to the fuzz factor
+        let diff = "--- a/main.rs\n+++ b/main.rs\n@@ -1,4 +1,4 @@\n fn start() {\n     let a = 1;\n-    let b = 2;\n+    let b = 4;\n";
+        let patches = parse_patch(diff).unwrap();
+        let (patched, _) = apply_hunks(FILE, &patches[0].hunks).unwrap();
+        assert_eq!(patched, FILE.replace("let b = 2", "let b = 4"));
+
+        // Removed lines must match
+        let diff = "--- a/main.rs\n+++ b/main.rs\n@@ -1,2 +1,2 @@\n fn main() {\n-    let a = 5;\n+    let a = 6;\n";
+        let patches = parse_patch(diff).unwrap();
+        assert!(apply_hunks(FILE, &patches[0].hunks).is_err());
+    }
+
+    #[test]
+    fn test_apply_hunks_whitespace_and_new_files() {
+        let diff = "--- a/main.rs\n+++ b/main.rs\n@@ -2,1 +2,1 @@\n-    let a = 1;   \n+    let a = 7;\n";
+        let patches = parse_patch(diff).unwrap();
+        let (patched, _) = apply_hunks(FILE, &patches[0].hunks).unwrap();
+        assert_eq!(patched, FILE.replace("let a = 1", "let a = 7"));
+
+        let crlf = "a\r\nb\r\n";
+        let diff = "--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n";
+        let patches = parse_patch(diff).unwrap();
+        assert_eq!(apply_hunks(crlf, &patches[0].hunks).unwrap().0, "a\r\nc\r\n");
+
+        let diff = "--- /dev/null\n+++ b/f\n@@ -0,0 +1,2 @@\n+a\n+b\n";
+        let patches = parse_patch(diff).unwrap();
+        assert_eq!(apply_hunks("", &patches[0].hunks).unwrap().0, "a\nb\n");
+    }
+}
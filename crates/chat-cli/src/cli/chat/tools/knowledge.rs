@@ -433,9 +433,20 @@ impl Knowledge {
                         } else {
                             let mut output = format!("Search results for \"{}\":\n\n", search.query);
                             for result in results {
-                                if let Some(text) = result.text() {
-                                    output.push_str(&format!("{}\n\n", text));
+                                let Some(text) = result.text() else {
+                                    continue;
+                                };
+                                // Cite source code as `path:start-end`, which can be read back with fs_read,
+                                // and documents with the sheet, slide or cell the result was found in
+                                let label = result.symbol().map(str::to_string).or_else(|| result.section());
+                                match (result.location(), label) {
+                                    (Some(location), Some(label)) => {
+                                        output.push_str(&format!("{} ({})\n", location, label));
+                                    },
+                                    (Some(location), None) => output.push_str(&format!("{}\n", location)),
+                                    _ => {},
                                 }
+                                output.push_str(&format!("{}\n\n", text));
                             }
                             output
                         }
@@ -552,10 +563,17 @@ impl Knowledge {
             // Description/path line (indented like knowledge entries)
             // Use actual path from operation type if available, otherwise use message
             let description = match &op.operation_type {
-                semantic_search_client::OperationType::Indexing { path, .. } => path.clone(),
+                semantic_search_client::OperationType::Indexing { path, .. }
+                | semantic_search_client::OperationType::Updating { path, .. } => path.clone(),
                 semantic_search_client::OperationType::Clearing => op.message.clone(),
             };
             output.push_str(&format!("   {}\n", description));
+            if matches!(
+                op.operation_type,
+                semantic_search_client::OperationType::Updating { .. }
+            ) {
+                output.push_str(&format!("   {}\n", op.message));
+            }
 
             // Status/progress line with ETA if available
             if op.is_cancelled {
@@ -58,11 +58,15 @@ use super::consts::{
     USER_AGENT_VERSION_KEY,
     USER_AGENT_VERSION_VALUE,
 };
+use super::util::documents::RichDocumentBlocks;
 use super::util::images::RichImageBlocks;
 use crate::cli::agent::{
     Agent,
     PermissionEvalResult,
 };
+use crate::cli::chat::cli::hooks::HookDecision;
+use crate::cli::chat::cli::model::ModelInfo;
+use crate::cli::chat::context::ContextManager;
 use crate::cli::chat::line_tracker::FileLineTracker;
 use crate::os::Os;
 use crate::theme::{
@@ -149,20 +153,22 @@ impl Tool {
         stdout: &mut impl Write,
         line_tracker: &mut HashMap<String, FileLineTracker>,
         agents: &crate::cli::agent::Agents,
+        model_info: Option<&ModelInfo>,
+        context_manager: Option<&ContextManager>,
     ) -> Result<InvokeOutput> {
         let active_agent = agents.get_active();
         match self {
             Tool::FsRead(fs_read) => fs_read.invoke(os, stdout).await,
             Tool::FsWrite(fs_write) => fs_write.invoke(os, stdout, line_tracker).await,
-            Tool::ExecuteCommand(execute_command) => execute_command.invoke(os, stdout).await,
+            Tool::ExecuteCommand(execute_command) => execute_command.invoke(os, stdout, active_agent).await,
             Tool::UseAws(use_aws) => use_aws.invoke(os, stdout).await,
-            Tool::Custom(custom_tool) => custom_tool.invoke(os, stdout).await,
+            Tool::Custom(custom_tool) => custom_tool.invoke(os, stdout, model_info).await,
             Tool::GhIssue(gh_issue) => gh_issue.invoke(os, stdout).await,
             Tool::Introspect(introspect) => introspect.invoke(os, stdout).await,
             Tool::Knowledge(knowledge) => knowledge.invoke(os, stdout, active_agent).await,
             Tool::Thinking(think) => think.invoke(stdout).await,
             Tool::Todo(todo) => todo.invoke(os, stdout).await,
-            Tool::Delegate(delegate) => delegate.invoke(os, stdout, agents).await,
+            Tool::Delegate(delegate) => delegate.invoke(os, stdout, agents, context_manager).await,
         }
     }
 
@@ -334,6 +340,8 @@ pub struct QueuedTool {
     pub accepted: bool,
     pub tool: Tool,
     pub tool_input: serde_json::Value,
+    /// What the `preToolUse` hooks decided, if any of them returned a decision
+    pub hook_decision: Option<HookDecision>,
 }
 
 /// The schema specification describing a tool's fields.
@@ -354,7 +362,7 @@ impl InvokeOutput {
                 .map_err(|err| error!(?err, "failed to serialize tool to json"))
                 .unwrap_or_default()
                 .into(),
-            OutputKind::Images(_) => "".into(),
+            OutputKind::Images(_) | OutputKind::Documents(_) => "".into(),
             OutputKind::Mixed { text, .. } => text.as_str().into(), // Return the text part
         }
     }
@@ -366,7 +374,12 @@ pub enum OutputKind {
     Text(String),
     Json(serde_json::Value),
     Images(RichImageBlocks),
-    Mixed { text: String, images: RichImageBlocks },
+    Documents(RichDocumentBlocks),
+    Mixed {
+        text: String,
+        images: RichImageBlocks,
+        documents: RichDocumentBlocks,
+    },
 }
 
 impl Default for OutputKind {
@@ -0,0 +1,199 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use crossterm::execute;
+use crossterm::style::{
+    self,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::api_client::model::{
+    DocumentBlock,
+    DocumentFormat,
+    DocumentSource,
+};
+use crate::cli::chat::consts::{
+    MAX_DOCUMENT_SIZE,
+    MAX_NUMBER_OF_DOCUMENTS_PER_REQUEST,
+};
+use crate::theme::StyledText;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DocumentMetadata {
+    pub filepath: String,
+    /// The size of the document in bytes
+    pub size: u64,
+    pub filename: String,
+}
+
+pub type RichDocumentBlocks = Vec<RichDocumentBlock>;
+pub type RichDocumentBlock = (DocumentBlock, DocumentMetadata);
+
+pub fn handle_documents_from_paths(output: &mut impl Write, paths: &[String]) -> RichDocumentBlocks {
+    let mut extracted_documents = Vec::new();
+    let mut seen_args = std::collections::HashSet::new();
+
+    for path in paths.iter() {
+        if !seen_args.insert(path) {
+            continue;
+        }
+        if let Some(document_block) = get_document_block_from_file_path(path) {
+            let filename = Path::new(path)
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+
+            let document_size = fs::metadata(path).map(|m| m.len()).unwrap_or_default();
+
+            extracted_documents.push((document_block, DocumentMetadata {
+                filename,
+                filepath: path.clone(),
+                size: document_size,
+            }));
+        }
+    }
+
+    let (mut valid_documents, documents_exceeding_size_limit): (RichDocumentBlocks, RichDocumentBlocks) =
+        extracted_documents
+            .into_iter()
+            .partition(|(_, metadata)| metadata.size as usize <= MAX_DOCUMENT_SIZE);
+
+    if valid_documents.len() > MAX_NUMBER_OF_DOCUMENTS_PER_REQUEST {
+        execute!(
+            &mut *output,
+            StyledText::warning_fg(),
+            style::Print(format!(
+                "\nMore than {} documents detected. Extra ones will be dropped.\n",
+                MAX_NUMBER_OF_DOCUMENTS_PER_REQUEST
+            )),
+            StyledText::reset(),
+        )
+        .ok();
+        valid_documents.truncate(MAX_NUMBER_OF_DOCUMENTS_PER_REQUEST);
+    }
+
+    if !documents_exceeding_size_limit.is_empty() {
+        execute!(
+            &mut *output,
+            StyledText::warning_fg(),
+            style::Print(format!(
+                "\nThe following documents are dropped due to exceeding size limit ({:.1}MB):\n",
+                MAX_DOCUMENT_SIZE as f64 / (1024.0 * 1024.0)
+            )),
+            StyledText::reset(),
+        )
+        .ok();
+        for (_, metadata) in &documents_exceeding_size_limit {
+            execute!(
+                &mut *output,
+                StyledText::warning_fg(),
+                style::Print(format!(
+                    "  - {} ({:.2} MB)\n",
+                    metadata.filename,
+                    metadata.size as f64 / (1024.0 * 1024.0)
+                )),
+                StyledText::reset(),
+            )
+            .ok();
+        }
+    }
+    valid_documents
+}
+
+/// Checks if the file path has a document type that can be sent to the model.
+/// Supported document types are: pdf, csv, doc, docx, xls, xlsx, html, txt, md
+pub fn is_supported_document_type(maybe_file_path: &str) -> bool {
+    Path::new(maybe_file_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| DocumentFormat::from_str(ext).is_ok())
+}
+
+pub fn get_document_block_from_file_path(maybe_file_path: &str) -> Option<DocumentBlock> {
+    let file_path = Path::new(maybe_file_path);
+    let format = DocumentFormat::from_str(file_path.extension()?.to_str()?).ok()?;
+    let bytes = fs::read(file_path).ok()?;
+    let name = file_path.file_stem().unwrap_or_default().to_string_lossy();
+
+    Some(DocumentBlock {
+        format,
+        name: DocumentBlock::sanitize_name(&name),
+        source: DocumentSource::Bytes(bytes),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bstr::ByteSlice;
+
+    use super::*;
+
+    #[test]
+    fn test_is_supported_document_type() {
+        let test_cases = vec![
+            ("report.pdf", true),
+            ("report.PDF", true),
+            ("sheet.xlsx", true),
+            ("notes.docx", true),
+            ("image.png", false),
+            ("document", false),
+        ];
+
+        for (path, expected) in test_cases {
+            assert_eq!(is_supported_document_type(path), expected, "Failed for path: {}", path);
+        }
+    }
+
+    #[test]
+    fn test_get_document_block_from_file_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let document_path = temp_dir.path().join("Q3 report_final.pdf");
+        std::fs::write(&document_path, b"fake_pdf_data").unwrap();
+
+        let document_block = get_document_block_from_file_path(&document_path.to_string_lossy()).unwrap();
+        assert_eq!(document_block.format, DocumentFormat::Pdf);
+        assert_eq!(document_block.name, "Q3 report final");
+        if let DocumentSource::Bytes(bytes) = document_block.source {
+            assert_eq!(bytes, b"fake_pdf_data");
+        } else {
+            panic!("Expected DocumentSource::Bytes");
+        }
+    }
+
+    #[test]
+    fn test_handle_documents_size_limit_exceeded() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let large_document_path = temp_dir.path().join("large.pdf");
+        std::fs::write(&large_document_path, vec![0; MAX_DOCUMENT_SIZE + 1]).unwrap();
+
+        let mut output = vec![];
+        let documents =
+            handle_documents_from_paths(&mut output, &[large_document_path.to_string_lossy().to_string()]);
+        let output_str = output.to_str_lossy();
+        assert!(output_str.contains("The following documents are dropped due to exceeding size limit (4.5MB):"));
+        assert!(output_str.contains("- large.pdf"));
+        assert!(documents.is_empty());
+    }
+
+    #[test]
+    fn test_handle_documents_number_exceeded() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let mut paths = vec![];
+        for i in 0..(MAX_NUMBER_OF_DOCUMENTS_PER_REQUEST + 2) {
+            let document_path = temp_dir.path().join(format!("document_{}.pdf", i));
+            paths.push(document_path.to_string_lossy().to_string());
+            std::fs::write(&document_path, b"fake_pdf_data").unwrap();
+        }
+
+        let documents = handle_documents_from_paths(&mut vec![], &paths);
+
+        assert_eq!(documents.len(), MAX_NUMBER_OF_DOCUMENTS_PER_REQUEST);
+    }
+}
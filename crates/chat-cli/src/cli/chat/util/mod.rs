@@ -1,4 +1,5 @@
 pub mod clipboard;
+pub mod documents;
 pub mod images;
 pub mod issue;
 #[cfg(test)]
@@ -166,7 +167,7 @@ fn should_play_bell() -> bool {
 ///
 /// Returns the dropped files
 pub fn drop_matched_context_files(files: &mut [(String, String)], limit: usize) -> Result<Vec<(String, String)>> {
-    files.sort_by(|a, b| TokenCounter::count_tokens(&b.1).cmp(&TokenCounter::count_tokens(&a.1)));
+    files.sort_by_cached_key(|(_, content)| std::cmp::Reverse(TokenCounter::count_tokens(content)));
     let mut total_size = 0;
     let mut dropped_files = Vec::new();
 
@@ -427,6 +427,24 @@ impl StatusArgs {
             bail!("No MCP server named '{}' found in any agent\n", self.name);
         }
 
+        // The status is saved by the chat sessions running the server
+        if let Some(status) = os.database.get_mcp_server_status(&self.name)? {
+            execute!(
+                output,
+                style::Print(format!(
+                    "Last known status: {} (as of {})\n",
+                    status.state,
+                    status.updated_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S")
+                )),
+            )?;
+            if !status.stderr.is_empty() {
+                writeln!(output, "Last stderr output:")?;
+                for line in &status.stderr {
+                    writeln!(output, "  {line}")?;
+                }
+            }
+        }
+
         Ok(())
     }
 }
@@ -21,6 +21,10 @@ use std::io::{
 use std::process::ExitCode;
 
 use agent::AgentArgs;
+pub use agent::guardrail::{
+    GuardrailConfig,
+    GuardrailTraceMode,
+};
 pub use agent::{
     Agent,
     DEFAULT_AGENT_NAME,
@@ -315,6 +319,10 @@ mod test {
     };
 
     use super::*;
+    use crate::cli::chat::{
+        ChatSubcommand,
+        SessionsSubcommand,
+    };
     use crate::util::CHAT_BINARY_NAME;
     use crate::util::test::assert_parse;
 
@@ -346,10 +354,14 @@ mod test {
 
         assert_eq!(Cli::parse_from([CHAT_BINARY_NAME, "chat", "-vv"]), Cli {
             subcommand: Some(RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: false,
+                resume_id: None,
                 input: None,
                 agent: None,
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: false,
@@ -386,10 +398,14 @@ mod test {
         assert_parse!(
             ["chat", "--profile", "my-profile"],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: false,
+                resume_id: None,
                 input: None,
                 agent: Some("my-profile".to_string()),
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: false,
@@ -403,10 +419,14 @@ mod test {
         assert_parse!(
             ["chat", "--profile", "my-profile", "Hello"],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: false,
+                resume_id: None,
                 input: Some("Hello".to_string()),
                 agent: Some("my-profile".to_string()),
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: false,
@@ -420,10 +440,14 @@ mod test {
         assert_parse!(
             ["chat", "--profile", "my-profile", "--trust-all-tools"],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: false,
+                resume_id: None,
                 input: None,
                 agent: Some("my-profile".to_string()),
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: true,
                 trust_tools: None,
                 no_interactive: false,
@@ -437,10 +461,14 @@ mod test {
         assert_parse!(
             ["chat", "--no-interactive", "--resume"],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: true,
+                resume_id: None,
                 input: None,
                 agent: None,
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: true,
@@ -450,10 +478,14 @@ mod test {
         assert_parse!(
             ["chat", "--non-interactive", "-r"],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: true,
+                resume_id: None,
                 input: None,
                 agent: None,
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: true,
@@ -462,15 +494,77 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_chat_with_resume_and_input() {
+        assert_parse!(
+            ["chat", "-r", "Hello"],
+            RootSubcommand::Chat(ChatArgs {
+                resume: true,
+                input: Some("Hello".to_string()),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_chat_with_resume_session() {
+        assert_parse!(
+            ["chat", "--resume-id", "pick"],
+            RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
+                resume: false,
+                resume_id: Some("pick".to_string()),
+                input: None,
+                agent: None,
+                model: None,
+                service_tier: None,
+                reasoning_budget: None,
+                trust_all_tools: false,
+                trust_tools: None,
+                no_interactive: false,
+                wrap: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_chat_sessions() {
+        assert_parse!(
+            ["chat", "sessions", "list", "--all"],
+            RootSubcommand::Chat(ChatArgs {
+                subcommand: Some(ChatSubcommand::Sessions(SessionsSubcommand::List {
+                    all: true,
+                    format: OutputFormat::Plain,
+                })),
+                ..Default::default()
+            })
+        );
+        assert_parse!(
+            ["chat", "sessions", "search", "flaky", "test"],
+            RootSubcommand::Chat(ChatArgs {
+                subcommand: Some(ChatSubcommand::Sessions(SessionsSubcommand::Search {
+                    query: vec!["flaky".to_string(), "test".to_string()],
+                    limit: 20,
+                    format: OutputFormat::Plain,
+                })),
+                ..Default::default()
+            })
+        );
+    }
+
     #[test]
     fn test_chat_with_tool_trust_all() {
         assert_parse!(
             ["chat", "--trust-all-tools"],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: false,
+                resume_id: None,
                 input: None,
                 agent: None,
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: true,
                 trust_tools: None,
                 no_interactive: false,
@@ -484,10 +578,14 @@ mod test {
         assert_parse!(
             ["chat", "--trust-tools="],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: false,
+                resume_id: None,
                 input: None,
                 agent: None,
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: false,
                 trust_tools: Some(vec!["".to_string()]),
                 no_interactive: false,
@@ -501,10 +599,14 @@ mod test {
         assert_parse!(
             ["chat", "--trust-tools=fs_read,fs_write"],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: false,
+                resume_id: None,
                 input: None,
                 agent: None,
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: false,
                 trust_tools: Some(vec!["fs_read".to_string(), "fs_write".to_string()]),
                 no_interactive: false,
@@ -518,10 +620,14 @@ mod test {
         assert_parse!(
             ["chat", "-w", "never"],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: false,
+                resume_id: None,
                 input: None,
                 agent: None,
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: false,
@@ -531,10 +637,14 @@ mod test {
         assert_parse!(
             ["chat", "--wrap", "always"],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: false,
+                resume_id: None,
                 input: None,
                 agent: None,
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: false,
@@ -544,10 +654,14 @@ mod test {
         assert_parse!(
             ["chat", "--wrap", "auto"],
             RootSubcommand::Chat(ChatArgs {
+                subcommand: None,
                 resume: false,
+                resume_id: None,
                 input: None,
                 agent: None,
                 model: None,
+                service_tier: None,
+                reasoning_budget: None,
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: false,
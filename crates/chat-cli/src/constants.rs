@@ -241,6 +241,10 @@ pub mod tips {
                 "You can resume the last conversation from your current directory by launching with {}",
                 StyledText::command("q chat --resume")
             ),
+            format!(
+                "Every conversation is saved as a session. Run {} to pick any of them to continue",
+                StyledText::command("q chat --resume-id pick")
+            ),
             format!(
                 "Get notified whenever Amazon Q CLI finishes responding. Just run {}",
                 StyledText::command("q settings chat.enableNotifications true")
@@ -1,7 +1,7 @@
+pub mod sessions;
 pub mod settings;
 
 use std::ops::Deref;
-use std::path::Path;
 use std::str::FromStr;
 use std::sync::PoisonError;
 
@@ -34,7 +34,8 @@ use tracing::{
 };
 use uuid::Uuid;
 
-use crate::cli::ConversationState;
+use crate::api_client::model_catalog::ModelCatalogCache;
+use crate::mcp_client::health::ServerStatus;
 use crate::util::env_var::is_integ_test;
 use crate::util::paths::{
     DirectoryError,
@@ -63,6 +64,8 @@ const IDC_REGION_KEY: &str = "auth.idc.region";
 const CUSTOMIZATION_STATE_KEY: &str = "api.selectedCustomization";
 const PROFILE_MIGRATION_KEY: &str = "profile.Migrated";
 const HEARTBEAT_DATE_KEY: &str = "telemetry.lastHeartbeatDate";
+const MODEL_CATALOG_KEY: &str = "api.bedrock.modelCatalog";
+const MCP_SERVER_STATUS_KEY_PREFIX: &str = "mcp.serverStatus.";
 
 const MIGRATIONS: &[Migration] = migrations![
     "000_migration_table",
@@ -72,7 +75,9 @@ const MIGRATIONS: &[Migration] = migrations![
     "004_state_table",
     "005_auth_table",
     "006_make_state_blob",
-    "007_conversations_table"
+    "007_conversations_table",
+    "008_sessions_table",
+    "009_session_branches"
 ];
 
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
@@ -158,8 +163,6 @@ impl<T> From<PoisonError<T>> for DatabaseError {
 pub enum Table {
     /// The state table contains persistent application state.
     State,
-    /// The conversations tables contains user chat conversations.
-    Conversations,
     /// The auth table contains SSO and Builder ID credentials.
     Auth,
 }
@@ -168,7 +171,6 @@ impl std::fmt::Display for Table {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Table::State => write!(f, "state"),
-            Table::Conversations => write!(f, "conversations"),
             Table::Auth => write!(f, "auth_kv"),
         }
     }
@@ -366,6 +368,35 @@ impl Database {
         Ok(())
     }
 
+    /// Get the Bedrock model catalog cached by the last model discovery.
+    pub fn get_model_catalog(&self) -> Result<Option<ModelCatalogCache>, DatabaseError> {
+        self.get_json_entry(Table::State, MODEL_CATALOG_KEY)
+    }
+
+    /// Set the Bedrock model catalog cached by the last model discovery.
+    pub fn set_model_catalog(&self, catalog: &ModelCatalogCache) -> Result<usize, DatabaseError> {
+        self.set_json_entry(Table::State, MODEL_CATALOG_KEY, catalog)
+    }
+
+    /// Unset the cached Bedrock model catalog so the next lookup rediscovers the models.
+    pub fn unset_model_catalog(&self) -> Result<(), DatabaseError> {
+        self.delete_entry(Table::State, MODEL_CATALOG_KEY)
+    }
+
+    /// Get the last known status of an MCP server.
+    pub fn get_mcp_server_status(&self, server_name: &str) -> Result<Option<ServerStatus>, DatabaseError> {
+        self.get_json_entry(Table::State, format!("{MCP_SERVER_STATUS_KEY_PREFIX}{server_name}"))
+    }
+
+    /// Set the status of an MCP server, so it can be shown outside of the chat session running it.
+    pub fn set_mcp_server_status(&self, server_name: &str, status: &ServerStatus) -> Result<usize, DatabaseError> {
+        self.set_json_entry(
+            Table::State,
+            format!("{MCP_SERVER_STATUS_KEY_PREFIX}{server_name}"),
+            status,
+        )
+    }
+
     // /// Get the model id used for last conversation state.
     // pub fn get_last_used_model_id(&self) -> Result<Option<String>, DatabaseError> {
     //     self.get_json_entry::<String>(Table::State, LAST_USED_MODEL_ID)
@@ -381,35 +412,6 @@ impl Database {
     //     self.delete_entry(Table::State, LAST_USED_MODEL_ID)
     // }
 
-    /// Get a chat conversation given a path to the conversation.
-    pub fn get_conversation_by_path(
-        &mut self,
-        path: impl AsRef<Path>,
-    ) -> Result<Option<ConversationState>, DatabaseError> {
-        // We would need to encode this to support non utf8 paths.
-        let path = match path.as_ref().to_str() {
-            Some(path) => path,
-            None => return Ok(None),
-        };
-
-        self.get_json_entry(Table::Conversations, path)
-    }
-
-    /// Set a chat conversation given a path to the conversation.
-    pub fn set_conversation_by_path(
-        &mut self,
-        path: impl AsRef<Path>,
-        state: &ConversationState,
-    ) -> Result<usize, DatabaseError> {
-        // We would need to encode this to support non utf8 paths.
-        let path = match path.as_ref().to_str() {
-            Some(path) => path,
-            None => return Ok(0),
-        };
-
-        self.set_json_entry(Table::Conversations, path, state)
-    }
-
     pub async fn get_secret(&self, key: &str) -> Result<Option<Secret>, DatabaseError> {
         trace!(key, "getting secret");
         Ok(self.get_entry::<String>(Table::Auth, key)?.map(Into::into))
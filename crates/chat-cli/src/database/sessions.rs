@@ -0,0 +1,525 @@
+//! Chat sessions, each stored under the id of its conversation.
+//!
+//! Besides the conversation itself, a session records its title, working directory, agent and
+//! model, so that sessions can be listed without deserializing every conversation. The titles and
+//! transcripts of the sessions are indexed for full-text search.
+//!
+//! Sessions created with `/fork` record the session and turn they were forked from, which links
+//! the branches of a conversation into a tree.
+
+use std::path::Path;
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use rusqlite::{
+    OptionalExtension,
+    Row,
+    params,
+};
+use serde::Serialize;
+
+use super::{
+    Database,
+    DatabaseError,
+};
+use crate::cli::ConversationState;
+
+const SUMMARY_COLUMNS: &str = "id, title, cwd, agent, model, created_at, updated_at, parent_id, fork_turn";
+
+/// A stored session, without its conversation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub id: String,
+    pub title: String,
+    pub cwd: String,
+    pub agent: Option<String>,
+    pub model: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// The session this one was forked from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// The turn of the parent session this one was forked from, counting from 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fork_turn: Option<usize>,
+}
+
+impl SessionSummary {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            cwd: row.get(2)?,
+            agent: row.get(3)?,
+            model: row.get(4)?,
+            created_at: DateTime::from_timestamp(row.get(5)?, 0).unwrap_or_default(),
+            updated_at: DateTime::from_timestamp(row.get(6)?, 0).unwrap_or_default(),
+            parent_id: row.get(7)?,
+            fork_turn: row.get(8)?,
+        })
+    }
+}
+
+/// A session that matched a search, with an excerpt of the match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMatch {
+    #[serde(flatten)]
+    pub session: SessionSummary,
+    pub snippet: String,
+}
+
+impl Database {
+    /// Saves a conversation as the session of its id. The title, creation time and parent of an
+    /// existing session are kept.
+    pub fn set_session(&self, cwd: impl AsRef<Path>, state: &ConversationState) -> Result<usize, DatabaseError> {
+        let id = state.conversation_id();
+        let title = state.title();
+        let now = Utc::now().timestamp();
+        let forked_from = state.forked_from.as_ref();
+        let mut conn = self.pool.get()?;
+        let transaction = conn.transaction()?;
+        let changed = transaction.execute(
+            "INSERT INTO sessions (id, title, cwd, agent, model, created_at, updated_at, value, parent_id, fork_turn)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, ?7, ?8, ?9)
+             ON CONFLICT (id) DO UPDATE SET
+                 title = CASE WHEN sessions.title = '' THEN excluded.title ELSE sessions.title END,
+                 cwd = excluded.cwd,
+                 agent = excluded.agent,
+                 model = excluded.model,
+                 updated_at = excluded.updated_at,
+                 value = excluded.value",
+            params![
+                id,
+                title,
+                cwd.as_ref().to_string_lossy(),
+                state.current_profile(),
+                state.model_info.as_ref().map(|m| m.model_id.as_str()),
+                now,
+                serde_json::to_string(state)?,
+                forked_from.map(|origin| origin.conversation_id.as_str()),
+                forked_from.map(|origin| origin.turn),
+            ],
+        )?;
+        transaction.execute("DELETE FROM sessions_fts WHERE id = ?1", [id])?;
+        transaction.execute(
+            "INSERT INTO sessions_fts (id, title, transcript) SELECT id, title, ?2 FROM sessions WHERE id = ?1",
+            params![id, state.transcript.iter().cloned().collect::<Vec<_>>().join("\n")],
+        )?;
+        transaction.commit()?;
+        Ok(changed)
+    }
+
+    /// Get the conversation of a session.
+    pub fn get_session(&self, id: &str) -> Result<Option<ConversationState>, DatabaseError> {
+        let value = self
+            .pool
+            .get()?
+            .query_row("SELECT value FROM sessions WHERE id = ?1", [id], |row| {
+                row.get::<_, String>(0)
+            })
+            .optional()?;
+        Ok(match value {
+            Some(value) => Some(serde_json::from_str(&value)?),
+            None => None,
+        })
+    }
+
+    pub fn get_session_summary(&self, id: &str) -> Result<Option<SessionSummary>, DatabaseError> {
+        Ok(self
+            .pool
+            .get()?
+            .query_row(
+                &format!("SELECT {SUMMARY_COLUMNS} FROM sessions WHERE id = ?1"),
+                [id],
+                SessionSummary::from_row,
+            )
+            .optional()?)
+    }
+
+    /// Lists the sessions, most recently updated first, optionally only those of a directory.
+    pub fn list_sessions(&self, cwd: Option<&Path>) -> Result<Vec<SessionSummary>, DatabaseError> {
+        let conn = self.pool.get()?;
+        let sessions = match cwd {
+            Some(cwd) => conn
+                .prepare(&format!(
+                    "SELECT {SUMMARY_COLUMNS} FROM sessions WHERE cwd = ?1 ORDER BY updated_at DESC"
+                ))?
+                .query_map([cwd.to_string_lossy()], SessionSummary::from_row)?
+                .collect::<Result<Vec<_>, _>>()?,
+            None => conn
+                .prepare(&format!(
+                    "SELECT {SUMMARY_COLUMNS} FROM sessions ORDER BY updated_at DESC"
+                ))?
+                .query_map([], SessionSummary::from_row)?
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        Ok(sessions)
+    }
+
+    /// Lists the branches of the conversation of a session: the session it was forked from, going
+    /// up to the first one that still exists, and every session forked from it since, oldest
+    /// first.
+    pub fn list_session_family(&self, id: &str) -> Result<Vec<SessionSummary>, DatabaseError> {
+        let mut root = id.to_string();
+        let mut visited = vec![root.clone()];
+        while let Some(parent_id) = self.get_session_summary(&root)?.and_then(|s| s.parent_id) {
+            if visited.contains(&parent_id) || self.get_session_summary(&parent_id)?.is_none() {
+                break;
+            }
+            visited.push(parent_id.clone());
+            root = parent_id;
+        }
+
+        Ok(self
+            .pool
+            .get()?
+            .prepare(&format!(
+                "WITH RECURSIVE family (id) AS (
+                     SELECT ?1
+                     UNION
+                     SELECT sessions.id FROM sessions JOIN family ON sessions.parent_id = family.id
+                 )
+                 SELECT {SUMMARY_COLUMNS} FROM sessions WHERE id IN (SELECT id FROM family) ORDER BY created_at, rowid"
+            ))?
+            .query_map([root], SessionSummary::from_row)?
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// The ids of the sessions starting with `prefix`, so that sessions can be referred to by the
+    /// first characters of their id.
+    pub fn find_session_ids(&self, prefix: &str) -> Result<Vec<String>, DatabaseError> {
+        let pattern = format!(
+            "{}%",
+            prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        Ok(self
+            .pool
+            .get()?
+            .prepare("SELECT id FROM sessions WHERE id LIKE ?1 ESCAPE '\\' ORDER BY updated_at DESC")?
+            .query_map([pattern], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Deletes a session, returning whether it existed.
+    ///
+    /// The sessions forked from it become forks of its own parent, from the last turn they share
+    /// with it, so that the rest of the family stays linked.
+    pub fn delete_session(&self, id: &str) -> Result<bool, DatabaseError> {
+        let mut conn = self.pool.get()?;
+        let transaction = conn.transaction()?;
+        transaction.execute(
+            "UPDATE sessions SET
+                 parent_id = (SELECT parent_id FROM sessions WHERE id = ?1),
+                 fork_turn = MIN(fork_turn, (SELECT fork_turn FROM sessions WHERE id = ?1))
+             WHERE parent_id = ?1",
+            [id],
+        )?;
+        let deleted = transaction.execute("DELETE FROM sessions WHERE id = ?1", [id])?;
+        transaction.execute("DELETE FROM sessions_fts WHERE id = ?1", [id])?;
+        transaction.commit()?;
+        Ok(deleted > 0)
+    }
+
+    /// Searches the titles and transcripts of the sessions, best matches first. Every word of
+    /// `query` must appear in the session.
+    pub fn search_sessions(&self, query: &str, limit: usize) -> Result<Vec<SessionMatch>, DatabaseError> {
+        let Some(query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let columns = SUMMARY_COLUMNS
+            .split(", ")
+            .map(|column| format!("s.{column}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Ok(self
+            .pool
+            .get()?
+            .prepare(&format!(
+                "SELECT {columns}, snippet(sessions_fts, 2, '[', ']', '...', 12)
+                 FROM sessions_fts JOIN sessions s ON s.id = sessions_fts.id
+                 WHERE sessions_fts MATCH ?1
+                 ORDER BY rank
+                 LIMIT ?2"
+            ))?
+            .query_map(params![query, limit as i64], |row| {
+                Ok(SessionMatch {
+                    session: SessionSummary::from_row(row)?,
+                    snippet: row.get(9)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+/// Converts a search into an FTS5 query matching every word of it, quoting the words so that
+/// characters such as `-` or `:` aren't interpreted as query syntax.
+fn fts_query(query: &str) -> Option<String> {
+    let terms = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::chat::tool_manager::ToolManager;
+    use crate::database::MIGRATIONS;
+    use crate::os::Os;
+
+    fn insert_session(db: &Database, id: &str, title: &str, transcript: &str, updated_at: i64) {
+        insert_fork(db, id, title, transcript, updated_at, None);
+    }
+
+    fn insert_fork(
+        db: &Database,
+        id: &str,
+        title: &str,
+        transcript: &str,
+        updated_at: i64,
+        parent: Option<(&str, usize)>,
+    ) {
+        let conn = db.pool.get().unwrap();
+        conn.execute(
+            "INSERT INTO sessions (id, title, cwd, created_at, updated_at, value, parent_id, fork_turn)
+             VALUES (?1, ?2, '/work', ?3, ?3, '{}', ?4, ?5)",
+            params![id, title, updated_at, parent.map(|p| p.0), parent.map(|p| p.1)],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO sessions_fts (id, title, transcript) VALUES (?1, ?2, ?3)",
+            params![id, title, transcript],
+        )
+        .unwrap();
+    }
+
+    fn fts_rows(db: &Database, id: &str) -> i64 {
+        db.pool
+            .get()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM sessions_fts WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_set_session() {
+        let mut os = Os::new().await.unwrap();
+        let mut tool_manager = ToolManager::default();
+        let mut state = ConversationState::new(
+            "session_id",
+            Default::default(),
+            tool_manager.load_tools(&mut os, &mut vec![]).await.unwrap(),
+            tool_manager,
+            None,
+            &os,
+            false,
+        )
+        .await;
+        let db = Database::new().await.unwrap();
+
+        // New sessions are inserted with the title of the conversation, empty without prompts
+        assert_eq!(db.set_session("/work", &state).unwrap(), 1);
+        assert_eq!(db.get_session_summary("session_id").unwrap().unwrap().title, "");
+
+        // Existing sessions keep their title and creation time
+        db.pool
+            .get()
+            .unwrap()
+            .execute(
+                "UPDATE sessions SET title = 'Fix the flaky test', created_at = 1 WHERE id = 'session_id'",
+                [],
+            )
+            .unwrap();
+        state.append_user_transcript("Why is the cache shared?");
+        db.set_session("/other", &state).unwrap();
+        let session = db.get_session_summary("session_id").unwrap().unwrap();
+        assert_eq!(session.title, "Fix the flaky test");
+        assert_eq!(session.cwd, "/other");
+        assert_eq!(session.created_at.timestamp(), 1);
+        assert!(session.updated_at.timestamp() > 1);
+        assert_eq!(
+            db.get_session("session_id").unwrap().unwrap().transcript,
+            state.transcript
+        );
+
+        // The index is replaced, with the title that was kept
+        assert_eq!(fts_rows(&db, "session_id"), 1);
+        assert_eq!(db.search_sessions("flaky", 10).unwrap().len(), 1);
+        assert_eq!(db.search_sessions("cache", 10).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_sessions() {
+        let db = Database::new().await.unwrap();
+        insert_session(&db, "a", "Build", "> why does cargo build fail\nmissing feature", 1);
+        insert_session(&db, "b", "Cargo", "> run cargo test -v", 2);
+        insert_session(&db, "c", "Docs", "> build the docs", 3);
+
+        let ids = |matches: Vec<SessionMatch>| matches.into_iter().map(|m| m.session.id).collect::<Vec<_>>();
+        assert_eq!(ids(db.search_sessions("cargo build", 10).unwrap()), vec!["a"]);
+        assert_eq!(db.search_sessions("cargo", 10).unwrap().len(), 2);
+        assert_eq!(db.search_sessions("cargo", 1).unwrap().len(), 1);
+        assert_eq!(ids(db.search_sessions("test -v", 10).unwrap()), vec!["b"]);
+        assert_eq!(ids(db.search_sessions("docs", 10).unwrap()), vec!["c"]);
+        assert!(db.search_sessions("  ", 10).unwrap().is_empty());
+
+        let matches = db.search_sessions("missing", 10).unwrap();
+        assert!(matches[0].snippet.contains("[missing]"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_session() {
+        let db = Database::new().await.unwrap();
+        insert_session(&db, "a", "Build", "> cargo build", 1);
+        insert_session(&db, "b", "Test", "> cargo test", 2);
+
+        assert!(db.delete_session("a").unwrap());
+        assert!(db.get_session_summary("a").unwrap().is_none());
+        assert_eq!(fts_rows(&db, "a"), 0);
+        assert_eq!(fts_rows(&db, "b"), 1);
+        assert!(!db.delete_session("a").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_list_session_family() {
+        let db = Database::new().await.unwrap();
+        let family = |id: &str| {
+            db.list_session_family(id)
+                .unwrap()
+                .into_iter()
+                .map(|s| (s.id, s.parent_id, s.fork_turn))
+                .collect::<Vec<_>>()
+        };
+        let fork =
+            |id: &str, parent: Option<&str>, turn: Option<usize>| (id.to_string(), parent.map(str::to_string), turn);
+
+        // root <- middle (turn 3) <- leaf (turn 5), root <- sibling (turn 1)
+        insert_session(&db, "root", "Root", "", 1);
+        insert_fork(&db, "middle", "Middle", "", 2, Some(("root", 3)));
+        insert_fork(&db, "leaf", "Leaf", "", 3, Some(("middle", 5)));
+        insert_fork(&db, "sibling", "Sibling", "", 4, Some(("root", 1)));
+        insert_session(&db, "other", "Other", "", 5);
+
+        let all = vec![
+            fork("root", None, None),
+            fork("middle", Some("root"), Some(3)),
+            fork("leaf", Some("middle"), Some(5)),
+            fork("sibling", Some("root"), Some(1)),
+        ];
+        assert_eq!(family("leaf"), all);
+        assert_eq!(family("root"), all);
+        assert_eq!(family("other"), vec![fork("other", None, None)]);
+
+        // The leaf shares the first 3 turns of the root through the deleted session
+        assert!(db.delete_session("middle").unwrap());
+        let remaining = vec![
+            fork("root", None, None),
+            fork("leaf", Some("root"), Some(3)),
+            fork("sibling", Some("root"), Some(1)),
+        ];
+        assert_eq!(family("leaf"), remaining);
+        assert_eq!(family("sibling"), remaining);
+
+        // Forks of a deleted root become roots themselves
+        assert!(db.delete_session("root").unwrap());
+        assert_eq!(family("leaf"), vec![fork("leaf", None, None)]);
+        assert_eq!(family("sibling"), vec![fork("sibling", None, None)]);
+    }
+
+    #[tokio::test]
+    async fn test_find_session_ids() {
+        let db = Database::new().await.unwrap();
+        insert_session(&db, "abc_1", "", "", 1);
+        insert_session(&db, "abcx1", "", "", 2);
+        insert_session(&db, "ab%c", "", "", 3);
+        insert_session(&db, "a\\b", "", "", 4);
+
+        assert_eq!(db.find_session_ids("abc_").unwrap(), vec!["abc_1"]);
+        assert_eq!(db.find_session_ids("ab%").unwrap(), vec!["ab%c"]);
+        assert_eq!(db.find_session_ids("a\\").unwrap(), vec!["a\\b"]);
+        assert_eq!(db.find_session_ids("ab").unwrap(), vec!["ab%c", "abcx1", "abc_1"]);
+        assert!(db.find_session_ids("zz").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate_conversations() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let index = MIGRATIONS
+            .iter()
+            .position(|migration| migration.name == "008_sessions_table")
+            .unwrap();
+        for migration in &MIGRATIONS[..index] {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+
+        let conversation = serde_json::json!({
+            "conversation_id": "c1",
+            "transcript": ["response first", "> Why is the build flaky?> \nIt fails on CI", "> Thanks"],
+            "context_manager": { "current_profile": "rust" },
+            "model_info": { "model_id": "claude" },
+        });
+        let long_prompt = "x".repeat(100);
+        let untitled = serde_json::json!({ "conversation_id": "c2", "transcript": [format!("> {long_prompt}")] });
+        for (key, value) in [
+            ("/work/repo", conversation.to_string()),
+            ("/work/other", untitled.to_string()),
+            ("/work/invalid", "not json".to_string()),
+            ("/work/no-id", "{}".to_string()),
+        ] {
+            conn.execute("INSERT INTO conversations (key, value) VALUES (?1, ?2)", [key, &value])
+                .unwrap();
+        }
+
+        conn.execute_batch(MIGRATIONS[index].sql).unwrap();
+
+        let sessions = conn
+            .prepare("SELECT id, title, cwd, agent, model FROM sessions ORDER BY id")
+            .unwrap()
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(sessions, vec![
+            (
+                "c1".to_string(),
+                "Why is the build flaky?".to_string(),
+                "/work/repo".to_string(),
+                Some("rust".to_string()),
+                Some("claude".to_string())
+            ),
+            ("c2".to_string(), "x".repeat(80), "/work/other".to_string(), None, None),
+        ]);
+
+        let matched: String = conn
+            .query_row("SELECT id FROM sessions_fts WHERE sessions_fts MATCH 'CI'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(matched, "c1");
+    }
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(fts_query("  "), None);
+        assert_eq!(fts_query("cargo build"), Some("\"cargo\" \"build\"".to_string()));
+        assert_eq!(
+            fts_query("say \"hi\" -v"),
+            Some("\"say\" \"\"\"hi\"\"\" \"-v\"".to_string())
+        );
+    }
+}
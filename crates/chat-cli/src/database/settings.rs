@@ -40,6 +40,8 @@ pub enum Setting {
     KnowledgeChunkOverlap,
     #[strum(message = "Type of knowledge index to use (string)")]
     KnowledgeIndexType,
+    #[strum(message = "Rerank knowledge search results with a cross-encoder (boolean)")]
+    KnowledgeRerank,
     #[strum(message = "Key binding for fuzzy search command (single character)")]
     SkimCommandKey,
     #[strum(message = "Key binding for autocompletion hint acceptance (single character)")]
@@ -65,6 +67,8 @@ pub enum Setting {
     ApiCodeWhispererService,
     #[strum(message = "Q service endpoint URL (string)")]
     ApiQService,
+    #[strum(message = "OpenAI compatible chat completions endpoint URL (string)")]
+    ApiOpenAiEndpoint,
     #[strum(message = "MCP server initialization timeout (number)")]
     McpInitTimeout,
     #[strum(message = "Non-interactive MCP timeout (number)")]
@@ -75,8 +79,12 @@ pub enum Setting {
     EnabledContextUsageIndicator,
     #[strum(message = "Default AI model for conversations (string)")]
     ChatDefaultModel,
+    #[strum(message = "Model provider for conversations, bedrock or openai (string)")]
+    ChatProvider,
     #[strum(message = "Disable markdown formatting in chat (boolean)")]
     ChatDisableMarkdownRendering,
+    #[strum(message = "Show a one-line summary instead of the model's reasoning (boolean)")]
+    ChatCollapseReasoning,
     #[strum(message = "Default agent configuration (string)")]
     ChatDefaultAgent,
     #[strum(message = "Disable automatic conversation summarization (boolean)")]
@@ -107,6 +115,7 @@ impl AsRef<str> for Setting {
             Self::KnowledgeChunkSize => "knowledge.chunkSize",
             Self::KnowledgeChunkOverlap => "knowledge.chunkOverlap",
             Self::KnowledgeIndexType => "knowledge.indexType",
+            Self::KnowledgeRerank => "knowledge.rerank",
             Self::SkimCommandKey => "chat.skimCommandKey",
             Self::AutocompletionKey => "chat.autocompletionKey",
             Self::EnabledTangentMode => "chat.enableTangentMode",
@@ -120,11 +129,14 @@ impl AsRef<str> for Setting {
             Self::ChatEnableNotifications => "chat.enableNotifications",
             Self::ApiCodeWhispererService => "api.codewhisperer.service",
             Self::ApiQService => "api.q.service",
+            Self::ApiOpenAiEndpoint => "api.openai.endpoint",
             Self::McpInitTimeout => "mcp.initTimeout",
             Self::McpNoInteractiveTimeout => "mcp.noInteractiveTimeout",
             Self::McpLoadedBefore => "mcp.loadedBefore",
             Self::ChatDefaultModel => "chat.defaultModel",
+            Self::ChatProvider => "chat.provider",
             Self::ChatDisableMarkdownRendering => "chat.disableMarkdownRendering",
+            Self::ChatCollapseReasoning => "chat.collapseReasoning",
             Self::ChatDefaultAgent => "chat.defaultAgent",
             Self::ChatDisableAutoCompaction => "chat.disableAutoCompaction",
             Self::ChatEnableHistoryHints => "chat.enableHistoryHints",
@@ -159,6 +171,7 @@ impl TryFrom<&str> for Setting {
             "knowledge.chunkSize" => Ok(Self::KnowledgeChunkSize),
             "knowledge.chunkOverlap" => Ok(Self::KnowledgeChunkOverlap),
             "knowledge.indexType" => Ok(Self::KnowledgeIndexType),
+            "knowledge.rerank" => Ok(Self::KnowledgeRerank),
             "chat.skimCommandKey" => Ok(Self::SkimCommandKey),
             "chat.autocompletionKey" => Ok(Self::AutocompletionKey),
             "chat.enableTangentMode" => Ok(Self::EnabledTangentMode),
@@ -171,11 +184,14 @@ impl TryFrom<&str> for Setting {
             "chat.enableNotifications" => Ok(Self::ChatEnableNotifications),
             "api.codewhisperer.service" => Ok(Self::ApiCodeWhispererService),
             "api.q.service" => Ok(Self::ApiQService),
+            "api.openai.endpoint" => Ok(Self::ApiOpenAiEndpoint),
             "mcp.initTimeout" => Ok(Self::McpInitTimeout),
             "mcp.noInteractiveTimeout" => Ok(Self::McpNoInteractiveTimeout),
             "mcp.loadedBefore" => Ok(Self::McpLoadedBefore),
             "chat.defaultModel" => Ok(Self::ChatDefaultModel),
+            "chat.provider" => Ok(Self::ChatProvider),
             "chat.disableMarkdownRendering" => Ok(Self::ChatDisableMarkdownRendering),
+            "chat.collapseReasoning" => Ok(Self::ChatCollapseReasoning),
             "chat.defaultAgent" => Ok(Self::ChatDefaultAgent),
             "chat.disableAutoCompaction" => Ok(Self::ChatDisableAutoCompaction),
             "chat.enableHistoryHints" => Ok(Self::ChatEnableHistoryHints),
@@ -0,0 +1,45 @@
+CREATE TABLE sessions (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    cwd TEXT NOT NULL,
+    agent TEXT,
+    model TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    value TEXT NOT NULL
+);
+
+CREATE INDEX sessions_cwd_updated_at ON sessions (cwd, updated_at);
+
+-- Full-text index of the titles and transcripts of the sessions
+CREATE VIRTUAL TABLE sessions_fts USING fts5 (
+    id UNINDEXED,
+    title,
+    transcript
+);
+
+-- Conversations used to be saved per directory, keyed by its path. They become the sessions of
+-- their directories, titled after the first line of their first prompt.
+INSERT OR IGNORE INTO sessions (id, title, cwd, agent, model, created_at, updated_at, value)
+SELECT
+    json_extract(value, '$.conversation_id'),
+    COALESCE((
+        SELECT rtrim(substr(entry.value, 3, CASE WHEN instr(entry.value, char(10)) BETWEEN 1 AND 83
+            THEN instr(entry.value, char(10)) - 3 ELSE 80 END), '> ')
+        FROM json_each(conversations.value, '$.transcript') AS entry
+        WHERE entry.value LIKE '> %'
+        ORDER BY entry.key
+        LIMIT 1
+    ), ''),
+    key,
+    json_extract(value, '$.context_manager.current_profile'),
+    json_extract(value, '$.model_info.model_id'),
+    CAST(strftime('%s', 'now') AS INTEGER),
+    CAST(strftime('%s', 'now') AS INTEGER),
+    value
+FROM conversations
+WHERE json_valid(value) AND json_extract(value, '$.conversation_id') IS NOT NULL;
+
+INSERT INTO sessions_fts (id, title, transcript)
+SELECT id, title, (SELECT group_concat(entry.value, char(10)) FROM json_each(sessions.value, '$.transcript') AS entry)
+FROM sessions;
@@ -0,0 +1,5 @@
+-- Sessions forked from an earlier turn of another session with /fork
+ALTER TABLE sessions ADD COLUMN parent_id TEXT;
+ALTER TABLE sessions ADD COLUMN fork_turn INTEGER;
+
+CREATE INDEX sessions_parent_id ON sessions (parent_id);
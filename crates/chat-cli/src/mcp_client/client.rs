@@ -1,29 +1,53 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
 
 use regex::Regex;
 use rmcp::model::{
+    CallToolRequest,
     CallToolRequestParam,
     CallToolResult,
+    CancelledNotificationParam,
+    ClientCapabilities,
+    ClientRequest,
     ClientResult,
+    CreateElicitationRequestParam,
+    CreateMessageRequestParam,
     ErrorCode,
     GetPromptRequestParam,
     GetPromptResult,
     Implementation,
     InitializeRequestParam,
     ListPromptsResult,
+    ListResourceTemplatesResult,
+    ListResourcesResult,
+    ListRootsResult,
     ListToolsResult,
     LoggingLevel,
     LoggingMessageNotificationParam,
     PaginatedRequestParam,
+    ProgressNotificationParam,
+    ProgressToken,
+    ReadResourceRequestParam,
+    ReadResourceResult,
+    ResourceUpdatedNotificationParam,
+    Root,
+    RootsCapabilities,
+    ServerInfo,
     ServerNotification,
     ServerRequest,
+    ServerResult,
+    SubscribeRequestParam,
 };
 use rmcp::service::{
     ClientInitializeError,
     DynService,
     NotificationContext,
+    Peer,
+    PeerRequestOptions,
+    RequestHandle,
 };
 use rmcp::transport::{
     ConfigureCommandExt,
@@ -36,18 +60,51 @@ use rmcp::{
     ServiceError,
     ServiceExt,
 };
-use tokio::io::AsyncReadExt as _;
+use tokio::io::{
+    AsyncBufReadExt as _,
+    BufReader,
+};
 use tokio::process::{
     ChildStderr,
     Command,
 };
-use tokio::task::JoinHandle;
+use tokio::sync::{
+    Mutex,
+    mpsc,
+    oneshot,
+};
+use tokio::task::{
+    JoinHandle,
+    JoinSet,
+};
 use tracing::{
+    debug,
     error,
     info,
 };
 
+use super::elicitation::{
+    ElicitationRequest,
+    ElicitationResult,
+    declined,
+};
+use super::health::{
+    ServerHealth,
+    ServerState,
+};
+use super::interaction::{
+    ProgressReceiver,
+    UserRequest,
+    UserRequestReceiver,
+    progress_channel,
+    user_request_channel,
+};
 use super::messenger::Messenger;
+use super::sampling::{
+    SamplingRequest,
+    SamplingResult,
+    not_interactive,
+};
 use super::{
     AuthClientWrapper,
     HttpServiceBuilder,
@@ -240,10 +297,22 @@ impl Clone for InnerService {
 /// # Fields
 /// * `inner_service` - The underlying MCP service instance (original or peer)
 /// * `auth_dropguard` - Optional authentication manager for OAuth token handling
+/// * `user_requests` - Sampling and elicitation requests of the server, to be handled by the tool
+///   call in flight
+/// * `progress` - Progress notifications of the server, to be shown by the tool call in flight
+/// * `timeout` - How long a tool call can go without a response
+/// * `reset_timeout_on_progress` - Whether progress notifications restart the timeout of a tool
+///   call
+/// * `cancellations` - Cancellations of interrupted tool calls that are still being sent
 #[derive(Debug)]
 pub struct RunningService {
     pub inner_service: InnerService,
     auth_client: Option<AuthClientWrapper>,
+    pub user_requests: UserRequestReceiver,
+    pub progress: ProgressReceiver,
+    pub timeout: Duration,
+    pub reset_timeout_on_progress: bool,
+    cancellations: Arc<std::sync::Mutex<JoinSet<()>>>,
 }
 
 impl Clone for RunningService {
@@ -251,14 +320,162 @@ impl Clone for RunningService {
         RunningService {
             inner_service: self.inner_service.clone(),
             auth_client: self.auth_client.clone(),
+            user_requests: self.user_requests.clone(),
+            progress: self.progress.clone(),
+            timeout: self.timeout,
+            reset_timeout_on_progress: self.reset_timeout_on_progress,
+            cancellations: self.cancellations.clone(),
         }
     }
 }
 
 impl RunningService {
-    decorate_with_auth_retry!(CallToolRequestParam, call_tool, CallToolResult);
-
     decorate_with_auth_retry!(GetPromptRequestParam, get_prompt, GetPromptResult);
+
+    decorate_with_auth_retry!(ReadResourceRequestParam, read_resource, ReadResourceResult);
+
+    decorate_with_auth_retry!(SubscribeRequestParam, subscribe, ());
+
+    pub fn peer_info(&self) -> Option<&ServerInfo> {
+        match &self.inner_service {
+            InnerService::Original(rs) => rs.peer_info(),
+            InnerService::Peer(peer) => peer.peer_info(),
+        }
+    }
+
+    /// Whether the server notifies the client of updates to the resources it subscribed to
+    pub fn supports_resource_subscriptions(&self) -> bool {
+        self.peer_info()
+            .and_then(|info| info.capabilities.resources.as_ref())
+            .is_some_and(|resources| resources.subscribe == Some(true))
+    }
+
+    pub async fn notify_roots_list_changed(&self) -> Result<(), rmcp::ServiceError> {
+        match &self.inner_service {
+            InnerService::Original(rs) => rs.notify_roots_list_changed().await,
+            InnerService::Peer(peer) => peer.notify_roots_list_changed().await,
+        }
+    }
+
+    /// Starts a tool call without waiting for its result, so that its progress can be followed
+    /// and it can be cancelled.
+    pub async fn start_tool_call(&self, param: CallToolRequestParam) -> Result<ToolCall, rmcp::ServiceError> {
+        let peer = match &self.inner_service {
+            InnerService::Original(rs) => rs.peer().clone(),
+            InnerService::Peer(peer) => peer.clone(),
+        };
+        let request = ClientRequest::CallToolRequest(CallToolRequest {
+            method: Default::default(),
+            params: param,
+            extensions: Default::default(),
+        });
+        let RequestHandle {
+            rx, id, progress_token, ..
+        } = peer
+            .send_request_with_option(request, PeerRequestOptions::no_options())
+            .await?;
+
+        Ok(ToolCall {
+            progress_token,
+            id,
+            peer,
+            response: Some(rx),
+            cancellations: self.cancellations.clone(),
+        })
+    }
+
+    /// Waits for the cancellations of the tool calls that were interrupted to be sent, giving up
+    /// at `deadline`. Cancellations still in flight keep running in the background.
+    pub async fn finish_cancellations(&self, deadline: tokio::time::Instant) {
+        let mut cancellations = std::mem::take(&mut *self.cancellations.lock().unwrap_or_else(|e| e.into_inner()));
+        let all_sent = async { while cancellations.join_next().await.is_some() {} };
+        if tokio::time::timeout_at(deadline, all_sent).await.is_err() {
+            debug!(target: "mcp", "Gave up waiting for interrupted tool calls to be cancelled");
+            cancellations.detach_all();
+        }
+    }
+
+    /// Refreshes the OAuth token of the server, if it uses one. Returns whether the token was
+    /// refreshed, in which case failed requests are worth retrying.
+    pub async fn refresh_token(&self) -> bool {
+        match &self.auth_client {
+            Some(auth_client) => auth_client.refresh_token().await.is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// How long to wait for a server to respond to the cancellation of a tool call.
+const CANCELLATION_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// A tool call in flight. If it is dropped before the server responds, which happens when the
+/// user interrupts it with Ctrl-C, the server is told to stop working on it. The interrupt path
+/// then waits for that with [RunningService::finish_cancellations].
+#[derive(Debug)]
+pub struct ToolCall {
+    pub progress_token: ProgressToken,
+    id: rmcp::model::RequestId,
+    peer: Peer<RoleClient>,
+    response: Option<oneshot::Receiver<Result<ServerResult, ServiceError>>>,
+    cancellations: Arc<std::sync::Mutex<JoinSet<()>>>,
+}
+
+impl ToolCall {
+    /// Waits for the result of the call. This is cancel safe, so it can be used in a
+    /// `tokio::select!` loop.
+    pub async fn result(&mut self) -> Result<CallToolResult, ServiceError> {
+        let Some(response) = self.response.as_mut() else {
+            return Err(ServiceError::TransportClosed);
+        };
+        let result = response.await.map_err(|_e| ServiceError::TransportClosed)?;
+        self.response = None;
+        match result? {
+            ServerResult::CallToolResult(result) => Ok(result),
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    /// Tells the server to stop working on the call, and waits briefly for it to acknowledge.
+    pub async fn cancel(mut self, reason: &str) {
+        if let Some(response) = self.response.take() {
+            cancel_tool_call(self.peer.clone(), self.id.clone(), reason.to_string(), response).await;
+        }
+    }
+}
+
+impl Drop for ToolCall {
+    fn drop(&mut self) {
+        if let Some(response) = self.response.take() {
+            let (peer, id) = (self.peer.clone(), self.id.clone());
+            let cancellation = cancel_tool_call(peer, id, "The user interrupted the tool call".to_string(), response);
+            self.cancellations
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .spawn(cancellation);
+        }
+    }
+}
+
+async fn cancel_tool_call(
+    peer: Peer<RoleClient>,
+    request_id: rmcp::model::RequestId,
+    reason: String,
+    response: oneshot::Receiver<Result<ServerResult, ServiceError>>,
+) {
+    let params = CancelledNotificationParam {
+        request_id: request_id.clone(),
+        reason: Some(reason),
+    };
+    if let Err(e) = peer.notify_cancelled(params).await {
+        error!(target: "mcp", "Failed to cancel tool call {request_id}: {e}");
+        return;
+    }
+
+    // Servers usually respond to cancelled requests with an error, which tells us they stopped
+    match tokio::time::timeout(CANCELLATION_GRACE_PERIOD, response).await {
+        Ok(_) => debug!(target: "mcp", "Server acknowledged the cancellation of tool call {request_id}"),
+        Err(_) => debug!(target: "mcp", "Server did not acknowledge the cancellation of tool call {request_id}"),
+    }
 }
 
 /// This struct implements the [Service] trait from rmcp. It is within this trait the logic of
@@ -269,28 +486,66 @@ pub struct McpClientService {
     pub config: CustomToolConfig,
     server_name: String,
     messenger: ServerMessenger,
+    /// The roots of the workspace, which are shared by all servers and updated by the chat
+    /// session
+    roots: Arc<Mutex<Vec<Root>>>,
+    /// Whether the user can be asked for input. Elicitation requests are declined otherwise.
+    interactive: bool,
+    user_request_sender: mpsc::Sender<UserRequest>,
+    user_requests: UserRequestReceiver,
+    progress_sender: mpsc::Sender<ProgressNotificationParam>,
+    progress: ProgressReceiver,
+    /// Whether the server is running, and what it last wrote to its stderr. This is shared by
+    /// every start of the server.
+    health: ServerHealth,
 }
 
 impl McpClientService {
-    pub fn new(server_name: String, config: CustomToolConfig, messenger: ServerMessenger) -> Self {
+    pub fn new(
+        server_name: String,
+        config: CustomToolConfig,
+        messenger: ServerMessenger,
+        roots: Arc<Mutex<Vec<Root>>>,
+        interactive: bool,
+        health: ServerHealth,
+    ) -> Self {
+        let (user_request_sender, user_requests) = user_request_channel();
+        let (progress_sender, progress) = progress_channel();
         Self {
             server_name,
             config,
             messenger,
+            roots,
+            interactive,
+            user_request_sender,
+            user_requests,
+            progress_sender,
+            progress,
+            health,
         }
     }
 
+    pub fn health(&self) -> &ServerHealth {
+        &self.health
+    }
+
     pub async fn init(self, os: &Os) -> Result<InitializedMcpClient, McpClientError> {
         let os_clone = os.clone();
 
         let handle: JoinHandle<Result<RunningService, McpClientError>> = tokio::spawn(async move {
             let messenger_clone = self.messenger.clone();
             let server_name = self.server_name.clone();
+            let user_requests = self.user_requests.clone();
+            let progress = self.progress.clone();
+            let timeout = Duration::from_millis(self.config.timeout);
+            let reset_timeout_on_progress = self.config.reset_timeout_on_progress;
+            let health = self.health.clone();
 
             let (service, child_stderr, auth_dropguard) = match self.into_service(&os_clone, &messenger_clone).await {
                 Ok((service, stderr, auth_dg)) => (service, stderr, auth_dg),
                 Err(e) => {
                     let msg = e.to_string();
+                    health.set_state(ServerState::Failed { error: msg.clone() });
                     let error_data = ErrorData {
                         code: ErrorCode::RESOURCE_NOT_FOUND,
                         message: Cow::from(msg),
@@ -306,18 +561,23 @@ impl McpClientService {
                 },
             };
 
-            if let Some(mut stderr) = child_stderr {
+            health.set_running(service.peer().clone());
+
+            if let Some(stderr) = child_stderr {
                 let server_name_clone = server_name.clone();
+                let health = health.clone();
                 tokio::spawn(async move {
-                    let mut buf = [0u8; 1024];
+                    let mut lines = BufReader::new(stderr).lines();
                     loop {
-                        match stderr.read(&mut buf).await {
-                            Ok(0) => {
+                        match lines.next_line().await {
+                            Ok(None) => {
                                 tracing::info!(target: "mcp", "{server_name_clone} stderr listening process exited due to EOF");
+                                health.save_stderr();
                                 break;
                             },
-                            Ok(size) => {
-                                tracing::info!(target: "mcp", "{server_name_clone} logged to its stderr: {}", String::from_utf8_lossy(&buf[0..size]));
+                            Ok(Some(line)) => {
+                                tracing::info!(target: "mcp", "{server_name_clone} logged to its stderr: {line}");
+                                health.push_stderr(&line);
                             },
                             Err(e) => {
                                 tracing::info!(target: "mcp", "{server_name_clone} stderr listening process exited due to error: {e}");
@@ -353,6 +613,29 @@ impl McpClientService {
                                 service_method: list_prompts,
                                 result_field: prompts,
                                 messenger_method: send_prompts_list_result,
+                                service: service_clone.clone(),
+                                messenger: messenger_clone,
+                                server_name: server_name
+                            };
+                        }
+
+                        if init_result.capabilities.resources.is_some() {
+                            paginated_fetch! {
+                                final_result_type: ListResourcesResult,
+                                content_type: rmcp::model::Resource,
+                                service_method: list_resources,
+                                result_field: resources,
+                                messenger_method: send_resources_list_result,
+                                service: service_clone.clone(),
+                                messenger: messenger_clone,
+                                server_name: server_name
+                            };
+                            paginated_fetch! {
+                                final_result_type: ListResourceTemplatesResult,
+                                content_type: rmcp::model::ResourceTemplate,
+                                service_method: list_resource_templates,
+                                result_field: resource_templates,
+                                messenger_method: send_resource_templates_list_result,
                                 service: service_clone,
                                 messenger: messenger_clone,
                                 server_name: server_name
@@ -371,6 +654,11 @@ impl McpClientService {
             Ok(RunningService {
                 inner_service: InnerService::Original(service),
                 auth_client: auth_dropguard,
+                user_requests,
+                progress,
+                timeout,
+                reset_timeout_on_progress,
+                cancellations: Default::default(),
             })
         });
 
@@ -451,6 +739,7 @@ impl McpClientService {
                     oauth_scopes: scopes,
                     oauth,
                     timeout,
+                    reset_timeout_on_progress,
                     ..
                 } = &self.config;
 
@@ -460,8 +749,17 @@ impl McpClientService {
                     *value = substitute_env_vars(value, &os.env);
                 }
 
-                let http_service_builder =
-                    HttpServiceBuilder::new(url, os, url, *timeout, scopes, &processed_headers, oauth, messenger);
+                let http_service_builder = HttpServiceBuilder::new(
+                    url,
+                    os,
+                    url,
+                    *timeout,
+                    *reset_timeout_on_progress,
+                    scopes,
+                    &processed_headers,
+                    oauth,
+                    messenger,
+                );
 
                 let (service, auth_client_wrapper) = http_service_builder.try_build(&self).await?;
 
@@ -527,6 +825,110 @@ impl McpClientService {
             server_name: self.server_name
         };
     }
+
+    async fn on_resource_list_changed(&self, context: NotificationContext<RoleClient>) {
+        let NotificationContext { peer, .. } = context;
+
+        paginated_fetch! {
+            final_result_type: ListResourcesResult,
+            content_type: rmcp::model::Resource,
+            service_method: list_resources,
+            result_field: resources,
+            messenger_method: send_resources_list_result,
+            service: peer.clone(),
+            messenger: self.messenger,
+            server_name: self.server_name
+        };
+        paginated_fetch! {
+            final_result_type: ListResourceTemplatesResult,
+            content_type: rmcp::model::ResourceTemplate,
+            service_method: list_resource_templates,
+            result_field: resource_templates,
+            messenger_method: send_resource_templates_list_result,
+            service: peer,
+            messenger: self.messenger,
+            server_name: self.server_name
+        };
+    }
+
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) {
+        let NotificationContext { peer, .. } = context;
+        let ResourceUpdatedNotificationParam { uri } = params;
+
+        let result = peer.read_resource(ReadResourceRequestParam { uri: uri.clone() }).await;
+        if let Err(e) = self.messenger.send_resource_updated(uri, result).await {
+            error!(target: "mcp", "Updated resource failed to send for server {}: {}", self.server_name, e);
+        }
+    }
+
+    async fn on_create_message(&self, mut params: CreateMessageRequestParam) -> SamplingResult {
+        if !self.config.allow_sampling {
+            return Err(ErrorData::method_not_found::<rmcp::model::CreateMessageRequestMethod>());
+        }
+        if let Some(max_tokens) = self.config.sampling_max_tokens {
+            params.max_tokens = params.max_tokens.min(max_tokens);
+        }
+
+        if !self.is_tool_running() {
+            return Err(ErrorData::invalid_request(
+                "Sampling is only available while one of the server's tools is running",
+                None,
+            ));
+        }
+        if !self.interactive {
+            return Err(not_interactive());
+        }
+
+        self.request_user(|responder| UserRequest::Sampling(SamplingRequest { params, responder }))
+            .await
+    }
+
+    async fn on_create_elicitation(&self, params: CreateElicitationRequestParam) -> ElicitationResult {
+        if !self.interactive || !self.is_tool_running() {
+            return Ok(declined());
+        }
+
+        self.request_user(|responder| UserRequest::Elicitation(ElicitationRequest { params, responder }))
+            .await
+    }
+
+    async fn on_progress(&self, params: ProgressNotificationParam) {
+        // Progress is only shown while a tool is running, so older notifications don't pile up
+        if self.is_tool_running() && self.progress_sender.try_send(params).is_err() {
+            debug!(target: "mcp", "Dropped a progress notification of {}", self.server_name);
+        }
+    }
+
+    async fn on_list_roots(&self) -> ListRootsResult {
+        ListRootsResult {
+            roots: self.roots.lock().await.clone(),
+        }
+    }
+
+    /// The receiver of user requests is held by the tool call in flight. If it is free, no one
+    /// would handle a request and the server would wait for a response forever.
+    fn is_tool_running(&self) -> bool {
+        self.user_requests.try_lock().is_err()
+    }
+
+    /// Queues a request for the tool call in flight and waits for its response.
+    async fn request_user<T>(
+        &self,
+        request: impl FnOnce(oneshot::Sender<Result<T, ErrorData>>) -> UserRequest,
+    ) -> Result<T, ErrorData> {
+        let (responder, response) = oneshot::channel();
+        self.user_request_sender
+            .send(request(responder))
+            .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        response
+            .await
+            .map_err(|_e| ErrorData::internal_error("The tool call ended before the request was handled", None))?
+    }
 }
 
 impl Service<RoleClient> for McpClientService {
@@ -537,15 +939,13 @@ impl Service<RoleClient> for McpClientService {
     ) -> Result<<RoleClient as rmcp::service::ServiceRole>::Resp, rmcp::ErrorData> {
         match request {
             ServerRequest::PingRequest(_) => Ok(ClientResult::empty(())),
-            ServerRequest::CreateMessageRequest(_) => Err(rmcp::ErrorData::method_not_found::<
-                rmcp::model::CreateMessageRequestMethod,
-            >()),
-            ServerRequest::ListRootsRequest(_) => {
-                Err(rmcp::ErrorData::method_not_found::<rmcp::model::ListRootsRequestMethod>())
+            ServerRequest::CreateMessageRequest(request) => {
+                self.on_create_message(request.params).await.map(ClientResult::from)
+            },
+            ServerRequest::ListRootsRequest(_) => Ok(ClientResult::from(self.on_list_roots().await)),
+            ServerRequest::CreateElicitationRequest(request) => {
+                self.on_create_elicitation(request.params).await.map(ClientResult::from)
             },
-            ServerRequest::CreateElicitationRequest(_) => Err(rmcp::ErrorData::method_not_found::<
-                rmcp::model::ElicitationCreateRequestMethod,
-            >()),
         }
     }
 
@@ -560,19 +960,32 @@ impl Service<RoleClient> for McpClientService {
                 self.on_logging_message(notification.params, context).await;
             },
             ServerNotification::PromptListChangedNotification(_) => self.on_prompt_list_changed(context).await,
+            ServerNotification::ResourceUpdatedNotification(notification) => {
+                self.on_resource_updated(notification.params, context).await;
+            },
+            ServerNotification::ResourceListChangedNotification(_) => self.on_resource_list_changed(context).await,
+            ServerNotification::ProgressNotification(notification) => self.on_progress(notification.params).await,
             // TODO: support these
             ServerNotification::CancelledNotification(_) => (),
-            ServerNotification::ResourceUpdatedNotification(_) => (),
-            ServerNotification::ResourceListChangedNotification(_) => (),
-            ServerNotification::ProgressNotification(_) => (),
         };
         Ok(())
     }
 
     fn get_info(&self) -> <RoleClient as rmcp::service::ServiceRole>::Info {
+        let mut capabilities = ClientCapabilities {
+            roots: Some(RootsCapabilities {
+                list_changed: Some(true),
+            }),
+            elicitation: Some(Default::default()),
+            ..Default::default()
+        };
+        if self.config.allow_sampling {
+            capabilities.sampling = Some(Default::default());
+        }
+
         InitializeRequestParam {
             protocol_version: Default::default(),
-            capabilities: Default::default(),
+            capabilities,
             client_info: Implementation {
                 name: "Q DEV CLI".to_string(),
                 version: "1.0.0".to_string(),
@@ -0,0 +1,341 @@
+//! Support for `elicitation/create`, which lets MCP servers ask the user for structured input
+//! while one of their tools is running.
+//!
+//! The requested schema is an object of primitive properties, which is rendered as a form with
+//! one prompt per property.
+
+use std::io::{
+    ErrorKind,
+    Write,
+};
+
+use crossterm::{
+    queue,
+    style,
+};
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{
+    Confirm,
+    Input,
+    Select,
+};
+use rmcp::ErrorData;
+use rmcp::model::{
+    CreateElicitationRequestParam,
+    CreateElicitationResult,
+    ElicitationAction,
+    ElicitationSchema,
+    PrimitiveSchema,
+    StringFormat,
+};
+use serde_json::{
+    Map,
+    Value,
+};
+use tokio::sync::oneshot;
+
+use crate::cli::chat::CONTINUATION_LINE;
+use crate::theme::StyledText;
+use crate::util::dialoguer_theme;
+
+pub type ElicitationResult = Result<CreateElicitationResult, ErrorData>;
+
+/// An elicitation request from a server, waiting to be handled by the chat session.
+#[derive(Debug)]
+pub struct ElicitationRequest {
+    pub params: CreateElicitationRequestParam,
+    pub responder: oneshot::Sender<ElicitationResult>,
+}
+
+/// The response to send without asking the user, e.g. when the session is not interactive.
+pub fn declined() -> CreateElicitationResult {
+    CreateElicitationResult {
+        action: ElicitationAction::Decline,
+        content: None,
+    }
+}
+
+/// Shows an elicitation request of `server_name` to the user and asks them to fill in the
+/// requested schema.
+pub async fn handle_elicitation_request(
+    server_name: &str,
+    params: CreateElicitationRequestParam,
+    output: &mut impl Write,
+) -> ElicitationResult {
+    queue_elicitation_request(server_name, &params, output)
+        .and_then(|_| output.flush().map_err(Into::into))
+        .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+
+    let schema = params.requested_schema;
+    tokio::task::spawn_blocking(move || fill_form(&schema))
+        .await
+        .map_err(|e| ErrorData::internal_error(e.to_string(), None))?
+        .map_err(|e| ErrorData::internal_error(e.to_string(), None))
+}
+
+fn queue_elicitation_request(
+    server_name: &str,
+    params: &CreateElicitationRequestParam,
+    output: &mut impl Write,
+) -> eyre::Result<()> {
+    queue!(
+        output,
+        style::Print("\nThe MCP server "),
+        StyledText::success_fg(),
+        style::Print(server_name),
+        StyledText::reset(),
+        style::Print(" is asking for input:\n"),
+    )?;
+    for line in params.message.lines() {
+        queue!(output, style::Print(format!("{CONTINUATION_LINE}  {line}\n")))?;
+    }
+    Ok(())
+}
+
+/// The answer to a single prompt of the form.
+enum Answer {
+    Value(Value),
+    /// An optional property was left empty.
+    Skip,
+    /// The user pressed Esc or Ctrl-C, which cancels the whole request.
+    Cancel,
+}
+
+fn fill_form(schema: &ElicitationSchema) -> Result<CreateElicitationResult, dialoguer::Error> {
+    let theme = dialoguer_theme();
+    let cancelled = CreateElicitationResult {
+        action: ElicitationAction::Cancel,
+        content: None,
+    };
+
+    let choice = Select::with_theme(&theme)
+        .with_prompt("Respond to this request?")
+        .items(&["Respond", "Decline", "Cancel"])
+        .default(0)
+        .interact_opt();
+    match choice {
+        Ok(Some(0)) => (),
+        Ok(Some(1)) => return Ok(declined()),
+        Ok(_) => return Ok(cancelled),
+        Err(dialoguer::Error::IO(e)) if e.kind() == ErrorKind::Interrupted => return Ok(cancelled),
+        Err(e) => return Err(e),
+    }
+
+    let required = schema.required.as_deref().unwrap_or_default();
+    let mut content = Map::new();
+    for (name, property) in &schema.properties {
+        let answer = match prompt_property(&theme, name, property, required.contains(name)) {
+            Err(dialoguer::Error::IO(e)) if e.kind() == ErrorKind::Interrupted => Answer::Cancel,
+            answer => answer?,
+        };
+        match answer {
+            Answer::Value(value) => {
+                content.insert(name.clone(), value);
+            },
+            Answer::Skip => (),
+            Answer::Cancel => return Ok(cancelled),
+        }
+    }
+
+    Ok(CreateElicitationResult {
+        action: ElicitationAction::Accept,
+        content: Some(Value::Object(content)),
+    })
+}
+
+fn prompt_property(
+    theme: &ColorfulTheme,
+    name: &str,
+    property: &PrimitiveSchema,
+    required: bool,
+) -> Result<Answer, dialoguer::Error> {
+    let label = property_label(name, property, required);
+    match property {
+        PrimitiveSchema::Boolean(schema) => Ok(Confirm::with_theme(theme)
+            .with_prompt(label)
+            .default(schema.default.unwrap_or_default())
+            .interact_opt()?
+            .map_or(Answer::Cancel, |value| Answer::Value(Value::Bool(value)))),
+        PrimitiveSchema::Enum(schema) => {
+            let mut items = schema.enum_names.clone().unwrap_or_else(|| schema.enum_values.clone());
+            if !required {
+                items.push("(skip)".to_string());
+            }
+            Ok(
+                match Select::with_theme(theme)
+                    .with_prompt(label)
+                    .items(&items)
+                    .default(0)
+                    .interact_opt()?
+                {
+                    Some(i) => schema
+                        .enum_values
+                        .get(i)
+                        .map_or(Answer::Skip, |value| Answer::Value(Value::String(value.clone()))),
+                    None => Answer::Cancel,
+                },
+            )
+        },
+        PrimitiveSchema::String(_) | PrimitiveSchema::Number(_) | PrimitiveSchema::Integer(_) => {
+            let input = Input::<String>::with_theme(theme)
+                .with_prompt(label)
+                .allow_empty(!required)
+                .validate_with(|input: &String| -> Result<(), String> {
+                    if input.is_empty() {
+                        return Ok(());
+                    }
+                    parse_input(property, input).map(|_| ())
+                })
+                .interact_text()?;
+            if input.is_empty() {
+                return Ok(Answer::Skip);
+            }
+            Ok(parse_input(property, &input).map_or(Answer::Skip, Answer::Value))
+        },
+    }
+}
+
+/// The prompt of a property: its title (or name) and description, marked if it is optional.
+fn property_label(name: &str, property: &PrimitiveSchema, required: bool) -> String {
+    let (title, description) = match property {
+        PrimitiveSchema::String(s) => (&s.title, &s.description),
+        PrimitiveSchema::Number(s) => (&s.title, &s.description),
+        PrimitiveSchema::Integer(s) => (&s.title, &s.description),
+        PrimitiveSchema::Boolean(s) => (&s.title, &s.description),
+        PrimitiveSchema::Enum(s) => (&s.title, &s.description),
+    };
+
+    let mut label = title.as_deref().unwrap_or(name).to_string();
+    if let Some(description) = description {
+        label.push_str(&format!(" - {description}"));
+    }
+    if !required {
+        label.push_str(" (optional)");
+    }
+    label
+}
+
+/// Converts the text entered for a string, number or integer property to its JSON value,
+/// checking the constraints of the schema.
+fn parse_input(property: &PrimitiveSchema, input: &str) -> Result<Value, String> {
+    match property {
+        PrimitiveSchema::String(schema) => {
+            let len = input.chars().count() as u32;
+            if schema.min_length.is_some_and(|min| len < min) || schema.max_length.is_some_and(|max| len > max) {
+                return Err(format!(
+                    "Must be between {} and {} characters",
+                    schema.min_length.unwrap_or(0),
+                    schema
+                        .max_length
+                        .map_or("any number of".to_string(), |max| max.to_string())
+                ));
+            }
+            match schema.format {
+                Some(StringFormat::Email) if !input.contains('@') => Err("Must be an email address".to_string()),
+                Some(StringFormat::Uri) if url::Url::parse(input).is_err() => Err("Must be a URI".to_string()),
+                _ => Ok(Value::String(input.to_string())),
+            }
+        },
+        PrimitiveSchema::Number(schema) => {
+            let value = input
+                .trim()
+                .parse::<f64>()
+                .map_err(|_e| "Must be a number".to_string())?;
+            check_range(value, schema.minimum, schema.maximum)?;
+            Ok(serde_json::json!(value))
+        },
+        PrimitiveSchema::Integer(schema) => {
+            let value = input
+                .trim()
+                .parse::<i64>()
+                .map_err(|_e| "Must be an integer".to_string())?;
+            check_range(value, schema.minimum, schema.maximum)?;
+            Ok(Value::from(value))
+        },
+        PrimitiveSchema::Boolean(_) | PrimitiveSchema::Enum(_) => Err("Expected a choice".to_string()),
+    }
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(value: T, min: Option<T>, max: Option<T>) -> Result<(), String> {
+    match (min, max) {
+        (Some(min), _) if value < min => Err(format!("Must be at least {min}")),
+        (_, Some(max)) if value > max => Err(format!("Must be at most {max}")),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::model::{
+        IntegerSchema,
+        NumberSchema,
+        StringSchema,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_parse_input() {
+        let name = PrimitiveSchema::String(StringSchema {
+            min_length: Some(2),
+            max_length: Some(4),
+            ..Default::default()
+        });
+        assert_eq!(parse_input(&name, "abc"), Ok(Value::from("abc")));
+        assert!(parse_input(&name, "a").is_err());
+        assert!(parse_input(&name, "abcde").is_err());
+
+        let email = PrimitiveSchema::String(StringSchema {
+            format: Some(StringFormat::Email),
+            ..Default::default()
+        });
+        assert!(parse_input(&email, "me@example.com").is_ok());
+        assert!(parse_input(&email, "me").is_err());
+
+        let count = PrimitiveSchema::Integer(IntegerSchema {
+            minimum: Some(1),
+            maximum: Some(10),
+            ..Default::default()
+        });
+        assert_eq!(parse_input(&count, " 3 "), Ok(Value::from(3)));
+        assert!(parse_input(&count, "11").is_err());
+        assert!(parse_input(&count, "2.5").is_err());
+
+        let ratio = PrimitiveSchema::Number(NumberSchema {
+            maximum: Some(1.0),
+            ..Default::default()
+        });
+        assert_eq!(parse_input(&ratio, "0.5"), Ok(serde_json::json!(0.5)));
+        assert!(parse_input(&ratio, "1.5").is_err());
+    }
+
+    #[test]
+    fn test_property_label() {
+        let property = PrimitiveSchema::String(StringSchema {
+            title: Some("Branch".into()),
+            description: Some("The branch to deploy".into()),
+            ..Default::default()
+        });
+        assert_eq!(
+            property_label("branch", &property, true),
+            "Branch - The branch to deploy"
+        );
+
+        let property = PrimitiveSchema::Integer(IntegerSchema::default());
+        assert_eq!(property_label("replicas", &property, false), "replicas (optional)");
+    }
+
+    #[test]
+    fn test_queue_elicitation_request() {
+        let params = CreateElicitationRequestParam {
+            message: "Which environment?\nPick one".to_string(),
+            requested_schema: ElicitationSchema::new(Default::default()),
+        };
+        let mut buf = Vec::new();
+        queue_elicitation_request("deploy", &params, &mut buf).unwrap();
+        let output = String::from_utf8(strip_ansi_escapes::strip(buf)).unwrap();
+        assert!(output.contains("deploy is asking for input"));
+        assert!(output.contains("  Which environment?\n"));
+        assert!(output.contains("  Pick one\n"));
+    }
+}
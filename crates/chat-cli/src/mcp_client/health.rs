@@ -0,0 +1,312 @@
+//! Health tracking of MCP servers.
+//!
+//! Each [McpClientService] shares a [ServerHealth] with the tasks it spawns, which records
+//! whether the server is running, the last lines it wrote to its stderr, and the peer used to
+//! notice that its transport closed. [supervise] watches the server for the whole chat session,
+//! and when a stdio server exits or an HTTP server drops the connection it starts the server
+//! again, backing off exponentially between attempts.
+//!
+//! The status is also saved to the database, so that `q mcp status` can show it from another
+//! process. It is saved when the state changes, while the lines written to stderr are only saved
+//! periodically by [supervise] since servers may log a lot.
+
+use std::collections::{
+    HashMap,
+    VecDeque,
+};
+use std::sync::atomic::{
+    AtomicBool,
+    Ordering,
+};
+use std::sync::{
+    Arc,
+    Mutex as StdMutex,
+};
+use std::time::Duration;
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use rmcp::{
+    Peer,
+    RoleClient,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use tokio::sync::Mutex;
+use tracing::{
+    error,
+    info,
+    warn,
+};
+
+use super::{
+    InitializedMcpClient,
+    McpClientError,
+    McpClientService,
+    RunningService,
+};
+use crate::database::Database;
+use crate::os::Os;
+
+/// How many lines of a server's stderr are kept.
+pub const STDERR_TAIL_LINES: usize = 10;
+
+/// How many times a server is started again before it is considered failed.
+pub const MAX_RESTART_ATTEMPTS: u32 = 5;
+
+const INITIAL_RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Servers started again by [supervise], waiting to replace the stopped clients of the tool
+/// manager.
+pub type RestartedClients = Arc<Mutex<HashMap<String, RunningService>>>;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum ServerState {
+    /// The server is being started for the first time, or was restarted with `/mcp restart`.
+    #[default]
+    Starting,
+    Running,
+    /// The server stopped unexpectedly and is being started again.
+    Restarting {
+        attempt: u32,
+    },
+    /// The server could not be started, or stopped and could not be started again.
+    Failed {
+        error: String,
+    },
+    /// The chat session running the server has exited.
+    Stopped,
+}
+
+impl std::fmt::Display for ServerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerState::Starting => write!(f, "starting"),
+            ServerState::Running => write!(f, "running"),
+            ServerState::Restarting { attempt } => {
+                write!(f, "restarting (attempt {attempt} of {MAX_RESTART_ATTEMPTS})")
+            },
+            ServerState::Failed { error } => write!(f, "failed: {error}"),
+            ServerState::Stopped => write!(f, "stopped"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    #[serde(flatten)]
+    pub state: ServerState,
+    /// The last lines the server wrote to its stderr, oldest first.
+    pub stderr: VecDeque<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct HealthInner {
+    status: ServerStatus,
+    peer: Option<Peer<RoleClient>>,
+    /// Whether lines were written to stderr since the status was last saved.
+    unsaved_stderr: bool,
+}
+
+/// The health of a server, shared by its client and the tasks watching it.
+#[derive(Clone, Debug)]
+pub struct ServerHealth {
+    server_name: String,
+    database: Database,
+    inner: Arc<StdMutex<HealthInner>>,
+}
+
+impl ServerHealth {
+    pub fn new(server_name: String, database: Database) -> Self {
+        Self {
+            server_name,
+            database,
+            inner: Default::default(),
+        }
+    }
+
+    pub fn server_name(&self) -> &str {
+        &self.server_name
+    }
+
+    pub fn status(&self) -> ServerStatus {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).status.clone()
+    }
+
+    pub fn state(&self) -> ServerState {
+        self.status().state
+    }
+
+    pub fn set_state(&self, state: ServerState) {
+        let status = {
+            let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            if inner.status.state == state {
+                return;
+            }
+            inner.status.state = state;
+            inner.status.updated_at = Utc::now();
+            inner.unsaved_stderr = false;
+            inner.status.clone()
+        };
+        info!(target: "mcp", "{} is {}", self.server_name, status.state);
+        self.save(&status);
+    }
+
+    /// Records that the server started, connected through `peer`.
+    pub fn set_running(&self, peer: Peer<RoleClient>) {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).peer = Some(peer);
+        self.set_state(ServerState::Running);
+    }
+
+    /// Keeps a line the server wrote to its stderr, which is saved later by [Self::save_stderr].
+    pub fn push_stderr(&self, line: &str) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let stderr = &mut inner.status.stderr;
+        stderr.push_back(line.trim_end().to_string());
+        while stderr.len() > STDERR_TAIL_LINES {
+            stderr.pop_front();
+        }
+        inner.status.updated_at = Utc::now();
+        inner.unsaved_stderr = true;
+    }
+
+    /// Saves the status if lines were written to stderr since it was last saved.
+    pub fn save_stderr(&self) {
+        let status = {
+            let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            if !std::mem::take(&mut inner.unsaved_stderr) {
+                return;
+            }
+            inner.status.clone()
+        };
+        self.save(&status);
+    }
+
+    fn save(&self, status: &ServerStatus) {
+        if let Err(e) = self.database.set_mcp_server_status(&self.server_name, status) {
+            error!(target: "mcp", "Failed to save the status of {}: {e}", self.server_name);
+        }
+    }
+
+    /// Whether the server was running and its transport has since closed, which happens when a
+    /// stdio server exits or an HTTP server drops the connection.
+    fn has_stopped(&self) -> bool {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.status.state == ServerState::Running && inner.peer.as_ref().is_some_and(|p| p.is_transport_closed())
+    }
+}
+
+/// How long to wait before the given attempt to start a server again.
+pub fn restart_delay(attempt: u32) -> Duration {
+    INITIAL_RESTART_DELAY
+        .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_RESTART_DELAY)
+}
+
+/// Watches a server until it fails, starting it again whenever it stops.
+///
+/// Restarted servers are put in `restarted` for the tool manager to pick up. Since starting a
+/// server lists its tools and prompts again, nothing else needs to be refreshed. If a server can't
+/// be started again, `has_new_stuff` is set so that its tools are removed.
+pub async fn supervise(service: McpClientService, os: Os, restarted: RestartedClients, has_new_stuff: Arc<AtomicBool>) {
+    let health = service.health().clone();
+    let server_name = health.server_name().to_string();
+    loop {
+        tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+        health.save_stderr();
+        if matches!(health.state(), ServerState::Failed { .. }) {
+            return;
+        }
+        if !health.has_stopped() {
+            continue;
+        }
+
+        warn!(target: "mcp", "{server_name} stopped unexpectedly, starting it again");
+        let mut last_error = None;
+        let mut running_service = None;
+        for attempt in 1..=MAX_RESTART_ATTEMPTS {
+            health.set_state(ServerState::Restarting { attempt });
+            tokio::time::sleep(restart_delay(attempt)).await;
+            match start(service.clone(), &os).await {
+                Ok(service) => {
+                    running_service = Some(service);
+                    break;
+                },
+                Err(e) => {
+                    warn!(target: "mcp", "Failed to start {server_name} again: {e}");
+                    last_error = Some(e);
+                },
+            }
+        }
+
+        match running_service {
+            Some(running_service) => {
+                restarted.lock().await.insert(server_name.clone(), running_service);
+            },
+            None => {
+                let error = last_error.map_or("unknown error".to_string(), |e| e.to_string());
+                health.set_state(ServerState::Failed {
+                    error: format!("stopped and could not be started again: {error}"),
+                });
+                has_new_stuff.store(true, Ordering::Release);
+                return;
+            },
+        }
+    }
+}
+
+async fn start(service: McpClientService, os: &Os) -> Result<RunningService, McpClientError> {
+    match service.init(os).await? {
+        InitializedMcpClient::Pending(handle) => handle.await?,
+        InitializedMcpClient::Ready(running_service) => Ok(running_service),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_delay() {
+        assert_eq!(restart_delay(1), Duration::from_secs(1));
+        assert_eq!(restart_delay(2), Duration::from_secs(2));
+        assert_eq!(restart_delay(4), Duration::from_secs(8));
+        assert_eq!(restart_delay(6), MAX_RESTART_DELAY);
+        assert_eq!(restart_delay(100), MAX_RESTART_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_server_health() {
+        let database = Database::new().await.unwrap();
+        let health = ServerHealth::new("git".to_string(), database.clone());
+        for i in 0..STDERR_TAIL_LINES + 2 {
+            health.push_stderr(&format!("line {i}\n"));
+        }
+        assert_eq!(database.get_mcp_server_status("git").unwrap(), None);
+        health.save_stderr();
+        let status = database.get_mcp_server_status("git").unwrap().unwrap();
+        assert_eq!(status.stderr.back().map(String::as_str), Some("line 11"));
+
+        health.set_state(ServerState::Restarting { attempt: 2 });
+
+        let status = database.get_mcp_server_status("git").unwrap().unwrap();
+        assert_eq!(status, health.status());
+        assert_eq!(status.state, ServerState::Restarting { attempt: 2 });
+        assert_eq!(status.stderr.len(), STDERR_TAIL_LINES);
+        assert_eq!(status.stderr.front().map(String::as_str), Some("line 2"));
+        assert!(!health.has_stopped());
+
+        health.set_state(ServerState::Stopped);
+        let status = database.get_mcp_server_status("git").unwrap().unwrap();
+        assert_eq!(status.state, ServerState::Stopped);
+    }
+}
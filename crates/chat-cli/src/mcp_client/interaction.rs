@@ -0,0 +1,42 @@
+//! Requests of MCP servers that need the user, such as sampling and elicitation requests, and
+//! progress notifications of their tool calls.
+//!
+//! Servers send these while one of their tools is running, so [McpClientService] queues them and
+//! the tool call that is in flight handles them, since it is the one with access to the terminal.
+//!
+//! [McpClientService]: super::McpClientService
+
+use std::sync::Arc;
+
+use rmcp::model::ProgressNotificationParam;
+use tokio::sync::{
+    Mutex,
+    mpsc,
+};
+
+use super::elicitation::ElicitationRequest;
+use super::sampling::SamplingRequest;
+
+/// A request of a server, waiting to be handled by the chat session.
+#[derive(Debug)]
+pub enum UserRequest {
+    Sampling(SamplingRequest),
+    Elicitation(ElicitationRequest),
+}
+
+/// Receiving end of the user requests of a server. It is locked by the tool call in flight, so a
+/// request that arrives while it is unlocked has no one to handle it.
+pub type UserRequestReceiver = Arc<Mutex<mpsc::Receiver<UserRequest>>>;
+
+pub fn user_request_channel() -> (mpsc::Sender<UserRequest>, UserRequestReceiver) {
+    let (tx, rx) = mpsc::channel(8);
+    (tx, Arc::new(Mutex::new(rx)))
+}
+
+/// Receiving end of the progress notifications of a server, read by the tool call in flight.
+pub type ProgressReceiver = Arc<Mutex<mpsc::Receiver<ProgressNotificationParam>>>;
+
+pub fn progress_channel() -> (mpsc::Sender<ProgressNotificationParam>, ProgressReceiver) {
+    let (tx, rx) = mpsc::channel(32);
+    (tx, Arc::new(Mutex::new(rx)))
+}
@@ -3,6 +3,7 @@ use rmcp::model::{
     ListResourceTemplatesResult,
     ListResourcesResult,
     ListToolsResult,
+    ReadResourceResult,
 };
 use rmcp::{
     Peer,
@@ -53,6 +54,10 @@ pub trait Messenger: std::fmt::Debug + Send + Sync + 'static {
         peer: Option<Peer<RoleClient>>,
     ) -> MessengerResult;
 
+    /// Sends the new content of a resource the server notified as updated to the consumer
+    /// This function is used to refresh the resource content cached by the consumer
+    async fn send_resource_updated(&self, uri: String, result: Result<ReadResourceResult>) -> MessengerResult;
+
     /// Sends an OAuth authorization link to the consumer
     /// This function is used to deliver OAuth links that users need to visit for authentication
     async fn send_oauth_link(&self, link: String) -> MessengerResult;
@@ -111,6 +116,10 @@ impl Messenger for NullMessenger {
         Ok(())
     }
 
+    async fn send_resource_updated(&self, _uri: String, _result: Result<ReadResourceResult>) -> MessengerResult {
+        Ok(())
+    }
+
     async fn send_oauth_link(&self, _link: String) -> MessengerResult {
         Ok(())
     }
@@ -1,6 +1,10 @@
 pub mod client;
+pub mod elicitation;
+pub mod health;
+pub mod interaction;
 pub mod messenger;
 pub mod oauth_util;
+pub mod sampling;
 
 pub use client::*;
 pub use oauth_util::*;
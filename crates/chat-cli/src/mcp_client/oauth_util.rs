@@ -194,6 +194,7 @@ pub struct HttpServiceBuilder<'a> {
     pub os: &'a Os,
     pub url: &'a str,
     pub timeout: u64,
+    pub reset_timeout_on_progress: bool,
     pub scopes: &'a [String],
     pub headers: &'a HashMap<String, String>,
     pub oauth_config: &'a Option<crate::cli::chat::tools::custom_tool::OAuthConfig>,
@@ -207,6 +208,7 @@ impl<'a> HttpServiceBuilder<'a> {
         os: &'a Os,
         url: &'a str,
         timeout: u64,
+        reset_timeout_on_progress: bool,
         scopes: &'a [String],
         headers: &'a HashMap<String, String>,
         oauth_config: &'a Option<crate::cli::chat::tools::custom_tool::OAuthConfig>,
@@ -217,6 +219,7 @@ impl<'a> HttpServiceBuilder<'a> {
             os,
             url,
             timeout,
+            reset_timeout_on_progress,
             scopes,
             headers,
             oauth_config,
@@ -233,6 +236,7 @@ impl<'a> HttpServiceBuilder<'a> {
             os,
             url,
             timeout,
+            reset_timeout_on_progress,
             scopes,
             headers,
             oauth_config,
@@ -247,7 +251,14 @@ impl<'a> HttpServiceBuilder<'a> {
         let reg_full_path = cred_dir.join(format!("{key}.registration.json"));
         let mut auth_client = None::<AuthClient<Client>>;
 
-        let mut client_builder = reqwest::ClientBuilder::new().timeout(std::time::Duration::from_millis(timeout));
+        // A timeout for the whole response would end tool calls that are still streaming progress
+        // notifications, so only reads time out when the timeout resets on progress
+        let timeout = std::time::Duration::from_millis(timeout);
+        let mut client_builder = if reset_timeout_on_progress {
+            reqwest::ClientBuilder::new().read_timeout(timeout)
+        } else {
+            reqwest::ClientBuilder::new().timeout(timeout)
+        };
         if !headers.is_empty() {
             let headers = HeaderMap::try_from(headers).map_err(|e| OauthUtilError::Http(e.to_string()))?;
             client_builder = client_builder.default_headers(headers);
@@ -0,0 +1,314 @@
+//! Support for `sampling/createMessage`, which lets MCP servers request completions from the model
+//! of the chat session.
+//!
+//! Servers send these requests while one of their tools is running, so [McpClientService] queues
+//! them and the tool call that is in flight shows each request to the user for approval before
+//! sending it to the active model.
+//!
+//! [McpClientService]: super::McpClientService
+
+use std::io::{
+    ErrorKind,
+    Write,
+};
+
+use crossterm::{
+    queue,
+    style,
+};
+use dialoguer::Select;
+use rmcp::ErrorData;
+use rmcp::model::{
+    Content,
+    CreateMessageRequestParam,
+    CreateMessageResult,
+    ErrorCode,
+    Role,
+    SamplingMessage,
+};
+use tokio::sync::oneshot;
+use tracing::debug;
+
+use crate::api_client::model::{
+    AssistantResponseMessage,
+    ChatMessage,
+    ChatResponseStream,
+    ConversationState,
+    UserInputMessage,
+};
+use crate::cli::chat::CONTINUATION_LINE;
+use crate::cli::chat::cli::model::ModelInfo;
+use crate::os::Os;
+use crate::theme::StyledText;
+use crate::util::dialoguer_theme;
+
+/// Error code for requests the user rejected, as suggested by the MCP specification.
+const USER_REJECTED: ErrorCode = ErrorCode(-1);
+
+pub type SamplingResult = Result<CreateMessageResult, ErrorData>;
+
+/// A sampling request from a server, waiting to be handled by the chat session.
+#[derive(Debug)]
+pub struct SamplingRequest {
+    pub params: CreateMessageRequestParam,
+    pub responder: oneshot::Sender<SamplingResult>,
+}
+
+/// The error to send without asking the user, e.g. when the session is not interactive.
+pub fn not_interactive() -> ErrorData {
+    ErrorData::new(
+        USER_REJECTED,
+        "Sampling requests need approval, but the chat session is not interactive",
+        None,
+    )
+}
+
+/// Asks the user to approve a sampling request of `server_name`, and sends it to the model if
+/// approved.
+pub async fn handle_sampling_request(
+    os: &Os,
+    server_name: &str,
+    params: CreateMessageRequestParam,
+    model_info: Option<&ModelInfo>,
+    output: &mut impl Write,
+) -> SamplingResult {
+    // Validate before asking, so the user isn't asked to approve a request that can't be sent
+    let conversation = conversation_state(&params, model_info)?;
+
+    queue_sampling_request(server_name, &params, output)
+        .and_then(|_| output.flush().map_err(Into::into))
+        .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+    let approved = tokio::task::spawn_blocking(ask_approval)
+        .await
+        .map_err(|e| ErrorData::internal_error(e.to_string(), None))?
+        .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+    if !approved {
+        return Err(ErrorData::new(
+            USER_REJECTED,
+            "The user rejected the sampling request",
+            None,
+        ));
+    }
+
+    create_message(os, conversation).await
+}
+
+/// Converts a sampling request to a request for the active model.
+///
+/// Model preferences are ignored, since the user chose the model of the session. Only text
+/// content is supported.
+pub fn conversation_state(
+    params: &CreateMessageRequestParam,
+    model_info: Option<&ModelInfo>,
+) -> Result<ConversationState, ErrorData> {
+    if params.temperature.is_some() || params.stop_sequences.is_some() {
+        debug!("Ignoring the temperature and stop sequences of a sampling request");
+    }
+
+    // Consecutive messages of the same role are merged, since models expect the roles to
+    // alternate
+    let mut messages = Vec::<(Role, String)>::new();
+    for message in &params.messages {
+        let Some(text) = message.content.as_text() else {
+            return Err(ErrorData::invalid_params(
+                "Only text content is supported in sampling requests",
+                None,
+            ));
+        };
+        match messages.last_mut() {
+            Some((role, content)) if *role == message.role => {
+                content.push_str("\n\n");
+                content.push_str(&text.text);
+            },
+            _ => messages.push((message.role.clone(), text.text.clone())),
+        }
+    }
+
+    let Some((Role::User, content)) = messages.pop() else {
+        return Err(ErrorData::invalid_params(
+            "The last message of a sampling request must be from the user",
+            None,
+        ));
+    };
+    let model_id = model_info.map(|m| m.model_id.clone());
+    let history = messages
+        .into_iter()
+        .map(|(role, content)| match role {
+            Role::User => ChatMessage::UserInputMessage(user_message(content, model_id.clone())),
+            Role::Assistant => ChatMessage::AssistantResponseMessage(AssistantResponseMessage {
+                message_id: None,
+                content,
+                tool_uses: None,
+                reasoning: None,
+            }),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ConversationState {
+        conversation_id: None,
+        user_input_message: user_message(content, model_id),
+        history: (!history.is_empty()).then_some(history),
+        service_tier: None,
+        model_info: model_info.cloned(),
+        agent_prompt: params.system_prompt.clone(),
+        reasoning_budget_tokens: None,
+        max_tokens: Some(params.max_tokens),
+        guardrail: None,
+    })
+}
+
+fn user_message(content: String, model_id: Option<String>) -> UserInputMessage {
+    UserInputMessage {
+        content,
+        user_input_message_context: None,
+        user_intent: None,
+        images: None,
+        documents: None,
+        model_id,
+    }
+}
+
+fn queue_sampling_request(
+    server_name: &str,
+    params: &CreateMessageRequestParam,
+    output: &mut impl Write,
+) -> eyre::Result<()> {
+    queue!(
+        output,
+        style::Print("\nThe MCP server "),
+        StyledText::success_fg(),
+        style::Print(server_name),
+        StyledText::reset(),
+        style::Print(format!(
+            " wants to send the following to the model, generating up to {} tokens:\n",
+            params.max_tokens
+        )),
+    )?;
+
+    let system_prompt = params.system_prompt.as_deref().map(|prompt| ("System", prompt));
+    let messages = params.messages.iter().map(|SamplingMessage { role, content }| {
+        let role = match role {
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+        };
+        (role, content.as_text().map_or("", |text| text.text.as_str()))
+    });
+    for (role, text) in system_prompt.into_iter().chain(messages) {
+        queue!(
+            output,
+            StyledText::secondary_fg(),
+            style::Print(format!("{CONTINUATION_LINE}{role}:\n")),
+            StyledText::reset(),
+        )?;
+        for line in text.lines() {
+            queue!(output, style::Print(format!("{CONTINUATION_LINE}  {line}\n")))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Asks whether to send the request, which is rejected if the user presses Esc or Ctrl-C.
+fn ask_approval() -> Result<bool, dialoguer::Error> {
+    let choice = Select::with_theme(&dialoguer_theme())
+        .with_prompt("Allow this request?")
+        .items(&["Allow", "Reject"])
+        .default(1)
+        .interact_opt();
+    match choice {
+        Ok(choice) => Ok(choice == Some(0)),
+        Err(dialoguer::Error::IO(e)) if e.kind() == ErrorKind::Interrupted => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+async fn create_message(os: &Os, conversation: ConversationState) -> SamplingResult {
+    let model = conversation
+        .model_info
+        .as_ref()
+        .map(|m| m.model_id.clone())
+        .unwrap_or_default();
+    let to_error = |e: &dyn std::fmt::Display| ErrorData::internal_error(e.to_string(), None);
+
+    let mut response = os.client.send_message(conversation).await.map_err(|e| to_error(&e))?;
+    let mut text = String::new();
+    while let Some(event) = response.recv().await.map_err(|e| to_error(&e))? {
+        if let ChatResponseStream::AssistantResponseEvent { content } = event {
+            text.push_str(&content);
+        }
+    }
+
+    Ok(CreateMessageResult {
+        model,
+        stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.to_string()),
+        message: SamplingMessage {
+            role: Role::Assistant,
+            content: Content::text(text),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(text: &str) -> SamplingMessage {
+        SamplingMessage {
+            role: Role::User,
+            content: Content::text(text),
+        }
+    }
+
+    fn assistant(text: &str) -> SamplingMessage {
+        SamplingMessage {
+            role: Role::Assistant,
+            content: Content::text(text),
+        }
+    }
+
+    fn params(messages: Vec<SamplingMessage>) -> CreateMessageRequestParam {
+        CreateMessageRequestParam {
+            messages,
+            model_preferences: None,
+            system_prompt: Some("Be brief".to_string()),
+            include_context: None,
+            temperature: None,
+            max_tokens: 100,
+            stop_sequences: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_conversation_state() {
+        let params = params(vec![user("a"), user("b"), assistant("c"), user("d")]);
+        let state = conversation_state(&params, None).unwrap();
+        assert_eq!(state.user_input_message.content, "d");
+        assert_eq!(state.agent_prompt.as_deref(), Some("Be brief"));
+        assert_eq!(state.max_tokens, Some(100));
+
+        let history = state.history.unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(matches!(&history[0], ChatMessage::UserInputMessage(m) if m.content == "a\n\nb"));
+        assert!(matches!(&history[1], ChatMessage::AssistantResponseMessage(m) if m.content == "c"));
+    }
+
+    #[test]
+    fn test_conversation_state_invalid() {
+        let last_from_assistant = params(vec![user("a"), assistant("b")]);
+        assert!(conversation_state(&last_from_assistant, None).is_err());
+        assert!(conversation_state(&params(vec![]), None).is_err());
+    }
+
+    #[test]
+    fn test_queue_sampling_request() {
+        let mut buf = Vec::new();
+        let params = params(vec![user("Summarize this:\nsome text")]);
+        queue_sampling_request("docs", &params, &mut buf).unwrap();
+        let output = String::from_utf8(strip_ansi_escapes::strip(buf)).unwrap();
+        assert!(output.contains("docs wants to send the following to the model, generating up to 100 tokens"));
+        assert!(output.contains("  Be brief\n"));
+        assert!(output.contains("  Summarize this:\n"));
+        assert!(output.contains("  some text\n"));
+    }
+}
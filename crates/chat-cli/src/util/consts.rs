@@ -111,6 +111,9 @@ pub mod env_var {
         /// AWS region
         AWS_REGION = "AWS_REGION",
 
+        /// API key sent to the OpenAI compatible chat completions endpoint
+        OPENAI_API_KEY = "OPENAI_API_KEY",
+
         /// GitHub Codespaces environment
         CODESPACES = "CODESPACES",
 
@@ -29,6 +29,11 @@ pub fn get_mock_chat_response(env: &Env) -> Option<String> {
     env.get(Q_MOCK_CHAT_RESPONSE).ok()
 }
 
+/// Get the API key for the OpenAI compatible chat completions endpoint
+pub fn get_openai_api_key(env: &Env) -> Option<String> {
+    env.get(OPENAI_API_KEY).ok().filter(|key| !key.is_empty())
+}
+
 /// Check if truecolor is disabled
 pub fn is_truecolor_disabled() -> bool {
     Env::new().get_os(Q_DISABLE_TRUECOLOR).is_some_and(|s| !s.is_empty())
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{
     Arc,
@@ -13,6 +14,7 @@ use semantic_search_client::types::{
     SearchResult,
 };
 use tokio::sync::Mutex;
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::cli::DEFAULT_AGENT_NAME;
@@ -55,6 +57,7 @@ pub struct AddOptions {
     pub include_patterns: Vec<String>,
     pub exclude_patterns: Vec<String>,
     pub embedding_type: Option<String>,
+    pub watch: bool,
 }
 
 impl AddOptions {
@@ -99,6 +102,7 @@ impl AddOptions {
             include_patterns: default_include,
             exclude_patterns: default_exclude,
             embedding_type: default_embedding_type,
+            watch: false,
         }
     }
 
@@ -116,6 +120,11 @@ impl AddOptions {
         self.embedding_type = embedding_type;
         self
     }
+
+    pub fn with_watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -181,6 +190,26 @@ impl KnowledgeStore {
         }
     }
 
+    /// Load the knowledge store if the agent has watched contexts, so that they keep updating
+    pub async fn resume_watching(os: &Os, agent: Option<&crate::cli::Agent>) {
+        let Ok(agent_dir) = agent_knowledge_dir(os, agent) else {
+            return;
+        };
+        let Ok(contents) = tokio::fs::read(agent_dir.join("contexts.json")).await else {
+            return;
+        };
+        let Ok(contexts) = serde_json::from_slice::<HashMap<String, KnowledgeContext>>(&contents) else {
+            return;
+        };
+
+        if !contexts.values().any(|context| context.watch && context.persistent) {
+            return;
+        }
+        if let Err(e) = Self::get_async_instance(os, agent).await {
+            warn!("Failed to resume watching knowledge contexts: {}", e);
+        }
+    }
+
     /// Migrate legacy knowledge base from old location if needed
     async fn migrate_legacy_knowledge_base(agent_dir: &PathBuf) -> bool {
         let mut migrated = false;
@@ -260,11 +289,18 @@ impl KnowledgeStore {
             .and_then(|s| EmbeddingType::from_str(&s))
             .unwrap_or_default();
 
+        let rerank = os
+            .database
+            .settings
+            .get_bool(Setting::KnowledgeRerank)
+            .unwrap_or(default_config.rerank);
+
         SemanticSearchConfig {
             chunk_size,
             chunk_overlap,
             max_files,
             embedding_type,
+            rerank,
             base_dir,
             ..default_config
         }
@@ -328,11 +364,15 @@ impl KnowledgeStore {
                 Some(s) => match EmbeddingType::from_str(s) {
                     Some(et) => Some(et),
                     None => {
-                        return Err(format!("Invalid embedding type '{}'. Valid options are: fast, best", s));
+                        return Err(format!(
+                            "Invalid embedding type '{}'. Valid options are: fast, best, hybrid",
+                            s
+                        ));
                     },
                 },
                 None => None,
             },
+            watch: options.watch,
         };
 
         match self.agent_client.add_context(request).await {
@@ -353,6 +393,9 @@ impl KnowledgeStore {
                     }
                     message.push_str("\n✅ Only matching files will be indexed");
                 }
+                if options.watch {
+                    message.push_str("\n👀 Watching for changes, the knowledge base will update as files change");
+                }
                 Ok(message)
             },
             Err(e) => {
@@ -501,20 +544,7 @@ impl KnowledgeStore {
     /// Update context by path
     pub async fn update_by_path(&mut self, path_str: &str) -> Result<String, String> {
         if let Some(context) = self.agent_client.get_context_by_path(path_str).await {
-            // Remove the existing context first
-            self.agent_client
-                .remove_context_by_id(&context.id)
-                .await
-                .map_err(|e| e.to_string())?;
-
-            // Then add it back with the same name and original patterns (agent scope)
-            let options = AddOptions {
-                description: None,
-                include_patterns: context.include_patterns.clone(),
-                exclude_patterns: context.exclude_patterns.clone(),
-                embedding_type: None,
-            };
-            self.add(&context.name, path_str, options).await
+            self.update(&context, path_str).await
         } else {
             // Debug: List all available contexts
             let available_paths = self.agent_client.list_context_paths().await;
@@ -538,45 +568,35 @@ impl KnowledgeStore {
             .find(|c| c.id == context_id)
             .ok_or_else(|| format!("Context '{}' not found", context_id))?;
 
-        let context_name = context.name.clone();
-
-        // Remove the existing context first
-        self.agent_client
-            .remove_context_by_id(context_id)
-            .await
-            .map_err(|e| e.to_string())?;
-
-        // Then add it back with the same name and original patterns
-        let options = AddOptions {
-            description: None,
-            include_patterns: context.include_patterns.clone(),
-            exclude_patterns: context.exclude_patterns.clone(),
-            embedding_type: None,
-        };
-        self.add(&context_name, path_str, options).await
+        self.update(context, path_str).await
     }
 
     /// Update context by name
     pub async fn update_context_by_name(&mut self, name: &str, path_str: &str) -> Result<String, String> {
         if let Some(context) = self.agent_client.get_context_by_name(name).await {
-            // Remove the existing context first
-            self.agent_client
-                .remove_context_by_id(&context.id)
-                .await
-                .map_err(|e| e.to_string())?;
-
-            // Then add it back with the same name and original patterns (agent scope)
-            let options = AddOptions {
-                description: None,
-                include_patterns: context.include_patterns.clone(),
-                exclude_patterns: context.exclude_patterns.clone(),
-                embedding_type: None,
-            };
-            self.add(name, path_str, options).await
+            self.update(&context, path_str).await
         } else {
             Err(format!("Context with name '{}' not found", name))
         }
     }
+
+    /// Re-index the files of a context that changed since it was last indexed, keeping its
+    /// name, patterns and embedding type
+    async fn update(&mut self, context: &KnowledgeContext, path_str: &str) -> Result<String, String> {
+        let canonical_path = std::path::PathBuf::from(path_str)
+            .canonicalize()
+            .map_err(|_io_error| format!("❌ Path does not exist: {}", path_str))?;
+
+        match self.agent_client.update_context(&context.id, &canonical_path).await {
+            Ok((operation_id, _)) => Ok(format!(
+                "🚀 Started updating '{}'\n📁 Path: {}\n🆔 Operation ID: {}\n✅ Only files added or changed since the last indexing will be indexed again",
+                context.name,
+                canonical_path.display(),
+                &operation_id.to_string()[..8]
+            )),
+            Err(e) => Err(format!("Failed to start updating: {}", e)),
+        }
+    }
 }
 
 #[cfg(test)]
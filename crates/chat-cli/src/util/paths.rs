@@ -64,6 +64,9 @@ pub mod global {
     pub const GLOBAL_CONTEXT: &str = ".aws/amazonq/global_context.json";
     pub const PROFILES_DIR: &str = ".aws/amazonq/profiles";
     pub const KNOWLEDGE_BASES_DIR: &str = ".aws/amazonq/knowledge_bases";
+    pub const MODELS_CONFIG: &str = ".aws/amazonq/models.json";
+    pub const TOKENIZERS_DIR: &str = ".aws/amazonq/tokenizers";
+    pub const TRANSCRIPTS_DIR: &str = ".aws/amazonq/transcripts";
 }
 
 type Result<T, E = DirectoryError> = std::result::Result<T, E>;
@@ -296,6 +299,18 @@ impl<'a> GlobalPaths<'a> {
         Ok(home_dir(self.os)?.join(global::KNOWLEDGE_BASES_DIR))
     }
 
+    pub fn models_config(&self) -> Result<PathBuf> {
+        Ok(home_dir(self.os)?.join(global::MODELS_CONFIG))
+    }
+
+    pub fn tokenizers_dir(&self) -> Result<PathBuf> {
+        Ok(home_dir(self.os)?.join(global::TOKENIZERS_DIR))
+    }
+
+    pub fn transcripts_dir(&self) -> Result<PathBuf> {
+        Ok(home_dir(self.os)?.join(global::TRANSCRIPTS_DIR))
+    }
+
     pub async fn ensure_agents_dir(&self) -> Result<PathBuf> {
         let dir = self.agents_dir()?;
         if !dir.exists() {
@@ -9,7 +9,10 @@ use uuid::Uuid;
 
 use super::background::BackgroundWorker;
 // Use the new modular structure
-use super::context::ContextManager;
+use super::context::{
+    ContextManager,
+    ranking,
+};
 use super::model::ModelDownloader;
 use super::operation::OperationManager;
 use crate::client::embedder_factory;
@@ -17,7 +20,10 @@ use crate::config::{
     self,
     SemanticSearchConfig,
 };
-use crate::embedding::TextEmbedderTrait;
+use crate::embedding::{
+    RerankerTrait,
+    TextEmbedderTrait,
+};
 use crate::error::{
     Result,
     SemanticSearchError,
@@ -28,6 +34,7 @@ use crate::types::*;
 pub struct AsyncSemanticSearchClient {
     base_dir: PathBuf,
     embedder: Box<dyn TextEmbedderTrait>,
+    reranker: Option<Box<dyn RerankerTrait>>,
     config: SemanticSearchConfig,
     job_tx: mpsc::UnboundedSender<IndexingJob>,
     context_manager: ContextManager,
@@ -84,6 +91,11 @@ impl AsyncSemanticSearchClient {
         ModelDownloader::ensure_models_downloaded(&config.embedding_type).await?;
 
         let embedder = embedder_factory::create_embedder(config.embedding_type)?;
+        let reranker = if config.rerank {
+            embedder_factory::create_reranker().await
+        } else {
+            None
+        };
         let context_manager = ContextManager::new(&base_dir).await?;
         let operation_manager = OperationManager::new();
 
@@ -91,6 +103,7 @@ impl AsyncSemanticSearchClient {
 
         let worker = BackgroundWorker::new(
             job_rx,
+            job_tx.downgrade(),
             context_manager.clone(),
             operation_manager.clone(),
             config.clone(),
@@ -103,6 +116,7 @@ impl AsyncSemanticSearchClient {
         let client = Self {
             base_dir,
             embedder,
+            reranker,
             config,
             job_tx,
             context_manager,
@@ -257,6 +271,7 @@ impl AsyncSemanticSearchClient {
     ///     include_patterns: Some(vec!["*.txt".to_string(), "*.md".to_string()]),
     ///     exclude_patterns: Some(vec!["*.tmp".to_string()]),
     ///     embedding_type: None, // Use default
+    ///     watch: false,
     /// };
     ///
     /// let (operation_id, cancel_token) = client.add_context(request).await?;
@@ -310,6 +325,74 @@ impl AsyncSemanticSearchClient {
             include_patterns: request.include_patterns.clone(),
             exclude_patterns: request.exclude_patterns.clone(),
             embedding_type: request.embedding_type,
+            watch: request.watch,
+        };
+
+        self.job_tx
+            .send(job)
+            .map_err(|_send_error| SemanticSearchError::OperationFailed("Background worker unavailable".to_string()))?;
+
+        Ok((operation_id, cancel_token))
+    }
+
+    /// Updates an existing context with the current contents of a directory or file asynchronously.
+    ///
+    /// Only the files added or changed since the context was last indexed are chunked and
+    /// embedded again, and the data points of the files removed since are deleted. Files are
+    /// compared using the size, modification time and SHA-256 recorded when they were indexed.
+    /// The include and exclude patterns and the embedding type of the context are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - The unique identifier of the context to update
+    /// * `path` - The directory or file to index, usually the source path of the context
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result<(Uuid, CancellationToken)>` for tracking the update operation.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if:
+    /// - The context does not exist
+    /// - The specified path does not exist or is not accessible
+    /// - The path is already being indexed by another operation
+    /// - The background worker is unavailable
+    pub async fn update_context(&self, context_id: &str, path: impl AsRef<Path>) -> Result<(Uuid, CancellationToken)> {
+        let path = path.as_ref();
+        let canonical_path = path.canonicalize().map_err(|_e| {
+            SemanticSearchError::InvalidPath(format!("Path does not exist or is not accessible: {}", path.display()))
+        })?;
+
+        let context = self
+            .context_manager
+            .get_contexts()
+            .await
+            .into_iter()
+            .find(|context| context.id == context_id)
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+
+        ContextManager::check_path_not_indexing(&canonical_path, &self.operation_manager)?;
+
+        let operation_id = Uuid::new_v4();
+        let cancel_token = CancellationToken::new();
+
+        self.operation_manager
+            .register_operation(
+                operation_id,
+                OperationType::Updating {
+                    name: context.name,
+                    path: canonical_path.to_string_lossy().to_string(),
+                },
+                cancel_token.clone(),
+            )
+            .await;
+
+        let job = IndexingJob::UpdateContext {
+            id: operation_id,
+            cancel: cancel_token.clone(),
+            context_id: context_id.to_string(),
+            path: canonical_path,
         };
 
         self.job_tx
@@ -351,14 +434,15 @@ impl AsyncSemanticSearchClient {
 
     /// Performs a semantic search across all available contexts.
     ///
-    /// This method searches through all indexed contexts using the provided query text,
-    /// returning the most relevant results ranked by semantic similarity or BM25 score
-    /// depending on the context type.
+    /// This method searches through all indexed contexts using the provided query text.
+    /// Semantic similarities, BM25 scores and fused hybrid scores are normalized into distances
+    /// between 0 and 1, so the most relevant results overall are returned. When reranking is
+    /// enabled, the best `rerank_candidates` results are reordered by a cross-encoder.
     ///
     /// # Arguments
     ///
     /// * `query_text` - The search query string
-    /// * `result_limit` - Optional limit on the number of results per context. If `None`, uses the
+    /// * `result_limit` - Optional limit on the number of results overall. If `None`, uses the
     ///   default limit from configuration
     ///
     /// # Returns
@@ -367,6 +451,8 @@ impl AsyncSemanticSearchClient {
     /// - `ContextId` - The unique identifier of the context
     /// - `SearchResults` - The search results from that context, ranked by relevance
     ///
+    /// Contexts with the most relevant results come first.
+    ///
     /// # Errors
     ///
     /// This method will return an error if:
@@ -414,9 +500,22 @@ impl AsyncSemanticSearchClient {
         }
 
         let effective_limit = result_limit.unwrap_or(self.config.default_results);
-        self.context_manager
-            .search_all(query_text, effective_limit, &*self.embedder)
-            .await
+        let Some(reranker) = &self.reranker else {
+            return self
+                .context_manager
+                .search_all(query_text, effective_limit, &*self.embedder)
+                .await;
+        };
+
+        let candidates = self
+            .context_manager
+            .search_all(
+                query_text,
+                effective_limit.max(self.config.rerank_candidates),
+                &*self.embedder,
+            )
+            .await?;
+        ranking::rerank(&**reranker, query_text, candidates, effective_limit)
     }
 
     /// Search in a specific context
@@ -450,11 +549,27 @@ impl AsyncSemanticSearchClient {
         }
 
         let effective_limit = result_limit.unwrap_or(self.config.default_results);
+        let candidates = match self.reranker {
+            Some(_) => effective_limit.max(self.config.rerank_candidates),
+            None => effective_limit,
+        };
 
-        self.context_manager
-            .search_context(context_id, query_text, effective_limit, &*self.embedder)
+        let results = self
+            .context_manager
+            .search_context(context_id, query_text, candidates, &*self.embedder)
             .await?
-            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+
+        let Some(reranker) = &self.reranker else {
+            return Ok(results);
+        };
+        let reranked = ranking::rerank(
+            &**reranker,
+            query_text,
+            vec![(context_id.to_string(), results)],
+            effective_limit,
+        )?;
+        Ok(reranked.into_iter().flat_map(|(_, results)| results).collect())
     }
 
     /// Cancels a running background operation.
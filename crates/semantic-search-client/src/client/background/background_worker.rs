@@ -1,8 +1,14 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{
+    Path,
+    PathBuf,
+};
 use std::sync::Arc;
 
+use tokio::sync::mpsc::WeakUnboundedSender;
 use tokio::sync::{
     Semaphore,
+    SemaphorePermit,
     mpsc,
 };
 use tokio_util::sync::CancellationToken;
@@ -14,6 +20,10 @@ use super::super::context::{
     ContextManager,
 };
 use super::super::operation::OperationManager;
+use super::context_watcher::{
+    self,
+    exclude_patterns,
+};
 use super::file_processor::FileProcessor;
 use crate::client::{
     embedder_factory,
@@ -21,6 +31,7 @@ use crate::client::{
 };
 use crate::config::SemanticSearchConfig;
 use crate::embedding::TextEmbedderTrait;
+use crate::processing::diff_manifest;
 use crate::types::*;
 
 const MAX_CONCURRENT_OPERATIONS: usize = 3;
@@ -28,6 +39,8 @@ const MAX_CONCURRENT_OPERATIONS: usize = 3;
 /// Background worker for processing indexing jobs
 pub struct BackgroundWorker {
     job_rx: mpsc::UnboundedReceiver<IndexingJob>,
+    /// Sender for the watchers to queue updates, which doesn't keep the worker running
+    job_tx: WeakUnboundedSender<IndexingJob>,
     context_manager: ContextManager,
     operation_manager: OperationManager,
     embedder: Box<dyn TextEmbedderTrait>,
@@ -42,6 +55,7 @@ impl BackgroundWorker {
     /// Create new background worker
     pub async fn new(
         job_rx: mpsc::UnboundedReceiver<IndexingJob>,
+        job_tx: WeakUnboundedSender<IndexingJob>,
         context_manager: ContextManager,
         operation_manager: OperationManager,
         config: SemanticSearchConfig,
@@ -53,6 +67,7 @@ impl BackgroundWorker {
 
         Ok(Self {
             job_rx,
+            job_tx,
             context_manager,
             operation_manager,
             embedder,
@@ -68,6 +83,19 @@ impl BackgroundWorker {
     pub async fn run(mut self) {
         debug!("Background worker started for async semantic search client");
 
+        // Resume watching the persistent contexts that were watched
+        let watched = {
+            let contexts = self.context_manager.get_contexts_ref().read().await;
+            contexts
+                .values()
+                .filter(|context| context.watch && context.persistent)
+                .map(|context| context.id.clone())
+                .collect::<Vec<_>>()
+        };
+        for context_id in watched {
+            self.start_watching(context_id);
+        }
+
         while let Some(job) = self.job_rx.recv().await {
             match job {
                 IndexingJob::AddDirectory {
@@ -80,6 +108,7 @@ impl BackgroundWorker {
                     include_patterns,
                     exclude_patterns,
                     embedding_type,
+                    watch,
                 } => {
                     let params = IndexingParams {
                         path,
@@ -89,10 +118,19 @@ impl BackgroundWorker {
                         include_patterns,
                         exclude_patterns,
                         embedding_type,
+                        watch,
                     };
 
                     self.process_add_directory(id, params, cancel).await;
                 },
+                IndexingJob::UpdateContext {
+                    id,
+                    cancel,
+                    context_id,
+                    path,
+                } => {
+                    self.process_update_context(id, context_id, path, cancel).await;
+                },
                 IndexingJob::Clear { id, cancel } => {
                     self.process_clear(id, cancel).await;
                 },
@@ -114,14 +152,47 @@ impl BackgroundWorker {
             return;
         }
 
+        let Some(_permit) = self.acquire_indexing_slot(operation_id).await else {
+            return;
+        };
+
+        let watch = params.watch;
+        let result = self.perform_indexing(operation_id, params, cancel_token).await;
+
+        match result {
+            Ok(context_id) => {
+                debug!("Successfully indexed context: {}", context_id);
+                self.mark_operation_completed(operation_id).await;
+                if watch {
+                    self.start_watching(context_id);
+                }
+            },
+            Err(e) => {
+                tracing::error!("Indexing failed: {}", e);
+                self.mark_operation_failed(operation_id, e).await;
+            },
+        }
+    }
+
+    fn start_watching(&self, context_id: String) {
+        tokio::spawn(context_watcher::watch_context(
+            context_id,
+            self.context_manager.clone(),
+            self.operation_manager.clone(),
+            self.file_processor.clone(),
+            self.job_tx.clone(),
+        ));
+    }
+
+    async fn acquire_indexing_slot(&self, operation_id: Uuid) -> Option<SemaphorePermit<'_>> {
         self.update_operation_status(operation_id, "Waiting in queue...".to_string())
             .await;
 
-        let _permit = match self.indexing_semaphore.try_acquire() {
+        match self.indexing_semaphore.try_acquire() {
             Ok(permit) => {
                 self.update_operation_status(operation_id, "Acquired slot, starting indexing...".to_string())
                     .await;
-                permit
+                Some(permit)
             },
             Err(_) => {
                 self.update_operation_status(
@@ -136,28 +207,15 @@ impl BackgroundWorker {
                     Ok(permit) => {
                         self.update_operation_status(operation_id, "Acquired slot, starting indexing...".to_string())
                             .await;
-                        permit
+                        Some(permit)
                     },
                     Err(_) => {
                         self.mark_operation_failed(operation_id, "Semaphore unavailable".to_string())
                             .await;
-                        return;
+                        None
                     },
                 }
             },
-        };
-
-        let result = self.perform_indexing(operation_id, params, cancel_token).await;
-
-        match result {
-            Ok(context_id) => {
-                debug!("Successfully indexed context: {}", context_id);
-                self.mark_operation_completed(operation_id).await;
-            },
-            Err(e) => {
-                tracing::error!("Indexing failed: {}", e);
-                self.mark_operation_failed(operation_id, e).await;
-            },
         }
     }
 
@@ -190,13 +248,19 @@ impl BackgroundWorker {
             return Err("Operation was cancelled during setup".to_string());
         }
 
+        let exclude_patterns = exclude_patterns(
+            &params.path,
+            params.exclude_patterns.as_deref().unwrap_or(&[]),
+            params.watch,
+        );
+
         let file_count = self
             .file_processor
             .count_files_in_directory(
                 &params.path,
                 operation_id,
                 &params.include_patterns,
-                &params.exclude_patterns,
+                &exclude_patterns,
                 &self.operation_manager,
             )
             .await?;
@@ -229,7 +293,7 @@ impl BackgroundWorker {
                 operation_id,
                 &cancel_token,
                 &params.include_patterns,
-                &params.exclude_patterns,
+                &exclude_patterns,
                 &self.operation_manager,
             )
             .await?;
@@ -238,6 +302,14 @@ impl BackgroundWorker {
             return Err("Failed: Operation was cancelled before semantic context creation".to_string());
         }
 
+        let files = self
+            .file_processor
+            .list_files_in_directory(&params.path, &params.include_patterns, &exclude_patterns)
+            .await?;
+        let manifest = tokio::task::spawn_blocking(move || diff_manifest(&FileManifest::new(), &files).manifest)
+            .await
+            .map_err(|e| format!("File hashing task failed: {}", e))?;
+
         let effective_embedding_type = params.embedding_type.unwrap_or(self.config.embedding_type);
 
         self.context_creator
@@ -253,22 +325,160 @@ impl BackgroundWorker {
             )
             .await?;
 
-        self.store_context_metadata(
-            &context_id,
-            &params.name,
-            &params.description,
-            params.persistent,
-            Some(params.path.to_string_lossy().to_string()),
-            &params.include_patterns,
-            &params.exclude_patterns,
-            file_count,
-            effective_embedding_type,
-        )
-        .await?;
+        self.store_context_metadata(&context_id, &params, file_count, effective_embedding_type, manifest)
+            .await?;
 
         Ok(context_id)
     }
 
+    async fn process_update_context(
+        &self,
+        operation_id: Uuid,
+        context_id: String,
+        path: PathBuf,
+        cancel_token: CancellationToken,
+    ) {
+        debug!("Processing UpdateContext job: {} -> {}", context_id, path.display());
+
+        if cancel_token.is_cancelled() {
+            self.mark_operation_cancelled(operation_id).await;
+            return;
+        }
+
+        let Some(_permit) = self.acquire_indexing_slot(operation_id).await else {
+            return;
+        };
+
+        match self
+            .perform_update(operation_id, &context_id, &path, cancel_token)
+            .await
+        {
+            Ok(status) => {
+                debug!("Successfully updated context {}: {}", context_id, status);
+                self.mark_operation_completed(operation_id).await;
+            },
+            Err(e) => {
+                tracing::error!("Updating failed: {}", e);
+                self.mark_operation_failed(operation_id, e).await;
+            },
+        }
+    }
+
+    /// Index again the files of a context that were added or changed since it was last indexed,
+    /// and remove the files that were removed since
+    async fn perform_update(
+        &self,
+        operation_id: Uuid,
+        context_id: &str,
+        path: &Path,
+        cancel_token: CancellationToken,
+    ) -> std::result::Result<String, String> {
+        if !path.exists() {
+            return Err(format!("Path '{}' does not exist", path.display()));
+        }
+
+        let context = self
+            .context_manager
+            .get_contexts_ref()
+            .read()
+            .await
+            .get(context_id)
+            .cloned()
+            .ok_or_else(|| format!("Context {} not found", context_id))?;
+
+        let include_patterns = (!context.include_patterns.is_empty()).then(|| context.include_patterns.clone());
+        let exclude_patterns = exclude_patterns(path, &context.exclude_patterns, context.watch);
+
+        self.update_operation_status(operation_id, "Checking for changed files...".to_string())
+            .await;
+
+        let files = self
+            .file_processor
+            .list_files_in_directory(path, &include_patterns, &exclude_patterns)
+            .await?;
+
+        if files.len() > self.config.max_files {
+            return Err(format!(
+                "Failed: Directory contains {} files, which exceeds the maximum limit of {} files",
+                files.len(),
+                self.config.max_files
+            ));
+        }
+
+        if cancel_token.is_cancelled() {
+            return Err("Operation was cancelled before checking for changed files".to_string());
+        }
+
+        let previous = context.files.clone();
+        let diff = tokio::task::spawn_blocking(move || diff_manifest(&previous, &files))
+            .await
+            .map_err(|e| format!("File hashing task failed: {}", e))?;
+
+        let status = format!(
+            "{} skipped, {} changed, {} removed",
+            diff.unchanged.len(),
+            diff.changed.len(),
+            diff.removed.len()
+        );
+        self.update_operation_progress(
+            operation_id,
+            0,
+            diff.changed.len() as u64,
+            format!("Indexing changed files · {}", status),
+        )
+        .await;
+
+        let items = self
+            .file_processor
+            .process_files(
+                &diff.changed,
+                &status,
+                operation_id,
+                &cancel_token,
+                &self.operation_manager,
+            )
+            .await?;
+
+        if cancel_token.is_cancelled() {
+            return Err("Operation was cancelled before updating the context".to_string());
+        }
+
+        let unchanged_files = diff.unchanged.iter().cloned().collect::<HashSet<_>>();
+        self.context_creator
+            .update_context(
+                context_id,
+                &items,
+                &unchanged_files,
+                &status,
+                context.embedding_type,
+                operation_id,
+                &cancel_token,
+                &self.operation_manager,
+                &*self.embedder,
+                &self.context_manager,
+            )
+            .await?;
+
+        {
+            let mut contexts = self.context_manager.get_contexts_ref().write().await;
+            if let Some(context) = contexts.get_mut(context_id) {
+                context.source_path = Some(path.to_string_lossy().to_string());
+                context.item_count = diff.manifest.len();
+                context.files = diff.manifest;
+                context.updated_at = chrono::Utc::now();
+            }
+        }
+
+        if context.persistent {
+            self.context_manager
+                .save_contexts_metadata(&self.base_dir)
+                .await
+                .map_err(|e| format!("Failed to save contexts metadata: {}", e))?;
+        }
+
+        Ok(status)
+    }
+
     async fn process_clear(&self, operation_id: Uuid, cancel_token: CancellationToken) {
         debug!("Processing Clear job");
 
@@ -410,32 +620,30 @@ impl BackgroundWorker {
         debug!("Operation {} cancelled", operation_id);
     }
 
-    #[allow(clippy::too_many_arguments)]
     async fn store_context_metadata(
         &self,
         context_id: &str,
-        name: &str,
-        description: &str,
-        persistent: bool,
-        source_path: Option<String>,
-        include_patterns: &Option<Vec<String>>,
-        exclude_patterns: &Option<Vec<String>>,
+        params: &IndexingParams,
         item_count: usize,
         embedding_type: crate::embedding::EmbeddingType,
+        files: FileManifest,
     ) -> std::result::Result<(), String> {
-        let context = KnowledgeContext::new(
+        let persistent = params.persistent;
+        let mut context = KnowledgeContext::new(
             context_id.to_string(),
-            name,
-            description,
+            &params.name,
+            &params.description,
             persistent,
-            source_path,
+            Some(params.path.to_string_lossy().to_string()),
             (
-                include_patterns.as_deref().unwrap_or(&[]).to_vec(),
-                exclude_patterns.as_deref().unwrap_or(&[]).to_vec(),
+                params.include_patterns.as_deref().unwrap_or(&[]).to_vec(),
+                params.exclude_patterns.as_deref().unwrap_or(&[]).to_vec(),
             ),
             item_count,
             embedding_type,
         );
+        context.files = files;
+        context.watch = params.watch;
 
         {
             let mut contexts = self.context_manager.get_contexts_ref().write().await;
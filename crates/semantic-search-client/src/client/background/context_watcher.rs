@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::time::Duration;
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use notify_debouncer_mini::notify::{
+    RecommendedWatcher,
+    RecursiveMode,
+};
+use notify_debouncer_mini::{
+    DebounceEventResult,
+    Debouncer,
+    new_debouncer,
+};
+use tokio::sync::mpsc::{
+    UnboundedReceiver,
+    WeakUnboundedSender,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{
+    debug,
+    warn,
+};
+use uuid::Uuid;
+
+use super::super::context::ContextManager;
+use super::super::operation::OperationManager;
+use super::file_processor::FileProcessor;
+use crate::pattern_filter::{
+    PatternFilter,
+    gitignore_patterns,
+};
+use crate::types::*;
+
+/// How often watched paths are checked for changes when their file system events can't be
+/// watched, and for whether they are still watched otherwise
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long the files of a watched path must stop changing before their events are delivered
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Size and modification time of the files of a watched path, by path
+type Snapshot = BTreeMap<String, (u64, Option<DateTime<Utc>>)>;
+
+/// Watcher of the file system events of a path, and the receiver of its debounced events
+type Events = (Debouncer<RecommendedWatcher>, UnboundedReceiver<DebounceEventResult>);
+
+/// Exclude patterns of a context, with those of the `.gitignore` of its path if it is watched
+pub fn exclude_patterns(path: &Path, exclude_patterns: &[String], watch: bool) -> Option<Vec<String>> {
+    let mut patterns = exclude_patterns.to_vec();
+    if watch {
+        patterns.extend(gitignore_patterns(path));
+    }
+    (!patterns.is_empty()).then_some(patterns)
+}
+
+/// Watch the source path of a context until the context is removed or stops being watched
+///
+/// Once the files the context indexes stopped changing, an update of the context is queued, which
+/// only indexes again the files that changed. The changes are found from the file system events of
+/// the path, or when they can't be watched by checking the files every few seconds. No update is
+/// queued while the path is being indexed, and the watcher stops when the client is dropped.
+pub async fn watch_context(
+    context_id: String,
+    context_manager: ContextManager,
+    operation_manager: OperationManager,
+    file_processor: FileProcessor,
+    job_tx: WeakUnboundedSender<IndexingJob>,
+) {
+    debug!("Watching context {}", context_id);
+
+    let source_path = context_manager
+        .get_contexts_ref()
+        .read()
+        .await
+        .get(&context_id)
+        .and_then(|context| context.source_path.clone());
+    let mut events = match source_path.map(|path| watch_events(Path::new(&path))) {
+        Some(Ok(events)) => Some(events),
+        Some(Err(e)) => {
+            warn!(
+                "Failed to watch the file system events of context {}, polling it instead: {}",
+                context_id, e
+            );
+            None
+        },
+        None => None,
+    };
+
+    let mut pending: Option<Snapshot> = None;
+    let mut last_update: Option<Snapshot> = None;
+    let mut changed_paths: Vec<PathBuf> = Vec::new();
+    let mut changed = false;
+
+    loop {
+        match events.as_mut() {
+            Some((_, event_rx)) => {
+                tokio::select! {
+                    result = event_rx.recv() => match result {
+                        Some(Ok(debounced)) => changed_paths.extend(debounced.into_iter().map(|event| event.path)),
+                        Some(Err(e)) => {
+                            // Events may have been missed, so the files are checked instead
+                            warn!("Failed to watch the file system events of context {}: {}", context_id, e);
+                            changed = true;
+                        },
+                        None => break,
+                    },
+                    _ = tokio::time::sleep(WATCH_INTERVAL) => {},
+                }
+            },
+            None => tokio::time::sleep(WATCH_INTERVAL).await,
+        }
+
+        let context = context_manager
+            .get_contexts_ref()
+            .read()
+            .await
+            .get(&context_id)
+            .cloned();
+        let Some(context) = context.filter(|context| context.watch) else {
+            break;
+        };
+        let Some(path) = context.source_path.as_ref().map(PathBuf::from) else {
+            break;
+        };
+        if job_tx.strong_count() == 0 {
+            break;
+        }
+
+        let include_patterns = (!context.include_patterns.is_empty()).then(|| context.include_patterns.clone());
+        let exclude_patterns = exclude_patterns(&path, &context.exclude_patterns, true);
+
+        // Changes are kept until the path isn't being indexed anymore
+        if events.is_some() {
+            let filter = PatternFilter::new(
+                &context.include_patterns,
+                exclude_patterns.as_deref().unwrap_or_default(),
+            );
+            changed |= changed_paths
+                .drain(..)
+                .any(|changed_path| is_indexed(&changed_path, filter.as_ref().ok()));
+            if !changed {
+                continue;
+            }
+        }
+
+        if ContextManager::check_path_not_indexing(&path, &operation_manager).is_err() {
+            pending = None;
+            continue;
+        }
+        let files = match file_processor
+            .list_files_in_directory(&path, &include_patterns, &exclude_patterns)
+            .await
+        {
+            Ok(files) => files,
+            Err(e) => {
+                warn!("Failed to list the files of watched context {}: {}", context_id, e);
+                continue;
+            },
+        };
+        let Ok(snapshot) = tokio::task::spawn_blocking(move || snapshot(&files)).await else {
+            continue;
+        };
+
+        // Files that can't be indexed keep differing from the manifest, so the same changes are
+        // only ever updated once
+        if !has_changes(&context.files, &snapshot) || last_update.as_ref() == Some(&snapshot) {
+            pending = None;
+            changed = false;
+            continue;
+        }
+
+        // Debounced events are only delivered once the files stopped changing
+        if events.is_none() && pending.as_ref() != Some(&snapshot) {
+            pending = Some(snapshot);
+            continue;
+        }
+        pending = None;
+        changed = false;
+
+        let Some(job_tx) = job_tx.upgrade() else {
+            break;
+        };
+
+        let operation_id = Uuid::new_v4();
+        let cancel_token = CancellationToken::new();
+        operation_manager
+            .register_operation(
+                operation_id,
+                OperationType::Updating {
+                    name: context.name.clone(),
+                    path: path.to_string_lossy().to_string(),
+                },
+                cancel_token.clone(),
+            )
+            .await;
+
+        let job = IndexingJob::UpdateContext {
+            id: operation_id,
+            cancel: cancel_token,
+            context_id: context_id.clone(),
+            path,
+        };
+        if job_tx.send(job).is_err() {
+            break;
+        }
+
+        debug!("Files of watched context {} changed, queued an update", context_id);
+        last_update = Some(snapshot);
+    }
+
+    debug!("Stopped watching context {}", context_id);
+}
+
+/// Watch the file system events of a path and everything under it
+fn watch_events(path: &Path) -> notify_debouncer_mini::notify::Result<Events> {
+    let (event_tx, event_rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut debouncer = new_debouncer(DEBOUNCE_TIMEOUT, move |result: DebounceEventResult| {
+        let _ = event_tx.send(result);
+    })?;
+    debouncer.watcher().watch(path, RecursiveMode::Recursive)?;
+    Ok((debouncer, event_rx))
+}
+
+/// Whether a changed path is one the context indexes, like the files of its path are listed
+fn is_indexed(path: &Path, filter: Option<&PatternFilter>) -> bool {
+    let hidden = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'));
+    !hidden && filter.is_none_or(|filter| filter.should_include(path))
+}
+
+fn snapshot(files: &[PathBuf]) -> Snapshot {
+    files
+        .iter()
+        .filter_map(|path| {
+            let metadata = std::fs::metadata(path).ok()?;
+            let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+            Some((path.to_string_lossy().to_string(), (metadata.len(), modified)))
+        })
+        .collect()
+}
+
+/// Whether files were added, changed or removed since the manifest was recorded
+fn has_changes(manifest: &FileManifest, snapshot: &Snapshot) -> bool {
+    manifest.len() != snapshot.len()
+        || snapshot.iter().any(|(path, (size, modified))| {
+            manifest
+                .get(path)
+                .is_none_or(|entry| entry.size != *size || Some(entry.modified) != *modified)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::diff_manifest;
+
+    #[test]
+    fn test_has_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let files = vec![dir.path().join("a.txt"), dir.path().join("b.txt")];
+        std::fs::write(&files[0], "a").unwrap();
+        std::fs::write(&files[1], "b").unwrap();
+
+        let manifest = diff_manifest(&FileManifest::new(), &files).manifest;
+        assert!(!has_changes(&manifest, &snapshot(&files)));
+        assert!(has_changes(&FileManifest::new(), &snapshot(&files)));
+
+        std::fs::write(&files[1], "b changed").unwrap();
+        assert!(has_changes(&manifest, &snapshot(&files)));
+
+        std::fs::remove_file(&files[1]).unwrap();
+        assert!(has_changes(&manifest, &snapshot(&files)));
+    }
+
+    #[test]
+    fn test_is_indexed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        let exclude_patterns = exclude_patterns(dir.path(), &[], true).unwrap();
+        let filter = PatternFilter::new(&["**/*.rs".to_string()], &exclude_patterns).unwrap();
+
+        assert!(is_indexed(&dir.path().join("src/main.rs"), Some(&filter)));
+        assert!(!is_indexed(&dir.path().join("README.md"), Some(&filter)));
+        assert!(!is_indexed(&dir.path().join("target/debug/build.rs"), Some(&filter)));
+        assert!(!is_indexed(&dir.path().join(".hidden.rs"), Some(&filter)));
+        assert!(is_indexed(&dir.path().join("README.md"), None));
+    }
+}
@@ -1,4 +1,7 @@
-use std::path::Path;
+use std::path::{
+    Path,
+    PathBuf,
+};
 
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
@@ -8,6 +11,7 @@ use crate::config::SemanticSearchConfig;
 use crate::processing::process_file_with_config;
 
 /// File processor for handling directory operations
+#[derive(Clone)]
 pub struct FileProcessor {
     config: SemanticSearchConfig,
 }
@@ -160,6 +164,77 @@ impl FileProcessor {
         Ok(items)
     }
 
+    /// List the files in a directory that would be indexed
+    pub async fn list_files_in_directory(
+        &self,
+        dir_path: &Path,
+        include_patterns: &Option<Vec<String>>,
+        exclude_patterns: &Option<Vec<String>>,
+    ) -> std::result::Result<Vec<PathBuf>, String> {
+        let dir_path = dir_path.to_path_buf();
+        let pattern_filter = Self::create_pattern_filter(include_patterns, exclude_patterns)?;
+
+        tokio::task::spawn_blocking(move || {
+            walkdir::WalkDir::new(&dir_path)
+                .follow_links(true)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .filter(|e| {
+                    !e.path()
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|s| s.starts_with('.'))
+                })
+                .filter(|e| {
+                    pattern_filter
+                        .as_ref()
+                        .is_none_or(|filter| filter.should_include(e.path()))
+                })
+                .map(|e| e.into_path())
+                .collect()
+        })
+        .await
+        .map_err(|e| format!("File listing task failed: {}", e))
+    }
+
+    /// Process the given files, reporting progress with `status` appended
+    pub async fn process_files(
+        &self,
+        files: &[PathBuf],
+        status: &str,
+        operation_id: Uuid,
+        cancel_token: &CancellationToken,
+        operation_manager: &OperationManager,
+    ) -> std::result::Result<Vec<serde_json::Value>, String> {
+        let mut items = Vec::new();
+
+        for (i, path) in files.iter().enumerate() {
+            if cancel_token.is_cancelled() {
+                return Err("Operation was cancelled during file processing".to_string());
+            }
+
+            if let Ok(mut file_items) =
+                process_file_with_config(path, Some(self.config.chunk_size), Some(self.config.chunk_overlap))
+            {
+                items.append(&mut file_items);
+            }
+
+            if (i + 1) % 10 == 0 || i + 1 == files.len() {
+                self.update_operation_progress(
+                    operation_manager,
+                    operation_id,
+                    (i + 1) as u64,
+                    files.len() as u64,
+                    format!("Indexing changed files ({}/{}) · {}", i + 1, files.len(), status),
+                )
+                .await;
+            }
+        }
+
+        Ok(items)
+    }
+
     fn create_pattern_filter(
         include_patterns: &Option<Vec<String>>,
         exclude_patterns: &Option<Vec<String>>,
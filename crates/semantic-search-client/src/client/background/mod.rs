@@ -1,5 +1,7 @@
 /// Background worker for async operations
 pub mod background_worker;
+/// Watching the source paths of contexts for changes
+pub mod context_watcher;
 /// File processing utilities
 pub mod file_processor;
 
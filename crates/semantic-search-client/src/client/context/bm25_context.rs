@@ -95,6 +95,34 @@ impl BM25Context {
         Ok(count)
     }
 
+    /// Replace data points, keeping those matching `keep` and adding `data_points`
+    ///
+    /// The points are renumbered so that their IDs stay their positions, and the index is rebuilt.
+    ///
+    /// Returns the number of data points removed.
+    pub fn replace_data_points(
+        &mut self,
+        keep: impl Fn(&BM25DataPoint) -> bool,
+        data_points: Vec<BM25DataPoint>,
+    ) -> Result<usize> {
+        let count = self.data_points.len();
+        self.data_points.retain(|point| keep(point));
+        let removed = count - self.data_points.len();
+
+        self.data_points.extend(data_points);
+        for (i, point) in self.data_points.iter_mut().enumerate() {
+            point.id = i;
+        }
+
+        if self.data_points.is_empty() {
+            self.index = None;
+        } else {
+            self.rebuild_index()?;
+        }
+
+        Ok(removed)
+    }
+
     /// Search the context
     pub fn search(&self, query: &str, limit: usize) -> Vec<(usize, f32)> {
         match &self.index {
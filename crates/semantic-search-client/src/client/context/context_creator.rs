@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{
+    HashMap,
+    HashSet,
+};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -50,7 +53,27 @@ impl ContextCreator {
         embedder: &dyn TextEmbedderTrait,
         context_manager: &ContextManager,
     ) -> std::result::Result<(), String> {
-        if embedding_type.is_bm25() {
+        if embedding_type.is_hybrid() {
+            self.create_bm25_context(
+                context_dir,
+                items,
+                operation_id,
+                cancel_token,
+                operation_manager,
+                context_manager,
+            )
+            .await?;
+            self.create_semantic_context(
+                context_dir,
+                items,
+                operation_id,
+                cancel_token,
+                operation_manager,
+                embedder,
+                context_manager,
+            )
+            .await
+        } else if embedding_type.is_bm25() {
             self.create_bm25_context(
                 context_dir,
                 items,
@@ -223,6 +246,169 @@ impl ContextCreator {
         Ok(())
     }
 
+    /// Update an existing context, keeping the data points of the files in `unchanged_files` and
+    /// replacing all others with data points created from `items`, reporting progress with
+    /// `status` appended
+    ///
+    /// Returns the number of data points removed.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_context(
+        &self,
+        context_id: &str,
+        items: &[serde_json::Value],
+        unchanged_files: &HashSet<String>,
+        status: &str,
+        embedding_type: EmbeddingType,
+        operation_id: Uuid,
+        cancel_token: &CancellationToken,
+        operation_manager: &OperationManager,
+        embedder: &dyn TextEmbedderTrait,
+        context_manager: &ContextManager,
+    ) -> std::result::Result<usize, String> {
+        let keep = |payload: &HashMap<String, serde_json::Value>| {
+            payload
+                .get("path")
+                .and_then(|path| path.as_str())
+                .is_some_and(|path| unchanged_files.contains(path))
+        };
+
+        // Hybrid contexts fuse the results of both indexes by the position of their data points,
+        // so both sets are built before either index is touched
+        let bm25_data_points = if embedding_type.is_bm25() || embedding_type.is_hybrid() {
+            Some(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| Self::create_bm25_data_point_from_item(item, i))
+                    .collect::<Result<Vec<_>>>()
+                    .map_err(|e| format!("Failed to create BM25 data point: {}", e))?,
+            )
+        } else {
+            None
+        };
+        let semantic_data_points = if embedding_type.is_bm25() {
+            None
+        } else {
+            Some(
+                self.create_semantic_data_points(
+                    items,
+                    status,
+                    operation_id,
+                    cancel_token,
+                    operation_manager,
+                    embedder,
+                )
+                .await?,
+            )
+        };
+
+        if cancel_token.is_cancelled() {
+            return Err("Operation was cancelled before updating index".to_string());
+        }
+
+        let mut removed = 0;
+        if let Some(data_points) = bm25_data_points {
+            self.update_operation_status(
+                operation_manager,
+                operation_id,
+                format!("Updating BM25 index · {}", status),
+            )
+            .await;
+            removed = Self::replace_bm25_data_points(context_id, &keep, data_points, context_manager).await?;
+        }
+        if let Some(data_points) = semantic_data_points {
+            self.update_operation_status(
+                operation_manager,
+                operation_id,
+                format!("Updating vector index · {}", status),
+            )
+            .await;
+            removed = Self::replace_semantic_data_points(context_id, &keep, data_points, context_manager).await?;
+        }
+        Ok(removed)
+    }
+
+    async fn create_semantic_data_points(
+        &self,
+        items: &[serde_json::Value],
+        status: &str,
+        operation_id: Uuid,
+        cancel_token: &CancellationToken,
+        operation_manager: &OperationManager,
+        embedder: &dyn TextEmbedderTrait,
+    ) -> std::result::Result<Vec<DataPoint>, String> {
+        let mut data_points = Vec::new();
+        let total_items = items.len();
+
+        for (i, item) in items.iter().enumerate() {
+            if cancel_token.is_cancelled() {
+                return Err("Operation was cancelled during embedding generation".to_string());
+            }
+
+            if i % 10 == 0 {
+                self.update_operation_progress(
+                    operation_manager,
+                    operation_id,
+                    i as u64,
+                    total_items as u64,
+                    format!("Generating embeddings ({}/{}) · {}", i, total_items, status),
+                )
+                .await;
+            }
+
+            let data_point = Self::create_data_point_from_item(item, i, embedder)
+                .map_err(|e| format!("Failed to create data point: {}", e))?;
+            data_points.push(data_point);
+        }
+        Ok(data_points)
+    }
+
+    async fn replace_bm25_data_points(
+        context_id: &str,
+        keep: &(dyn Fn(&HashMap<String, serde_json::Value>) -> bool + Sync),
+        data_points: Vec<BM25DataPoint>,
+        context_manager: &ContextManager,
+    ) -> std::result::Result<usize, String> {
+        let context = context_manager
+            .get_bm25_contexts_ref()
+            .read()
+            .await
+            .get(context_id)
+            .cloned()
+            .ok_or_else(|| format!("BM25 context {} is not loaded", context_id))?;
+        let mut context = context.lock().await;
+        let removed = context
+            .replace_data_points(|point| keep(&point.payload), data_points)
+            .map_err(|e| format!("Failed to update BM25 data points: {}", e))?;
+        context
+            .save()
+            .map_err(|e| format!("Failed to save BM25 context: {}", e))?;
+        Ok(removed)
+    }
+
+    async fn replace_semantic_data_points(
+        context_id: &str,
+        keep: &(dyn Fn(&HashMap<String, serde_json::Value>) -> bool + Sync),
+        data_points: Vec<DataPoint>,
+        context_manager: &ContextManager,
+    ) -> std::result::Result<usize, String> {
+        let context = context_manager
+            .get_volatile_contexts_ref()
+            .read()
+            .await
+            .get(context_id)
+            .cloned()
+            .ok_or_else(|| format!("Semantic context {} is not loaded", context_id))?;
+        let mut context = context.lock().await;
+        let removed = context
+            .replace_data_points(|point| keep(&point.payload), data_points)
+            .map_err(|e| format!("Failed to update data points: {}", e))?;
+        context
+            .save()
+            .map_err(|e| format!("Failed to save semantic context: {}", e))?;
+        Ok(removed)
+    }
+
     fn create_bm25_data_point_from_item(item: &serde_json::Value, id: usize) -> Result<BM25DataPoint> {
         let text = item.get("text").and_then(|v| v.as_str()).unwrap_or("");
 
@@ -15,6 +15,7 @@ use tracing::warn;
 use super::{
     BM25Context,
     SemanticContext,
+    ranking,
 };
 use crate::client::utils;
 use crate::embedding::{
@@ -33,6 +34,8 @@ type BM25Contexts = Arc<RwLock<HashMap<ContextId, Arc<Mutex<BM25Context>>>>>;
 const SEMANTIC_DATA_FILE: &str = "data.json";
 const BM25_DATA_FILE: &str = "data.bm25.json";
 const DEFAULT_BM25_SCORE: f64 = 100.0;
+/// Minimum number of results of each search fused for hybrid contexts
+const HYBRID_CANDIDATES: usize = 50;
 
 #[derive(Clone)]
 /// Context manager for handling contexts
@@ -73,6 +76,9 @@ impl ContextManager {
     }
 
     /// Search all contexts
+    ///
+    /// Distances are normalized across index types, so the best `effective_limit` results overall
+    /// are returned, grouped by context with the contexts with the best results first.
     pub async fn search_all(
         &self,
         query_text: &str,
@@ -83,28 +89,21 @@ impl ContextManager {
         let contexts_metadata = self.contexts.read().await;
 
         for (context_id, context_meta) in contexts_metadata.iter() {
-            if context_meta.embedding_type.is_bm25() {
-                if let Some(results) = self.search_bm25_context(context_id, query_text, effective_limit).await {
-                    all_results.push((context_id.clone(), results));
-                }
-            } else if let Some(results) = self
-                .search_semantic_context(context_id, query_text, effective_limit, embedder)
+            if let Some(results) = self
+                .search_context_of_type(
+                    context_id,
+                    context_meta.embedding_type,
+                    query_text,
+                    effective_limit,
+                    embedder,
+                )
                 .await?
             {
                 all_results.push((context_id.clone(), results));
             }
         }
 
-        all_results.sort_by(|(_, a), (_, b)| {
-            if a.is_empty() || b.is_empty() {
-                return std::cmp::Ordering::Equal;
-            }
-            a[0].distance
-                .partial_cmp(&b[0].distance)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        Ok(all_results)
+        Ok(ranking::rank_across_contexts(all_results, effective_limit))
     }
 
     /// Search in a specific context
@@ -120,14 +119,56 @@ impl ContextManager {
             .get(context_id)
             .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
 
-        if context_meta.embedding_type.is_bm25() {
-            Ok(self.search_bm25_context(context_id, query_text, effective_limit).await)
+        self.search_context_of_type(
+            context_id,
+            context_meta.embedding_type,
+            query_text,
+            effective_limit,
+            embedder,
+        )
+        .await
+    }
+
+    async fn search_context_of_type(
+        &self,
+        context_id: &str,
+        embedding_type: EmbeddingType,
+        query_text: &str,
+        limit: usize,
+        embedder: &dyn TextEmbedderTrait,
+    ) -> Result<Option<SearchResults>> {
+        if embedding_type.is_hybrid() {
+            self.search_hybrid_context(context_id, query_text, limit, embedder)
+                .await
+        } else if embedding_type.is_bm25() {
+            Ok(self.search_bm25_context(context_id, query_text, limit).await)
         } else {
-            self.search_semantic_context(context_id, query_text, effective_limit, embedder)
+            self.search_semantic_context(context_id, query_text, limit, embedder)
                 .await
         }
     }
 
+    async fn search_hybrid_context(
+        &self,
+        context_id: &str,
+        query_text: &str,
+        limit: usize,
+        embedder: &dyn TextEmbedderTrait,
+    ) -> Result<Option<SearchResults>> {
+        let candidates = limit.max(HYBRID_CANDIDATES);
+        let bm25_results = self
+            .search_bm25_context(context_id, query_text, candidates)
+            .await
+            .unwrap_or_default();
+        let vector_results = self
+            .search_semantic_context(context_id, query_text, candidates, embedder)
+            .await?
+            .unwrap_or_default();
+
+        let results = ranking::fuse_hybrid(bm25_results, vector_results, limit);
+        Ok(if results.is_empty() { None } else { Some(results) })
+    }
+
     async fn search_bm25_context(&self, context_id: &str, query_text: &str, limit: usize) -> Option<SearchResults> {
         let bm25_contexts = tokio::time::timeout(Duration::from_millis(100), self.bm25_contexts.read())
             .await
@@ -146,7 +187,7 @@ impl ContextManager {
                         vector,
                         payload: data_point.payload.clone(),
                     };
-                    SearchResult::new(point, score)
+                    SearchResult::new(point, ranking::bm25_distance(score))
                 })
             })
             .collect();
@@ -169,7 +210,12 @@ impl ContextManager {
         if let Some(context_arc) = volatile_contexts.get(context_id) {
             if let Ok(context_guard) = context_arc.try_lock() {
                 match context_guard.search(&query_vector, limit) {
-                    Ok(results) => Ok(if results.is_empty() { None } else { Some(results) }),
+                    Ok(mut results) => {
+                        for result in &mut results {
+                            result.distance = ranking::vector_distance(result.distance);
+                        }
+                        Ok(if results.is_empty() { None } else { Some(results) })
+                    },
                     Err(e) => {
                         warn!("Failed to search context {}: {}", context_id, e);
                         Ok(None)
@@ -190,43 +236,48 @@ impl ContextManager {
         operation_manager: &crate::client::operation::OperationManager,
     ) -> Result<()> {
         // First check if there's already an ACTIVE indexing operation for this exact path
-        if let Ok(operations) = operation_manager.get_active_operations().try_read() {
-            for handle in operations.values() {
-                if let crate::types::OperationType::Indexing { path, name } = &handle.operation_type {
-                    if let Ok(operation_canonical) = PathBuf::from(path).canonicalize() {
-                        if operation_canonical == *canonical_path {
-                            if let Ok(progress) = handle.progress.try_lock() {
-                                // Only block if the operation is truly active (not cancelled, failed, or completed)
-                                let is_cancelled = progress.message.contains("cancelled");
-                                let is_failed =
-                                    progress.message.contains("failed") || progress.message.contains("error");
-                                let is_completed = progress.message.contains("complete");
-
-                                if !is_cancelled && !is_failed && !is_completed {
-                                    return Err(crate::error::SemanticSearchError::InvalidArgument(format!(
-                                        "Already indexing this path: {} (Operation: {})",
-                                        path, name
-                                    )));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        Self::check_path_not_indexing(canonical_path, operation_manager)?;
 
         // Then check if path already exists in knowledge base contexts
         if let Ok(contexts_guard) = self.contexts.try_read() {
             for context in contexts_guard.values() {
-                if let Some(existing_path) = &context.source_path {
-                    let existing_path_buf = PathBuf::from(existing_path);
-                    if let Ok(existing_canonical) = existing_path_buf.canonicalize() {
-                        if existing_canonical == *canonical_path {
-                            return Err(crate::error::SemanticSearchError::InvalidArgument(format!(
-                                "Path already exists in knowledge base: {} (Context: '{}')",
-                                existing_path, context.name
-                            )));
-                        }
+                if let Some(existing_path) = &context.source_path
+                    && let Ok(existing_canonical) = PathBuf::from(existing_path).canonicalize()
+                    && existing_canonical == *canonical_path
+                {
+                    return Err(crate::error::SemanticSearchError::InvalidArgument(format!(
+                        "Path already exists in knowledge base: {} (Context: '{}')",
+                        existing_path, context.name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that no active operation is indexing or updating the path
+    pub fn check_path_not_indexing(
+        canonical_path: &Path,
+        operation_manager: &crate::client::operation::OperationManager,
+    ) -> Result<()> {
+        if let Ok(operations) = operation_manager.get_active_operations().try_read() {
+            for handle in operations.values() {
+                if let crate::types::OperationType::Indexing { path, name }
+                | crate::types::OperationType::Updating { path, name } = &handle.operation_type
+                    && let Ok(operation_canonical) = PathBuf::from(path).canonicalize()
+                    && operation_canonical == *canonical_path
+                    && let Ok(progress) = handle.progress.try_lock()
+                {
+                    // Only block if the operation is truly active (not cancelled, failed, or completed)
+                    let is_cancelled = progress.message.contains("cancelled");
+                    let is_failed = progress.message.contains("failed") || progress.message.contains("error");
+                    let is_completed = progress.message.contains("complete");
+
+                    if !is_cancelled && !is_failed && !is_completed {
+                        return Err(crate::error::SemanticSearchError::InvalidArgument(format!(
+                            "Already indexing this path: {} (Operation: {})",
+                            path, name
+                        )));
                     }
                 }
             }
@@ -261,7 +312,10 @@ impl ContextManager {
             return Ok(());
         }
 
-        if embedding_type.is_bm25() {
+        if embedding_type.is_hybrid() {
+            self.load_bm25_context(context_id, &context_dir).await?;
+            self.load_semantic_context(context_id, &context_dir).await
+        } else if embedding_type.is_bm25() {
             self.load_bm25_context(context_id, &context_dir).await
         } else {
             self.load_semantic_context(context_id, &context_dir).await
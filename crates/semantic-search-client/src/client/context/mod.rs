@@ -4,6 +4,8 @@ pub mod bm25_context;
 pub mod context_creator;
 /// Context management
 pub mod context_manager;
+/// Score normalization, rank fusion and reranking of search results
+pub mod ranking;
 /// Semantic context implementation
 pub mod semantic_context;
 
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use crate::embedding::RerankerTrait;
+use crate::error::Result;
+use crate::types::{
+    ContextId,
+    SearchResult,
+    SearchResults,
+};
+
+/// Constant of reciprocal rank fusion, dampening the advantage of the first ranks
+const RRF_K: f32 = 60.0;
+
+/// BM25 score at which a result is considered half relevant
+const BM25_HALF_RELEVANCE_SCORE: f32 = 5.0;
+
+/// Normalize the cosine distance of a vector search result
+///
+/// Search results of all contexts have distances between 0 (most relevant) and 1 (irrelevant),
+/// so that they can be ranked against each other.
+pub fn vector_distance(cosine_distance: f32) -> f32 {
+    cosine_distance.clamp(0.0, 1.0)
+}
+
+/// Normalize the score of a BM25 search result
+///
+/// BM25 scores are unbounded and higher is better, so they are saturated into a distance between
+/// 0 and 1.
+pub fn bm25_distance(score: f32) -> f32 {
+    BM25_HALF_RELEVANCE_SCORE / (score.max(0.0) + BM25_HALF_RELEVANCE_SCORE)
+}
+
+/// Normalize the logit of a cross-encoder into a distance between 0 and 1
+pub fn reranker_distance(logit: f32) -> f32 {
+    1.0 - 1.0 / (1.0 + (-logit).exp())
+}
+
+/// Fuse rankings of the same data points with reciprocal rank fusion
+///
+/// # Arguments
+///
+/// * `rankings` - IDs of the data points of each ranking, best first
+///
+/// # Returns
+///
+/// The IDs with their fused scores, best first
+pub fn reciprocal_rank_fusion(rankings: &[Vec<usize>]) -> Vec<(usize, f32)> {
+    let mut scores: HashMap<usize, f32> = HashMap::new();
+    for ranking in rankings {
+        for (rank, id) in ranking.iter().enumerate() {
+            *scores.entry(*id).or_default() += 1.0 / (RRF_K + rank as f32 + 1.0);
+        }
+    }
+
+    let mut fused = scores.into_iter().collect::<Vec<_>>();
+    fused.sort_by(|(a_id, a), (b_id, b)| b.total_cmp(a).then(a_id.cmp(b_id)));
+    fused
+}
+
+/// Fuse the BM25 and vector search results of a hybrid context
+///
+/// The results are ordered by reciprocal rank fusion. A result ranked first by both searches gets
+/// the best normalized distance of either search, and the others are scaled down by their fused
+/// score, so that hybrid results stay comparable with those of other contexts.
+///
+/// # Arguments
+///
+/// * `bm25` - BM25 search results, best first
+/// * `vector` - Vector search results, best first
+/// * `limit` - Maximum number of results to return
+///
+/// # Returns
+///
+/// The fused search results, best first
+pub fn fuse_hybrid(bm25: SearchResults, vector: SearchResults, limit: usize) -> SearchResults {
+    let best_distance = bm25.iter().chain(&vector).map(|r| r.distance).fold(1.0, f32::min);
+    let rankings = [ids(&bm25), ids(&vector)];
+    let max_score = rankings.len() as f32 / (RRF_K + 1.0);
+
+    // Vector results carry the embeddings, so they are preferred for points found by both
+    let mut points = bm25
+        .into_iter()
+        .chain(vector)
+        .map(|result| (result.point.id, result))
+        .collect::<HashMap<_, _>>();
+
+    reciprocal_rank_fusion(&rankings)
+        .into_iter()
+        .take(limit)
+        .filter_map(|(id, score)| {
+            let mut result = points.remove(&id)?;
+            result.distance = 1.0 - (1.0 - best_distance) * score / max_score;
+            Some(result)
+        })
+        .collect()
+}
+
+/// Rank the search results of several contexts against each other
+///
+/// # Arguments
+///
+/// * `results` - Search results of each context, with normalized distances
+/// * `limit` - Maximum number of results to return overall
+///
+/// # Returns
+///
+/// The best results overall, grouped by context, contexts with the best results first
+pub fn rank_across_contexts(results: Vec<(ContextId, SearchResults)>, limit: usize) -> Vec<(ContextId, SearchResults)> {
+    rank(flatten(results), limit)
+}
+
+/// Rerank search results with a cross-encoder
+///
+/// # Arguments
+///
+/// * `reranker` - Cross-encoder scoring the results against the query
+/// * `query` - Search query
+/// * `results` - Search results of each context
+/// * `limit` - Maximum number of results to return overall
+///
+/// # Returns
+///
+/// The best results overall according to the reranker, grouped by context
+pub fn rerank(
+    reranker: &dyn RerankerTrait,
+    query: &str,
+    results: Vec<(ContextId, SearchResults)>,
+    limit: usize,
+) -> Result<Vec<(ContextId, SearchResults)>> {
+    let mut results = flatten(results);
+    let texts = results
+        .iter()
+        .map(|(_, result)| result.text().unwrap_or_default().to_string())
+        .collect::<Vec<_>>();
+
+    let scores = reranker.score(query, &texts)?;
+    for ((_, result), score) in results.iter_mut().zip(scores) {
+        result.distance = reranker_distance(score);
+    }
+
+    Ok(rank(results, limit))
+}
+
+fn ids(results: &[SearchResult]) -> Vec<usize> {
+    results.iter().map(|result| result.point.id).collect()
+}
+
+fn flatten(results: Vec<(ContextId, SearchResults)>) -> Vec<(ContextId, SearchResult)> {
+    results
+        .into_iter()
+        .flat_map(|(context_id, results)| results.into_iter().map(move |result| (context_id.clone(), result)))
+        .collect()
+}
+
+fn rank(mut results: Vec<(ContextId, SearchResult)>, limit: usize) -> Vec<(ContextId, SearchResults)> {
+    results.sort_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance));
+    results.truncate(limit);
+
+    let mut grouped: Vec<(ContextId, SearchResults)> = Vec::new();
+    for (context_id, result) in results {
+        match grouped.iter_mut().find(|(id, _)| *id == context_id) {
+            Some((_, context_results)) => context_results.push(result),
+            None => grouped.push((context_id, vec![result])),
+        }
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DataPoint;
+
+    fn result(id: usize, text: &str, distance: f32) -> SearchResult {
+        let point = DataPoint {
+            id,
+            payload: HashMap::from([("text".to_string(), serde_json::Value::String(text.to_string()))]),
+            vector: Vec::new(),
+        };
+        SearchResult::new(point, distance)
+    }
+
+    fn texts(results: &[(ContextId, SearchResults)]) -> Vec<(&str, Vec<&str>)> {
+        results
+            .iter()
+            .map(|(id, results)| (id.as_str(), results.iter().filter_map(|r| r.text()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let fused = reciprocal_rank_fusion(&[vec![1, 2, 3], vec![3, 1, 4]]);
+        let ids = fused.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 3, 2, 4]);
+        assert!((fused[0].1 - (1.0 / 61.0 + 1.0 / 62.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fuse_hybrid() {
+        let bm25 = vec![result(0, "a", bm25_distance(12.0)), result(1, "b", bm25_distance(3.0))];
+        let vector = vec![result(0, "a", 0.2), result(2, "c", 0.4)];
+
+        let fused = fuse_hybrid(bm25, vector, 2);
+        assert_eq!(fused.iter().filter_map(|r| r.text()).collect::<Vec<_>>(), vec![
+            "a", "b"
+        ]);
+        assert!((fused[0].distance - 0.2).abs() < 1e-6);
+        assert!(fused[1].distance > fused[0].distance && fused[1].distance < 1.0);
+    }
+
+    #[test]
+    fn test_rank_across_contexts() {
+        let results = vec![
+            ("bm25".to_string(), vec![
+                result(0, "keyword", bm25_distance(20.0)),
+                result(1, "weak", bm25_distance(0.5)),
+            ]),
+            ("vector".to_string(), vec![
+                result(0, "close", 0.1),
+                result(1, "far", 0.9),
+            ]),
+        ];
+
+        let ranked = rank_across_contexts(results, 3);
+        assert_eq!(texts(&ranked), vec![
+            ("vector", vec!["close", "far"]),
+            ("bm25", vec!["keyword"])
+        ]);
+    }
+
+    struct LengthReranker;
+
+    impl RerankerTrait for LengthReranker {
+        fn score(&self, _query: &str, texts: &[String]) -> Result<Vec<f32>> {
+            Ok(texts.iter().map(|text| text.len() as f32).collect())
+        }
+    }
+
+    #[test]
+    fn test_rerank() {
+        let results = vec![
+            ("a".to_string(), vec![result(0, "x", 0.1), result(1, "xxx", 0.5)]),
+            ("b".to_string(), vec![result(0, "xx", 0.3)]),
+        ];
+
+        let reranked = rerank(&LengthReranker, "query", results, 2).unwrap();
+        assert_eq!(texts(&reranked), vec![("a", vec!["xxx"]), ("b", vec!["xx"])]);
+    }
+}
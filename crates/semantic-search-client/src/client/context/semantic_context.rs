@@ -101,6 +101,35 @@ impl SemanticContext {
         Ok(count)
     }
 
+    /// Replace data points, keeping those matching `keep` and adding `data_points`
+    ///
+    /// The points are renumbered so that their IDs stay their positions. The vector index can't
+    /// remove points, so it is rebuilt, which is cheap compared to generating the embeddings.
+    ///
+    /// Returns the number of data points removed.
+    pub fn replace_data_points(
+        &mut self,
+        keep: impl Fn(&DataPoint) -> bool,
+        data_points: Vec<DataPoint>,
+    ) -> Result<usize> {
+        let count = self.data_points.len();
+        self.data_points.retain(|point| keep(point));
+        let removed = count - self.data_points.len();
+
+        self.data_points.extend(data_points);
+        for (i, point) in self.data_points.iter_mut().enumerate() {
+            point.id = i;
+        }
+
+        if self.data_points.is_empty() {
+            self.index = None;
+        } else {
+            self.rebuild_index()?;
+        }
+
+        Ok(removed)
+    }
+
     /// Update the index with data points in a specific range
     pub fn update_index_by_range(&mut self, start_idx: usize, end_idx: usize) -> Result<()> {
         // If we don't have an index yet, or if the index is small and we're adding many points,
@@ -1,10 +1,13 @@
 #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+use crate::embedding::CandleReranker;
+#[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
 use crate::embedding::CandleTextEmbedder;
 use crate::embedding::MockTextEmbedder; // Used for Fast type since BM25 doesn't need embeddings
 #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
 use crate::embedding::ModelType;
 use crate::embedding::{
     EmbeddingType,
+    RerankerTrait,
     TextEmbedderTrait,
 };
 use crate::error::Result;
@@ -23,7 +26,9 @@ pub fn create_embedder(embedding_type: EmbeddingType) -> Result<Box<dyn TextEmbe
     let embedder: Box<dyn TextEmbedderTrait> = match embedding_type {
         EmbeddingType::Fast => Box::new(MockTextEmbedder::new(384)), // BM25 doesn't use embeddings
         #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
-        EmbeddingType::Best => Box::new(CandleTextEmbedder::with_model_type(ModelType::MiniLML6V2)?),
+        EmbeddingType::Best | EmbeddingType::Hybrid => {
+            Box::new(CandleTextEmbedder::with_model_type(ModelType::MiniLML6V2)?)
+        },
         #[cfg(test)]
         EmbeddingType::Mock => Box::new(MockTextEmbedder::new(384)),
     };
@@ -46,10 +51,47 @@ pub fn create_embedder(embedding_type: EmbeddingType) -> Result<Box<dyn TextEmbe
     let embedder: Box<dyn TextEmbedderTrait> = match embedding_type {
         EmbeddingType::Fast => Box::new(MockTextEmbedder::new(384)), // BM25 doesn't use embeddings
         #[cfg(not(target_arch = "aarch64"))]
-        EmbeddingType::Best => Box::new(CandleTextEmbedder::with_model_type(ModelType::MiniLML6V2)?),
+        EmbeddingType::Best | EmbeddingType::Hybrid => {
+            Box::new(CandleTextEmbedder::with_model_type(ModelType::MiniLML6V2)?)
+        },
         #[cfg(test)]
         EmbeddingType::Mock => Box::new(MockTextEmbedder::new(384)),
     };
 
     Ok(embedder)
 }
+
+/// Creates the cross-encoder used to rerank search results, downloading it if needed
+///
+/// # Returns
+///
+/// The reranker, or `None` if it can't be loaded
+#[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+pub async fn create_reranker() -> Option<Box<dyn RerankerTrait>> {
+    if let Err(e) = super::model::ModelDownloader::ensure_reranker_downloaded().await {
+        tracing::warn!(
+            "Failed to download the reranker model, results won't be reranked: {}",
+            e
+        );
+        return None;
+    }
+
+    match CandleReranker::new() {
+        Ok(reranker) => Some(Box::new(reranker)),
+        Err(e) => {
+            tracing::warn!("Failed to load the reranker model, results won't be reranked: {}", e);
+            None
+        },
+    }
+}
+
+/// Creates the cross-encoder used to rerank search results
+/// (Linux ARM version, where Candle models are not available)
+///
+/// # Returns
+///
+/// Always `None`
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+pub async fn create_reranker() -> Option<Box<dyn RerankerTrait>> {
+    None
+}
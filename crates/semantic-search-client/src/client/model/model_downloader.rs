@@ -14,7 +14,7 @@ impl ModelDownloader {
     pub async fn ensure_models_downloaded(embedding_type: &EmbeddingType) -> Result<()> {
         match embedding_type {
             #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
-            EmbeddingType::Best => {
+            EmbeddingType::Best | EmbeddingType::Hybrid => {
                 Self::download_best_model().await?;
             },
             EmbeddingType::Fast => {
@@ -28,12 +28,21 @@ impl ModelDownloader {
         Ok(())
     }
 
+    /// Ensure the cross-encoder used to rerank search results is downloaded
+    #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+    pub async fn ensure_reranker_downloaded() -> Result<()> {
+        Self::download_model(crate::embedding::ModelConfig::reranker()).await
+    }
+
     #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
     async fn download_best_model() -> Result<()> {
+        Self::download_model(crate::embedding::ModelType::default().get_config()).await
+    }
+
+    #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+    async fn download_model(model_config: crate::embedding::ModelConfig) -> Result<()> {
         use crate::client::hosted_model_client::HostedModelClient;
-        use crate::embedding::ModelType;
 
-        let model_config = ModelType::default().get_config();
         let (model_path, _tokenizer_path) = model_config.get_local_paths();
 
         // Create model directory if it doesn't exist
@@ -47,6 +47,18 @@ pub struct SemanticSearchConfig {
 
     /// Embedding engine type to use
     pub embedding_type: EmbeddingType,
+
+    /// Whether to rerank search results with a cross-encoder
+    #[serde(default)]
+    pub rerank: bool,
+
+    /// Number of best search results reranked by the cross-encoder
+    #[serde(default = "default_rerank_candidates")]
+    pub rerank_candidates: usize,
+}
+
+fn default_rerank_candidates() -> usize {
+    20
 }
 
 impl SemanticSearchConfig {
@@ -93,6 +105,8 @@ impl Default for SemanticSearchConfig {
             max_files: 10000, // Default limit of 10000 files
             hosted_models_base_url: "https://desktop-release.q.us-east-1.amazonaws.com/models".to_string(),
             embedding_type: EmbeddingType::default(),
+            rerank: false,
+            rerank_candidates: default_rerank_candidates(),
         }
     }
 }
@@ -341,6 +355,8 @@ mod tests {
             max_files: 10000,
             hosted_models_base_url: "http://test.example.com/models".to_string(),
             embedding_type: EmbeddingType::default(),
+            rerank: false,
+            rerank_candidates: 20,
         };
 
         // Update the config
@@ -114,6 +114,20 @@ impl ModelType {
 }
 
 impl ModelConfig {
+    /// Get the configuration of the cross-encoder used to rerank search results
+    ///
+    /// ms-marco-MiniLM-L-6-v2 has the architecture of all-MiniLM-L6-v2, with a classification
+    /// head scoring query and passage pairs.
+    pub fn reranker() -> Self {
+        Self {
+            name: "ms-marco-MiniLM-L-6-v2".to_string(),
+            repo_path: "cross-encoder/ms-marco-MiniLM-L-6-v2".to_string(),
+            normalize_embeddings: false,
+            batch_size: 16,
+            ..ModelType::MiniLML6V2.get_config()
+        }
+    }
+
     /// Get the local paths for model files
     pub fn get_local_paths(&self) -> (PathBuf, PathBuf) {
         // Get the base directory and model directory
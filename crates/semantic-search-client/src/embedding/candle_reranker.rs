@@ -0,0 +1,179 @@
+use std::path::Path;
+
+use candle_core::{
+    Device,
+    Tensor,
+};
+use candle_nn::{
+    Linear,
+    Module,
+    VarBuilder,
+};
+use candle_transformers::models::bert::{
+    BertModel,
+    DTYPE,
+};
+use tokenizers::{
+    Tokenizer,
+    TruncationParams,
+};
+use tracing::{
+    debug,
+    info,
+};
+
+use super::candle::{
+    get_best_available_device,
+    initialize_thread_pool,
+    load_tokenizer,
+    prepare_tokenizer,
+};
+use crate::embedding::candle_models::ModelConfig;
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+
+/// Cross-encoder reranker using Candle
+///
+/// Unlike an embedding model, a cross-encoder reads the query and a passage together, which
+/// scores their relevance more accurately but is too slow to run on more than a few results.
+pub struct CandleReranker {
+    /// The BERT model
+    model: BertModel,
+    /// The pooler applied to the classification token
+    pooler: Linear,
+    /// The classification head producing the relevance logit
+    classifier: Linear,
+    /// The tokenizer
+    tokenizer: Tokenizer,
+    /// The device to run on
+    device: Device,
+    /// Model configuration
+    config: ModelConfig,
+}
+
+impl CandleReranker {
+    /// Create a new reranker with the default model (ms-marco-MiniLM-L-6-v2)
+    ///
+    /// # Returns
+    ///
+    /// A new CandleReranker instance
+    pub fn new() -> Result<Self> {
+        let config = ModelConfig::reranker();
+        let (model_path, tokenizer_path) = config.get_local_paths();
+
+        Self::with_model_config(&model_path, &tokenizer_path, config)
+    }
+
+    /// Create a new reranker with specific model paths and configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `model_path` - Path to the model file (.safetensors)
+    /// * `tokenizer_path` - Path to the tokenizer file (.json)
+    /// * `config` - Model configuration
+    ///
+    /// # Returns
+    ///
+    /// A new CandleReranker instance
+    pub fn with_model_config(model_path: &Path, tokenizer_path: &Path, config: ModelConfig) -> Result<Self> {
+        info!("Initializing reranker with model: {:?}", model_path);
+
+        initialize_thread_pool()?;
+
+        let mut tokenizer = prepare_tokenizer(&load_tokenizer(tokenizer_path)?)?;
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: config.config.max_position_embeddings,
+                ..Default::default()
+            }))
+            .map_err(|e| SemanticSearchError::EmbeddingError(format!("Failed to configure tokenizer: {}", e)))?;
+
+        let device = get_best_available_device();
+
+        let vb = unsafe { VarBuilder::from_mmaped_safetensors(&[model_path], DTYPE, &device) }
+            .map_err(|e| SemanticSearchError::EmbeddingError(format!("Failed to load model weights: {}", e)))?;
+        let hidden_size = config.config.hidden_size;
+        let model = BertModel::load(vb.clone(), &config.config).map_err(model_error)?;
+        let pooler = candle_nn::linear(hidden_size, hidden_size, vb.pp("bert.pooler.dense")).map_err(model_error)?;
+        let classifier = candle_nn::linear(hidden_size, 1, vb.pp("classifier")).map_err(model_error)?;
+
+        debug!("Reranker initialized successfully");
+
+        Ok(Self {
+            model,
+            pooler,
+            classifier,
+            tokenizer,
+            device,
+            config,
+        })
+    }
+
+    /// Score texts against a query
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query
+    /// * `texts` - The texts to score
+    ///
+    /// # Returns
+    ///
+    /// The relevance logit of each text, higher meaning more relevant
+    pub fn score(&self, query: &str, texts: &[String]) -> Result<Vec<f32>> {
+        let mut scores = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.config.batch_size) {
+            let batch_scores = self
+                .score_batch(query, batch)
+                .map_err(|e| SemanticSearchError::EmbeddingError(format!("Reranking failed: {}", e)))?;
+            scores.extend(batch_scores);
+        }
+        Ok(scores)
+    }
+
+    fn score_batch(&self, query: &str, batch: &[String]) -> std::result::Result<Vec<f32>, String> {
+        let pairs = batch
+            .iter()
+            .map(|text| (query.to_string(), text.clone()))
+            .collect::<Vec<_>>();
+        let encodings = self
+            .tokenizer
+            .encode_batch(pairs, true)
+            .map_err(|e| format!("Failed to tokenize texts: {}", e))?;
+
+        let seq_len = encodings.first().map_or(0, |encoding| encoding.len());
+        let tensor = |values: Vec<u32>| {
+            Tensor::from_vec(values, (encodings.len(), seq_len), &self.device).map_err(|e| e.to_string())
+        };
+        let token_ids = tensor(encodings.iter().flat_map(|e| e.get_ids().to_vec()).collect())?;
+        let token_type_ids = tensor(encodings.iter().flat_map(|e| e.get_type_ids().to_vec()).collect())?;
+        let attention_mask = tensor(encodings.iter().flat_map(|e| e.get_attention_mask().to_vec()).collect())?;
+
+        let logits = self
+            .model
+            .forward(&token_ids, &token_type_ids, Some(&attention_mask))
+            .and_then(|hidden| hidden.narrow(1, 0, 1)?.squeeze(1))
+            .and_then(|cls| self.pooler.forward(&cls)?.tanh())
+            .and_then(|pooled| self.classifier.forward(&pooled)?.squeeze(1))
+            .map_err(|e| format!("Model inference failed: {}", e))?;
+
+        logits.to_vec1::<f32>().map_err(|e| e.to_string())
+    }
+}
+
+fn model_error(e: candle_core::Error) -> SemanticSearchError {
+    SemanticSearchError::EmbeddingError(format!("Failed to create reranker model: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_handling() {
+        let invalid_path = Path::new("/nonexistent/path");
+        let result = CandleReranker::with_model_config(invalid_path, invalid_path, ModelConfig::reranker());
+        assert!(result.is_err());
+    }
+}
@@ -4,6 +4,8 @@ mod benchmark_utils;
 #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
 mod candle;
 mod candle_models;
+#[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+mod candle_reranker;
 /// Mock embedder for testing and as placeholder for BM25
 pub mod mock;
 mod trait_def;
@@ -20,8 +22,11 @@ pub use candle_models::{
     ModelConfig,
     ModelType,
 };
+#[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+pub use candle_reranker::CandleReranker;
 pub use mock::MockTextEmbedder;
 pub use trait_def::{
     EmbeddingType,
+    RerankerTrait,
     TextEmbedderTrait,
 };
@@ -13,6 +13,9 @@ pub enum EmbeddingType {
     /// Best embedding using all-MiniLM-L6-v2 (not available on Linux ARM)
     #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
     Best,
+    /// Both BM25 and all-MiniLM-L6-v2, with their results fused (not available on Linux ARM)
+    #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+    Hybrid,
     /// Use Mock embedding engine (only available in tests)
     #[cfg(test)]
     Mock,
@@ -43,7 +46,7 @@ impl EmbeddingType {
     pub fn to_model_type(&self) -> Option<super::ModelType> {
         match self {
             Self::Fast => None, // BM25 doesn't use Candle models
-            Self::Best => Some(super::ModelType::MiniLML6V2),
+            Self::Best | Self::Hybrid => Some(super::ModelType::MiniLML6V2),
             #[cfg(test)]
             Self::Mock => None,
         }
@@ -54,10 +57,19 @@ impl EmbeddingType {
         matches!(self, Self::Fast)
     }
 
+    /// Check if this embedding type indexes both BM25 and embeddings
+    pub fn is_hybrid(&self) -> bool {
+        match self {
+            #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+            Self::Hybrid => true,
+            _ => false,
+        }
+    }
+
     /// Check if this embedding type uses Candle
     #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
     pub fn is_candle(&self) -> bool {
-        matches!(self, Self::Best)
+        matches!(self, Self::Best | Self::Hybrid)
     }
 
     /// Get a human-readable description of the embedding type
@@ -66,6 +78,8 @@ impl EmbeddingType {
             Self::Fast => "Fast",
             #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
             Self::Best => "Best",
+            #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+            Self::Hybrid => "Hybrid",
             #[cfg(test)]
             Self::Mock => "Mock",
         }
@@ -78,6 +92,8 @@ impl EmbeddingType {
             "fast" => Some(Self::Fast),
             #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
             "best" => Some(Self::Best),
+            #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+            "hybrid" => Some(Self::Hybrid),
             #[cfg(test)]
             "mock" => Some(Self::Mock),
             _ => None,
@@ -90,6 +106,8 @@ impl EmbeddingType {
             Self::Fast => "Fast",
             #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
             Self::Best => "Best",
+            #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+            Self::Hybrid => "Hybrid",
             #[cfg(test)]
             Self::Mock => "Mock",
         }
@@ -107,6 +125,22 @@ pub trait TextEmbedderTrait: Send + Sync {
     fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
 }
 
+/// Trait for reranking implementations
+///
+/// This trait defines the interface for scoring how relevant texts are to a query, used to rerank
+/// search results.
+pub trait RerankerTrait: Send + Sync {
+    /// Score texts against a query, as logits where higher means more relevant
+    fn score(&self, query: &str, texts: &[String]) -> Result<Vec<f32>>;
+}
+
+#[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+impl RerankerTrait for super::CandleReranker {
+    fn score(&self, query: &str, texts: &[String]) -> Result<Vec<f32>> {
+        self.score(query, texts)
+    }
+}
+
 #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
 impl TextEmbedderTrait for super::CandleTextEmbedder {
     fn embed(&self, text: &str) -> Result<Vec<f32>> {
@@ -39,6 +39,8 @@ pub use error::{
 pub use types::{
     BM25DataPoint,
     DataPoint,
+    FileManifest,
+    FileManifestEntry,
     FileType,
     KnowledgeContext,
     OperationStatus,
@@ -85,12 +85,60 @@ impl PatternFilter {
     }
 }
 
+/// Convert the `.gitignore` file at the root of a directory into exclude patterns
+///
+/// Like patterns without a slash in `.gitignore`, the patterns match any part of the path.
+/// Negated patterns (`!pattern`) aren't supported and are skipped.
+pub fn gitignore_patterns(root: &Path) -> Vec<String> {
+    let Ok(contents) = std::fs::read_to_string(root.join(".gitignore")) else {
+        return Vec::new();
+    };
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .map(|line| line.trim_start_matches('/'))
+        .flat_map(|line| match line.strip_suffix('/') {
+            Some(dir) => vec![format!("{}/**", dir)],
+            None => vec![line.to_string(), format!("{}/**", line)],
+        })
+        .filter(|pattern| Pattern::new(pattern).is_ok())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
     use super::*;
 
+    #[test]
+    fn test_gitignore_patterns() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(gitignore_patterns(dir.path()).is_empty());
+
+        std::fs::write(
+            dir.path().join(".gitignore"),
+            "# build output\n/target\nnode_modules/\n*.log\n!keep.log\n\n",
+        )
+        .unwrap();
+        let patterns = gitignore_patterns(dir.path());
+        assert_eq!(patterns, vec![
+            "target",
+            "target/**",
+            "node_modules/**",
+            "*.log",
+            "*.log/**"
+        ]);
+
+        let filter = PatternFilter::new(&[], &patterns).unwrap();
+        assert!(!filter.should_include(&dir.path().join("target/debug/build.rs")));
+        assert!(!filter.should_include(&dir.path().join("web/node_modules/react/index.js")));
+        assert!(!filter.should_include(&dir.path().join("logs/server.log")));
+        assert!(filter.should_include(&dir.path().join("src/main.rs")));
+    }
+
     #[test]
     fn test_pattern_filter_creation() {
         let filter = PatternFilter::new(&["*.rs".to_string()], &["target/**".to_string()]);
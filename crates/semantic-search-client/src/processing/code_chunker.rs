@@ -0,0 +1,564 @@
+use std::path::Path;
+
+use tree_sitter::{
+    Language,
+    Node,
+    Parser,
+};
+
+use crate::config;
+
+/// A chunk of source code along with where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeChunk {
+    /// The source code of the chunk
+    pub text: String,
+    /// Qualified name of the function, class or impl the chunk belongs to, if any
+    pub symbol: Option<String>,
+    /// First line of the chunk (1-based)
+    pub start_line: usize,
+    /// Last line of the chunk (1-based, inclusive)
+    pub end_line: usize,
+}
+
+/// Grammar and definition node kinds of a language supported by the syntax-aware chunker
+struct Grammar {
+    language: Language,
+    /// Node kinds of functions, classes, impls and similar definitions
+    definitions: &'static [&'static str],
+    /// Separator between the names of nested definitions
+    separator: &'static str,
+}
+
+/// Get the name of the language of a source code file
+///
+/// # Arguments
+///
+/// * `path` - Path to the file
+///
+/// # Returns
+///
+/// The language name, or None if the extension is not a known source code extension
+pub fn language_name(path: &Path) -> Option<&'static str> {
+    let language = match path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|s| s.to_lowercase())
+        .as_deref()
+    {
+        Some("rs") => "rust",
+        Some("py") => "python",
+        Some("js" | "jsx") => "javascript",
+        Some("ts") => "typescript",
+        Some("tsx") => "tsx",
+        Some("java") => "java",
+        Some("c" | "h") => "c",
+        Some("cpp" | "hpp") => "cpp",
+        Some("go") => "go",
+        Some("rb") => "ruby",
+        Some("php") => "php",
+        Some("swift") => "swift",
+        Some("kt" | "kts") => "kotlin",
+        Some("cs") => "csharp",
+        Some("sh" | "bash" | "zsh") => "shell",
+        Some("xml") => "xml",
+        Some("css" | "scss" | "sass" | "less") => "css",
+        Some("sql") => "sql",
+        Some("yaml" | "yml") => "yaml",
+        Some("toml") => "toml",
+        _ => return None,
+    };
+    Some(language)
+}
+
+fn grammar(language: &str) -> Option<Grammar> {
+    let (language, definitions, separator): (Language, &'static [&'static str], &'static str) = match language {
+        "rust" => (
+            tree_sitter_rust::LANGUAGE.into(),
+            &[
+                "function_item",
+                "function_signature_item",
+                "impl_item",
+                "trait_item",
+                "struct_item",
+                "enum_item",
+                "union_item",
+                "mod_item",
+                "macro_definition",
+            ],
+            "::",
+        ),
+        "python" => (
+            tree_sitter_python::LANGUAGE.into(),
+            &["function_definition", "class_definition", "decorated_definition"],
+            ".",
+        ),
+        "javascript" => (
+            tree_sitter_javascript::LANGUAGE.into(),
+            &[
+                "function_declaration",
+                "generator_function_declaration",
+                "class_declaration",
+                "method_definition",
+                "export_statement",
+            ],
+            ".",
+        ),
+        "typescript" | "tsx" => {
+            let grammar = if language == "tsx" {
+                tree_sitter_typescript::LANGUAGE_TSX
+            } else {
+                tree_sitter_typescript::LANGUAGE_TYPESCRIPT
+            };
+            (
+                grammar.into(),
+                &[
+                    "function_declaration",
+                    "generator_function_declaration",
+                    "class_declaration",
+                    "abstract_class_declaration",
+                    "interface_declaration",
+                    "enum_declaration",
+                    "module",
+                    "internal_module",
+                    "method_definition",
+                    "export_statement",
+                ],
+                ".",
+            )
+        },
+        "java" => (
+            tree_sitter_java::LANGUAGE.into(),
+            &[
+                "class_declaration",
+                "interface_declaration",
+                "enum_declaration",
+                "record_declaration",
+                "method_declaration",
+                "constructor_declaration",
+            ],
+            ".",
+        ),
+        "c" => (
+            tree_sitter_c::LANGUAGE.into(),
+            &[
+                "function_definition",
+                "struct_specifier",
+                "union_specifier",
+                "enum_specifier",
+            ],
+            "::",
+        ),
+        "cpp" => (
+            tree_sitter_cpp::LANGUAGE.into(),
+            &[
+                "function_definition",
+                "class_specifier",
+                "struct_specifier",
+                "union_specifier",
+                "enum_specifier",
+                "namespace_definition",
+                "template_declaration",
+            ],
+            "::",
+        ),
+        "go" => (
+            tree_sitter_go::LANGUAGE.into(),
+            &["function_declaration", "method_declaration", "type_declaration"],
+            ".",
+        ),
+        "ruby" => (
+            tree_sitter_ruby::LANGUAGE.into(),
+            &["method", "singleton_method", "class", "module"],
+            "::",
+        ),
+        "php" => (
+            tree_sitter_php::LANGUAGE_PHP.into(),
+            &[
+                "function_definition",
+                "class_declaration",
+                "interface_declaration",
+                "trait_declaration",
+                "enum_declaration",
+                "method_declaration",
+            ],
+            "::",
+        ),
+        "swift" => (
+            tree_sitter_swift::LANGUAGE.into(),
+            &[
+                "function_declaration",
+                "class_declaration",
+                "protocol_declaration",
+                "init_declaration",
+            ],
+            ".",
+        ),
+        "csharp" => (
+            tree_sitter_c_sharp::LANGUAGE.into(),
+            &[
+                "namespace_declaration",
+                "class_declaration",
+                "struct_declaration",
+                "interface_declaration",
+                "enum_declaration",
+                "record_declaration",
+                "method_declaration",
+                "constructor_declaration",
+            ],
+            ".",
+        ),
+        "shell" => (tree_sitter_bash::LANGUAGE.into(), &["function_definition"], "."),
+        // There is no Kotlin grammar among the dependencies yet, so Kotlin is chunked by lines
+        // like the markup and data languages
+        _ => return None,
+    };
+
+    Some(Grammar {
+        language,
+        definitions,
+        separator,
+    })
+}
+
+/// Chunk source code along function, class and impl boundaries
+///
+/// Each top-level definition becomes its own chunk, together with its leading comments and
+/// attributes. Definitions larger than the chunk size are split into their nested definitions, or
+/// into runs of lines if they have none. Code between definitions is chunked by lines. Languages
+/// without a grammar are chunked by lines only.
+///
+/// # Arguments
+///
+/// * `content` - The source code
+/// * `language` - The language name, as returned by [`language_name`]
+/// * `chunk_size` - Optional maximum number of words per chunk (if None, uses config value)
+///
+/// # Returns
+///
+/// A vector of code chunks in file order
+pub fn chunk_code(content: &str, language: &str, chunk_size: Option<usize>) -> Vec<CodeChunk> {
+    let chunk_size = chunk_size.unwrap_or_else(|| config::get_config().chunk_size);
+    let lines = content.lines().collect::<Vec<_>>();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let mut chunker = Chunker {
+        source: content.as_bytes(),
+        lines,
+        chunk_size,
+        definitions: &[],
+        separator: ".",
+        chunks: Vec::new(),
+    };
+    let last_row = chunker.lines.len() - 1;
+
+    let tree = grammar(language).and_then(|grammar| {
+        chunker.definitions = grammar.definitions;
+        chunker.separator = grammar.separator;
+
+        let mut parser = Parser::new();
+        parser.set_language(&grammar.language).ok()?;
+        parser.parse(content, None)
+    });
+
+    match tree {
+        Some(tree) => chunker.chunk_block(tree.root_node(), 0, last_row, None),
+        None => chunker.push_lines(0, last_row, None),
+    }
+
+    chunker.chunks
+}
+
+struct Chunker<'a> {
+    source: &'a [u8],
+    lines: Vec<&'a str>,
+    chunk_size: usize,
+    definitions: &'static [&'static str],
+    separator: &'static str,
+    chunks: Vec<CodeChunk>,
+}
+
+impl Chunker<'_> {
+    /// Chunk the rows `start..=end` containing the definitions among the children of `parent`
+    fn chunk_block(&mut self, parent: Node<'_>, start: usize, end: usize, symbol: Option<&str>) {
+        let mut gap_start = start;
+        let mut cursor = parent.walk();
+        let children = parent.named_children(&mut cursor).collect::<Vec<_>>();
+
+        for node in children {
+            if !self.is_definition(node) || node.start_position().row < gap_start {
+                continue;
+            }
+
+            let def_start = self.leading_comments_start(node, gap_start);
+            let def_end = self.end_row(node).min(end);
+            if def_start > gap_start {
+                self.push_lines(gap_start, def_start - 1, symbol);
+            }
+            self.chunk_definition(node, def_start, def_end, symbol);
+            gap_start = def_end + 1;
+        }
+
+        if gap_start <= end {
+            self.push_lines(gap_start, end, symbol);
+        }
+    }
+
+    fn chunk_definition(&mut self, node: Node<'_>, start: usize, end: usize, parent_symbol: Option<&str>) {
+        let name = self.symbol_name(node);
+        let symbol = match (parent_symbol, name) {
+            (Some(parent), Some(name)) => Some(format!("{}{}{}", parent, self.separator, name)),
+            (parent, name) => name.or(parent.map(str::to_string)),
+        };
+
+        if self.word_count(start, end) <= self.chunk_size {
+            self.push(start, end, symbol);
+            return;
+        }
+
+        let body = inner_definition(node, self.definitions).child_by_field_name("body");
+        match body.filter(|body| self.has_definitions(*body)) {
+            Some(body) => self.chunk_block(body, start, end, symbol.as_deref()),
+            None => self.push_lines(start, end, symbol.as_deref()),
+        }
+    }
+
+    /// Chunk the rows `start..=end` into runs of lines of at most the chunk size
+    fn push_lines(&mut self, start: usize, end: usize, symbol: Option<&str>) {
+        let mut run_start = start;
+        let mut words = 0;
+
+        for row in start..=end {
+            let line_words = self.lines[row].split_whitespace().count();
+            if words > 0 && words + line_words > self.chunk_size {
+                self.push(run_start, row - 1, symbol.map(str::to_string));
+                run_start = row;
+                words = 0;
+            }
+            words += line_words;
+        }
+
+        self.push(run_start, end, symbol.map(str::to_string));
+    }
+
+    /// Add a chunk for the rows `start..=end`, without leading and trailing blank lines
+    fn push(&mut self, start: usize, end: usize, symbol: Option<String>) {
+        let is_blank = |row: &usize| self.lines[*row].trim().is_empty();
+        let Some(start) = (start..=end).find(|row| !is_blank(row)) else {
+            return;
+        };
+        let end = (start..=end).rev().find(|row| !is_blank(row)).unwrap_or(start);
+
+        self.chunks.push(CodeChunk {
+            text: self.lines[start..=end].join("\n"),
+            symbol,
+            start_line: start + 1,
+            end_line: end + 1,
+        });
+    }
+
+    fn is_definition(&self, node: Node<'_>) -> bool {
+        self.definitions.contains(&node.kind())
+    }
+
+    fn has_definitions(&self, node: Node<'_>) -> bool {
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor).any(|child| self.is_definition(child))
+    }
+
+    /// Find the first row of the comments, attributes and decorators directly above a definition
+    fn leading_comments_start(&self, node: Node<'_>, min_row: usize) -> usize {
+        let mut start = node.start_position().row;
+        let mut sibling = node.prev_named_sibling();
+
+        while let Some(prev) = sibling {
+            let is_leading = prev.kind().contains("comment") || prev.kind().contains("attribute");
+            if !is_leading || prev.start_position().row < min_row || self.end_row(prev) + 1 < start {
+                break;
+            }
+            start = prev.start_position().row;
+            sibling = prev.prev_named_sibling();
+        }
+
+        start
+    }
+
+    fn end_row(&self, node: Node<'_>) -> usize {
+        let end = node.end_position();
+        let row = if end.column == 0 && end.row > node.start_position().row {
+            end.row - 1
+        } else {
+            end.row
+        };
+        row.min(self.lines.len() - 1)
+    }
+
+    fn word_count(&self, start: usize, end: usize) -> usize {
+        self.lines[start..=end]
+            .iter()
+            .map(|line| line.split_whitespace().count())
+            .sum()
+    }
+
+    fn symbol_name(&self, node: Node<'_>) -> Option<String> {
+        let node = inner_definition(node, self.definitions);
+
+        if node.kind() == "impl_item" {
+            let ty = self.text(node.child_by_field_name("type")?);
+            return Some(match node.child_by_field_name("trait") {
+                Some(name) => format!("<{} as {}>", ty, self.text(name)),
+                None => ty,
+            });
+        }
+
+        if let Some(name) = node.child_by_field_name("name") {
+            return Some(self.text(name));
+        }
+
+        // C and C++ nest the name of functions in their declarators
+        let mut declarator = node.child_by_field_name("declarator");
+        while let Some(current) = declarator {
+            match current.child_by_field_name("declarator") {
+                Some(inner) => declarator = Some(inner),
+                None => return Some(self.text(current)),
+            }
+        }
+
+        // Go type declarations name their types in type specs
+        let mut cursor = node.walk();
+        let spec = node
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "type_spec")?;
+        spec.child_by_field_name("name").map(|name| self.text(name))
+    }
+
+    fn text(&self, node: Node<'_>) -> String {
+        node.utf8_text(self.source).unwrap_or_default().trim().to_string()
+    }
+}
+
+/// Unwrap decorated, exported and templated definitions
+fn inner_definition<'tree>(node: Node<'tree>, definitions: &[&str]) -> Node<'tree> {
+    if let Some(inner) = node
+        .child_by_field_name("definition")
+        .or_else(|| node.child_by_field_name("declaration"))
+    {
+        return inner;
+    }
+
+    if node.kind() == "template_declaration" {
+        let mut cursor = node.walk();
+        let inner = node
+            .named_children(&mut cursor)
+            .find(|child| definitions.contains(&child.kind()));
+        if let Some(inner) = inner {
+            return inner;
+        }
+    }
+
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST_SOURCE: &str = r#"use std::fmt;
+
+/// A point
+#[derive(Debug)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    fn sum(&self) -> i32 {
+        self.x + self.y
+    }
+}
+
+fn main() {
+    let point = Point::new(1, 2);
+    println!("{}", point.sum());
+}
+"#;
+
+    fn symbols(chunks: &[CodeChunk]) -> Vec<(Option<&str>, usize, usize)> {
+        chunks
+            .iter()
+            .map(|chunk| (chunk.symbol.as_deref(), chunk.start_line, chunk.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_language_name() {
+        assert_eq!(language_name(Path::new("main.rs")), Some("rust"));
+        assert_eq!(language_name(Path::new("App.TSX")), Some("tsx"));
+        assert_eq!(language_name(Path::new("notes.txt")), None);
+    }
+
+    #[test]
+    fn test_chunk_rust_definitions() {
+        let chunks = chunk_code(RUST_SOURCE, "rust", Some(100));
+        assert_eq!(symbols(&chunks), vec![
+            (None, 1, 1),
+            (Some("Point"), 3, 8),
+            (Some("Point"), 10, 18),
+            (Some("main"), 20, 23),
+        ]);
+        assert!(chunks[1].text.starts_with("/// A point\n#[derive(Debug)]"));
+        assert!(chunks[3].text.ends_with('}'));
+    }
+
+    #[test]
+    fn test_chunk_large_definition_into_nested_definitions() {
+        let chunks = chunk_code(RUST_SOURCE, "rust", Some(15));
+        let impl_chunks = chunks
+            .iter()
+            .filter(|chunk| chunk.start_line >= 10 && chunk.end_line <= 18)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            impl_chunks
+                .iter()
+                .map(|chunk| (chunk.symbol.as_deref(), chunk.start_line, chunk.end_line))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some("Point"), 10, 10),
+                (Some("Point::new"), 11, 13),
+                (Some("Point::sum"), 15, 17),
+                (Some("Point"), 18, 18),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chunk_python_classes() {
+        let source = "import os\n\nclass Greeter:\n    def greet(self):\n        return 'hi'\n\n@cache\ndef load():\n    return os.getcwd()\n";
+        let chunks = chunk_code(source, "python", Some(100));
+        assert_eq!(symbols(&chunks), vec![
+            (None, 1, 1),
+            (Some("Greeter"), 3, 5),
+            (Some("load"), 7, 9)
+        ]);
+    }
+
+    #[test]
+    fn test_chunk_without_grammar_by_lines() {
+        let source = "a: 1\nb: 2\n\nc: 3\n";
+        let chunks = chunk_code(source, "yaml", Some(4));
+        assert_eq!(symbols(&chunks), vec![(None, 1, 2), (None, 4, 4)]);
+        assert_eq!(chunks[1].text, "c: 3");
+    }
+
+    #[test]
+    fn test_chunk_empty() {
+        assert!(chunk_code("", "rust", Some(10)).is_empty());
+    }
+}
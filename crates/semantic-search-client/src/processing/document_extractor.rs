@@ -0,0 +1,643 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use quick_xml::Reader;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{
+    BytesStart,
+    Event,
+};
+use serde_json::Value;
+use zip::ZipArchive;
+
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+use crate::types::FileType;
+
+/// Width HTML is wrapped to when converted to text
+const HTML_TEXT_WIDTH: usize = 100;
+
+/// Largest total uncompressed size of the parts of a zipped document that are read, so that a zip
+/// bomb can't exhaust memory, whether it has one large part or many
+const MAX_DOCUMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// A part of a document, such as a slide, sheet or notebook cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentSection {
+    /// The readable text of the section
+    pub text: String,
+    /// Metadata locating the section in the document, added to the payload of each of its chunks
+    pub metadata: serde_json::Map<String, Value>,
+}
+
+impl DocumentSection {
+    fn new(text: String) -> Self {
+        Self {
+            text,
+            metadata: serde_json::Map::new(),
+        }
+    }
+
+    fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+/// Extract the text of an office document, notebook, e-book or HTML page
+///
+/// Extraction runs entirely offline: office documents and e-books are zipped XML, notebooks are
+/// JSON whose cell outputs are left out, and HTML is converted to readable text.
+///
+/// # Arguments
+///
+/// * `path` - Path to the document
+/// * `file_type` - Type of the document
+///
+/// # Returns
+///
+/// The sections of the document in reading order
+pub fn extract_sections(path: &Path, file_type: FileType) -> Result<Vec<DocumentSection>> {
+    let sections = match file_type {
+        FileType::Docx => open_zip(path).and_then(|mut zip| extract_docx(&mut zip)),
+        FileType::Pptx => open_zip(path).and_then(|mut zip| extract_pptx(&mut zip)),
+        FileType::Xlsx => open_zip(path).and_then(|mut zip| extract_xlsx(&mut zip)),
+        FileType::Epub => open_zip(path).and_then(|mut zip| extract_epub(&mut zip)),
+        FileType::Notebook => read_file(path).and_then(|content| extract_notebook(&content)),
+        FileType::Html => {
+            read_file(path).and_then(|content| html_to_text(&content).map(|text| vec![DocumentSection::new(text)]))
+        },
+        _ => Err(format!("{:?} is not a document type", file_type)),
+    };
+
+    sections.map_err(|e| {
+        SemanticSearchError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Failed to extract text from {:?} {}: {}", file_type, path.display(), e),
+        ))
+    })
+}
+
+/// A zipped document, with the number of uncompressed bytes that may still be read from it
+struct Zip {
+    archive: ZipArchive<File>,
+    remaining: u64,
+}
+
+fn open_zip(path: &Path) -> std::result::Result<Zip, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+    Ok(Zip {
+        archive,
+        remaining: MAX_DOCUMENT_SIZE,
+    })
+}
+
+fn read_file(path: &Path) -> std::result::Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| e.to_string())
+}
+
+/// Read an entry, counting it against the bytes that may still be read from the document. The size
+/// in the header of the entry is checked first, but it can't be trusted, so reading stops at the
+/// limit regardless.
+fn read_entry(zip: &mut Zip, name: &str) -> std::result::Result<String, String> {
+    let limit = zip.remaining;
+    let entry = zip.archive.by_name(name).map_err(|e| format!("{}: {}", name, e))?;
+    let too_large = || {
+        format!(
+            "{} doesn't fit in the {} bytes that are read of a document",
+            name, MAX_DOCUMENT_SIZE
+        )
+    };
+    if entry.size() > limit {
+        return Err(too_large());
+    }
+
+    let mut content = String::new();
+    entry
+        .take(limit + 1)
+        .read_to_string(&mut content)
+        .map_err(|e| format!("{}: {}", name, e))?;
+    if content.len() as u64 > limit {
+        return Err(too_large());
+    }
+    zip.remaining -= content.len() as u64;
+    Ok(content)
+}
+
+/// Extract a Word document, starting a new section at each heading
+fn extract_docx(zip: &mut Zip) -> std::result::Result<Vec<DocumentSection>, String> {
+    let xml = read_entry(zip, "word/document.xml")?;
+    let mut sections = Vec::new();
+    let mut heading: Option<String> = None;
+    let mut text = String::new();
+    let mut paragraph = String::new();
+    let mut is_heading = false;
+
+    let mut flush = |heading: &Option<String>, text: &mut String| {
+        if !text.trim().is_empty() {
+            let section = DocumentSection::new(std::mem::take(text));
+            sections.push(match heading {
+                Some(heading) => section.with("heading", heading.clone()),
+                None => section,
+            });
+        }
+        text.clear();
+    };
+
+    let mut in_text = false;
+    for_each_xml_event(&xml, |event| match event {
+        XmlEvent::Start("p", _) => {
+            paragraph.clear();
+            is_heading = false;
+        },
+        XmlEvent::Start("pStyle", attributes) => {
+            let style = attributes.get("val").map(String::as_str).unwrap_or_default();
+            is_heading = style.starts_with("Heading") || style == "Title";
+        },
+        XmlEvent::Start("t", _) => in_text = true,
+        XmlEvent::End("t") => in_text = false,
+        XmlEvent::Start("tab", _) => paragraph.push('\t'),
+        XmlEvent::Start("br" | "cr", _) => paragraph.push('\n'),
+        XmlEvent::Text(content) if in_text => paragraph.push_str(content),
+        XmlEvent::End("p") => {
+            if is_heading && !paragraph.trim().is_empty() {
+                flush(&heading, &mut text);
+                heading = Some(paragraph.trim().to_string());
+            }
+            text.push_str(&paragraph);
+            text.push('\n');
+        },
+        _ => {},
+    })?;
+    flush(&heading, &mut text);
+
+    Ok(sections)
+}
+
+/// Extract a PowerPoint presentation, one section per slide
+fn extract_pptx(zip: &mut Zip) -> std::result::Result<Vec<DocumentSection>, String> {
+    let mut slides = zip
+        .archive
+        .file_names()
+        .filter_map(|name| {
+            let number = name.strip_prefix("ppt/slides/slide")?.strip_suffix(".xml")?;
+            Some((number.parse::<u64>().ok()?, name.to_string()))
+        })
+        .collect::<Vec<_>>();
+    slides.sort();
+
+    let mut sections = Vec::new();
+    for (number, name) in slides {
+        let xml = read_entry(zip, &name)?;
+        let text = paragraphs_text(&xml)?;
+        if !text.trim().is_empty() {
+            sections.push(DocumentSection::new(text).with("slide", number));
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Extract an Excel workbook, one section per sheet with a line per row
+fn extract_xlsx(zip: &mut Zip) -> std::result::Result<Vec<DocumentSection>, String> {
+    let shared_strings = match zip.archive.index_for_name("xl/sharedStrings.xml") {
+        Some(_) => shared_strings(&read_entry(zip, "xl/sharedStrings.xml")?)?,
+        None => Vec::new(),
+    };
+
+    let mut targets = HashMap::new();
+    for_each_xml_event(&read_entry(zip, "xl/_rels/workbook.xml.rels")?, |event| {
+        if let XmlEvent::Start("Relationship", attributes) = event {
+            targets.extend(attributes.get("Id").cloned().zip(attributes.get("Target").cloned()));
+        }
+    })?;
+
+    let mut sheets = Vec::new();
+    for_each_xml_event(&read_entry(zip, "xl/workbook.xml")?, |event| {
+        if let XmlEvent::Start("sheet", attributes) = event {
+            sheets.extend(attributes.get("name").cloned().zip(attributes.get("id").cloned()));
+        }
+    })?;
+
+    let mut sections = Vec::new();
+    for (name, id) in sheets {
+        let Some(target) = targets.get(&id) else {
+            continue;
+        };
+        let entry = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{}", target),
+        };
+
+        let text = sheet_text(&read_entry(zip, &entry)?, &shared_strings)?;
+        if !text.trim().is_empty() {
+            sections.push(DocumentSection::new(text).with("sheet", name));
+        }
+    }
+
+    Ok(sections)
+}
+
+fn shared_strings(xml: &str) -> std::result::Result<Vec<String>, String> {
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+
+    for_each_xml_event(xml, |event| match event {
+        XmlEvent::Start("t", _) => in_text = true,
+        XmlEvent::End("t") => in_text = false,
+        XmlEvent::Text(content) if in_text => current.push_str(content),
+        XmlEvent::End("si") => strings.push(std::mem::take(&mut current)),
+        _ => {},
+    })?;
+
+    Ok(strings)
+}
+
+fn sheet_text(xml: &str, shared_strings: &[String]) -> std::result::Result<String, String> {
+    let mut text = String::new();
+    let mut row = Vec::new();
+    let mut cell_type = String::new();
+    let mut value = String::new();
+    let mut in_value = false;
+
+    for_each_xml_event(xml, |event| match event {
+        XmlEvent::Start("c", attributes) => {
+            cell_type = attributes.get("t").cloned().unwrap_or_default();
+            value.clear();
+        },
+        XmlEvent::Start("v" | "t", _) => in_value = true,
+        XmlEvent::End("v" | "t") => in_value = false,
+        XmlEvent::Text(content) if in_value => value.push_str(content),
+        XmlEvent::End("c") => {
+            let cell = match cell_type.as_str() {
+                "s" => value
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| shared_strings.get(index))
+                    .cloned()
+                    .unwrap_or_default(),
+                _ => std::mem::take(&mut value),
+            };
+            row.push(cell);
+        },
+        XmlEvent::End("row") => {
+            if row.iter().any(|cell| !cell.trim().is_empty()) {
+                text.push_str(&row.join(" | "));
+                text.push('\n');
+            }
+            row.clear();
+        },
+        _ => {},
+    })?;
+
+    Ok(text)
+}
+
+/// Extract an EPUB e-book, one section per chapter in reading order
+fn extract_epub(zip: &mut Zip) -> std::result::Result<Vec<DocumentSection>, String> {
+    let mut package_path = None;
+    for_each_xml_event(&read_entry(zip, "META-INF/container.xml")?, |event| {
+        if let XmlEvent::Start("rootfile", attributes) = event {
+            package_path = package_path.take().or_else(|| attributes.get("full-path").cloned());
+        }
+    })?;
+    let package_path = package_path.ok_or("missing package document")?;
+    let base = match package_path.rfind('/') {
+        Some(index) => &package_path[..=index],
+        None => "",
+    };
+
+    let mut manifest = HashMap::new();
+    let mut spine = Vec::new();
+    for_each_xml_event(&read_entry(zip, &package_path)?, |event| match event {
+        XmlEvent::Start("item", attributes) => {
+            manifest.extend(attributes.get("id").cloned().zip(attributes.get("href").cloned()));
+        },
+        XmlEvent::Start("itemref", attributes) => spine.extend(attributes.get("idref").cloned()),
+        _ => {},
+    })?;
+
+    let mut sections = Vec::new();
+    for href in spine.iter().filter_map(|id| manifest.get(id)) {
+        let text = html_to_text(&read_entry(zip, &format!("{}{}", base, href))?)?;
+        if !text.trim().is_empty() {
+            let chapter = sections.len() + 1;
+            sections.push(DocumentSection::new(text).with("chapter", chapter));
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Extract a Jupyter notebook, one section per cell without its outputs
+fn extract_notebook(content: &str) -> std::result::Result<Vec<DocumentSection>, String> {
+    let notebook: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let cells = notebook.get("cells").and_then(Value::as_array).ok_or("missing cells")?;
+
+    let sections = cells
+        .iter()
+        .enumerate()
+        .filter_map(|(index, cell)| {
+            let text = match cell.get("source")? {
+                Value::String(source) => source.clone(),
+                Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+                _ => return None,
+            };
+            if text.trim().is_empty() {
+                return None;
+            }
+
+            let cell_type = cell.get("cell_type").and_then(Value::as_str).unwrap_or("code");
+            Some(
+                DocumentSection::new(text)
+                    .with("cell_index", index)
+                    .with("cell_type", cell_type),
+            )
+        })
+        .collect();
+
+    Ok(sections)
+}
+
+fn html_to_text(html: &str) -> std::result::Result<String, String> {
+    html2text::config::plain_no_decorate()
+        .string_from_read(html.as_bytes(), HTML_TEXT_WIDTH)
+        .map_err(|e| e.to_string())
+}
+
+/// Join the text of the paragraphs of a DrawingML document, such as a slide
+fn paragraphs_text(xml: &str) -> std::result::Result<String, String> {
+    let mut text = String::new();
+    let mut in_text = false;
+
+    for_each_xml_event(xml, |event| match event {
+        XmlEvent::Start("t", _) => in_text = true,
+        XmlEvent::End("t") => in_text = false,
+        XmlEvent::Text(content) if in_text => text.push_str(content),
+        XmlEvent::Start("br", _) | XmlEvent::End("p") => text.push('\n'),
+        _ => {},
+    })?;
+
+    Ok(text)
+}
+
+/// An element or text of an XML document, with namespace prefixes removed from names
+enum XmlEvent<'a> {
+    Start(&'a str, &'a HashMap<String, String>),
+    End(&'a str),
+    Text(&'a str),
+}
+
+fn for_each_xml_event(xml: &str, mut visit: impl FnMut(XmlEvent<'_>)) -> std::result::Result<(), String> {
+    let mut reader = Reader::from_str(xml);
+
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(element) => {
+                let (name, attributes) = element_parts(&element);
+                visit(XmlEvent::Start(&name, &attributes));
+            },
+            Event::Empty(element) => {
+                let (name, attributes) = element_parts(&element);
+                visit(XmlEvent::Start(&name, &attributes));
+                visit(XmlEvent::End(&name));
+            },
+            Event::End(element) => {
+                let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+                visit(XmlEvent::End(&name));
+            },
+            Event::Text(text) => visit(XmlEvent::Text(&text.decode().map_err(|e| e.to_string())?)),
+            Event::CData(text) => visit(XmlEvent::Text(&text.decode().map_err(|e| e.to_string())?)),
+            Event::GeneralRef(reference) => {
+                let resolved = match reference.resolve_char_ref().map_err(|e| e.to_string())? {
+                    Some(character) => Some(character.to_string()),
+                    None => {
+                        let name = reference.decode().map_err(|e| e.to_string())?;
+                        resolve_predefined_entity(&name).map(str::to_string)
+                    },
+                };
+                if let Some(resolved) = resolved {
+                    visit(XmlEvent::Text(&resolved));
+                }
+            },
+            Event::Eof => return Ok(()),
+            _ => {},
+        }
+    }
+}
+
+fn element_parts(element: &BytesStart<'_>) -> (String, HashMap<String, String>) {
+    let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+    let attributes = element
+        .attributes()
+        .flatten()
+        .filter_map(|attribute| {
+            let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned();
+            let value = attribute.unescape_value().ok()?.into_owned();
+            Some((key, value))
+        })
+        .collect();
+    (name, attributes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn summary(sections: &[DocumentSection]) -> Vec<(String, Value)> {
+        sections
+            .iter()
+            .map(|section| {
+                let metadata = section.metadata.values().next().cloned().unwrap_or(Value::Null);
+                (section.text.trim().to_string(), metadata)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_docx() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("design.docx");
+        write_zip(&path, &[(
+            "word/document.xml",
+            r#"<w:document xmlns:w="w"><w:body>
+                <w:p><w:r><w:t>Preface</w:t></w:r></w:p>
+                <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Rollout</w:t></w:r></w:p>
+                <w:p><w:r><w:t xml:space="preserve">Deploy </w:t></w:r><w:r><w:t>A &amp; B</w:t></w:r></w:p>
+            </w:body></w:document>"#,
+        )]);
+
+        let sections = extract_sections(&path, FileType::Docx).unwrap();
+        assert_eq!(summary(&sections), vec![
+            ("Preface".to_string(), Value::Null),
+            ("Rollout\nDeploy A & B".to_string(), Value::from("Rollout")),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_pptx() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deck.pptx");
+        let slide = |text: &str| {
+            format!(
+                r#"<p:sld xmlns:a="a" xmlns:p="p"><a:p><a:r><a:t>{}</a:t></a:r></a:p></p:sld>"#,
+                text
+            )
+        };
+        write_zip(&path, &[
+            ("ppt/slides/slide10.xml", &slide("Tenth")),
+            ("ppt/slides/slide2.xml", &slide("Second")),
+            ("ppt/slides/_rels/slide2.xml.rels", "<Relationships/>"),
+        ]);
+
+        let sections = extract_sections(&path, FileType::Pptx).unwrap();
+        assert_eq!(summary(&sections), vec![
+            ("Second".to_string(), Value::from(2)),
+            ("Tenth".to_string(), Value::from(10)),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_xlsx() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("budget.xlsx");
+        write_zip(&path, &[
+            (
+                "xl/workbook.xml",
+                r#"<workbook xmlns:r="r"><sheets><sheet name="Costs" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/></Relationships>"#,
+            ),
+            (
+                "xl/sharedStrings.xml",
+                "<sst><si><t>Item</t></si><si><t>Servers</t></si></sst>",
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData>
+                    <row><c t="s"><v>0</v></c><c t="inlineStr"><is><t>Cost</t></is></c></row>
+                    <row><c t="s"><v>1</v></c><c><v>1200</v></c></row>
+                </sheetData></worksheet>"#,
+            ),
+        ]);
+
+        let sections = extract_sections(&path, FileType::Xlsx).unwrap();
+        assert_eq!(summary(&sections), vec![(
+            "Item | Cost\nServers | 1200".to_string(),
+            Value::from("Costs")
+        )]);
+    }
+
+    #[test]
+    fn test_extract_notebook_without_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("analysis.ipynb");
+        let notebook = serde_json::json!({
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Analysis\n", "Load the data"]},
+                {"cell_type": "code", "source": "", "outputs": []},
+                {"cell_type": "code", "source": "df.describe()", "outputs": [{"text": "OUTPUT"}]},
+            ]
+        });
+        std::fs::write(&path, notebook.to_string()).unwrap();
+
+        let sections = extract_sections(&path, FileType::Notebook).unwrap();
+        assert_eq!(summary(&sections), vec![
+            ("# Analysis\nLoad the data".to_string(), Value::from(0)),
+            ("df.describe()".to_string(), Value::from(2)),
+        ]);
+        assert_eq!(sections[1].metadata["cell_type"], "code");
+    }
+
+    #[test]
+    fn test_extract_html() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("runbook.html");
+        std::fs::write(
+            &path,
+            "<html><body><h1>Runbook</h1><p>Restart the <b>service</b>.</p></body></html>",
+        )
+        .unwrap();
+
+        let sections = extract_sections(&path, FileType::Html).unwrap();
+        assert_eq!(sections.len(), 1);
+        assert!(sections[0].text.contains("Runbook"));
+        assert!(sections[0].text.contains("Restart the service."));
+        assert!(!sections[0].text.contains("<p>"));
+    }
+
+    #[test]
+    fn test_extract_epub() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("guide.epub");
+        write_zip(&path, &[
+            (
+                "META-INF/container.xml",
+                r#"<container><rootfiles><rootfile full-path="OEBPS/content.opf"/></rootfiles></container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                r#"<package><manifest><item id="b" href="b.xhtml"/><item id="a" href="a.xhtml"/></manifest>
+                <spine><itemref idref="a"/><itemref idref="b"/></spine></package>"#,
+            ),
+            ("OEBPS/a.xhtml", "<html><body><p>First chapter</p></body></html>"),
+            ("OEBPS/b.xhtml", "<html><body><p>Second chapter</p></body></html>"),
+        ]);
+
+        let sections = extract_sections(&path, FileType::Epub).unwrap();
+        assert_eq!(summary(&sections), vec![
+            ("First chapter".to_string(), Value::from(1)),
+            ("Second chapter".to_string(), Value::from(2)),
+        ]);
+    }
+
+    #[test]
+    fn test_read_entry_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bomb.epub");
+        let chapter = "a".repeat(1000);
+        write_zip(&path, &[("OEBPS/a.xhtml", &chapter), ("OEBPS/b.xhtml", &chapter)]);
+
+        let mut zip = open_zip(&path).unwrap();
+        zip.remaining = 999;
+        assert!(read_entry(&mut zip, "OEBPS/a.xhtml").is_err());
+
+        // The limit is shared by all the entries of the document
+        zip.remaining = 1500;
+        assert_eq!(read_entry(&mut zip, "OEBPS/a.xhtml").unwrap().len(), 1000);
+        assert_eq!(zip.remaining, 500);
+        assert!(read_entry(&mut zip, "OEBPS/b.xhtml").is_err());
+    }
+
+    #[test]
+    fn test_extract_invalid_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.docx");
+        std::fs::write(&path, "not a zip").unwrap();
+
+        assert!(extract_sections(&path, FileType::Docx).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use sha2::{
+    Digest,
+    Sha256,
+};
+
+use crate::error::Result;
+use crate::types::{
+    FileManifest,
+    FileManifestEntry,
+};
+
+/// Files of a context compared with the manifest of their last indexing
+#[derive(Debug, Default)]
+pub struct ManifestDiff {
+    /// Files that did not change since they were indexed
+    pub unchanged: Vec<String>,
+    /// Files that were added or changed since they were indexed
+    pub changed: Vec<PathBuf>,
+    /// Files that were indexed but no longer exist or can't be read
+    pub removed: Vec<String>,
+    /// Manifest of the files as they are now
+    pub manifest: FileManifest,
+}
+
+/// Read the manifest entry of a file, hashing its contents
+///
+/// # Arguments
+///
+/// * `path` - Path to the file
+///
+/// # Returns
+///
+/// The size, modification time and SHA-256 of the file
+pub fn manifest_entry(path: &Path) -> Result<FileManifestEntry> {
+    let metadata = std::fs::metadata(path)?;
+    Ok(FileManifestEntry {
+        size: metadata.len(),
+        modified: DateTime::<Utc>::from(metadata.modified()?),
+        sha256: sha256_file(path)?,
+    })
+}
+
+/// Compare files with the manifest of their last indexing
+///
+/// Files whose size and modification time are unchanged are not read. Other files are hashed, so
+/// that files that were only touched are not indexed again.
+///
+/// # Arguments
+///
+/// * `previous` - Manifest of the files when they were last indexed
+/// * `files` - Files to index now
+///
+/// # Returns
+///
+/// The unchanged, changed and removed files, and the manifest of the files now
+pub fn diff_manifest(previous: &FileManifest, files: &[PathBuf]) -> ManifestDiff {
+    let mut diff = ManifestDiff::default();
+
+    for path in files {
+        let key = path.to_string_lossy().to_string();
+        let Ok(metadata) = std::fs::metadata(path) else {
+            continue;
+        };
+        let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+
+        let entry = match previous.get(&key) {
+            Some(entry) if entry.size == metadata.len() && Some(entry.modified) == modified => entry.clone(),
+            _ => match manifest_entry(path) {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            },
+        };
+
+        if previous.get(&key).is_some_and(|old| old.sha256 == entry.sha256) {
+            diff.unchanged.push(key.clone());
+        } else {
+            diff.changed.push(path.clone());
+        }
+        diff.manifest.insert(key, entry);
+    }
+
+    diff.removed = previous
+        .keys()
+        .filter(|key| !diff.manifest.contains_key(*key))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0; 8192];
+
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn files(dir: &TempDir, names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(|name| dir.path().join(name)).collect()
+    }
+
+    #[test]
+    fn test_diff_manifest() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("same.txt"), "same").unwrap();
+        fs::write(dir.path().join("touched.txt"), "touched").unwrap();
+        fs::write(dir.path().join("edited.txt"), "before").unwrap();
+        fs::write(dir.path().join("deleted.txt"), "deleted").unwrap();
+
+        let first = diff_manifest(
+            &FileManifest::new(),
+            &files(&dir, &["same.txt", "touched.txt", "edited.txt", "deleted.txt"]),
+        );
+        assert!(first.unchanged.is_empty());
+        assert_eq!(first.changed.len(), 4);
+        assert_eq!(first.manifest.len(), 4);
+
+        // Rewriting a file with the same contents changes its modification time but not its hash
+        let mut previous = first.manifest;
+        let touched = dir.path().join("touched.txt").to_string_lossy().to_string();
+        previous.get_mut(&touched).unwrap().modified = DateTime::<Utc>::UNIX_EPOCH;
+        fs::write(dir.path().join("edited.txt"), "after the edit").unwrap();
+        fs::remove_file(dir.path().join("deleted.txt")).unwrap();
+        fs::write(dir.path().join("added.txt"), "added").unwrap();
+
+        let second = diff_manifest(
+            &previous,
+            &files(&dir, &["same.txt", "touched.txt", "edited.txt", "added.txt"]),
+        );
+        let mut unchanged = second.unchanged.clone();
+        unchanged.sort();
+        assert_eq!(unchanged, vec![
+            dir.path().join("same.txt").to_string_lossy().to_string(),
+            touched.clone(),
+        ]);
+        assert_eq!(second.changed, files(&dir, &["edited.txt", "added.txt"]));
+        assert_eq!(second.removed, vec![
+            dir.path().join("deleted.txt").to_string_lossy().to_string()
+        ]);
+        assert_eq!(second.manifest.len(), 4);
+        assert_ne!(second.manifest[&touched].modified, DateTime::<Utc>::UNIX_EPOCH);
+    }
+}
@@ -7,6 +7,11 @@ use crate::error::{
     Result,
     SemanticSearchError,
 };
+use crate::processing::code_chunker::{
+    chunk_code,
+    language_name,
+};
+use crate::processing::document_extractor::extract_sections;
 use crate::processing::text_chunker::chunk_text;
 use crate::types::FileType;
 
@@ -42,6 +47,16 @@ pub fn get_file_type(path: &Path) -> FileType {
         // PDF files
         Some("pdf") => FileType::Pdf,
 
+        // Office documents, notebooks and e-books
+        Some("docx") => FileType::Docx,
+        Some("pptx") => FileType::Pptx,
+        Some("xlsx") => FileType::Xlsx,
+        Some("ipynb") => FileType::Notebook,
+        Some("epub") => FileType::Epub,
+
+        // HTML pages, converted to readable text
+        Some("html" | "htm") => FileType::Html,
+
         // Web and markup formats (text-based)
         Some("svg") => FileType::Text,
 
@@ -58,7 +73,7 @@ pub fn get_file_type(path: &Path) -> FileType {
         Some("kt" | "kts") => FileType::Code,
         Some("cs") => FileType::Code,
         Some("sh" | "bash" | "zsh") => FileType::Code,
-        Some("html" | "htm" | "xml") => FileType::Code,
+        Some("xml") => FileType::Code,
         Some("css" | "scss" | "sass" | "less") => FileType::Code,
         Some("sql") => FileType::Code,
         Some("yaml" | "yml") => FileType::Code,
@@ -74,7 +89,7 @@ pub fn get_file_type(path: &Path) -> FileType {
             _ => FileType::Unknown,
         },
 
-        // Default to unknown (includes office docs, PDFs, etc.)
+        // Default to unknown (includes binary files, legacy office formats, etc.)
         _ => FileType::Unknown,
     }
 }
@@ -117,71 +132,81 @@ pub fn process_file_with_config(
 
     let file_type = get_file_type(path);
 
-    let content = match file_type {
-        FileType::Pdf => {
-            // Extract text from PDF
-            pdf_extract::extract_text(path).map_err(|e| {
-                SemanticSearchError::IoError(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("Failed to extract text from PDF {}: {}", path.display(), e),
-                ))
-            })?
-        },
-        _ => {
-            // Read as text file
-            fs::read_to_string(path).map_err(|e| {
-                SemanticSearchError::IoError(std::io::Error::new(
-                    e.kind(),
-                    format!("Failed to read file {}: {}", path.display(), e),
-                ))
-            })?
-        },
-    };
-
     match file_type {
         FileType::Text | FileType::Markdown | FileType::Code | FileType::Json | FileType::Pdf => {
-            // For text-based files (including JSON), chunk the content and create multiple data points
-            // Use the configured chunk size and overlap
-            let chunks = chunk_text(&content, chunk_size, chunk_overlap);
+            let content = read_content(path, file_type)?;
             let path_str = path.to_string_lossy().to_string();
             let file_type_str = format!("{:?}", file_type);
 
-            let mut results = Vec::new();
-
-            for (i, chunk) in chunks.iter().enumerate() {
-                let mut metadata = serde_json::Map::new();
-                metadata.insert("text".to_string(), Value::String(chunk.clone()));
-                metadata.insert("path".to_string(), Value::String(path_str.clone()));
-                metadata.insert("file_type".to_string(), Value::String(file_type_str.clone()));
-                metadata.insert("chunk_index".to_string(), Value::Number((i as u64).into()));
-                metadata.insert("total_chunks".to_string(), Value::Number((chunks.len() as u64).into()));
-
-                // For code files, add additional metadata
-                if file_type == FileType::Code {
-                    metadata.insert(
-                        "language".to_string(),
-                        Value::String(
-                            path.extension()
-                                .and_then(|ext| ext.to_str())
-                                .unwrap_or("unknown")
-                                .to_string(),
-                        ),
-                    );
-                }
-
-                results.push(Value::Object(metadata));
-            }
+            let mut results = if file_type == FileType::Code {
+                // For code files, chunk along definitions and cite the lines of each chunk
+                let language = language_name(path).unwrap_or("unknown");
+                let chunks = chunk_code(&content, language, chunk_size);
+                let total_chunks = chunks.len();
+
+                chunks
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, chunk)| {
+                        let mut metadata = chunk_metadata(&chunk.text, &path_str, &file_type_str, i, total_chunks);
+                        metadata.insert("language".to_string(), Value::String(language.to_string()));
+                        metadata.insert(
+                            "start_line".to_string(),
+                            Value::Number((chunk.start_line as u64).into()),
+                        );
+                        metadata.insert("end_line".to_string(), Value::Number((chunk.end_line as u64).into()));
+                        if let Some(symbol) = chunk.symbol {
+                            metadata.insert("symbol".to_string(), Value::String(symbol));
+                        }
+                        Value::Object(metadata)
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                // For text-based files (including JSON), chunk the content and create multiple data points
+                // Use the configured chunk size and overlap
+                let chunks = chunk_text(&content, chunk_size, chunk_overlap);
+
+                chunks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, chunk)| Value::Object(chunk_metadata(chunk, &path_str, &file_type_str, i, chunks.len())))
+                    .collect::<Vec<_>>()
+            };
 
             // If no chunks were created (empty file), create at least one entry
             if results.is_empty() {
-                let mut metadata = serde_json::Map::new();
-                metadata.insert("text".to_string(), Value::String(String::new()));
-                metadata.insert("path".to_string(), Value::String(path_str));
-                metadata.insert("file_type".to_string(), Value::String(file_type_str));
-                metadata.insert("chunk_index".to_string(), Value::Number(0.into()));
-                metadata.insert("total_chunks".to_string(), Value::Number(1.into()));
-
-                results.push(Value::Object(metadata));
+                results.push(Value::Object(chunk_metadata("", &path_str, &file_type_str, 0, 1)));
+            }
+
+            Ok(results)
+        },
+        FileType::Docx | FileType::Pptx | FileType::Xlsx | FileType::Notebook | FileType::Epub | FileType::Html => {
+            // For documents, chunk each section and locate the chunks with the section's metadata
+            let sections = extract_sections(path, file_type)?;
+            let path_str = path.to_string_lossy().to_string();
+            let file_type_str = format!("{:?}", file_type);
+
+            let chunks = sections
+                .iter()
+                .flat_map(|section| {
+                    chunk_text(&section.text, chunk_size, chunk_overlap)
+                        .into_iter()
+                        .map(move |chunk| (chunk, &section.metadata))
+                })
+                .collect::<Vec<_>>();
+
+            let mut results = chunks
+                .iter()
+                .enumerate()
+                .map(|(i, (chunk, section_metadata))| {
+                    let mut metadata = chunk_metadata(chunk, &path_str, &file_type_str, i, chunks.len());
+                    metadata.extend(section_metadata.iter().map(|(k, v)| (k.clone(), v.clone())));
+                    Value::Object(metadata)
+                })
+                .collect::<Vec<_>>();
+
+            if results.is_empty() {
+                results.push(Value::Object(chunk_metadata("", &path_str, &file_type_str, 0, 1)));
             }
 
             Ok(results)
@@ -197,6 +222,46 @@ pub fn process_file_with_config(
     }
 }
 
+/// Read the text content of a text-based or PDF file
+fn read_content(path: &Path, file_type: FileType) -> Result<String> {
+    match file_type {
+        FileType::Pdf => {
+            // Extract text from PDF
+            pdf_extract::extract_text(path).map_err(|e| {
+                SemanticSearchError::IoError(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Failed to extract text from PDF {}: {}", path.display(), e),
+                ))
+            })
+        },
+        _ => {
+            // Read as text file
+            fs::read_to_string(path).map_err(|e| {
+                SemanticSearchError::IoError(std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to read file {}: {}", path.display(), e),
+                ))
+            })
+        },
+    }
+}
+
+fn chunk_metadata(
+    text: &str,
+    path: &str,
+    file_type: &str,
+    chunk_index: usize,
+    total_chunks: usize,
+) -> serde_json::Map<String, Value> {
+    let mut metadata = serde_json::Map::new();
+    metadata.insert("text".to_string(), Value::String(text.to_string()));
+    metadata.insert("path".to_string(), Value::String(path.to_string()));
+    metadata.insert("file_type".to_string(), Value::String(file_type.to_string()));
+    metadata.insert("chunk_index".to_string(), Value::Number((chunk_index as u64).into()));
+    metadata.insert("total_chunks".to_string(), Value::Number((total_chunks as u64).into()));
+    metadata
+}
+
 /// Process a directory and extract content from all files
 ///
 /// # Arguments
@@ -258,7 +323,6 @@ mod tests {
             ("component.tsx", FileType::Code),
             ("Main.java", FileType::Code),
             ("main.c", FileType::Code),
-            ("index.html", FileType::Code),
             ("styles.css", FileType::Code),
             ("config.yaml", FileType::Code),
             ("Cargo.toml", FileType::Code),
@@ -274,6 +338,14 @@ mod tests {
             ("Dockerfile", FileType::Text),
             ("LICENSE", FileType::Text),
             (".gitignore", FileType::Text),
+            // Documents
+            ("design.docx", FileType::Docx),
+            ("deck.pptx", FileType::Pptx),
+            ("budget.xlsx", FileType::Xlsx),
+            ("analysis.ipynb", FileType::Notebook),
+            ("guide.epub", FileType::Epub),
+            ("index.html", FileType::Html),
+            ("page.HTM", FileType::Html),
             // Case insensitive
             ("Main.RS", FileType::Code),
             ("README.MD", FileType::Markdown),
@@ -309,4 +381,40 @@ mod tests {
         assert_eq!(get_file_type(&PathBuf::from("document.pdf")), FileType::Pdf);
         assert_eq!(get_file_type(&PathBuf::from("report.PDF")), FileType::Pdf);
     }
+
+    #[test]
+    fn test_code_file_chunk_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "use std::io;\n\nfn read() -> io::Result<()> {\n    Ok(())\n}\n").unwrap();
+
+        let items = process_file_with_config(&path, Some(100), Some(10)).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1]["symbol"], "read");
+        assert_eq!(items[1]["language"], "rust");
+        assert_eq!(items[1]["start_line"], 3);
+        assert_eq!(items[1]["end_line"], 5);
+        assert!(items[0].get("symbol").is_none());
+    }
+
+    #[test]
+    fn test_document_chunk_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("analysis.ipynb");
+        let notebook = serde_json::json!({
+            "cells": [
+                {"cell_type": "markdown", "source": "Churn analysis"},
+                {"cell_type": "code", "source": "model.fit(data)", "outputs": [{"text": "done"}]},
+            ]
+        });
+        fs::write(&path, notebook.to_string()).unwrap();
+
+        let items = process_file_with_config(&path, Some(100), Some(10)).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1]["text"], "model.fit(data)");
+        assert_eq!(items[1]["file_type"], "Notebook");
+        assert_eq!(items[1]["cell_index"], 1);
+        assert_eq!(items[1]["chunk_index"], 1);
+        assert_eq!(items[1]["total_chunks"], 2);
+    }
 }
@@ -1,8 +1,28 @@
+/// Syntax-aware chunking of source code along function, class and impl boundaries
+pub mod code_chunker;
+/// Text extraction from office documents, notebooks, e-books and HTML
+pub mod document_extractor;
+/// Per-file manifests for re-indexing only the files that changed
+pub mod file_manifest;
 /// File processing utilities for handling different file types and extracting content
 pub mod file_processor;
 /// Text chunking utilities for breaking down text into manageable pieces for embedding
 pub mod text_chunker;
 
+pub use code_chunker::{
+    CodeChunk,
+    chunk_code,
+    language_name,
+};
+pub use document_extractor::{
+    DocumentSection,
+    extract_sections,
+};
+pub use file_manifest::{
+    ManifestDiff,
+    diff_manifest,
+    manifest_entry,
+};
 pub use file_processor::{
     get_file_type,
     process_directory,
@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
 use std::path::PathBuf;
 use std::sync::{
     Arc,
@@ -34,6 +37,8 @@ pub struct AddContextRequest {
     pub exclude_patterns: Option<Vec<String>>,
     /// Optional embedding type override for this context
     pub embedding_type: Option<EmbeddingType>,
+    /// Whether to watch the path and update the context when files change
+    pub watch: bool,
 }
 
 /// Parameters for indexing operations (internal use)
@@ -56,6 +61,8 @@ pub struct IndexingParams {
     pub exclude_patterns: Option<Vec<String>>,
     /// Optional embedding type override (uses client default if None)
     pub embedding_type: Option<EmbeddingType>,
+    /// Whether to watch the path and update the context when files change
+    pub watch: bool,
 }
 
 use crate::client::context::SemanticContext;
@@ -107,6 +114,14 @@ pub struct KnowledgeContext {
     /// Embedding type used for this context
     #[serde(default)]
     pub embedding_type: EmbeddingType,
+
+    /// Files indexed in this context, used to index again only the files that changed
+    #[serde(default)]
+    pub files: FileManifest,
+
+    /// Whether the source path is watched, updating the context when files change
+    #[serde(default)]
+    pub watch: bool,
 }
 
 impl KnowledgeContext {
@@ -135,10 +150,26 @@ impl KnowledgeContext {
             persistent,
             item_count,
             embedding_type,
+            files: FileManifest::new(),
+            watch: false,
         }
     }
 }
 
+/// Files indexed in a context, by path
+pub type FileManifest = BTreeMap<String, FileManifestEntry>;
+
+/// A file indexed in a context, as it was when it was indexed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileManifestEntry {
+    /// Size of the file in bytes
+    pub size: u64,
+    /// When the file was last modified
+    pub modified: DateTime<Utc>,
+    /// SHA-256 of the contents of the file
+    pub sha256: String,
+}
+
 /// A data point in the semantic index
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataPoint {
@@ -185,6 +216,47 @@ impl SearchResult {
     pub fn text(&self) -> Option<&str> {
         self.point.payload.get("text").and_then(|v| v.as_str())
     }
+
+    /// Get the path of the file this result was found in
+    pub fn path(&self) -> Option<&str> {
+        self.point.payload.get("path").and_then(|v| v.as_str())
+    }
+
+    /// Get the name of the function, class or impl this result belongs to, for source code
+    pub fn symbol(&self) -> Option<&str> {
+        self.point.payload.get("symbol").and_then(|v| v.as_str())
+    }
+
+    /// Get the first and last line (1-based, inclusive) of this result, for source code
+    pub fn line_range(&self) -> Option<(u64, u64)> {
+        let line = |key: &str| self.point.payload.get(key).and_then(|v| v.as_u64());
+        Some((line("start_line")?, line("end_line")?))
+    }
+
+    /// Get the part of the document this result was found in, such as `slide 3` or `sheet Costs`
+    pub fn section(&self) -> Option<String> {
+        let sections = [
+            ("sheet", "sheet"),
+            ("slide", "slide"),
+            ("cell_index", "cell"),
+            ("chapter", "chapter"),
+            ("heading", "section"),
+        ];
+        sections.iter().find_map(|(key, label)| {
+            let value = self.point.payload.get(*key)?;
+            let value = value.as_str().map_or_else(|| value.to_string(), str::to_string);
+            Some(format!("{} {}", label, value))
+        })
+    }
+
+    /// Get the location of this result as `path:start-end`, or just the path without line range
+    pub fn location(&self) -> Option<String> {
+        let path = self.path()?;
+        Some(match self.line_range() {
+            Some((start, end)) => format!("{}:{}-{}", path, start, end),
+            None => path.to_string(),
+        })
+    }
 }
 
 /// File type for processing
@@ -200,6 +272,18 @@ pub enum FileType {
     Code,
     /// PDF file
     Pdf,
+    /// Word document
+    Docx,
+    /// PowerPoint presentation
+    Pptx,
+    /// Excel workbook
+    Xlsx,
+    /// Jupyter notebook
+    Notebook,
+    /// EPUB e-book
+    Epub,
+    /// HTML page, indexed as readable text
+    Html,
     /// Unknown file type
     Unknown,
 }
@@ -248,6 +332,13 @@ pub enum OperationType {
         /// Path being indexed
         path: String,
     },
+    /// Updating an existing context with the files that changed
+    Updating {
+        /// Display name for the operation
+        name: String,
+        /// Path being indexed
+        path: String,
+    },
     /// Clearing all contexts
     Clearing,
 }
@@ -257,6 +348,7 @@ impl OperationType {
     pub fn display_name(&self) -> String {
         match self {
             OperationType::Indexing { name, .. } => format!("Indexing '{}'", name),
+            OperationType::Updating { name, .. } => format!("Updating '{}'", name),
             OperationType::Clearing => "Clearing all".to_string(),
         }
     }
@@ -392,6 +484,19 @@ pub enum IndexingJob {
         exclude_patterns: Option<Vec<String>>,
         /// Embedding type
         embedding_type: Option<EmbeddingType>,
+        /// Whether to watch the path once indexed
+        watch: bool,
+    },
+    /// Update context job, indexing again only the files that changed
+    UpdateContext {
+        /// Operation ID
+        id: Uuid,
+        /// Cancellation token
+        cancel: CancellationToken,
+        /// ID of the context to update
+        context_id: String,
+        /// Directory path
+        path: PathBuf,
     },
     /// Clear all contexts job
     Clear {
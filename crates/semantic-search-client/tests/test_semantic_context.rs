@@ -98,3 +98,41 @@ fn test_add_data_points() {
     // Clean up
     fs::remove_dir_all(temp_dir).unwrap_or(());
 }
+
+#[test]
+fn test_replace_data_points() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let mut semantic_context = SemanticContext::new(temp_dir.path().join("data.json")).unwrap();
+
+    let point = |path: &str, value: f32| {
+        let mut payload = HashMap::new();
+        payload.insert("path".to_string(), Value::String(path.to_string()));
+        DataPoint {
+            id: 0,
+            payload,
+            vector: vec![value; 384],
+        }
+    };
+    semantic_context
+        .add_data_points(vec![point("a.txt", 0.1), point("b.txt", 0.2), point("a.txt", 0.3)])
+        .unwrap();
+
+    // Replace the points of a.txt, keeping those of b.txt
+    let removed = semantic_context
+        .replace_data_points(
+            |p| p.payload.get("path").and_then(Value::as_str) == Some("b.txt"),
+            vec![point("a.txt", 0.4)],
+        )
+        .unwrap();
+
+    assert_eq!(removed, 2);
+    let data_points = semantic_context.get_data_points();
+    assert_eq!(data_points.len(), 2);
+    assert!(data_points.iter().enumerate().all(|(i, p)| p.id == i));
+    assert_eq!(semantic_context.search(&[0.4; 384], 2).unwrap().len(), 2);
+
+    // Removing every point leaves an empty context that can still be searched
+    semantic_context.replace_data_points(|_| false, Vec::new()).unwrap();
+    assert!(semantic_context.get_data_points().is_empty());
+    assert!(semantic_context.search(&[0.4; 384], 2).unwrap().is_empty());
+}